@@ -0,0 +1,232 @@
+use crate::{annotations, auth, html};
+use anyhow::Context;
+use axum::{
+    body::HttpBody,
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, header},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use std::{
+    fs::OpenOptions,
+    io::{self, LineWriter, Write},
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// Common Log Format.
+    Common,
+    /// Common Log Format with referer and user agent.
+    Combined,
+    /// One JSON object per line, with the dataset file and row broken out.
+    Json,
+}
+
+/// Routes whose first path parameter is a dataset file, and which of them also take a row.
+const FILE_ROUTES: [&str; 39] = [
+    "view",
+    "row",
+    "edit",
+    "undo",
+    "restore",
+    "audio",
+    "download",
+    "preview",
+    "features",
+    "augment",
+    "score",
+    "run",
+    "batch",
+    "export",
+    "charset",
+    "ngrams",
+    "duplicates",
+    "pitch",
+    "energy",
+    "phonemes",
+    "speakers",
+    "map",
+    "image",
+    "completeness",
+    "schema",
+    "wpm",
+    "stats",
+    "concat",
+    "agreement",
+    "asr",
+    "live",
+    "encodings",
+    "split",
+    "note",
+    "issues",
+    "comment",
+    "spelling",
+    "expansions",
+    "style",
+];
+const ROW_ROUTES: [&str; 16] = [
+    "row", "edit", "undo", "restore", "audio", "download", "preview", "features", "augment",
+    "score", "run", "pitch", "energy", "image", "asr", "comment",
+];
+
+/// Access log written one line per request to stdout or an appended file.
+pub struct AccessLog {
+    format: Format,
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    timestamp: u64,
+    client: String,
+    /// Logged-in user, when OIDC login is enabled.
+    user: Option<String>,
+    method: &'a str,
+    protocol: String,
+    path: &'a str,
+    query: Option<&'a str>,
+    status: u16,
+    bytes: Option<u64>,
+    duration_ms: f64,
+    referer: Option<&'a str>,
+    user_agent: Option<&'a str>,
+    file: Option<String>,
+    row: Option<usize>,
+}
+
+impl AccessLog {
+    pub fn open(format: Format, path: Option<&Path>) -> anyhow::Result<Self> {
+        let out: Box<dyn Write + Send> = match path {
+            Some(path) => Box::new(LineWriter::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("Failed to open access log {}", path.display()))?,
+            )),
+            None => Box::new(io::stdout()),
+        };
+        Ok(Self {
+            format,
+            out: Mutex::new(out),
+        })
+    }
+
+    fn write(&self, entry: &Entry) {
+        let line = match self.format {
+            Format::Common => common(entry),
+            Format::Combined => format!(
+                "{} \"{}\" \"{}\"",
+                common(entry),
+                entry.referer.unwrap_or("-"),
+                entry.user_agent.unwrap_or("-")
+            ),
+            Format::Json => serde_json::to_string(entry).unwrap_or_default(),
+        };
+        let mut out = self.out.lock().unwrap();
+        if let Err(err) = writeln!(out, "{}", line) {
+            eprintln!("Failed to write access log: {}", err);
+        }
+    }
+}
+
+/// Middleware recording every request once its response is ready.
+pub async fn record(State(log): State<Arc<AccessLog>>, request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map_or_else(|| "-".to_string(), |info| info.0.ip().to_string());
+    let method = request.method().clone();
+    let protocol = format!("{:?}", request.version());
+    let uri = request.uri().clone();
+    let headers = request.headers().clone();
+    let response = next.run(request).await;
+
+    let (file, row) = resource(uri.path());
+    log.write(&Entry {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs()),
+        client,
+        user: response
+            .extensions()
+            .get::<auth::Session>()
+            .map(|session| session.user.clone()),
+        method: method.as_str(),
+        protocol,
+        path: uri.path(),
+        query: uri.query(),
+        status: response.status().as_u16(),
+        // Streamed bodies only know their length from the header, if at all.
+        bytes: response.body().size_hint().exact().or_else(|| {
+            response
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+        }),
+        duration_ms: start.elapsed().as_secs_f64() * 1000.0,
+        referer: header_str(&headers, header::REFERER),
+        user_agent: header_str(&headers, header::USER_AGENT),
+        file,
+        row,
+    });
+    response
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// The dataset file and row a request path refers to, if any.
+pub fn resource(path: &str) -> (Option<String>, Option<usize>) {
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let (route, rest) = match segments.as_slice() {
+        ["api", "rows", rest @ ..] => ("api", rest),
+        [route, rest @ ..] if FILE_ROUTES.contains(route) => (*route, rest),
+        _ => return (None, None),
+    };
+    let file = rest.first().map(|name| html::url_decode(name));
+    let row = rest
+        .get(1)
+        .filter(|_| ROW_ROUTES.contains(&route))
+        .and_then(|row| row.parse().ok());
+    (file, row)
+}
+
+/// `host ident user [date] "request" status bytes`.
+fn common(entry: &Entry) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let (year, month, day) = annotations::civil_date(entry.timestamp);
+    let secs = entry.timestamp % 86_400;
+    let target = match entry.query {
+        Some(query) => format!("{}?{}", entry.path, query),
+        None => entry.path.to_string(),
+    };
+    format!(
+        "{} - {} [{:02}/{}/{}:{:02}:{:02}:{:02} +0000] \"{} {} {}\" {} {}",
+        entry.client,
+        entry.user.as_deref().unwrap_or("-"),
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+        entry.method,
+        target,
+        entry.protocol,
+        entry.status,
+        entry
+            .bytes
+            .map_or_else(|| "-".to_string(), |bytes| bytes.to_string())
+    )
+}
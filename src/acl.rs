@@ -0,0 +1,132 @@
+use crate::auth::Session;
+use anyhow::Context;
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// What a request does with a dataset file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    /// Browse rows, play clips and read the reports.
+    View,
+    /// Edit transcriptions, tag, flag, review, score and run commands.
+    Annotate,
+    /// Download original clips and export rows.
+    Export,
+}
+
+/// Principals granted each permission: user names, `group:NAME`, or `*` for every
+/// logged-in user.
+#[derive(Deserialize, Debug, Default)]
+struct Grants {
+    #[serde(default)]
+    view: Vec<String>,
+    #[serde(default)]
+    annotate: Vec<String>,
+    #[serde(default)]
+    export: Vec<String>,
+}
+
+impl Grants {
+    fn allows(&self, session: &Session, permission: Permission) -> bool {
+        let principals = match permission {
+            Permission::View => &self.view,
+            Permission::Annotate => &self.annotate,
+            Permission::Export => &self.export,
+        };
+        principals
+            .iter()
+            .any(|principal| is_principal(session, principal))
+    }
+}
+
+/// Whether a session is a principal: its user name, `group:NAME` of one of its groups,
+/// or `*`.
+pub fn is_principal(session: &Session, principal: &str) -> bool {
+    principal == "*"
+        || principal == session.user
+        || principal
+            .strip_prefix("group:")
+            .is_some_and(|group| session.groups.contains(group))
+}
+
+#[derive(Deserialize, Debug)]
+struct Rule {
+    /// File name pattern, where `*` matches any run of characters and `?` one.
+    pattern: String,
+    #[serde(flatten)]
+    grants: Grants,
+}
+
+/// Access control lists of the dataset root, read from a JSON file:
+///
+/// ```json
+/// {
+///   "default": {"view": ["*"], "annotate": ["group:reviewers"], "export": ["group:reviewers"]},
+///   "files": [
+///     {"pattern": "clinical-*.parquet", "view": ["group:clinical"], "annotate": ["group:clinical"]}
+///   ]
+/// }
+/// ```
+///
+/// The first rule whose pattern matches a file decides; files matching none fall back to
+/// `default`, and without one nobody may access them.
+#[derive(Deserialize, Debug)]
+pub struct Acl {
+    #[serde(default)]
+    default: Option<Grants>,
+    #[serde(default)]
+    files: Vec<Rule>,
+}
+
+impl Acl {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("invalid access control list in {}", path.display()))
+    }
+
+    /// Whether the user of a session may do `permission` with a file.
+    pub fn allows(&self, session: &Session, file: &str, permission: Permission) -> bool {
+        self.files
+            .iter()
+            .find(|rule| glob_matches(&rule.pattern, file))
+            .map(|rule| &rule.grants)
+            .or(self.default.as_ref())
+            .is_some_and(|grants| grants.allows(session, permission))
+    }
+}
+
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|skip| matches(rest, &name[skip..])),
+            Some((&c, rest)) => name
+                .split_first()
+                .is_some_and(|(&n, name)| (c == '?' || c == n) && matches(rest, name)),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+tokio::task_local! {
+    static SESSION: Option<Session>;
+}
+
+/// Logged-in user of the request being handled.
+pub fn current_session() -> Option<Session> {
+    SESSION.try_with(Clone::clone).ok().flatten()
+}
+
+/// Runs a future as the user of its request.
+pub async fn scope<F: Future>(session: Option<Session>, future: F) -> F::Output {
+    SESSION.scope(session, future).await
+}
+
+/// Runs blocking work of a request as the user of the request.
+pub fn sync_scope<T>(session: Option<Session>, work: impl FnOnce() -> T) -> T {
+    SESSION.sync_scope(session, work)
+}
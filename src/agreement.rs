@@ -0,0 +1,80 @@
+/// A range of word error rates between the human transcription and the pseudo-label.
+pub struct Bucket {
+    /// Value of the `wer` filter selecting the rows of the bucket.
+    pub name: &'static str,
+    pub label: &'static str,
+    /// Highest word error rate in the bucket; the lowest is above the previous bucket's.
+    pub max: f64,
+}
+
+/// Agreement buckets, from exact matches to mostly different transcripts.
+pub const BUCKETS: [Bucket; 5] = [
+    Bucket {
+        name: "exact",
+        label: "Exact",
+        max: 0.0,
+    },
+    Bucket {
+        name: "low",
+        label: "≤ 10%",
+        max: 0.1,
+    },
+    Bucket {
+        name: "medium",
+        label: "10 – 25%",
+        max: 0.25,
+    },
+    Bucket {
+        name: "high",
+        label: "25 – 50%",
+        max: 0.5,
+    },
+    Bucket {
+        name: "very-high",
+        label: "> 50%",
+        max: f64::INFINITY,
+    },
+];
+
+/// Index of the bucket holding a word error rate.
+pub fn bucket(wer: f64) -> usize {
+    BUCKETS
+        .iter()
+        .position(|bucket| wer <= bucket.max)
+        .unwrap_or(BUCKETS.len() - 1)
+}
+
+/// Word-level edit distance between a reference and a hypothesis, and the number of
+/// reference words. Words are compared case-insensitively, ignoring the punctuation
+/// around them.
+pub fn word_errors(reference: &str, hypothesis: &str) -> (usize, usize) {
+    let words = |text: &str| -> Vec<String> {
+        text.split_whitespace()
+            .map(|word| {
+                word.trim_matches(|c: char| !c.is_alphanumeric())
+                    .to_lowercase()
+            })
+            .filter(|word| !word.is_empty())
+            .collect()
+    };
+    let (reference, hypothesis) = (words(reference), words(hypothesis));
+
+    // previous[j] = distance between the reference words so far and hypothesis[..j].
+    let mut previous: Vec<usize> = (0..=hypothesis.len()).collect();
+    for (i, word) in reference.iter().enumerate() {
+        let mut current = vec![i + 1; hypothesis.len() + 1];
+        for (j, other) in hypothesis.iter().enumerate() {
+            let substitution = previous[j] + usize::from(word != other);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    (previous[hypothesis.len()], reference.len())
+}
+
+/// Word error rate of a hypothesis; an empty reference counts every hypothesis word as
+/// an error.
+pub fn word_error_rate(reference: &str, hypothesis: &str) -> f64 {
+    let (errors, words) = word_errors(reference, hypothesis);
+    errors as f64 / words.max(1) as f64
+}
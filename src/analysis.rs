@@ -0,0 +1,367 @@
+use crate::{
+    dsp,
+    events::{self, AudioEvent},
+    row_cache::RowCache,
+    wav::Wav,
+};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// Number of clips whose analysis is kept in memory.
+const CACHE_CAPACITY: usize = 4096;
+
+/// Peak level at or above which a clip is considered clipped.
+const CLIP_PEAK_DBFS: f32 = -0.1;
+/// Consecutive full-scale samples that indicate hard clipping.
+const CLIP_RUN_LENGTH: usize = 3;
+/// Integrated loudness under which a clip is flagged as too quiet.
+const LOW_LOUDNESS_LUFS: f32 = -35.0;
+/// Frame length of the noise floor estimate, in seconds.
+const NOISE_FRAME_SECONDS: f64 = 0.02;
+/// The noise floor is this percentile of the frame levels.
+const NOISE_PERCENTILE: f64 = 0.1;
+/// Frames more than this above the noise floor count as speech, in dB.
+const SPEECH_MARGIN_DB: f32 = 6.0;
+
+/// Level measurements for a single clip.
+#[derive(Clone, Copy, Debug)]
+pub struct LevelAnalysis {
+    pub peak_dbfs: f32,
+    /// Integrated loudness per ITU-R BS.1770 (gated).
+    pub loudness_lufs: f32,
+    /// Number of runs of consecutive full-scale samples.
+    pub clipped_runs: usize,
+    /// Level of the quietest frames, ignoring digital silence.
+    pub noise_floor_dbfs: f32,
+    /// Level of the speech frames over the noise floor, in dB; 0 without speech.
+    pub snr_db: f32,
+    /// Fraction of the frames that are speech, see [`SPEECH_MARGIN_DB`].
+    pub speech_ratio: f32,
+}
+
+/// Traffic-light classification of a clip's level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevelStatus {
+    Ok,
+    Low,
+    Clipping,
+}
+
+impl LevelAnalysis {
+    pub fn status(&self) -> LevelStatus {
+        if self.clipped_runs > 0 || self.peak_dbfs >= CLIP_PEAK_DBFS {
+            LevelStatus::Clipping
+        } else if self.loudness_lufs < LOW_LOUDNESS_LUFS {
+            LevelStatus::Low
+        } else {
+            LevelStatus::Ok
+        }
+    }
+
+    /// Renders a small colored badge for the table.
+    pub fn badge(&self) -> String {
+        let (label, class) = match self.status() {
+            LevelStatus::Ok => (
+                "OK",
+                "bg-green-100 text-green-800 dark:bg-green-900 dark:text-green-200",
+            ),
+            LevelStatus::Low => (
+                "Low",
+                "bg-yellow-100 text-yellow-800 dark:bg-yellow-900 dark:text-yellow-200",
+            ),
+            LevelStatus::Clipping => (
+                "Clip",
+                "bg-red-100 text-red-800 dark:bg-red-900 dark:text-red-200",
+            ),
+        };
+        format!(
+            r#"<span class="inline-block px-2 py-0.5 rounded text-xs font-semibold {}" title="Peak {:.1} dBFS, loudness {:.1} LUFS, noise floor {:.1} dBFS, {} clipped runs">{}</span>"#,
+            class,
+            self.peak_dbfs,
+            self.loudness_lufs,
+            self.noise_floor_dbfs,
+            self.clipped_runs,
+            label
+        )
+    }
+}
+
+/// Computes level measurements for decoded audio.
+pub fn analyze_levels(wav: &Wav) -> LevelAnalysis {
+    let peak = dsp::peak(&wav.samples);
+
+    let mut clipped_runs = 0;
+    let mut run = 0;
+    for &sample in &wav.samples {
+        if sample.abs() >= 0.999 {
+            run += 1;
+            if run == CLIP_RUN_LENGTH {
+                clipped_runs += 1;
+            }
+        } else {
+            run = 0;
+        }
+    }
+
+    let noise_floor_dbfs = noise_floor(wav);
+    let (snr_db, speech_ratio) = speech_activity(wav, noise_floor_dbfs);
+    LevelAnalysis {
+        peak_dbfs: dsp::to_dbfs(peak),
+        loudness_lufs: integrated_loudness(wav),
+        clipped_runs,
+        noise_floor_dbfs,
+        snr_db,
+        speech_ratio,
+    }
+}
+
+/// Levels of the 20 ms frames of a clip, in dBFS.
+fn frame_levels(wav: &Wav) -> Vec<f32> {
+    let frame = ((NOISE_FRAME_SECONDS * wav.sample_rate as f64) as usize).max(1)
+        * wav.channels.max(1) as usize;
+    wav.samples
+        .chunks(frame)
+        .map(|chunk| dsp::to_dbfs(dsp::rms(chunk)))
+        .collect()
+}
+
+/// Signal-to-noise ratio and speech ratio of a clip: frames more than
+/// [`SPEECH_MARGIN_DB`] above the noise floor are speech, and their mean power is
+/// compared with the noise floor.
+fn speech_activity(wav: &Wav, noise_floor_dbfs: f32) -> (f32, f32) {
+    let levels = frame_levels(wav);
+    let speech: Vec<f32> = levels
+        .iter()
+        .copied()
+        .filter(|&db| db > noise_floor_dbfs + SPEECH_MARGIN_DB)
+        .collect();
+    if speech.is_empty() {
+        return (0.0, 0.0);
+    }
+    let power = speech
+        .iter()
+        .map(|&db| dsp::from_db(db).powi(2))
+        .sum::<f32>()
+        / speech.len() as f32;
+    (
+        dsp::to_dbfs(power.sqrt()) - noise_floor_dbfs,
+        speech.len() as f32 / levels.len() as f32,
+    )
+}
+
+/// Estimates the background noise level from the quietest 20 ms frames, in dBFS.
+///
+/// Frames of digital silence (padding, muted sections) are skipped, since they say
+/// nothing about the recording; a clip made only of them gets -120 dBFS.
+pub fn noise_floor(wav: &Wav) -> f32 {
+    let mut levels: Vec<f32> = frame_levels(wav)
+        .into_iter()
+        .filter(|&db| db > -120.0)
+        .collect();
+    if levels.is_empty() {
+        return -120.0;
+    }
+    levels.sort_by(f32::total_cmp);
+    levels[((levels.len() - 1) as f64 * NOISE_PERCENTILE).round() as usize]
+}
+
+/// Second-order IIR section in direct form I.
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+}
+
+impl Biquad {
+    fn process(&self, input: &[f64]) -> Vec<f64> {
+        let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+        input
+            .iter()
+            .map(|&x| {
+                let y = self.b[0] * x + self.b[1] * x1 + self.b[2] * x2
+                    - self.a[1] * y1
+                    - self.a[2] * y2;
+                x2 = x1;
+                x1 = x;
+                y2 = y1;
+                y1 = y;
+                y
+            })
+            .collect()
+    }
+}
+
+/// K-weighting filters (high shelf followed by high pass) for an arbitrary sample rate.
+fn k_weighting(sample_rate: f64) -> [Biquad; 2] {
+    use std::f64::consts::PI;
+
+    // Stage 1: high shelf.
+    let (gain_db, q, fc) = (3.999_843_853_97, 0.707_175_236_955, 1_681.974_450_955_533);
+    let a = 10f64.powf(gain_db / 40.0);
+    let w0 = 2.0 * PI * fc / sample_rate;
+    let alpha = w0.sin() / (2.0 * q);
+    let cos = w0.cos();
+    let a0 = (a + 1.0) - (a - 1.0) * cos + 2.0 * a.sqrt() * alpha;
+    let shelf = Biquad {
+        b: [
+            a * ((a + 1.0) + (a - 1.0) * cos + 2.0 * a.sqrt() * alpha) / a0,
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos) / a0,
+            a * ((a + 1.0) + (a - 1.0) * cos - 2.0 * a.sqrt() * alpha) / a0,
+        ],
+        a: [
+            1.0,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos) / a0,
+            ((a + 1.0) - (a - 1.0) * cos - 2.0 * a.sqrt() * alpha) / a0,
+        ],
+    };
+
+    // Stage 2: high pass.
+    let (q, fc) = (0.500_327_037_323_877_3, 38.135_470_876_139_82);
+    let w0 = 2.0 * PI * fc / sample_rate;
+    let alpha = w0.sin() / (2.0 * q);
+    let cos = w0.cos();
+    let a0 = 1.0 + alpha;
+    let high_pass = Biquad {
+        b: [
+            (1.0 + cos) / 2.0 / a0,
+            -(1.0 + cos) / a0,
+            (1.0 + cos) / 2.0 / a0,
+        ],
+        a: [1.0, -2.0 * cos / a0, (1.0 - alpha) / a0],
+    };
+
+    [shelf, high_pass]
+}
+
+/// Gated integrated loudness (ITU-R BS.1770-4), in LUFS.
+pub fn integrated_loudness(wav: &Wav) -> f32 {
+    const SILENCE: f32 = -120.0;
+
+    let channels = wav.channels as usize;
+    let frames = wav.samples.len() / channels;
+    let rate = wav.sample_rate as f64;
+    let block = (0.4 * rate) as usize;
+    let step = (0.1 * rate) as usize;
+    if block == 0 || step == 0 || frames < block {
+        return SILENCE;
+    }
+
+    let [shelf, high_pass] = k_weighting(rate);
+    let weighted: Vec<Vec<f64>> = (0..channels)
+        .map(|ch| {
+            let channel: Vec<f64> = wav
+                .samples
+                .iter()
+                .skip(ch)
+                .step_by(channels)
+                .map(|&s| s as f64)
+                .collect();
+            high_pass.process(&shelf.process(&channel))
+        })
+        .collect();
+
+    // Mean square per 400 ms block with 75 % overlap, summed over channels.
+    let blocks: Vec<f64> = (0..=(frames - block) / step)
+        .map(|i| {
+            let start = i * step;
+            weighted
+                .iter()
+                .map(|ch| {
+                    ch[start..start + block].iter().map(|s| s * s).sum::<f64>() / block as f64
+                })
+                .sum()
+        })
+        .collect();
+
+    let loudness = |ms: f64| -0.691 + 10.0 * ms.log10();
+    let gated_mean = |threshold: f64| {
+        let kept: Vec<f64> = blocks
+            .iter()
+            .copied()
+            .filter(|&ms| ms > 0.0 && loudness(ms) > threshold)
+            .collect();
+        (!kept.is_empty()).then(|| kept.iter().sum::<f64>() / kept.len() as f64)
+    };
+
+    let Some(absolute) = gated_mean(-70.0) else {
+        return SILENCE;
+    };
+    let relative_threshold = loudness(absolute) - 10.0;
+    gated_mean(relative_threshold)
+        .map(|ms| loudness(ms) as f32)
+        .unwrap_or(SILENCE)
+}
+
+/// Caches per-clip analysis keyed by the extracted WAV path, keeping the
+/// [`CACHE_CAPACITY`] most recently computed clips.
+#[derive(Clone)]
+pub struct AnalysisCache {
+    levels: Arc<RowCache<PathBuf, Option<LevelAnalysis>>>,
+    events: Arc<RowCache<PathBuf, Option<AudioEvent>>>,
+    /// Clips queued by [`AnalysisCache::precompute`] and not analyzed yet.
+    pending: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl Default for AnalysisCache {
+    fn default() -> Self {
+        Self {
+            levels: Arc::new(RowCache::with_capacity(CACHE_CAPACITY)),
+            events: Arc::new(RowCache::with_capacity(CACHE_CAPACITY)),
+            pending: Arc::default(),
+        }
+    }
+}
+
+impl AnalysisCache {
+    /// Returns the level analysis for a clip, computing it on first access.
+    ///
+    /// Clips that cannot be decoded are cached as `None`.
+    pub fn levels(&self, wav_path: &Path) -> Option<LevelAnalysis> {
+        *self.levels.get_or_insert_with(wav_path.to_path_buf(), || {
+            decode(wav_path).map(|wav| analyze_levels(&wav))
+        })
+    }
+
+    /// Returns the level analysis for a clip if it has already been computed.
+    pub fn cached_levels(&self, wav_path: &Path) -> Option<LevelAnalysis> {
+        self.levels.get(wav_path).and_then(|levels| *levels)
+    }
+
+    /// Computes the level analysis of the clips on a background thread, so pages can
+    /// show it from the cache instead of decoding clips while they render.
+    pub fn precompute(&self, wav_paths: impl IntoIterator<Item = PathBuf>) {
+        let queued: Vec<PathBuf> = {
+            let mut pending = self.pending.lock().unwrap();
+            wav_paths
+                .into_iter()
+                .filter(|path| self.levels.get(path).is_none() && pending.insert(path.clone()))
+                .collect()
+        };
+        if queued.is_empty() {
+            return;
+        }
+        let cache = self.clone();
+        thread::spawn(move || {
+            for path in queued {
+                cache.levels(&path);
+                cache.pending.lock().unwrap().remove(&path);
+            }
+        });
+    }
+
+    /// Returns the audio event tag of a clip, computing it on first access.
+    pub fn events(&self, wav_path: &Path) -> Option<AudioEvent> {
+        *self.events.get_or_insert_with(wav_path.to_path_buf(), || {
+            decode(wav_path).map(|wav| events::classify(&wav.mono(), wav.sample_rate))
+        })
+    }
+}
+
+fn decode(wav_path: &Path) -> Option<Wav> {
+    std::fs::read(wav_path)
+        .ok()
+        .and_then(|bytes| Wav::decode(&bytes).ok())
+}
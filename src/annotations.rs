@@ -0,0 +1,299 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::broadcast;
+
+/// Reviewer annotations attached to a single row.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RowAnnotation {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub flagged: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reviewed: bool,
+    /// Every edit of the transcription, oldest first; the last one is current.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<Revision>,
+    /// Every ASR re-transcription of the row, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hypotheses: Vec<Hypothesis>,
+    /// Reviewer discussion of the row, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<Comment>,
+}
+
+/// A saved version of an edited transcription.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Revision {
+    pub text: String,
+    /// Unix time in seconds.
+    pub timestamp: u64,
+}
+
+/// A transcription produced by an ASR model.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Hypothesis {
+    pub model: String,
+    pub text: String,
+    /// Unix time in seconds.
+    pub timestamp: u64,
+}
+
+/// A comment on a row, either starting a thread or replying to one.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Comment {
+    pub text: String,
+    /// Unix time in seconds.
+    pub timestamp: u64,
+    /// Logged-in user who wrote it, when OIDC login is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Index of the comment starting the thread this one replies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<usize>,
+}
+
+impl RowAnnotation {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Version of the transcription, the number of revisions: an edit based on an
+    /// older version conflicts with the ones saved since.
+    pub fn version(&self) -> usize {
+        self.revisions.len()
+    }
+
+    /// The corrected transcription, if the row was edited.
+    pub fn transcription(&self) -> Option<&str> {
+        self.revisions.last().map(|revision| revision.text.as_str())
+    }
+
+    /// Records `text` as the new transcription unless it equals the current one.
+    ///
+    /// Restores and undos are recorded the same way, so history is never rewritten.
+    pub fn revise(&mut self, original: &str, text: String) {
+        if self.transcription().unwrap_or(original) != text {
+            self.revisions.push(Revision {
+                text,
+                timestamp: now(),
+            });
+        }
+    }
+
+    /// Records the output of an ASR model, keeping the earlier ones for comparison.
+    pub fn add_hypothesis(&mut self, model: &str, text: String) {
+        self.hypotheses.push(Hypothesis {
+            model: model.to_string(),
+            text,
+            timestamp: now(),
+        });
+    }
+
+    /// Adds a comment, replying to the thread of comment `reply_to` if given. Replies to
+    /// a reply join the same thread, so threads stay one level deep.
+    pub fn add_comment(&mut self, text: &str, user: Option<String>, reply_to: Option<usize>) {
+        let reply_to = reply_to
+            .filter(|&index| index < self.comments.len())
+            .map(|index| self.comments[index].reply_to.unwrap_or(index));
+        self.comments.push(Comment {
+            text: text.trim().replace("\r\n", "\n"),
+            timestamp: now(),
+            user,
+            reply_to,
+        });
+    }
+}
+
+/// Current Unix time in seconds.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Formats a Unix timestamp as `YYYY-MM-DD HH:MM:SS` UTC.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let (year, month, day) = civil_date(timestamp);
+    let secs = timestamp % 86_400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// The UTC `(year, month, day)` of a Unix timestamp.
+pub fn civil_date(timestamp: u64) -> (i64, i64, i64) {
+    let days = timestamp / 86_400;
+    // Civil-from-days, see https://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Annotations per Parquet file and row index.
+type Annotations = BTreeMap<String, BTreeMap<usize, RowAnnotation>>;
+
+/// Rows of a file changed by one update, announced to the live views.
+#[derive(Clone, Debug)]
+pub struct Change {
+    pub file: String,
+    pub rows: Vec<usize>,
+}
+
+/// Annotation store persisted as a JSON file, rewritten on every change.
+///
+/// The source Parquet files are never modified.
+#[derive(Clone)]
+pub struct AnnotationStore {
+    path: PathBuf,
+    rows: Arc<Mutex<Annotations>>,
+    /// Incremented on every change, so derived data can tell when it is stale.
+    version: Arc<AtomicU64>,
+    changes: broadcast::Sender<Change>,
+}
+
+impl AnnotationStore {
+    /// Opens the store at `path`, starting empty if the file does not exist yet.
+    pub fn open(path: PathBuf) -> anyhow::Result<Self> {
+        let rows = if path.is_file() {
+            let data =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            serde_json::from_slice(&data)
+                .with_context(|| format!("failed to parse {}", path.display()))?
+        } else {
+            Annotations::new()
+        };
+        Ok(Self {
+            path,
+            rows: Arc::new(Mutex::new(rows)),
+            version: Arc::default(),
+            changes: broadcast::channel(256).0,
+        })
+    }
+
+    /// Returns the annotation of a row (empty when it has none).
+    pub fn get(&self, filename: &str, row: usize) -> RowAnnotation {
+        self.rows
+            .lock()
+            .unwrap()
+            .get(filename)
+            .and_then(|rows| rows.get(&row))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Relaxed)
+    }
+
+    /// Receives every later change; slow receivers miss the oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<Change> {
+        self.changes.subscribe()
+    }
+
+    /// Returns all annotations of a file.
+    pub fn file(&self, filename: &str) -> BTreeMap<usize, RowAnnotation> {
+        self.rows
+            .lock()
+            .unwrap()
+            .get(filename)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Applies `change` to each of `rows` and saves the store.
+    pub fn update(
+        &self,
+        filename: &str,
+        rows: impl IntoIterator<Item = usize>,
+        mut change: impl FnMut(&mut RowAnnotation),
+    ) -> anyhow::Result<()> {
+        let mut annotations = self.rows.lock().unwrap();
+        let file = annotations.entry(filename.to_string()).or_default();
+        let mut changed = Vec::new();
+        for row in rows {
+            changed.push(row);
+            let annotation = file.entry(row).or_default();
+            change(annotation);
+            if annotation.is_empty() {
+                file.remove(&row);
+            }
+        }
+        if file.is_empty() {
+            annotations.remove(filename);
+        }
+        self.version.fetch_add(1, Ordering::Relaxed);
+        self.save(&annotations)?;
+        // Without live views there are no receivers, which is not an error.
+        let _ = self.changes.send(Change {
+            file: filename.to_string(),
+            rows: changed,
+        });
+        Ok(())
+    }
+
+    /// Applies `change` to one row and saves the store, all under the lock, so `change`
+    /// sees the row as it is saved on top of. When `change` fails it must leave the row
+    /// as it was, and nothing is saved.
+    pub fn try_update<T, E>(
+        &self,
+        filename: &str,
+        row: usize,
+        change: impl FnOnce(&mut RowAnnotation) -> Result<T, E>,
+    ) -> anyhow::Result<Result<T, E>> {
+        let mut annotations = self.rows.lock().unwrap();
+        let file = annotations.entry(filename.to_string()).or_default();
+        let annotation = file.entry(row).or_default();
+        let result = change(annotation);
+        if annotation.is_empty() {
+            file.remove(&row);
+        }
+        if file.is_empty() {
+            annotations.remove(filename);
+        }
+        if result.is_err() {
+            return Ok(result);
+        }
+        self.version.fetch_add(1, Ordering::Relaxed);
+        self.save(&annotations)?;
+        let _ = self.changes.send(Change {
+            file: filename.to_string(),
+            rows: vec![row],
+        });
+        Ok(result)
+    }
+
+    /// Writes to a temp file and renames it into place, so a crash never leaves a
+    /// truncated store behind.
+    fn save(&self, annotations: &Annotations) -> anyhow::Result<()> {
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(annotations)?)
+            .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("failed to replace {}", self.path.display()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{blobs::BlobStore, wav};
+use ::zip::ZipArchive;
+use anyhow::{Context, anyhow, ensure};
+use polars::prelude::*;
+use std::{
+    fs::{self, File},
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+/// Metadata tables looked up at the root of an archive, in order.
+const METADATA_FILES: [&str; 2] = ["metadata.csv", "metadata.jsonl"];
+/// Column of the metadata table naming the audio entry of each row, as in the
+/// Hugging Face `audiofolder` layout.
+pub const FILE_NAME_COLUMN: &str = "file_name";
+/// Manifest column with the uncompressed size of each audio entry, in bytes.
+pub const SIZE_COLUMN: &str = "audio_size";
+/// Largest archive entry read, against zip bombs and forged entry sizes.
+const MAX_ENTRY_BYTES: u64 = 1 << 30;
+/// Accepted names for the transcription column, renamed to `transcription`.
+const TRANSCRIPTION_COLUMNS: [&str; 3] = ["transcription", "text", "sentence"];
+
+/// Whether a data folder entry is a zip dataset rather than a Parquet file.
+pub fn is_archive(filename: &str) -> bool {
+    filename.ends_with(".zip")
+}
+
+/// Builds the manifest of a zip dataset: its metadata table, with `transcription`,
+/// `duration` and audio size columns, written as a Parquet file without any audio.
+///
+/// Durations missing from the metadata are computed by decoding the WAV entries once;
+/// entries that do not decode, such as other formats, get a null duration.
+pub fn build_manifest(archive_path: &Path, manifest: &Path) -> anyhow::Result<()> {
+    let mut archive = open(archive_path)?;
+    let (name, bytes) = METADATA_FILES
+        .iter()
+        .find_map(|name| {
+            read_entry(&mut archive, name)
+                .ok()
+                .map(|bytes| (name, bytes))
+        })
+        .ok_or_else(|| anyhow!("no {} in the archive", METADATA_FILES.join(" or ")))?;
+    let mut df = if name.ends_with(".csv") {
+        CsvReadOptions::default()
+            .with_has_header(true)
+            .with_infer_schema_length(None)
+            .into_reader_with_file_handle(Cursor::new(bytes))
+            .finish()
+    } else {
+        JsonReader::new(Cursor::new(bytes))
+            .with_json_format(JsonFormat::JsonLines)
+            .finish()
+    }
+    .with_context(|| format!("failed to parse {}", name))?;
+
+    let entries = df
+        .column(FILE_NAME_COLUMN)
+        .with_context(|| format!("{} has no {} column", name, FILE_NAME_COLUMN))?
+        .cast(&DataType::String)?;
+    let entries: Vec<String> = entries
+        .str()?
+        .into_iter()
+        .map(|entry| entry.unwrap_or_default().to_string())
+        .collect();
+
+    if !df.schema().contains("transcription") {
+        let column = TRANSCRIPTION_COLUMNS
+            .iter()
+            .find(|column| df.schema().contains(column))
+            .with_context(|| format!("{} has no transcription column", name))?;
+        df.rename(column, "transcription".into())?;
+    }
+    if !df.schema().contains("duration") {
+        let durations: Vec<Option<f64>> = entries
+            .iter()
+            .map(|entry| {
+                let bytes = read_entry(&mut archive, entry)?;
+                Ok(wav::Wav::decode(&bytes).ok().map(|wav| wav.duration()))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        df.with_column(Column::new("duration".into(), durations))?;
+    }
+    let sizes: Vec<Option<u64>> = entries
+        .iter()
+        .map(|entry| archive.by_name(entry).ok().map(|entry| entry.size()))
+        .collect();
+    df.with_column(Column::new(SIZE_COLUMN.into(), sizes))?;
+
+    if let Some(parent) = manifest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Concurrent requests may build the same manifest; readers only ever see a whole file.
+    let partial = manifest.with_extension("parquet.partial");
+    ParquetWriter::new(File::create(&partial)?).finish(&mut df)?;
+    fs::rename(&partial, manifest)?;
+    Ok(())
+}
+
+/// Extracts archive entries into the blob store, linked to the paired paths, skipping
+/// the ones already extracted.
+pub fn extract(
+    archive_path: &Path,
+    entries: &[(String, PathBuf)],
+    blobs: &BlobStore,
+) -> anyhow::Result<()> {
+    let mut missing = entries.iter().filter(|(_, out)| !out.is_file()).peekable();
+    if missing.peek().is_none() {
+        return Ok(());
+    }
+    let mut archive = open(archive_path)?;
+    for (entry, out) in missing {
+        blobs.write(&read_entry(&mut archive, entry)?, out)?;
+    }
+    Ok(())
+}
+
+/// Reads one archive entry as stored.
+pub fn read(archive_path: &Path, entry: &str) -> anyhow::Result<Vec<u8>> {
+    read_entry(&mut open(archive_path)?, entry)
+}
+
+fn open(path: &Path) -> anyhow::Result<ZipArchive<File>> {
+    let file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    ZipArchive::new(file).with_context(|| format!("{} is not a zip archive", path.display()))
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> anyhow::Result<Vec<u8>> {
+    let entry = archive
+        .by_name(name)
+        .with_context(|| format!("no {} in the archive", name))?;
+    let mut bytes = Vec::with_capacity(entry.size().min(MAX_ENTRY_BYTES) as usize);
+    // One byte more than allowed tells an oversized entry from one at the limit.
+    entry.take(MAX_ENTRY_BYTES + 1).read_to_end(&mut bytes)?;
+    ensure!(
+        bytes.len() as u64 <= MAX_ENTRY_BYTES,
+        "{} is larger than {} bytes",
+        name,
+        MAX_ENTRY_BYTES
+    );
+    Ok(bytes)
+}
@@ -0,0 +1,98 @@
+use anyhow::{Context, anyhow};
+use std::{path::Path, str::FromStr, sync::Arc};
+
+/// A speech recognition endpoint selectable on the row page, given as `NAME=URL`.
+#[derive(Clone, Debug)]
+pub struct AsrModel {
+    pub name: String,
+    pub url: String,
+}
+
+impl FromStr for AsrModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, url) = s
+            .split_once('=')
+            .ok_or_else(|| "expected NAME=URL".to_string())?;
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err(
+                "model names may only contain letters, digits, '-', '_' and '.'".to_string(),
+            );
+        }
+        if url.trim().is_empty() {
+            return Err(format!("model `{}` has no URL", name));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            url: url.trim().to_string(),
+        })
+    }
+}
+
+/// Client for the ASR models re-transcribing clips.
+///
+/// Each clip is POSTed as `audio/wav`; the response is either plain text or a JSON
+/// object holding the text under `field` (dots descend into nested objects).
+#[derive(Clone)]
+pub struct Transcriber {
+    client: reqwest::Client,
+    models: Arc<Vec<AsrModel>>,
+    field: String,
+}
+
+impl Transcriber {
+    pub fn new(models: Vec<AsrModel>, field: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            models: Arc::new(models),
+            field,
+        }
+    }
+
+    pub fn models(&self) -> &[AsrModel] {
+        &self.models
+    }
+
+    /// Transcribes a clip with the named model.
+    pub async fn transcribe(&self, model: &str, wav_path: &Path) -> anyhow::Result<String> {
+        let model = self
+            .models
+            .iter()
+            .find(|m| m.name == model)
+            .ok_or_else(|| anyhow!("unknown ASR model `{}`", model))?;
+        let bytes = tokio::fs::read(wav_path)
+            .await
+            .with_context(|| format!("failed to read {}", wav_path.display()))?;
+        let response = self
+            .client
+            .post(&model.url)
+            .header(reqwest::header::CONTENT_TYPE, "audio/wav")
+            .body(bytes)
+            .send()
+            .await?
+            .error_for_status()?;
+        parse_text(&response.text().await?, &self.field)
+    }
+}
+
+/// Extracts the transcription from a response body.
+fn parse_text(body: &str, field: &str) -> anyhow::Result<String> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(body) else {
+        return Ok(body.trim().to_string());
+    };
+    if let Some(text) = json.as_str() {
+        return Ok(text.trim().to_string());
+    }
+    field
+        .split('.')
+        .try_fold(&json, |value, key| value.get(key))
+        .and_then(|value| value.as_str())
+        .map(|text| text.trim().to_string())
+        .ok_or_else(|| anyhow!("response has no text `{}` field", field))
+}
@@ -0,0 +1,197 @@
+use crate::{annotations, stats};
+use anyhow::{Context, bail};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS downloads (
+        id INTEGER PRIMARY KEY,
+        timestamp INTEGER NOT NULL,
+        user TEXT,
+        kind TEXT NOT NULL,
+        file TEXT NOT NULL,
+        row INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS downloads_timestamp ON downloads (timestamp);
+";
+
+/// How a clip left the server.
+#[derive(Clone, Copy, Debug)]
+pub enum Kind {
+    /// Played in the browser.
+    Stream,
+    /// Saved as an attachment.
+    Download,
+    /// Part of a concatenated preview.
+    Concat,
+    /// Played with augmentation applied.
+    Augment,
+}
+
+impl Kind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Kind::Stream => "stream",
+            Kind::Download => "download",
+            Kind::Concat => "concat",
+            Kind::Augment => "augment",
+        }
+    }
+}
+
+/// A clip served to a user.
+#[derive(Serialize, Debug)]
+pub struct Download {
+    pub timestamp: u64,
+    /// Logged-in user, when OIDC login is enabled.
+    pub user: Option<String>,
+    pub kind: String,
+    pub file: String,
+    pub row: usize,
+}
+
+/// Which downloads an export holds; dates are `YYYY-MM-DD` in UTC and both inclusive.
+#[derive(Deserialize, Debug, Default)]
+pub struct Filter {
+    pub user: Option<String>,
+    pub file: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Every clip served, with who fetched it and when, persisted in SQLite for data
+/// licenses that require an audit trail.
+pub struct AuditLog {
+    connection: Mutex<Connection>,
+}
+
+impl AuditLog {
+    /// Opens the log at `path`, creating it if needed.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let connection =
+            Connection::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        connection
+            .execute_batch(SCHEMA)
+            .with_context(|| format!("failed to create the audit log in {}", path.display()))?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Records rows of a file served to `user` now.
+    pub fn record(
+        &self,
+        user: Option<&str>,
+        kind: Kind,
+        file: &str,
+        rows: &[usize],
+    ) -> anyhow::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO downloads (timestamp, user, kind, file, row)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for &row in rows {
+                insert.execute(params![
+                    timestamp as i64,
+                    user,
+                    kind.as_str(),
+                    file,
+                    row as i64
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Downloads matching a filter, oldest first.
+    pub fn query(&self, filter: &Filter) -> anyhow::Result<Vec<Download>> {
+        let from = filter.from.as_deref().map(day_start).transpose()?;
+        let to = filter
+            .to
+            .as_deref()
+            .map(|date| day_start(date).map(|start| start + 86_400))
+            .transpose()?;
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT timestamp, user, kind, file, row FROM downloads
+             WHERE (?1 IS NULL OR user = ?1) AND (?2 IS NULL OR file = ?2)
+               AND (?3 IS NULL OR timestamp >= ?3) AND (?4 IS NULL OR timestamp < ?4)
+             ORDER BY id",
+        )?;
+        let downloads = statement
+            .query_map(params![filter.user, filter.file, from, to], |row| {
+                Ok(Download {
+                    timestamp: row.get::<_, i64>(0)? as u64,
+                    user: row.get(1)?,
+                    kind: row.get(2)?,
+                    file: row.get(3)?,
+                    row: row.get::<_, i64>(4)? as usize,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(downloads)
+    }
+}
+
+/// Unix timestamp of midnight UTC starting a `YYYY-MM-DD` date.
+fn day_start(date: &str) -> anyhow::Result<i64> {
+    let parts: Vec<i64> = date
+        .split('-')
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .with_context(|| format!("invalid date `{}`, expected YYYY-MM-DD", date))?;
+    let [year, month, day] = parts[..] else {
+        bail!("invalid date `{}`, expected YYYY-MM-DD", date);
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        bail!("invalid date `{}`, expected YYYY-MM-DD", date);
+    }
+    // Days-from-civil, see https://howardhinnant.github.io/date_algorithms.html
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Ok((era * 146_097 + doe - 719_468) * 86_400)
+}
+
+/// Content type, file extension and body of the downloads in `format`.
+pub fn encode(
+    downloads: &[Download],
+    format: stats::Format,
+) -> (&'static str, &'static str, String) {
+    match format {
+        stats::Format::Json => (
+            "application/json",
+            "json",
+            serde_json::to_string_pretty(downloads).unwrap_or_default(),
+        ),
+        stats::Format::Csv => {
+            let mut out = String::from("time,user,kind,file,row\n");
+            for download in downloads {
+                out.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    annotations::format_timestamp(download.timestamp),
+                    stats::csv_field(download.user.as_deref().unwrap_or_default()),
+                    download.kind,
+                    stats::csv_field(&download.file),
+                    download.row
+                ));
+            }
+            ("text/csv", "csv", out)
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use anyhow::{Context, ensure};
+use rustfft::{FftPlanner, num_complex::Complex};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{dsp, wav::Wav};
+
+/// SNR used for a noise file given without `noise_snr`, in dB.
+const DEFAULT_NOISE_SNR: f32 = 10.0;
+
+/// Augmentations to apply, in the order speed -> pitch -> reverb -> noise.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AugmentParams {
+    /// Speed perturbation factor (changes tempo and pitch, like `sox speed`).
+    pub speed: Option<f32>,
+    /// Pitch shift in semitones, keeping the duration.
+    pub pitch: Option<f32>,
+    /// Additive noise at the given signal-to-noise ratio in dB; white noise unless
+    /// `noise` names a recording.
+    pub noise_snr: Option<f32>,
+    /// File of the `--noise-dir` to mix in instead of white noise.
+    pub noise: Option<String>,
+    /// Room impulse response of the `--rir-dir` to convolve the clip with.
+    pub rir: Option<String>,
+    /// Seed for the noise generator and the offset into noise files, so previews are
+    /// reproducible.
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// Recordings an augmentation uses, decoded at the rate of the signal.
+#[derive(Default)]
+pub struct Recordings {
+    /// The requested noise file.
+    pub noise: Option<Vec<f32>>,
+    /// The requested room impulse response.
+    pub rir: Option<Vec<f32>>,
+}
+
+/// Applies the requested augmentations to a mono signal.
+pub fn apply(
+    samples: &[f32],
+    params: &AugmentParams,
+    recordings: &Recordings,
+) -> anyhow::Result<Vec<f32>> {
+    let mut out = samples.to_vec();
+
+    if let Some(speed) = params.speed {
+        ensure!(
+            (0.25..=4.0).contains(&speed),
+            "speed must be between 0.25 and 4"
+        );
+        out = resample_linear(&out, speed);
+    }
+
+    if let Some(semitones) = params.pitch {
+        ensure!(
+            (-24.0..=24.0).contains(&semitones),
+            "pitch must be between -24 and 24 semitones"
+        );
+        let factor = 2f32.powf(semitones / 12.0);
+        // Stretch by the pitch factor, then resample back to the original length.
+        out = resample_linear(&time_stretch(&out, 1.0 / factor), factor);
+    }
+
+    if let Some(rir) = &recordings.rir {
+        out = reverberate(&out, rir);
+    }
+
+    let noise = recordings.noise.as_deref();
+    let snr_db = match noise {
+        Some(_) => Some(params.noise_snr.unwrap_or(DEFAULT_NOISE_SNR)),
+        None => params.noise_snr,
+    };
+    if let Some(snr_db) = snr_db {
+        ensure!(
+            (-20.0..=80.0).contains(&snr_db),
+            "noise_snr must be between -20 and 80 dB"
+        );
+        match noise {
+            Some(noise) => mix_noise(&mut out, noise, snr_db, params.seed),
+            None => add_noise(&mut out, snr_db, params.seed),
+        }
+    }
+
+    Ok(out)
+}
+
+/// Reads `samples` at `factor` times the original rate using linear interpolation.
+///
+/// A factor above 1 shortens the signal (and raises its pitch at the same sample rate).
+pub fn resample_linear(samples: &[f32], factor: f32) -> Vec<f32> {
+    if samples.is_empty() {
+        return Vec::new();
+    }
+    let out_len = (samples.len() as f64 / factor as f64).floor() as usize;
+    (0..out_len)
+        .map(|i| {
+            let pos = i as f64 * factor as f64;
+            let idx = pos.floor() as usize;
+            let frac = (pos - idx as f64) as f32;
+            let a = samples[idx.min(samples.len() - 1)];
+            let b = samples[(idx + 1).min(samples.len() - 1)];
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// WSOLA time stretch: `rate` above 1 speeds up, below 1 slows down, pitch unchanged.
+pub fn time_stretch(samples: &[f32], rate: f32) -> Vec<f32> {
+    const FRAME: usize = 1024;
+    const SYNTHESIS_HOP: usize = FRAME / 2;
+    const TOLERANCE: usize = FRAME / 4;
+
+    if samples.len() < FRAME * 2 {
+        return samples.to_vec();
+    }
+
+    let window: Vec<f32> = (0..FRAME)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / FRAME as f32).cos())
+        .collect();
+    let analysis_hop = SYNTHESIS_HOP as f32 * rate;
+    let frames = ((samples.len() - FRAME) as f32 / analysis_hop) as usize;
+
+    let mut out = vec![0.0f32; frames * SYNTHESIS_HOP + FRAME];
+    let mut previous = 0usize;
+    for frame in 0..frames {
+        let nominal = (frame as f32 * analysis_hop) as usize;
+        let start = if frame == 0 {
+            0
+        } else {
+            // Pick the offset that best continues the previously copied frame.
+            let natural = previous + SYNTHESIS_HOP;
+            let lo = nominal.saturating_sub(TOLERANCE);
+            let hi = (nominal + TOLERANCE).min(samples.len() - FRAME);
+            let len = SYNTHESIS_HOP.min(samples.len().saturating_sub(natural));
+            let score = |s: usize| -> f32 {
+                (0..len)
+                    .map(|k| samples[s + k] * samples[natural + k])
+                    .sum()
+            };
+            (lo..=hi)
+                .map(|s| (s, score(s)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(s, _)| s)
+                .unwrap_or(nominal)
+        };
+
+        let offset = frame * SYNTHESIS_HOP;
+        for k in 0..FRAME {
+            out[offset + k] += samples[start + k] * window[k];
+        }
+        previous = start;
+    }
+    out
+}
+
+/// Adds white Gaussian noise at the requested SNR.
+pub fn add_noise(samples: &mut [f32], snr_db: f32, seed: u64) {
+    let signal_rms = dsp::rms(samples);
+    if signal_rms <= 0.0 {
+        return;
+    }
+    let noise_rms = signal_rms / dsp::from_db(snr_db);
+
+    let mut rng = XorShift::new(seed);
+    for sample in samples.iter_mut() {
+        *sample += rng.gaussian() * noise_rms;
+    }
+}
+
+/// Mixes a noise recording in at the requested SNR, starting at an offset picked by the
+/// seed and looping it when it is shorter than the signal.
+pub fn mix_noise(samples: &mut [f32], noise: &[f32], snr_db: f32, seed: u64) {
+    let (signal_rms, noise_rms) = (dsp::rms(samples), dsp::rms(noise));
+    if signal_rms <= 0.0 || noise_rms <= 0.0 {
+        return;
+    }
+    let gain = signal_rms / dsp::from_db(snr_db) / noise_rms;
+
+    let offset = (XorShift::new(seed).next_u64() % noise.len() as u64) as usize;
+    for (sample, noise) in samples.iter_mut().zip(noise.iter().cycle().skip(offset)) {
+        *sample += noise * gain;
+    }
+}
+
+/// Convolves a signal with a room impulse response, simulating far-field recording.
+///
+/// The response is aligned on its direct path (its peak), so the clip is not delayed;
+/// the result keeps the length and the RMS level of the input.
+pub fn reverberate(samples: &[f32], rir: &[f32]) -> Vec<f32> {
+    let peak = rir
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+        .map_or(0, |(i, _)| i);
+    let rir = &rir[peak..];
+    if samples.is_empty() || rir.is_empty() {
+        return samples.to_vec();
+    }
+
+    let n = (samples.len() + rir.len() - 1).next_power_of_two();
+    let mut planner = FftPlanner::<f32>::new();
+    let (forward, inverse) = (planner.plan_fft_forward(n), planner.plan_fft_inverse(n));
+    let spectrum = |signal: &[f32]| {
+        let mut buffer: Vec<Complex<f32>> = signal
+            .iter()
+            .map(|&x| Complex::new(x, 0.0))
+            .chain(std::iter::repeat(Complex::default()))
+            .take(n)
+            .collect();
+        forward.process(&mut buffer);
+        buffer
+    };
+    let mut out: Vec<Complex<f32>> = spectrum(samples)
+        .iter()
+        .zip(spectrum(rir))
+        .map(|(a, b)| a * b)
+        .collect();
+    inverse.process(&mut out);
+
+    let mut out: Vec<f32> = out[..samples.len()]
+        .iter()
+        .map(|x| x.re / n as f32)
+        .collect();
+    let (before, after) = (dsp::rms(samples), dsp::rms(&out));
+    if after > 0.0 {
+        dsp::apply_gain(&mut out, before / after);
+    }
+    out
+}
+
+/// Folder of recordings used by augmentations: noise to mix into clips (`--noise-dir`,
+/// e.g. babble, street or music from the deployment environment) or room impulse
+/// responses to convolve them with (`--rir-dir`). Only WAV files directly in the folder
+/// are offered; they are decoded when used.
+pub struct SoundBank {
+    dir: PathBuf,
+    names: Vec<String>,
+}
+
+impl SoundBank {
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .with_context(|| format!("failed to read {}", dir.display()))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name.to_ascii_lowercase().ends_with(".wav"))
+            .collect();
+        names.sort();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            names,
+        })
+    }
+
+    /// File names of the recordings, sorted.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Decodes a recording to mono at `sample_rate`.
+    pub fn read(&self, name: &str, sample_rate: u32) -> anyhow::Result<Vec<f32>> {
+        ensure!(
+            self.names.iter().any(|known| known == name),
+            "unknown file `{}`",
+            name
+        );
+        let path = self.dir.join(name);
+        let wav = Wav::decode(&fs::read(&path)?)
+            .with_context(|| format!("failed to decode {}", path.display()))?;
+        Ok(resample_linear(
+            &wav.mono(),
+            wav.sample_rate as f32 / sample_rate as f32,
+        ))
+    }
+}
+
+/// Small xorshift64* generator; good enough for audible noise.
+pub struct XorShift(u64);
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        // Zero is a fixed point of xorshift, so mix in a constant.
+        Self(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform sample in `(0, 1]`.
+    pub fn uniform(&mut self) -> f32 {
+        ((self.next_u64() >> 40) as f32 + 1.0) / (1u64 << 24) as f32
+    }
+
+    /// Standard normal sample (Box-Muller).
+    pub fn gaussian(&mut self) -> f32 {
+        let (u1, u2) = (self.uniform(), self.uniform());
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+    }
+}
@@ -0,0 +1,349 @@
+use anyhow::{Context, anyhow, bail};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+
+/// Cookie holding the session id.
+pub const SESSION_COOKIE: &str = "dva_session";
+/// Sessions last one working day; there is no refresh.
+const SESSION_TTL: Duration = Duration::from_secs(12 * 3600);
+/// Time allowed between leaving for the provider and coming back.
+const LOGIN_TTL: Duration = Duration::from_secs(600);
+/// Clock difference with the provider tolerated when checking ID token times.
+const CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// OpenID Connect settings from the command line.
+pub struct OidcConfig {
+    pub issuer: String,
+    pub client_id: String,
+    /// Confidential clients authenticate with HTTP Basic; public clients rely on PKCE.
+    pub client_secret: Option<String>,
+    /// Callback URL registered with the provider, ending in `/auth/callback`.
+    pub redirect_url: String,
+    /// Claim listing the user's groups.
+    pub groups_claim: String,
+    /// Groups allowed to use this dataset root; empty allows every authenticated user.
+    pub allowed_groups: BTreeSet<String>,
+}
+
+#[derive(Deserialize)]
+struct Discovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    id_token: Option<String>,
+}
+
+struct PendingLogin {
+    verifier: String,
+    nonce: String,
+    next: String,
+    created: Instant,
+}
+
+/// A logged-in user.
+#[derive(Clone, Debug)]
+pub struct Session {
+    pub user: String,
+    /// Groups from the groups claim, for the access control lists.
+    pub groups: BTreeSet<String>,
+    expires: Instant,
+}
+
+/// Authorization code flow with PKCE against a single provider, with in-memory sessions.
+pub struct Oidc {
+    config: OidcConfig,
+    discovery: Discovery,
+    client: reqwest::Client,
+    pending: Mutex<HashMap<String, PendingLogin>>,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl Oidc {
+    /// Reads the provider metadata from `{issuer}/.well-known/openid-configuration`.
+    pub async fn discover(config: OidcConfig) -> anyhow::Result<Self> {
+        let client = reqwest::Client::new();
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            config.issuer.trim_end_matches('/')
+        );
+        let body = client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Failed to fetch {}", url))?
+            .bytes()
+            .await?;
+        let discovery = serde_json::from_slice(&body)
+            .with_context(|| format!("Invalid OpenID configuration at {}", url))?;
+        Ok(Self {
+            config,
+            discovery,
+            client,
+            pending: Mutex::new(HashMap::new()),
+            sessions: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Starts a login and returns the provider URL to redirect to.
+    ///
+    /// `next` is where the user returns afterwards; anything but a local path becomes `/`.
+    pub fn login_url(&self, next: &str) -> anyhow::Result<String> {
+        let next = if next.starts_with('/') && !next.starts_with("//") {
+            next
+        } else {
+            "/"
+        };
+        let state = random_token()?;
+        let nonce = random_token()?;
+        let verifier = random_token()?;
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+
+        let mut url = Url::parse(&self.discovery.authorization_endpoint)
+            .context("Invalid authorization endpoint")?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_url)
+            .append_pair("scope", "openid profile email")
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, login| login.created.elapsed() < LOGIN_TTL);
+        pending.insert(
+            state,
+            PendingLogin {
+                verifier,
+                nonce,
+                next: next.to_string(),
+                created: Instant::now(),
+            },
+        );
+        Ok(url.into())
+    }
+
+    /// Finishes a login from the provider callback.
+    ///
+    /// Returns the new session id and the path to return to; users outside the allowed
+    /// groups get an error and no session.
+    pub async fn complete(&self, code: &str, state: &str) -> anyhow::Result<(String, String)> {
+        let login = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(state)
+            .filter(|login| login.created.elapsed() < LOGIN_TTL)
+            .ok_or_else(|| anyhow!("Login expired or already used, please try again"))?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_url.as_str()),
+            ("code_verifier", login.verifier.as_str()),
+        ];
+        let mut request = self.client.post(&self.discovery.token_endpoint);
+        match &self.config.client_secret {
+            Some(secret) => request = request.basic_auth(&self.config.client_id, Some(secret)),
+            None => form.push(("client_id", self.config.client_id.as_str())),
+        }
+        let body = request
+            .form(&form)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .context("Token request failed")?
+            .bytes()
+            .await?;
+        let tokens: TokenResponse =
+            serde_json::from_slice(&body).context("Invalid token response")?;
+
+        // The ID token comes straight from the token endpoint over TLS, which OIDC accepts
+        // in place of a signature check for the code flow; the claims are still validated.
+        let mut claims = match &tokens.id_token {
+            Some(id_token) => {
+                let claims = id_token_claims(id_token)?;
+                self.validate(&claims, &login.nonce)?;
+                claims
+            }
+            None => Map::new(),
+        };
+        if !claims.contains_key(&self.config.groups_claim)
+            && let Some(endpoint) = &self.discovery.userinfo_endpoint
+        {
+            let body = self
+                .client
+                .get(endpoint)
+                .bearer_auth(&tokens.access_token)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .context("Userinfo request failed")?
+                .bytes()
+                .await?;
+            let userinfo: Map<String, Value> =
+                serde_json::from_slice(&body).context("Invalid userinfo response")?;
+            for (key, value) in userinfo {
+                claims.entry(key).or_insert(value);
+            }
+        }
+
+        let user = ["preferred_username", "email", "sub"]
+            .iter()
+            .find_map(|claim| claims.get(*claim).and_then(Value::as_str))
+            .ok_or_else(|| anyhow!("The provider did not identify the user"))?
+            .to_string();
+        let groups: BTreeSet<String> = match claims.get(&self.config.groups_claim) {
+            Some(Value::Array(values)) => values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            Some(Value::String(group)) => BTreeSet::from([group.clone()]),
+            _ => BTreeSet::new(),
+        };
+        if !self.config.allowed_groups.is_empty() && self.config.allowed_groups.is_disjoint(&groups)
+        {
+            bail!(
+                "{} is not a member of a group allowed to view this dataset",
+                user
+            );
+        }
+
+        let id = random_token()?;
+        let mut sessions = self.sessions.lock().unwrap();
+        let now = Instant::now();
+        sessions.retain(|_, session| session.expires > now);
+        sessions.insert(
+            id.clone(),
+            Session {
+                user,
+                groups,
+                expires: now + SESSION_TTL,
+            },
+        );
+        Ok((id, login.next))
+    }
+
+    fn validate(&self, claims: &Map<String, Value>, nonce: &str) -> anyhow::Result<()> {
+        let issuer = claims.get("iss").and_then(Value::as_str).unwrap_or("");
+        if issuer.trim_end_matches('/') != self.config.issuer.trim_end_matches('/') {
+            bail!(
+                "ID token issued by {}, expected {}",
+                issuer,
+                self.config.issuer
+            );
+        }
+        let audience_ok = match claims.get("aud") {
+            Some(Value::String(aud)) => *aud == self.config.client_id,
+            Some(Value::Array(auds)) => auds
+                .iter()
+                .any(|aud| aud.as_str() == Some(&self.config.client_id)),
+            _ => false,
+        };
+        if !audience_ok {
+            bail!("ID token is not meant for this client");
+        }
+        if claims.get("nonce").and_then(Value::as_str) != Some(nonce) {
+            bail!("ID token nonce does not match the login");
+        }
+        // NumericDate claims are seconds since the epoch, possibly fractional.
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let skew = CLOCK_SKEW.as_secs_f64();
+        let expires = claims
+            .get("exp")
+            .and_then(Value::as_f64)
+            .context("ID token has no expiry")?;
+        if expires + skew < now {
+            bail!("ID token has expired");
+        }
+        if claims
+            .get("iat")
+            .and_then(Value::as_f64)
+            .is_some_and(|issued| issued > now + skew)
+        {
+            bail!("ID token is issued in the future");
+        }
+        Ok(())
+    }
+
+    /// The session named by the `Cookie` header, if it is still valid.
+    pub fn session(&self, cookies: Option<&str>) -> Option<Session> {
+        let id = cookie_value(cookies?, SESSION_COOKIE)?;
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(id)
+            .filter(|session| session.expires > Instant::now())
+            .cloned()
+    }
+
+    /// Ends the session named by the `Cookie` header.
+    pub fn logout(&self, cookies: Option<&str>) {
+        if let Some(id) = cookies.and_then(|cookies| cookie_value(cookies, SESSION_COOKIE)) {
+            self.sessions.lock().unwrap().remove(id);
+        }
+    }
+
+    /// `Set-Cookie` value for a session id; an empty id clears the cookie.
+    pub fn cookie(&self, id: &str) -> String {
+        let secure = if self.config.redirect_url.starts_with("https://") {
+            "; Secure"
+        } else {
+            ""
+        };
+        let max_age = if id.is_empty() {
+            0
+        } else {
+            SESSION_TTL.as_secs()
+        };
+        format!(
+            "{}={}; Path=/; HttpOnly; SameSite=Lax; Max-Age={}{}",
+            SESSION_COOKIE, id, max_age, secure
+        )
+    }
+}
+
+/// Decodes the payload of a JWT without checking its signature.
+fn id_token_claims(token: &str) -> anyhow::Result<Map<String, Value>> {
+    let payload = token
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| anyhow!("Malformed ID token"))?;
+    let bytes = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .context("Malformed ID token")?;
+    serde_json::from_slice(&bytes).context("Malformed ID token")
+}
+
+fn cookie_value<'a>(cookies: &'a str, name: &str) -> Option<&'a str> {
+    cookies.split(';').find_map(|cookie| {
+        let (key, value) = cookie.trim().split_once('=')?;
+        (key == name).then_some(value)
+    })
+}
+
+/// 256 random bits, URL-safe.
+fn random_token() -> anyhow::Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|err| anyhow!("No randomness available: {}", err))?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
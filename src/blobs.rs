@@ -0,0 +1,84 @@
+use anyhow::Context;
+use sha2::{Digest, Sha256};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Name of the store in the temp folder, which is kept when the temp folder is cleared at
+/// startup.
+pub const DIR_NAME: &str = "blobs";
+
+/// Counter giving concurrent writes of the same blob distinct temp files.
+static WRITE_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// Content-addressable store of extracted and transcoded audio.
+///
+/// Each clip is stored once under the SHA-256 of its bytes and hard-linked to the
+/// per-row paths in the temp folder, so clips duplicated across shards take the space of
+/// one, and their cached transcodes survive restarts and re-sharding of the dataset.
+#[derive(Clone, Debug)]
+pub struct BlobStore {
+    dir: PathBuf,
+}
+
+impl BlobStore {
+    /// The store of a temp folder.
+    pub fn new(tmp_folder: &Path) -> Self {
+        Self {
+            dir: tmp_folder.join(DIR_NAME),
+        }
+    }
+
+    /// Stores `bytes` unless a blob with the same content exists, and links `path` to it.
+    pub fn write(&self, bytes: &[u8], path: &Path) -> anyhow::Result<()> {
+        let blob = self.blob_path(&hash(bytes), "wav");
+        if !blob.is_file() {
+            let partial = blob.with_extension(format!(
+                "{}.partial",
+                WRITE_SEQ.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::write(&partial, bytes)
+                .with_context(|| format!("failed to write {}", partial.display()))?;
+            fs::rename(&partial, &blob)?;
+        }
+        link(&blob, path)
+    }
+
+    /// Blob holding a version of the clip at `path` derived from its content with
+    /// `settings`, e.g. the `opus` transcode at a bitrate; it may not exist yet.
+    pub fn derived(&self, path: &Path, settings: &str, extension: &str) -> anyhow::Result<PathBuf> {
+        let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let key = format!("{} {}", hash(&bytes), settings);
+        Ok(self.blob_path(&hash(key.as_bytes()), extension))
+    }
+
+    /// Path of a blob, sharded by the first two hex digits of its hash; creates the shard.
+    fn blob_path(&self, hash: &str, extension: &str) -> PathBuf {
+        let shard = self.dir.join(&hash[..2]);
+        // A failure shows up when the blob is written.
+        let _ = fs::create_dir_all(&shard);
+        shard.join(format!("{}.{}", hash, extension))
+    }
+}
+
+/// Makes `path` another name of `blob`, copying it where hard links are not supported.
+pub fn link(blob: &Path, path: &Path) -> anyhow::Result<()> {
+    match fs::hard_link(blob, path) {
+        Ok(()) => Ok(()),
+        // Another request linked it meanwhile.
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+        Err(_) => fs::copy(blob, path)
+            .map(drop)
+            .with_context(|| format!("failed to link {}", path.display())),
+    }
+}
+
+/// Hex SHA-256 of `bytes`.
+pub fn hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
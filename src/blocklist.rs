@@ -0,0 +1,49 @@
+use anyhow::Context;
+use std::{fs, path::Path};
+
+/// Words and phrases flagged in transcriptions, e.g. profanity to leave out of a
+/// family-safe subset.
+///
+/// Entries are matched case-insensitively against whole words, ignoring the punctuation
+/// around them; a multi-word entry matches the words in sequence.
+#[derive(Clone, Debug)]
+pub struct Blocklist {
+    entries: Vec<(String, Vec<String>)>,
+}
+
+impl Blocklist {
+    /// Reads one word or phrase per line; blank lines and lines starting with `#` are
+    /// skipped.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let entries = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| (line.to_string(), words(line)))
+            .filter(|(_, words)| !words.is_empty())
+            .collect();
+        Ok(Self { entries })
+    }
+
+    /// The entries found in a text, in blocklist order.
+    pub fn matches(&self, text: &str) -> Vec<&str> {
+        let text = words(text);
+        self.entries
+            .iter()
+            .filter(|(_, entry)| text.windows(entry.len()).any(|window| window == entry))
+            .map(|(line, _)| line.as_str())
+            .collect()
+    }
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+
+/// Upper bounds of the clip duration buckets, in seconds; the last bucket is open.
+pub const DURATION_BUCKETS: [f64; 6] = [1.0, 2.0, 5.0, 10.0, 20.0, 30.0];
+/// Speakers listed by name; the rest are only counted.
+const MAX_SPEAKERS: usize = 20;
+
+/// Clips and total duration of a language or speaker.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tally {
+    pub clips: usize,
+    pub duration: f64,
+}
+
+/// One file of the data folder, shown as a split of the dataset.
+#[derive(Clone, Debug)]
+pub struct Split {
+    /// File name without extension, e.g. `train`.
+    pub name: String,
+    /// Duration of every clip in seconds.
+    pub durations: Vec<f64>,
+    /// By language, when the file has the language column.
+    pub languages: Option<BTreeMap<String, Tally>>,
+    /// By speaker, when the file has the speaker column.
+    pub speakers: Option<BTreeMap<String, Tally>>,
+}
+
+impl Split {
+    fn duration(&self) -> f64 {
+        self.durations.iter().sum()
+    }
+}
+
+/// Tallies the clips of a file by the value of a column; missing values count as
+/// `unknown`.
+pub fn tally(values: &[Option<String>], durations: &[f64]) -> BTreeMap<String, Tally> {
+    let mut tallies: BTreeMap<String, Tally> = BTreeMap::new();
+    for (value, duration) in values.iter().zip(durations) {
+        let value = value.as_deref().map(str::trim).unwrap_or_default();
+        let tally = tallies
+            .entry(if value.is_empty() { "unknown" } else { value }.to_string())
+            .or_default();
+        tally.clips += 1;
+        tally.duration += duration;
+    }
+    tallies
+}
+
+/// The Hugging Face `size_categories` tag of a number of rows.
+fn size_category(rows: usize) -> &'static str {
+    match rows {
+        0..1_000 => "n<1K",
+        1_000..10_000 => "1K<n<10K",
+        10_000..100_000 => "10K<n<100K",
+        100_000..1_000_000 => "100K<n<1M",
+        1_000_000..10_000_000 => "1M<n<10M",
+        _ => "10M<n<100M",
+    }
+}
+
+fn hours(seconds: f64) -> String {
+    format!("{:.2}", seconds / 3600.0)
+}
+
+/// Escapes the characters that would break a Markdown table cell.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = format!(
+        "| {} |\n|{}\n",
+        header.join(" | "),
+        "---|".repeat(header.len())
+    );
+    for row in rows {
+        out.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+    out
+}
+
+/// Merges the tallies of every split that has them; `None` when no split does.
+fn merged(
+    splits: &[Split],
+    tallies: impl Fn(&Split) -> Option<&BTreeMap<String, Tally>>,
+) -> Option<BTreeMap<String, Tally>> {
+    let mut merged: Option<BTreeMap<String, Tally>> = None;
+    for split in splits {
+        for (value, tally) in tallies(split).into_iter().flatten() {
+            let total = merged
+                .get_or_insert_default()
+                .entry(value.clone())
+                .or_default();
+            total.clips += tally.clips;
+            total.duration += tally.duration;
+        }
+    }
+    merged
+}
+
+/// Tallies as table rows, most clips first.
+fn tally_rows(tallies: &BTreeMap<String, Tally>, limit: usize) -> Vec<Vec<String>> {
+    let mut sorted: Vec<(&String, &Tally)> = tallies.iter().collect();
+    sorted.sort_by_key(|(_, tally)| std::cmp::Reverse(tally.clips));
+    sorted
+        .into_iter()
+        .take(limit)
+        .map(|(value, tally)| {
+            vec![
+                format!("`{}`", cell(value)),
+                tally.clips.to_string(),
+                hours(tally.duration),
+            ]
+        })
+        .collect()
+}
+
+/// Markdown of a Hugging Face dataset card (`README.md`) describing the splits: YAML
+/// metadata, clip counts and hours, duration buckets, and the language and speaker
+/// distributions. The license and the prose sections are left as placeholders.
+pub fn render(name: &str, splits: &[Split]) -> String {
+    let rows: usize = splits.iter().map(|split| split.durations.len()).sum();
+    let languages = merged(splits, |split| split.languages.as_ref());
+    let speakers = merged(splits, |split| split.speakers.as_ref());
+
+    let mut out =
+        String::from("---\n# TODO: replace with the license of the dataset.\nlicense: other\n");
+    match &languages {
+        Some(languages) => {
+            out.push_str("language:\n");
+            for language in languages.keys().filter(|language| *language != "unknown") {
+                out.push_str(&format!("- {}\n", language));
+            }
+        }
+        None => out.push_str("# TODO: list the languages as ISO 639-1 codes.\nlanguage: []\n"),
+    }
+    out.push_str(&format!(
+        "pretty_name: {}\ntask_categories:\n- automatic-speech-recognition\nsize_categories:\n- {}\n---\n\n",
+        name,
+        size_category(rows)
+    ));
+
+    out.push_str(&format!("# {}\n\n## Dataset Summary\n\n", name));
+    out.push_str(&format!(
+        "{} clips, {} hours of audio in {} split{}.\n\nTODO: describe where the audio and the transcriptions come from and what they are for.\n\n",
+        rows,
+        hours(splits.iter().map(Split::duration).sum()),
+        splits.len(),
+        if splits.len() == 1 { "" } else { "s" }
+    ));
+
+    out.push_str("## Dataset Structure\n\n### Splits\n\n");
+    let split_rows: Vec<Vec<String>> = splits
+        .iter()
+        .map(|split| {
+            let count = split.durations.len();
+            let min = split
+                .durations
+                .iter()
+                .copied()
+                .fold(f64::INFINITY, f64::min);
+            let max = split.durations.iter().copied().fold(0.0, f64::max);
+            vec![
+                format!("`{}`", cell(&split.name)),
+                count.to_string(),
+                hours(split.duration()),
+                format!(
+                    "{:.2}",
+                    if count == 0 {
+                        0.0
+                    } else {
+                        split.duration() / count as f64
+                    }
+                ),
+                format!("{:.2}", if count == 0 { 0.0 } else { min }),
+                format!("{:.2}", max),
+            ]
+        })
+        .collect();
+    out.push_str(&table(
+        &["Split", "Clips", "Hours", "Mean (s)", "Min (s)", "Max (s)"],
+        &split_rows,
+    ));
+
+    out.push_str("\n### Clip Durations\n\n");
+    let mut header = vec!["Split".to_string()];
+    let mut lower = 0.0;
+    for upper in DURATION_BUCKETS {
+        header.push(format!("{}–{} s", lower, upper));
+        lower = upper;
+    }
+    header.push(format!("≥ {} s", lower));
+    let bucket_rows: Vec<Vec<String>> = splits
+        .iter()
+        .map(|split| {
+            let mut counts = [0usize; DURATION_BUCKETS.len() + 1];
+            for duration in &split.durations {
+                counts[DURATION_BUCKETS
+                    .iter()
+                    .position(|upper| duration < upper)
+                    .unwrap_or(DURATION_BUCKETS.len())] += 1;
+            }
+            std::iter::once(format!("`{}`", cell(&split.name)))
+                .chain(counts.iter().map(usize::to_string))
+                .collect()
+        })
+        .collect();
+    out.push_str(&table(
+        &header.iter().map(String::as_str).collect::<Vec<_>>(),
+        &bucket_rows,
+    ));
+
+    if let Some(languages) = &languages {
+        out.push_str("\n### Languages\n\n");
+        out.push_str(&table(
+            &["Language", "Clips", "Hours"],
+            &tally_rows(languages, usize::MAX),
+        ));
+    }
+
+    if let Some(speakers) = &speakers {
+        out.push_str("\n### Speakers\n\n");
+        let speaker_rows: Vec<Vec<String>> = splits
+            .iter()
+            .filter_map(|split| {
+                let speakers = split.speakers.as_ref()?;
+                let clips: Vec<usize> = speakers.values().map(|tally| tally.clips).collect();
+                Some(vec![
+                    format!("`{}`", cell(&split.name)),
+                    speakers.len().to_string(),
+                    clips.iter().min().unwrap_or(&0).to_string(),
+                    clips.iter().max().unwrap_or(&0).to_string(),
+                ])
+            })
+            .collect();
+        out.push_str(&table(
+            &["Split", "Speakers", "Fewest clips", "Most clips"],
+            &speaker_rows,
+        ));
+        out.push_str(&format!(
+            "\n{} speakers in total{}:\n\n",
+            speakers.len(),
+            if speakers.len() > MAX_SPEAKERS {
+                format!(", the {} with the most clips", MAX_SPEAKERS)
+            } else {
+                String::new()
+            }
+        ));
+        out.push_str(&table(
+            &["Speaker", "Clips", "Hours"],
+            &tally_rows(speakers, MAX_SPEAKERS),
+        ));
+    }
+
+    out.push_str(
+        "\n## Licensing Information\n\nTODO: state the license of the audio and the transcriptions, and any terms of use.\n\n## Citation Information\n\nTODO: add a BibTeX entry, if any.\n",
+    );
+    out
+}
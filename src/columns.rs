@@ -0,0 +1,194 @@
+use crate::html;
+use anyhow::{Context, bail};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// Badge colors, as Tailwind color names.
+const COLORS: [&str; 8] = [
+    "gray", "red", "orange", "yellow", "green", "blue", "purple", "pink",
+];
+
+/// How the values of a dataset column are shown in the file view and on the row page.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Renderer {
+    /// Escaped text.
+    Text,
+    /// A colored pill. Numbers take the color of the first threshold they are below,
+    /// strings the color mapped to them; anything else the `default` color.
+    Badge {
+        #[serde(default)]
+        thresholds: Vec<Threshold>,
+        #[serde(default)]
+        colors: BTreeMap<String, String>,
+        #[serde(default = "default_color")]
+        default: String,
+    },
+    /// A link opening in a new tab, to the value itself or to `href` with `{value}`
+    /// replaced by the URL-encoded value.
+    Link {
+        #[serde(default)]
+        href: Option<String>,
+    },
+    /// A number of seconds, or of milliseconds with `"unit": "ms"`, as `MM:SS.mmm`.
+    Duration {
+        #[serde(default)]
+        unit: DurationUnit,
+    },
+    /// Monospace text, for IDs and hashes.
+    Code,
+    /// Left out of the file view, the row page, the facets and the nested columns.
+    Hidden,
+}
+
+/// Upper bound of a badge color; the last threshold may leave out `below`.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Threshold {
+    #[serde(default)]
+    below: Option<f64>,
+    color: String,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DurationUnit {
+    #[default]
+    S,
+    Ms,
+}
+
+fn default_color() -> String {
+    "gray".to_string()
+}
+
+impl Renderer {
+    /// Colors the badge can take, to validate them when loading.
+    fn colors(&self) -> Vec<&str> {
+        match self {
+            Renderer::Badge {
+                thresholds,
+                colors,
+                default,
+            } => thresholds
+                .iter()
+                .map(|threshold| threshold.color.as_str())
+                .chain(colors.values().map(String::as_str))
+                .chain([default.as_str()])
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// HTML of a cell; nulls render as a dash.
+    pub fn render(&self, value: &Value) -> String {
+        let text = match value {
+            Value::Null => return "&mdash;".to_string(),
+            Value::String(s) => s.clone(),
+            value => value.to_string(),
+        };
+        match self {
+            Renderer::Text | Renderer::Hidden => html::escape(&text),
+            Renderer::Badge {
+                thresholds,
+                colors,
+                default,
+            } => {
+                let color = match value.as_f64() {
+                    Some(number) => thresholds
+                        .iter()
+                        .find(|threshold| threshold.below.is_none_or(|below| number < below))
+                        .map(|threshold| &threshold.color),
+                    None => colors.get(&text),
+                }
+                .unwrap_or(default);
+                format!(
+                    r#"<span class="px-2 rounded bg-{0}-100 text-{0}-800 dark:bg-{0}-900 dark:text-{0}-200">{1}</span>"#,
+                    color,
+                    html::escape(&text)
+                )
+            }
+            Renderer::Link { href } => {
+                let href = match href {
+                    Some(template) => template.replace("{value}", &html::url_encode(&text)),
+                    None => text.clone(),
+                };
+                if !(href.starts_with("https://") || href.starts_with("http://")) {
+                    return html::escape(&text);
+                }
+                format!(
+                    r#"<a href="{}" target="_blank" rel="noopener noreferrer" onclick="event.stopPropagation()" class="text-blue-600 dark:text-blue-400 hover:underline break-all">{}</a>"#,
+                    html::escape(&href),
+                    html::escape(&text)
+                )
+            }
+            Renderer::Duration { unit } => match value.as_f64() {
+                Some(number) => crate::format_duration(match unit {
+                    DurationUnit::S => number,
+                    DurationUnit::Ms => number / 1000.0,
+                }),
+                None => html::escape(&text),
+            },
+            Renderer::Code => format!(r#"<code class="text-sm">{}</code>"#, html::escape(&text)),
+        }
+    }
+}
+
+/// Renderers by column name, from `--column-renderers`.
+#[derive(Clone, Debug, Default)]
+pub struct Renderers {
+    columns: BTreeMap<String, Renderer>,
+}
+
+impl Renderers {
+    /// Reads a JSON object of column names to renderers, either a type name
+    /// (`"url": "link"`) or an object with the options of the type
+    /// (`"snr_db": {"type": "badge", "thresholds": [{"below": 10, "color": "red"}]}`).
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let data = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let specs: BTreeMap<String, Value> = serde_json::from_slice(&data)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        let mut columns = BTreeMap::new();
+        for (column, spec) in specs {
+            let spec = match spec {
+                Value::String(kind) => serde_json::json!({ "type": kind }),
+                spec => spec,
+            };
+            let renderer: Renderer = serde_json::from_value(spec).with_context(|| {
+                format!("invalid renderer of `{}` in {}", column, path.display())
+            })?;
+            if let Some(color) = renderer
+                .colors()
+                .into_iter()
+                .find(|color| !COLORS.contains(color))
+            {
+                bail!(
+                    "unknown color `{}` for `{}` in {}; use one of {}",
+                    color,
+                    column,
+                    path.display(),
+                    COLORS.join(", ")
+                );
+            }
+            columns.insert(column, renderer);
+        }
+        Ok(Self { columns })
+    }
+
+    pub fn get(&self, column: &str) -> Option<&Renderer> {
+        self.columns.get(column)
+    }
+
+    pub fn is_hidden(&self, column: &str) -> bool {
+        matches!(self.get(column), Some(Renderer::Hidden))
+    }
+
+    /// The configured columns that are shown, in name order.
+    pub fn shown(&self) -> impl Iterator<Item = &str> {
+        self.columns
+            .iter()
+            .filter(|(_, renderer)| !matches!(renderer, Renderer::Hidden))
+            .map(|(column, _)| column.as_str())
+    }
+}
@@ -0,0 +1,184 @@
+use anyhow::{Context, bail};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::process::Command;
+
+/// A named shell command runnable on a single row, e.g. `stats=sox {path} -n stats`.
+///
+/// `{path}` expands to the extracted WAV file (shell-quoted), `{file}` and `{row}`
+/// to the Parquet file name and row index.
+#[derive(Clone, Debug)]
+pub struct RowCommand {
+    pub name: String,
+    pub template: String,
+}
+
+impl FromStr for RowCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, template) = s
+            .split_once('=')
+            .ok_or_else(|| "expected NAME=COMMAND".to_string())?;
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err("command names may only contain letters, digits, '-' and '_'".to_string());
+        }
+        if template.trim().is_empty() {
+            return Err(format!("command `{}` is empty", name));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            template: template.to_string(),
+        })
+    }
+}
+
+/// Captured result of a command run.
+#[derive(Clone, Debug)]
+pub struct CommandOutput {
+    pub command_line: String,
+    /// Exit code, `None` when the process was killed by a signal.
+    pub status: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Last output per `(command name, clip)`.
+type OutputCache = Arc<Mutex<HashMap<(String, PathBuf), CommandOutput>>>;
+
+/// Runs the configured row commands, optionally caching their output per clip.
+#[derive(Clone)]
+pub struct CommandRunner {
+    commands: Arc<Vec<RowCommand>>,
+    timeout: Duration,
+    cache: Option<OutputCache>,
+}
+
+impl CommandRunner {
+    pub fn new(commands: Vec<RowCommand>, timeout: Duration, cache: bool) -> Self {
+        Self {
+            commands: Arc::new(commands),
+            timeout,
+            cache: cache.then(Arc::default),
+        }
+    }
+
+    pub fn commands(&self) -> &[RowCommand] {
+        &self.commands
+    }
+
+    /// Returns the cached output of `name` for a clip, if caching is enabled and it ran before.
+    pub fn cached(&self, name: &str, wav_path: &Path) -> Option<CommandOutput> {
+        let cache = self.cache.as_ref()?.lock().unwrap();
+        cache
+            .get(&(name.to_string(), wav_path.to_path_buf()))
+            .cloned()
+    }
+
+    /// Runs `name` on a clip through `sh -c`, killing it after the configured timeout.
+    pub async fn run(
+        &self,
+        name: &str,
+        wav_path: &Path,
+        filename: &str,
+        row: &str,
+    ) -> anyhow::Result<CommandOutput> {
+        let Some(command) = self.commands.iter().find(|c| c.name == name) else {
+            bail!("unknown command `{}`", name);
+        };
+        let command_line = expand(
+            &command.template,
+            &[
+                ("path", &wav_path.to_string_lossy()),
+                ("file", filename),
+                ("row", row),
+            ],
+        );
+
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(&command_line)
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(self.timeout, child)
+            .await
+            .with_context(|| format!("`{}` timed out after {:?}", name, self.timeout))?
+            .with_context(|| format!("failed to run `{}`", name))?;
+
+        let result = CommandOutput {
+            command_line,
+            status: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        };
+        if let Some(cache) = &self.cache {
+            cache
+                .lock()
+                .unwrap()
+                .insert((name.to_string(), wav_path.to_path_buf()), result.clone());
+        }
+        Ok(result)
+    }
+}
+
+/// Replaces the `{name}` placeholders of a template with their shell-quoted values in one
+/// pass, so placeholders inside a substituted value are left alone.
+fn expand(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let placeholder = rest[start + 1..].split_once('}').and_then(|(name, after)| {
+            values
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (value, after))
+        });
+        match placeholder {
+            Some((value, after)) => {
+                out.push_str(&shell_quote(value));
+                rest = after;
+            }
+            None => {
+                out.push('{');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Quotes `value` for POSIX `sh`.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_placeholders_once() {
+        let values = [
+            ("path", "/tmp/a b.wav"),
+            ("file", "x'{row}'.parquet"),
+            ("row", "$(id)"),
+        ];
+        assert_eq!(
+            expand("sox {path} -n stats # {file} {row} {other}", &values),
+            r"sox '/tmp/a b.wav' -n stats # 'x'\''{row}'\''.parquet' '$(id)' {other}"
+        );
+        assert_eq!(expand("echo {", &values), "echo {");
+    }
+}
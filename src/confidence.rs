@@ -0,0 +1,66 @@
+use polars::prelude::*;
+
+/// Lower-cased name fragments marking a column as a per-row confidence, as found in
+/// pseudo-labeled corpora (`confidence`, `asr_conf`, `lang_prob`, ...).
+const NAME_HINTS: [&str; 2] = ["conf", "prob"];
+/// Confidences below this are shown in red.
+pub const LOW: f64 = 0.5;
+/// Confidences below this, and at least [`LOW`], are shown in yellow; higher ones in green.
+pub const HIGH: f64 = 0.8;
+
+/// Names of the floating-point columns holding a confidence or probability.
+///
+/// Only columns whose values all lie within [0, 1] count, which leaves out log
+/// probabilities such as Whisper's `avg_logprob`.
+pub fn columns(df: &DataFrame) -> Vec<String> {
+    df.columns()
+        .iter()
+        .filter(|column| {
+            let name = column.name().to_lowercase();
+            column.dtype().is_float()
+                && NAME_HINTS.iter().any(|hint| name.contains(hint))
+                && within_unit_range(column)
+        })
+        .map(|column| column.name().to_string())
+        .collect()
+}
+
+/// Whether every non-null value of the column lies within [0, 1].
+fn within_unit_range(column: &Column) -> bool {
+    let series = column.as_materialized_series();
+    let (Ok(min), Ok(max)) = (series.min::<f64>(), series.max::<f64>()) else {
+        return false;
+    };
+    min.is_none_or(|min| min >= 0.0) && max.is_none_or(|max| max <= 1.0)
+}
+
+/// Reads a confidence cell; nulls and NaNs have no value.
+pub fn parse(value: AnyValue) -> Option<f64> {
+    value.extract::<f64>().filter(|value| !value.is_nan())
+}
+
+/// The value colored red, yellow or green by the [`LOW`] and [`HIGH`] thresholds.
+pub fn badge(value: Option<f64>) -> String {
+    let Some(value) = value else {
+        return "&mdash;".to_string();
+    };
+    let class = if value < LOW {
+        "bg-red-100 text-red-800 dark:bg-red-900 dark:text-red-200"
+    } else if value < HIGH {
+        "bg-yellow-100 text-yellow-800 dark:bg-yellow-900 dark:text-yellow-200"
+    } else {
+        "bg-green-100 text-green-800 dark:bg-green-900 dark:text-green-200"
+    };
+    format!(
+        r#"<span class="inline-block px-2 py-0.5 rounded text-xs font-semibold {}">{:.3}</span>"#,
+        class, value
+    )
+}
+
+/// Lowest confidence of a row, which the confidence filter compares to its bounds.
+pub fn lowest(confidences: &[(String, Option<f64>)]) -> Option<f64> {
+    confidences
+        .iter()
+        .filter_map(|(_, value)| *value)
+        .min_by(f64::total_cmp)
+}
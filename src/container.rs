@@ -0,0 +1,336 @@
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+/// A structure found in an audio file: a RIFF chunk, a FLAC metadata block, ...
+#[derive(Debug)]
+pub struct Item {
+    /// Byte offset of the structure in the file.
+    pub offset: usize,
+    pub name: String,
+    pub detail: String,
+}
+
+/// Names the container of an audio file from its magic bytes and lists its structures,
+/// for finding out why a clip does not play. Malformed sizes are reported, not trusted.
+pub fn describe(bytes: &[u8]) -> (&'static str, Vec<Item>) {
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE" {
+        ("RIFF/WAVE", riff_chunks(bytes))
+    } else if let Some(start) = flac_start(bytes) {
+        ("FLAC", flac_blocks(bytes, start))
+    } else if bytes.starts_with(b"OggS") {
+        ("Ogg", ogg_page(bytes))
+    } else if bytes.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        ("Matroska/WebM", Vec::new())
+    } else if bytes.starts_with(b"ID3") || bytes.get(..2).is_some_and(|b| b == [0xFF, 0xFB]) {
+        ("MP3", Vec::new())
+    } else {
+        ("unknown", Vec::new())
+    }
+}
+
+/// Bytes read from the start of a clip to tell its encoding; enough for the WAVE `fmt `
+/// chunk, FLAC STREAMINFO, the first Ogg page and a few MP3 frames.
+const HEADER_BYTES: u64 = 8192;
+
+/// Reads the start of a clip, skipping a leading ID3v2 tag (which may hold cover art).
+pub fn read_header(path: &Path) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let mut id3 = [0u8; 10];
+    let read = file.read(&mut id3)?;
+    if read == 10 && id3.starts_with(b"ID3") {
+        file.seek(SeekFrom::Start(10 + syncsafe(&id3[6..10]) as u64))?;
+    } else {
+        file.seek(SeekFrom::Start(0))?;
+    }
+    let mut header = Vec::new();
+    file.take(HEADER_BYTES).read_to_end(&mut header)?;
+    Ok(header)
+}
+
+/// Short name of the encoding of a clip from its header (see [`read_header`]), e.g.
+/// `16-bit PCM`, `32-bit float`, `MP3 VBR` or `Opus`.
+pub fn encoding(header: &[u8]) -> String {
+    match describe(header).0 {
+        "RIFF/WAVE" => wave_encoding(header),
+        "FLAC" => {
+            // STREAMINFO always comes first, right after the marker and block header.
+            let streaminfo = flac_start(header).and_then(|start| header.get(start + 8..start + 42));
+            match streaminfo {
+                Some(b) => format!("{}-bit FLAC", (((b[12] & 0x01) << 4) | (b[13] >> 4)) + 1),
+                None => "FLAC".to_string(),
+            }
+        }
+        "Ogg" => match ogg_page(header).first() {
+            Some(page) if page.detail.starts_with("Opus") => "Opus".to_string(),
+            Some(page) if page.detail == "unknown codec" => "Ogg".to_string(),
+            Some(page) => page.detail.clone(),
+            None => "Ogg".to_string(),
+        },
+        "Matroska/WebM" => "WebM/Matroska".to_string(),
+        _ => mp3_encoding(header).unwrap_or_else(|| "unknown".to_string()),
+    }
+}
+
+/// Sample format of a WAVE file, resolving the extensible sub-format.
+fn wave_encoding(header: &[u8]) -> String {
+    let mut pos = 12;
+    while pos + 8 <= header.len() {
+        let size = u32_le(header, pos + 4) as usize;
+        let start = pos + 8;
+        if &header[pos..pos + 4] == b"fmt " {
+            if size < 16 || start + 16 > header.len() {
+                break;
+            }
+            let mut format_tag = u16_le(header, start);
+            if format_tag == 0xFFFE && size >= 26 && start + 26 <= header.len() {
+                format_tag = u16_le(header, start + 24);
+            }
+            let bits = u16_le(header, start + 14);
+            return match format_tag {
+                0x0001 => format!("{}-bit PCM", bits),
+                0x0003 => format!("{}-bit float", bits),
+                0x0006 => "A-law".to_string(),
+                0x0007 => "µ-law".to_string(),
+                0x0011 => "IMA ADPCM".to_string(),
+                0x0055 => "MP3 in WAV".to_string(),
+                tag => format!("WAVE format {:#06x}", tag),
+            };
+        }
+        pos = start.saturating_add(size).saturating_add(size & 1);
+    }
+    "WAVE without fmt".to_string()
+}
+
+/// MPEG audio frames of a header: `MP3 VBR` when a Xing or VBRI tag says so or the
+/// bitrate changes between frames, `MP3 CBR` otherwise.
+fn mp3_encoding(header: &[u8]) -> Option<String> {
+    // Bitrates of MPEG-1 and MPEG-2/2.5 Layer III, in kbit/s.
+    const MPEG1: [u32; 15] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ];
+    const MPEG2: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+    const RATES: [[u32; 3]; 3] = [
+        [44100, 48000, 32000],
+        [22050, 24000, 16000],
+        [11025, 12000, 8000],
+    ];
+
+    let mut pos = header
+        .windows(2)
+        .position(|pair| pair[0] == 0xFF && pair[1] & 0xE6 == 0xE2)?;
+    let first = pos;
+    let mut bitrates = BTreeSet::new();
+    while pos + 4 <= header.len() && header[pos] == 0xFF && header[pos + 1] & 0xE6 == 0xE2 {
+        let version = match (header[pos + 1] >> 3) & 0x03 {
+            3 => 0,
+            2 => 1,
+            0 => 2,
+            _ => return None,
+        };
+        let bitrate_index = (header[pos + 2] >> 4) as usize;
+        let rate_index = ((header[pos + 2] >> 2) & 0x03) as usize;
+        if bitrate_index == 0 || bitrate_index == 15 || rate_index == 3 {
+            break;
+        }
+        let bitrate = if version == 0 { MPEG1 } else { MPEG2 }[bitrate_index] * 1000;
+        let sample_rate = RATES[version][rate_index];
+        let padding = ((header[pos + 2] >> 1) & 0x01) as u32;
+        let samples = if version == 0 { 144 } else { 72 };
+        bitrates.insert(bitrate);
+        pos += (samples * bitrate / sample_rate + padding).max(4) as usize;
+    }
+    if bitrates.is_empty() {
+        return None;
+    }
+    // The Xing, Info or VBRI tag sits inside the first frame.
+    let first_frame = &header[first..pos.min(first + 200).min(header.len())];
+    let tagged = |tag: &[u8]| first_frame.windows(4).any(|window| window == tag);
+    let vbr = tagged(b"Xing") || tagged(b"VBRI") || (!tagged(b"Info") && bitrates.len() > 1);
+    Some(if vbr { "MP3 VBR" } else { "MP3 CBR" }.to_string())
+}
+
+/// Size of an ID3v2 tag body: 7 bits per byte.
+fn syncsafe(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .fold(0usize, |size, &b| (size << 7) | (b & 0x7F) as usize)
+}
+
+fn u16_le(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn u32_le(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+/// Every chunk after the RIFF header, with the `fmt ` fields spelled out.
+fn riff_chunks(bytes: &[u8]) -> Vec<Item> {
+    let mut items = vec![Item {
+        offset: 0,
+        name: "RIFF".to_string(),
+        detail: format!(
+            "declared size {} bytes, file holds {}",
+            u32_le(bytes, 4) as usize + 8,
+            bytes.len()
+        ),
+    }];
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = String::from_utf8_lossy(&bytes[pos..pos + 4]).into_owned();
+        let size = u32_le(bytes, pos + 4) as usize;
+        let start = pos + 8;
+        let end = start.saturating_add(size);
+        let mut detail = format!("{} bytes", size);
+        if end > bytes.len() {
+            detail.push_str(&format!(", truncated: {} bytes missing", end - bytes.len()));
+        }
+        if id == "fmt " && size >= 16 && start + 16 <= bytes.len() {
+            let format_tag = u16_le(bytes, start);
+            detail.push_str(&format!(
+                ": format {:#06x} ({}), {} channels, {} Hz, {} bytes/s, block align {}, {} bits",
+                format_tag,
+                match format_tag {
+                    0x0001 => "PCM",
+                    0x0003 => "IEEE float",
+                    0x0006 => "A-law",
+                    0x0007 => "µ-law",
+                    0x0011 => "IMA ADPCM",
+                    0x0055 => "MP3",
+                    0xFFFE => "extensible",
+                    _ => "other",
+                },
+                u16_le(bytes, start + 2),
+                u32_le(bytes, start + 4),
+                u32_le(bytes, start + 8),
+                u16_le(bytes, start + 12),
+                u16_le(bytes, start + 14)
+            ));
+        }
+        items.push(Item {
+            offset: pos,
+            name: id,
+            detail,
+        });
+        // Chunks are padded to an even size.
+        pos = end.saturating_add(size & 1);
+    }
+    items
+}
+
+/// Offset of the `fLaC` marker, after an ID3v2 tag if there is one.
+fn flac_start(bytes: &[u8]) -> Option<usize> {
+    let start = if bytes.starts_with(b"ID3") && bytes.len() >= 10 {
+        10 + syncsafe(&bytes[6..10])
+    } else {
+        0
+    };
+    (bytes.get(start..start + 4)? == b"fLaC").then_some(start)
+}
+
+/// Every metadata block, with the STREAMINFO fields spelled out.
+fn flac_blocks(bytes: &[u8], start: usize) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut pos = start + 4;
+    while pos + 4 <= bytes.len() {
+        let header = bytes[pos];
+        let size = u32::from_be_bytes([0, bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let body = pos + 4;
+        let kind = header & 0x7F;
+        let mut detail = format!("{} bytes", size);
+        if kind == 0 && body + 34 <= bytes.len() {
+            let b = &bytes[body..body + 34];
+            let sample_rate = (b[10] as u32) << 12 | (b[11] as u32) << 4 | (b[12] as u32) >> 4;
+            let channels = ((b[12] >> 1) & 0x07) + 1;
+            let bits = (((b[12] & 0x01) << 4) | (b[13] >> 4)) + 1;
+            let total_samples = ((b[13] & 0x0F) as u64) << 32
+                | u32::from_be_bytes(b[14..18].try_into().unwrap()) as u64;
+            detail.push_str(&format!(
+                ": {} Hz, {} channels, {} bits, {} samples, block size {}-{}",
+                sample_rate,
+                channels,
+                bits,
+                total_samples,
+                u16::from_be_bytes([b[0], b[1]]),
+                u16::from_be_bytes([b[2], b[3]])
+            ));
+        }
+        items.push(Item {
+            offset: pos,
+            name: match kind {
+                0 => "STREAMINFO",
+                1 => "PADDING",
+                2 => "APPLICATION",
+                3 => "SEEKTABLE",
+                4 => "VORBIS_COMMENT",
+                5 => "CUESHEET",
+                6 => "PICTURE",
+                _ => "invalid",
+            }
+            .to_string(),
+            detail,
+        });
+        pos = body.saturating_add(size);
+        // The high bit marks the last metadata block; audio frames follow.
+        if header & 0x80 != 0 {
+            break;
+        }
+    }
+    items
+}
+
+/// The codec of the first Ogg page, with the Opus header fields spelled out.
+fn ogg_page(bytes: &[u8]) -> Vec<Item> {
+    let Some(&segments) = bytes.get(26) else {
+        return Vec::new();
+    };
+    let packet = 27 + segments as usize;
+    let Some(head) = bytes.get(packet..) else {
+        return Vec::new();
+    };
+    let detail = if head.starts_with(b"OpusHead") && head.len() >= 19 {
+        format!(
+            "Opus: {} channels, pre-skip {}, input {} Hz",
+            head[9],
+            u16_le(head, 10),
+            u32_le(head, 12)
+        )
+    } else if head.starts_with(b"\x01vorbis") {
+        "Vorbis".to_string()
+    } else if head.starts_with(b"\x7fFLAC") {
+        "FLAC".to_string()
+    } else {
+        "unknown codec".to_string()
+    };
+    vec![Item {
+        offset: 0,
+        name: "first page".to_string(),
+        detail,
+    }]
+}
+
+/// Classic hex dump: offset, up to 16 bytes per line in hex, and their printable ASCII.
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(line, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!("{:08x}  {:<47}  {}\n", line * 16, hex.join(" "), ascii)
+        })
+        .collect()
+}
@@ -0,0 +1,63 @@
+/// Target RMS level used when normalizing playback loudness.
+pub const NORMALIZE_TARGET_DBFS: f32 = -20.0;
+/// Peak ceiling applied after normalization so loud clips never clip.
+pub const NORMALIZE_PEAK_CEILING_DBFS: f32 = -1.0;
+/// Upper bound on the gain applied to very quiet clips, to avoid blowing up noise.
+pub const NORMALIZE_MAX_GAIN_DB: f32 = 30.0;
+
+/// Converts a linear amplitude to dBFS, flooring silence at -120 dB.
+pub fn to_dbfs(amplitude: f32) -> f32 {
+    if amplitude <= 1e-6 {
+        -120.0
+    } else {
+        20.0 * amplitude.log10()
+    }
+}
+
+/// Converts dB to a linear gain factor.
+pub fn from_db(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Root mean square of the samples.
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f64 = samples.iter().map(|&s| (s as f64) * (s as f64)).sum();
+    (sum / samples.len() as f64).sqrt() as f32
+}
+
+/// Absolute sample peak.
+pub fn peak(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0f32, |acc, &s| acc.max(s.abs()))
+}
+
+/// Gain (linear) that brings the clip's RMS level to [`NORMALIZE_TARGET_DBFS`],
+/// limited so the peak stays under [`NORMALIZE_PEAK_CEILING_DBFS`].
+pub fn normalization_gain(samples: &[f32]) -> f32 {
+    let rms = rms(samples);
+    if rms <= 1e-6 {
+        return 1.0;
+    }
+    let gain_db = (NORMALIZE_TARGET_DBFS - to_dbfs(rms)).min(NORMALIZE_MAX_GAIN_DB);
+    let peak_limit_db = NORMALIZE_PEAK_CEILING_DBFS - to_dbfs(peak(samples));
+    from_db(gain_db.min(peak_limit_db))
+}
+
+/// Applies a linear gain in place.
+pub fn apply_gain(samples: &mut [f32], gain: f32) {
+    for sample in samples {
+        *sample *= gain;
+    }
+}
+
+/// RMS of `points` consecutive, equally long slices of the samples.
+///
+/// Clips shorter than `points` samples get one point per sample.
+pub fn rms_envelope(samples: &[f32], points: usize) -> Vec<f32> {
+    let points = points.min(samples.len());
+    (0..points)
+        .map(|i| rms(&samples[i * samples.len() / points..(i + 1) * samples.len() / points]))
+        .collect()
+}
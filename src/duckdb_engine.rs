@@ -0,0 +1,21 @@
+use anyhow::Context;
+use duckdb::Connection;
+use std::path::Path;
+
+/// Evaluates an SQL condition over a Parquet file with DuckDB, returning whether each
+/// of its `rows` rows matches.
+pub fn predicate_mask(condition: &str, path: &Path, rows: usize) -> anyhow::Result<Vec<bool>> {
+    let connection = Connection::open_in_memory().context("failed to start DuckDB")?;
+    let mut statement = connection.prepare(&format!(
+        "SELECT file_row_number FROM read_parquet(?, file_row_number = true) WHERE {}",
+        condition
+    ))?;
+    let matching = statement.query_map([path.to_string_lossy()], |row| row.get::<_, i64>(0))?;
+    let mut mask = vec![false; rows];
+    for row in matching {
+        if let Some(matched) = mask.get_mut(row? as usize) {
+            *matched = true;
+        }
+    }
+    Ok(mask)
+}
@@ -0,0 +1,53 @@
+use crate::wav;
+use anyhow::{Context, bail};
+use polars::prelude::*;
+use std::{fs, path::Path, process::Command};
+
+/// Sample-accurate length of a clip in seconds, from its decoded frames rather than
+/// the header or the dataset's `duration` column.
+///
+/// WAV is decoded in process; anything else (FLAC, MP3, Opus, ...) is decoded to PCM
+/// with `ffmpeg`, which must then be installed.
+pub fn decode(path: &Path, ffmpeg: &Path) -> anyhow::Result<f64> {
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if bytes.starts_with(b"RIFF")
+        && let Ok(wav) = wav::Wav::decode(&bytes)
+    {
+        return Ok(wav.duration());
+    }
+
+    let output = Command::new(ffmpeg)
+        .arg("-nostdin")
+        .args(["-loglevel", "error", "-i"])
+        .arg(path)
+        .args(["-vn", "-c:a", "pcm_s16le", "-f", "wav", "-"])
+        .output()
+        .with_context(|| format!("failed to run {}", ffmpeg.display()))?;
+    if !output.status.success() {
+        bail!(
+            "ffmpeg exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    // A piped WAV has no final sizes; the decoder reads the data chunk to the end.
+    Ok(wav::Wav::decode(&output.stdout)?.duration())
+}
+
+/// Writes a copy of the Parquet file at `source` to `out` with its `duration` column
+/// replaced by `durations`, keeping the column's type and every other column.
+pub fn write_corrected(source: &Path, out: &Path, durations: Vec<f64>) -> anyhow::Result<()> {
+    let mut df = ParquetReader::new(fs::File::open(source)?).finish()?;
+    let dtype = df
+        .column("duration")
+        .map(|column| column.dtype().clone())
+        .unwrap_or(DataType::Float64);
+    df.with_column(Column::new("duration".into(), durations).cast(&dtype)?)?;
+    if let Some(parent) = out.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    ParquetWriter::new(fs::File::create(out)?)
+        .finish(&mut df)
+        .with_context(|| format!("failed to write {}", out.display()))?;
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use anyhow::{Context, anyhow};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tokio::{sync::Semaphore, task::JoinSet};
+
+/// Client for an external speaker-embedding model (ECAPA, x-vector, ... behind HTTP).
+///
+/// Each clip is POSTed as `audio/wav`; the response is either a bare JSON array of
+/// numbers or a JSON object holding it under `field` (dots descend into nested objects).
+#[derive(Clone)]
+pub struct Embedder {
+    client: reqwest::Client,
+    url: String,
+    field: String,
+    concurrency: usize,
+    embeddings: Arc<Mutex<HashMap<PathBuf, Arc<Vec<f32>>>>>,
+}
+
+impl Embedder {
+    pub fn new(url: String, field: String, concurrency: usize) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            field,
+            concurrency: concurrency.max(1),
+            embeddings: Arc::default(),
+        }
+    }
+
+    /// Returns the embedding of a clip if it has already been computed.
+    pub fn cached(&self, wav_path: &Path) -> Option<Arc<Vec<f32>>> {
+        self.embeddings.lock().unwrap().get(wav_path).cloned()
+    }
+
+    /// Embeds a clip, reusing the cached value when present.
+    pub async fn embed(&self, wav_path: &Path) -> anyhow::Result<Arc<Vec<f32>>> {
+        if let Some(embedding) = self.cached(wav_path) {
+            return Ok(embedding);
+        }
+
+        let bytes = tokio::fs::read(wav_path)
+            .await
+            .with_context(|| format!("failed to read {}", wav_path.display()))?;
+        let response = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "audio/wav")
+            .body(bytes)
+            .send()
+            .await?
+            .error_for_status()?;
+        let embedding = Arc::new(parse_embedding(&response.text().await?, &self.field)?);
+
+        self.embeddings
+            .lock()
+            .unwrap()
+            .insert(wav_path.to_path_buf(), embedding.clone());
+        Ok(embedding)
+    }
+
+    /// Embeds every clip with at most `concurrency` requests in flight.
+    ///
+    /// Returns the number of embedded clips and the errors of the failed ones.
+    pub async fn embed_all(&self, wav_paths: Vec<PathBuf>) -> (usize, Vec<anyhow::Error>) {
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let mut tasks = JoinSet::new();
+        for path in wav_paths {
+            let embedder = self.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await?;
+                embedder
+                    .embed(&path)
+                    .await
+                    .with_context(|| format!("failed to embed {}", path.display()))
+            });
+        }
+
+        let mut embedded = 0;
+        let mut errors = Vec::new();
+        while let Some(result) = tasks.join_next().await {
+            match result.map_err(anyhow::Error::from).and_then(|r| r) {
+                Ok(_) => embedded += 1,
+                Err(err) => errors.push(err),
+            }
+        }
+        (embedded, errors)
+    }
+}
+
+/// Extracts the embedding vector from a response body.
+fn parse_embedding(body: &str, field: &str) -> anyhow::Result<Vec<f32>> {
+    let json: serde_json::Value = serde_json::from_str(body).context("response is not JSON")?;
+    let value = if json.is_array() {
+        Some(&json)
+    } else {
+        field
+            .split('.')
+            .try_fold(&json, |value, key| value.get(key))
+    };
+    let embedding: Option<Vec<f32>> = value.and_then(|value| value.as_array()).and_then(|items| {
+        items
+            .iter()
+            .map(|item| item.as_f64().map(|x| x as f32))
+            .collect()
+    });
+    match embedding {
+        Some(embedding) if !embedding.is_empty() => Ok(embedding),
+        _ => Err(anyhow!("response has no numeric array `{}` field", field)),
+    }
+}
+
+/// Cosine similarity, 0 when either vector is zero or the lengths differ.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = (a.iter().map(|x| x * x).sum::<f32>() * b.iter().map(|y| y * y).sum::<f32>()).sqrt();
+    if norm > 0.0 { dot / norm } else { 0.0 }
+}
+
+/// Mean of unit-normalized vectors, the usual speaker centroid.
+pub fn centroid<'a>(vectors: impl IntoIterator<Item = &'a [f32]>) -> Vec<f32> {
+    let mut sum: Vec<f32> = Vec::new();
+    for vector in vectors {
+        let norm = vector
+            .iter()
+            .map(|x| x * x)
+            .sum::<f32>()
+            .sqrt()
+            .max(f32::EPSILON);
+        if sum.is_empty() {
+            sum = vec![0.0; vector.len()];
+        }
+        for (total, x) in sum.iter_mut().zip(vector) {
+            *total += x / norm;
+        }
+    }
+    sum
+}
+
+/// Greedy leader clustering: each vector joins the cluster whose centroid is most similar,
+/// if that similarity reaches `threshold`, and otherwise starts a new cluster.
+///
+/// Returns the cluster index of every vector.
+pub fn cluster(vectors: &[&[f32]], threshold: f32) -> Vec<usize> {
+    let mut centroids: Vec<Vec<f32>> = Vec::new();
+    vectors
+        .iter()
+        .map(|vector| {
+            let best = centroids
+                .iter()
+                .enumerate()
+                .map(|(i, centroid)| (i, cosine(vector, centroid)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .filter(|(_, similarity)| *similarity >= threshold);
+            let norm = vector
+                .iter()
+                .map(|x| x * x)
+                .sum::<f32>()
+                .sqrt()
+                .max(f32::EPSILON);
+            match best {
+                Some((i, _)) => {
+                    for (total, x) in centroids[i].iter_mut().zip(vector.iter()) {
+                        *total += x / norm;
+                    }
+                    i
+                }
+                None => {
+                    centroids.push(vector.iter().map(|x| x / norm).collect());
+                    centroids.len() - 1
+                }
+            }
+        })
+        .collect()
+}
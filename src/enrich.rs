@@ -0,0 +1,58 @@
+use crate::analysis::LevelAnalysis;
+use anyhow::Context;
+use polars::prelude::*;
+use std::{fs, path::Path};
+
+/// Columns materializing the analysis of every row: the level measurements (null where
+/// the clip could not be decoded) and, with a tokenizer, the token counts.
+pub fn columns(levels: &[Option<LevelAnalysis>], token_counts: Option<&[usize]>) -> Vec<Column> {
+    let level = |name: &str, value: fn(&LevelAnalysis) -> f32| {
+        Column::new(
+            name.into(),
+            levels
+                .iter()
+                .map(|levels| levels.as_ref().map(value))
+                .collect::<Vec<Option<f32>>>(),
+        )
+    };
+    let mut columns = vec![
+        level("peak_dbfs", |levels| levels.peak_dbfs),
+        level("loudness_lufs", |levels| levels.loudness_lufs),
+        level("noise_floor_dbfs", |levels| levels.noise_floor_dbfs),
+        level("snr_db", |levels| levels.snr_db),
+        level("speech_ratio", |levels| levels.speech_ratio),
+        Column::new(
+            "clipped_runs".into(),
+            levels
+                .iter()
+                .map(|levels| levels.map(|levels| levels.clipped_runs as u32))
+                .collect::<Vec<Option<u32>>>(),
+        ),
+    ];
+    if let Some(counts) = token_counts {
+        columns.push(Column::new(
+            "token_count".into(),
+            counts
+                .iter()
+                .map(|&count| count as u32)
+                .collect::<Vec<u32>>(),
+        ));
+    }
+    columns
+}
+
+/// Writes a copy of a Parquet file with `columns` added, replacing the columns of the
+/// same name so the copy can be enriched again.
+pub fn write(source: &Path, out: &Path, columns: Vec<Column>) -> anyhow::Result<()> {
+    let mut df = ParquetReader::new(fs::File::open(source)?).finish()?;
+    for column in columns {
+        df.with_column(column)?;
+    }
+    if let Some(parent) = out.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    ParquetWriter::new(fs::File::create(out)?)
+        .finish(&mut df)
+        .with_context(|| format!("failed to write {}", out.display()))?;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::{dsp, features};
+
+/// Analysis frame length and hop, in seconds.
+const FRAME_SECONDS: f32 = 0.025;
+const HOP_SECONDS: f32 = 0.01;
+/// Frames quieter than this never count as active.
+const SILENCE_DBFS: f32 = -50.0;
+/// Frames more than this below the loudest frame are inactive.
+const DYNAMIC_RANGE_DB: f32 = 40.0;
+/// Clips with fewer active frames than this carry no speech.
+const MIN_ACTIVE_RATIO: f32 = 0.1;
+/// Speech pauses between syllables and words; sustained sounds have fewer quiet frames
+/// (below half the mean energy) than this between their first and last active frame.
+const MAX_SUSTAINED_LOW_ENERGY_RATIO: f32 = 0.15;
+/// Median spectral flatness above which a sustained sound is noise rather than music.
+const NOISE_FLATNESS: f32 = 0.2;
+
+/// Coarse content class of a clip, from heuristic detectors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AudioEvent {
+    Speech,
+    Music,
+    Noise,
+    /// Nothing above the silence threshold for most of the clip.
+    Silence,
+}
+
+impl AudioEvent {
+    pub const ALL: [AudioEvent; 4] = [
+        AudioEvent::Speech,
+        AudioEvent::Music,
+        AudioEvent::Noise,
+        AudioEvent::Silence,
+    ];
+
+    /// Value of the `event` filter parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioEvent::Speech => "speech",
+            AudioEvent::Music => "music",
+            AudioEvent::Noise => "noise",
+            AudioEvent::Silence => "silence",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AudioEvent::Speech => "Speech",
+            AudioEvent::Music => "Music",
+            AudioEvent::Noise => "Noise",
+            AudioEvent::Silence => "No speech",
+        }
+    }
+
+    /// Renders a small colored badge for the table.
+    pub fn badge(&self) -> String {
+        let class = match self {
+            AudioEvent::Speech => {
+                "bg-green-100 text-green-800 dark:bg-green-900 dark:text-green-200"
+            }
+            AudioEvent::Music => {
+                "bg-purple-100 text-purple-800 dark:bg-purple-900 dark:text-purple-200"
+            }
+            AudioEvent::Noise => {
+                "bg-yellow-100 text-yellow-800 dark:bg-yellow-900 dark:text-yellow-200"
+            }
+            AudioEvent::Silence => "bg-gray-200 text-gray-800 dark:bg-gray-700 dark:text-gray-200",
+        };
+        format!(
+            r#"<span class="inline-block px-2 py-0.5 rounded text-xs font-semibold {}">{}</span>"#,
+            class,
+            self.label()
+        )
+    }
+}
+
+/// Tags a mono signal as speech, music, sustained noise or silence.
+///
+/// Speech alternates syllables and short pauses, so its energy keeps dropping below the
+/// mean; a sound without such dips is sustained, and it is noise when its spectrum is
+/// flat and music when it is tonal.
+pub fn classify(samples: &[f32], sample_rate: u32) -> AudioEvent {
+    let frame = ((FRAME_SECONDS * sample_rate as f32) as usize)
+        .next_power_of_two()
+        .max(16);
+    let hop = ((HOP_SECONDS * sample_rate as f32) as usize).max(1);
+    let levels: Vec<f32> = samples
+        .chunks(hop)
+        .map(|chunk| dsp::to_dbfs(dsp::rms(chunk)))
+        .collect();
+    let loudest = levels.iter().copied().fold(f32::MIN, f32::max);
+    let threshold = SILENCE_DBFS.max(loudest - DYNAMIC_RANGE_DB);
+    let active: Vec<usize> = (0..levels.len())
+        .filter(|&i| levels[i] > threshold)
+        .collect();
+    if levels.is_empty() || (active.len() as f32) < MIN_ACTIVE_RATIO * levels.len() as f32 {
+        return AudioEvent::Silence;
+    }
+
+    // Leading and trailing silence says nothing about the sound itself.
+    let span = &levels[active[0]..=active[active.len() - 1]];
+    let energies: Vec<f32> = span.iter().map(|&db| dsp::from_db(db).powi(2)).collect();
+    let mean = energies.iter().sum::<f32>() / energies.len() as f32;
+    let low_energy =
+        energies.iter().filter(|&&e| e < 0.5 * mean).count() as f32 / energies.len() as f32;
+    if low_energy > MAX_SUSTAINED_LOW_ENERGY_RATIO {
+        return AudioEvent::Speech;
+    }
+
+    let power = features::stft_power(samples, frame, hop);
+    let mut flatness: Vec<f32> = active
+        .iter()
+        .filter_map(|&i| power.get(i))
+        .map(|bins| spectral_flatness(&bins[1..]))
+        .collect();
+    flatness.sort_by(f32::total_cmp);
+    if flatness
+        .get(flatness.len() / 2)
+        .is_some_and(|&median| median > NOISE_FLATNESS)
+    {
+        AudioEvent::Noise
+    } else {
+        AudioEvent::Music
+    }
+}
+
+/// Geometric over arithmetic mean of a power spectrum: 1 for white noise, near 0 for tones.
+fn spectral_flatness(bins: &[f32]) -> f32 {
+    const FLOOR: f32 = 1e-12;
+    let n = bins.len() as f32;
+    let arithmetic = bins.iter().map(|&p| p + FLOOR).sum::<f32>() / n;
+    let geometric = (bins.iter().map(|&p| (p + FLOOR).ln()).sum::<f32>() / n).exp();
+    geometric / arithmetic
+}
@@ -0,0 +1,164 @@
+use crate::text;
+
+/// Kinds of tokens a transcription can write compactly or spelled out, in report order.
+pub const CATEGORIES: [&str; 5] = [
+    "Numbers",
+    "Ordinals",
+    "Currency",
+    "Percent",
+    "Abbreviations",
+];
+
+/// Spelled-out ordinals; `second` is left out as it is mostly the unit of time.
+const ORDINAL_WORDS: [&str; 28] = [
+    "first",
+    "third",
+    "fourth",
+    "fifth",
+    "sixth",
+    "seventh",
+    "eighth",
+    "ninth",
+    "tenth",
+    "eleventh",
+    "twelfth",
+    "thirteenth",
+    "fourteenth",
+    "fifteenth",
+    "sixteenth",
+    "seventeenth",
+    "eighteenth",
+    "nineteenth",
+    "twentieth",
+    "thirtieth",
+    "fortieth",
+    "fiftieth",
+    "sixtieth",
+    "seventieth",
+    "eightieth",
+    "ninetieth",
+    "hundredth",
+    "thousandth",
+];
+
+const CURRENCY_SYMBOLS: [char; 6] = ['$', '€', '£', '¥', '₹', '₽'];
+/// Currency names; `pound` is left out as it is also the unit of weight.
+const CURRENCY_WORDS: [&str; 12] = [
+    "dollar", "dollars", "euro", "euros", "cent", "cents", "pence", "penny", "yen", "rupee",
+    "rupees", "rubles",
+];
+
+/// Common English abbreviations and the words they expand to.
+const ABBREVIATIONS: [(&str, &[&str]); 15] = [
+    ("dr", &["doctor"]),
+    ("mr", &["mister"]),
+    ("mrs", &["missus"]),
+    ("st", &["street", "saint"]),
+    ("ave", &["avenue"]),
+    ("etc", &["cetera"]),
+    ("vs", &["versus"]),
+    ("approx", &["approximately"]),
+    ("dept", &["department"]),
+    ("jr", &["junior"]),
+    ("sr", &["senior"]),
+    (
+        "km",
+        &["kilometer", "kilometers", "kilometre", "kilometres"],
+    ),
+    (
+        "kg",
+        &["kilogram", "kilograms", "kilogramme", "kilogrammes"],
+    ),
+    ("ft", &["foot", "feet"]),
+    ("lbs", &["pounds"]),
+];
+
+/// A token that has a compact and a spelled-out form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Use {
+    pub category: &'static str,
+    /// The abbreviation for abbreviations, the category otherwise: forms are compared
+    /// within a key.
+    pub key: &'static str,
+    pub spelled_out: bool,
+    /// The token as written.
+    pub token: String,
+}
+
+/// Label of a key in the report, e.g. `Numbers` or `dr / doctor`.
+pub fn label(key: &str) -> String {
+    match ABBREVIATIONS
+        .iter()
+        .find(|(abbreviation, _)| *abbreviation == key)
+    {
+        Some((abbreviation, expansions)) => {
+            format!("{} / {}", abbreviation, expansions.join(", "))
+        }
+        None => key.to_string(),
+    }
+}
+
+/// Numbers, ordinals, currencies, percentages and abbreviations found in a text, each
+/// with whether it is spelled out. English only.
+pub fn uses(text: &str) -> Vec<Use> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let mut uses = Vec::new();
+    let mut push = |category, key, spelled_out, token: &str| {
+        uses.push(Use {
+            category,
+            key,
+            spelled_out,
+            token: token.to_string(),
+        })
+    };
+    for (i, &token) in tokens.iter().enumerate() {
+        let word = token
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        if token.contains(CURRENCY_SYMBOLS) {
+            push("Currency", "Currency", false, token);
+        } else if CURRENCY_WORDS.contains(&word.as_str()) {
+            push("Currency", "Currency", true, token);
+        }
+        if token.contains('%') {
+            push("Percent", "Percent", false, token);
+        } else if word == "percent"
+            || (word == "per"
+                && tokens
+                    .get(i + 1)
+                    .is_some_and(|next| next.to_lowercase().starts_with("cent")))
+        {
+            push("Percent", "Percent", true, token);
+        }
+
+        let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+        let suffix = &word[digits.len()..];
+        let is_numeric = !digits.is_empty()
+            && digits.starts_with(|c: char| c.is_ascii_digit())
+            && digits
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == ',' || c == '.');
+        let parts: Vec<&str> = word.split('-').collect();
+        let (last, rest) = parts.split_last().unwrap_or((&"", &[]));
+        let spelled_number = rest.iter().all(|part| text::is_number_word(part));
+        if is_numeric && matches!(suffix, "st" | "nd" | "rd" | "th") {
+            push("Ordinals", "Ordinals", false, token);
+        } else if is_numeric && suffix.is_empty() {
+            push("Numbers", "Numbers", false, token);
+        } else if spelled_number && ORDINAL_WORDS.contains(last) {
+            push("Ordinals", "Ordinals", true, token);
+        } else if spelled_number && text::is_number_word(last) && word != "one" {
+            // `one` is left out as it is mostly a pronoun ("no one", "the one").
+            push("Numbers", "Numbers", true, token);
+        }
+
+        for (abbreviation, expansions) in ABBREVIATIONS {
+            if word == abbreviation {
+                push("Abbreviations", abbreviation, false, token);
+            } else if expansions.contains(&word.as_str()) {
+                push("Abbreviations", abbreviation, true, token);
+            }
+        }
+    }
+    uses
+}
@@ -0,0 +1,474 @@
+use anyhow::{anyhow, bail};
+use polars::prelude::*;
+use std::collections::BTreeSet;
+
+/// A parsed `?filter=` expression.
+///
+/// The grammar is a small subset of SQL:
+///
+/// ```text
+/// expr       := and ("OR" and)*
+/// and        := not ("AND" not)*
+/// not        := "NOT" not | "(" expr ")" | comparison
+/// comparison := column op value
+/// op         := "=" | "==" | "!=" | "<" | "<=" | ">" | ">=" | "~" | "!~"
+/// value      := number | 'text' | "text" | true | false
+/// ```
+///
+/// `~` and `!~` match a regular expression anywhere in a string column, so
+/// `transcription~'(?i)weather'` is a case-insensitive search. The dialect is the
+/// engine's: Polars uses the `regex` crate, where `\d`, `\w`, `\s` and `\b` are
+/// Unicode-aware, while DuckDB uses RE2, where they only cover ASCII.
+#[derive(Debug)]
+pub struct Expression {
+    pub expr: Expr,
+    /// The same predicate as an SQL condition, for engines other than Polars.
+    pub sql: String,
+    /// Columns the expression refers to.
+    pub columns: BTreeSet<String>,
+}
+
+impl Expression {
+    /// Expression over a single column.
+    pub fn new(expr: Expr, sql: String, column: &str) -> Self {
+        Self {
+            expr,
+            sql,
+            columns: BTreeSet::from([column.to_string()]),
+        }
+    }
+
+    /// Conjunction of two expressions.
+    pub fn and(mut self, other: Expression) -> Self {
+        self.columns.extend(other.columns);
+        Self {
+            expr: self.expr.and(other.expr),
+            sql: format!("({}) AND ({})", self.sql, other.sql),
+            columns: self.columns,
+        }
+    }
+
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+            depth: 0,
+            comparisons: 0,
+            columns: BTreeSet::new(),
+        };
+        let (expr, sql) = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            bail!("unexpected {}", token.describe());
+        }
+        Ok(Self {
+            expr,
+            sql,
+            columns: parser.columns,
+        })
+    }
+}
+
+/// Quotes a column name for SQL.
+fn sql_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quotes a string literal for SQL.
+fn sql_string(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// A parsed (sub)expression as a Polars expression and an SQL condition.
+type Parsed = (Expr, String);
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Text(String),
+    Number(f64),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("`{}`", name),
+            Token::Text(text) => format!("'{}'", text),
+            Token::Number(n) => n.to_string(),
+            Token::Op(op) => format!("`{}`", op),
+            Token::Open => "`(`".to_string(),
+            Token::Close => "`)`".to_string(),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Ident(name) if name.eq_ignore_ascii_case(keyword))
+    }
+}
+
+/// Deepest nesting of parentheses and `NOT`. The parser, and Polars evaluating the
+/// expression, recurse once per level, so deeper filters would overflow the stack.
+const MAX_NESTING: usize = 16;
+/// Most comparisons in a filter.
+const MAX_COMPARISONS: usize = 256;
+
+/// Longest operators first, so `<=` is not read as `<`.
+const OPERATORS: [&str; 9] = ["==", "!=", "<=", ">=", "!~", "=", "<", ">", "~"];
+
+fn tokenize(input: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if c == '(' || c == ')' {
+            tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            rest = &rest[1..];
+        } else if c == '\'' || c == '"' {
+            let end = rest[1..]
+                .find(c)
+                .ok_or_else(|| anyhow!("unterminated string {}", rest))?;
+            tokens.push(Token::Text(rest[1..=end].to_string()));
+            rest = &rest[end + 2..];
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else if c.is_ascii_digit() || c == '-' || c == '.' {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '+'))
+                .unwrap_or(rest.len());
+            let number = rest[..end]
+                .parse()
+                .map_err(|_| anyhow!("invalid number `{}`", &rest[..end]))?;
+            tokens.push(Token::Number(number));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            bail!("unexpected character `{}`", c);
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Joins the operands of an `AND` or `OR` chain into a balanced tree, so long chains
+/// stay shallow for Polars, which walks expressions recursively.
+fn chain(operands: Vec<Parsed>, keyword: &str, join: fn(Expr, Expr) -> Expr) -> Parsed {
+    let sql = operands
+        .iter()
+        .map(|(_, sql)| sql.as_str())
+        .collect::<Vec<_>>()
+        .join(&format!(" {} ", keyword));
+    let mut exprs: Vec<Expr> = operands.into_iter().map(|(expr, _)| expr).collect();
+    while exprs.len() > 1 {
+        let mut pairs = exprs.into_iter();
+        let mut joined = Vec::new();
+        while let Some(expr) = pairs.next() {
+            joined.push(match pairs.next() {
+                Some(other) => join(expr, other),
+                None => expr,
+            });
+        }
+        exprs = joined;
+    }
+    (exprs.pop().unwrap(), sql)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Parentheses and `NOT`s around the current position.
+    depth: usize,
+    comparisons: usize,
+    columns: BTreeSet<String>,
+}
+
+impl Parser {
+    fn next(&mut self) -> anyhow::Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| anyhow!("unexpected end of filter"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self
+            .tokens
+            .get(self.pos)
+            .is_some_and(|token| token.is_keyword(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// Parses a nested operand with `parse`, failing beyond [`MAX_NESTING`] levels.
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> anyhow::Result<Parsed>,
+    ) -> anyhow::Result<Parsed> {
+        if self.depth == MAX_NESTING {
+            bail!("filter nested deeper than {} levels", MAX_NESTING);
+        }
+        self.depth += 1;
+        let parsed = parse(self)?;
+        self.depth -= 1;
+        Ok(parsed)
+    }
+
+    fn or(&mut self) -> anyhow::Result<Parsed> {
+        let mut operands = vec![self.and()?];
+        while self.eat_keyword("OR") {
+            operands.push(self.and()?);
+        }
+        Ok(chain(operands, "OR", Expr::or))
+    }
+
+    fn and(&mut self) -> anyhow::Result<Parsed> {
+        let mut operands = vec![self.not()?];
+        while self.eat_keyword("AND") {
+            operands.push(self.not()?);
+        }
+        Ok(chain(operands, "AND", Expr::and))
+    }
+
+    fn not(&mut self) -> anyhow::Result<Parsed> {
+        if self.eat_keyword("NOT") {
+            let (expr, sql) = self.nested(Self::not)?;
+            return Ok((expr.not(), format!("NOT {}", sql)));
+        }
+        match self.next()? {
+            Token::Open => {
+                let (expr, sql) = self.nested(Self::or)?;
+                match self.next()? {
+                    Token::Close => Ok((expr, format!("({})", sql))),
+                    token => bail!("expected `)`, found {}", token.describe()),
+                }
+            }
+            Token::Ident(name) => self.comparison(name),
+            token => bail!("expected a column name, found {}", token.describe()),
+        }
+    }
+
+    fn comparison(&mut self, column: String) -> anyhow::Result<Parsed> {
+        self.comparisons += 1;
+        if self.comparisons > MAX_COMPARISONS {
+            bail!("filter has more than {} comparisons", MAX_COMPARISONS);
+        }
+        let op = match self.next()? {
+            Token::Op(op) => op,
+            token => bail!(
+                "expected an operator after `{}`, found {}",
+                column,
+                token.describe()
+            ),
+        };
+        let (value, sql_value) = match self.next()? {
+            Token::Number(n) => (lit(n), n.to_string()),
+            Token::Text(text) => (lit(text.clone()), sql_string(&text)),
+            Token::Ident(word) if word.eq_ignore_ascii_case("true") => (lit(true), "TRUE".into()),
+            Token::Ident(word) if word.eq_ignore_ascii_case("false") => {
+                (lit(false), "FALSE".into())
+            }
+            token => bail!("expected a value, found {}", token.describe()),
+        };
+        let target = col(column.as_str());
+        let sql_column = sql_identifier(&column);
+        self.columns.insert(column);
+        let sql = match op {
+            "~" => format!("regexp_matches({}, {})", sql_column, sql_value),
+            "!~" => format!("NOT regexp_matches({}, {})", sql_column, sql_value),
+            "=" | "==" => format!("{} = {}", sql_column, sql_value),
+            "!=" => format!("{} <> {}", sql_column, sql_value),
+            op => format!("{} {} {}", sql_column, op, sql_value),
+        };
+        let expr = match op {
+            "=" | "==" => target.eq(value),
+            "!=" => target.neq(value),
+            "<" => target.lt(value),
+            "<=" => target.lt_eq(value),
+            ">" => target.gt(value),
+            ">=" => target.gt_eq(value),
+            "~" => target.str().contains(value, true),
+            "!~" => target.str().contains(value, true).not(),
+            _ => unreachable!(),
+        };
+        Ok((expr, sql))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rows of a small frame matching a filter.
+    fn matching(filter: &str) -> Vec<i32> {
+        let df = df! {
+            "id" => [1, 2, 3, 4],
+            "duration" => [0.5, 2.0, 7.5, 12.0],
+            "transcription" => ["Hello there", "the weather", "", "WEATHER report"],
+            "reviewed" => [true, false, true, false],
+        }
+        .unwrap();
+        let expression = Expression::parse(filter).unwrap();
+        let filtered = df.lazy().filter(expression.expr).collect().unwrap();
+        filtered
+            .column("id")
+            .unwrap()
+            .i32()
+            .unwrap()
+            .into_no_null_iter()
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_longest_operators_first() {
+        assert_eq!(
+            tokenize("a<=1 b>=2 c!=3 d==4 e!~'x'").unwrap(),
+            vec![
+                Token::Ident("a".into()),
+                Token::Op("<="),
+                Token::Number(1.0),
+                Token::Ident("b".into()),
+                Token::Op(">="),
+                Token::Number(2.0),
+                Token::Ident("c".into()),
+                Token::Op("!="),
+                Token::Number(3.0),
+                Token::Ident("d".into()),
+                Token::Op("=="),
+                Token::Number(4.0),
+                Token::Ident("e".into()),
+                Token::Op("!~"),
+                Token::Text("x".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_strings_numbers_and_parentheses() {
+        assert_eq!(
+            tokenize(r#"(speaker.id = "a 'b'") -1.5e3"#).unwrap(),
+            vec![
+                Token::Open,
+                Token::Ident("speaker.id".into()),
+                Token::Op("="),
+                Token::Text("a 'b'".into()),
+                Token::Close,
+                Token::Number(-1500.0),
+            ]
+        );
+        assert_eq!(tokenize("  ").unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn rejects_invalid_tokens() {
+        assert_eq!(
+            tokenize("a = 'open").unwrap_err().to_string(),
+            "unterminated string 'open"
+        );
+        assert_eq!(
+            tokenize("a = 1.2.3").unwrap_err().to_string(),
+            "invalid number `1.2.3`"
+        );
+        assert_eq!(
+            tokenize("a = 1 ; b").unwrap_err().to_string(),
+            "unexpected character `;`"
+        );
+    }
+
+    #[test]
+    fn compares_columns() {
+        assert_eq!(matching("duration > 2"), [3, 4]);
+        assert_eq!(matching("duration >= 2"), [2, 3, 4]);
+        assert_eq!(matching("duration == 0.5"), [1]);
+        assert_eq!(matching("id != 1"), [2, 3, 4]);
+        assert_eq!(matching("transcription = ''"), [3]);
+        assert_eq!(matching("reviewed = TRUE"), [1, 3]);
+        assert_eq!(matching("transcription ~ '(?i)weather'"), [2, 4]);
+        assert_eq!(matching("transcription !~ 'the'"), [3, 4]);
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(matching("id = 1 OR id = 2 AND reviewed = true"), [1]);
+        assert_eq!(matching("(id = 1 OR id = 2) AND reviewed = true"), [1]);
+        assert_eq!(matching("(id = 1 OR id = 3) and reviewed = true"), [1, 3]);
+        assert_eq!(matching("NOT id = 1 AND duration < 5"), [2]);
+        assert_eq!(matching("not (id = 1 or id = 2)"), [3, 4]);
+        assert_eq!(matching("NOT NOT id = 4"), [4]);
+    }
+
+    #[test]
+    fn builds_sql_and_collects_columns() {
+        let expression = Expression::parse(r#"NOT (a = "it's" OR b ~ 'x') AND c != 1"#).unwrap();
+        assert_eq!(
+            expression.sql,
+            r#"NOT ("a" = 'it''s' OR regexp_matches("b", 'x')) AND "c" <> 1"#
+        );
+        assert_eq!(
+            expression.columns,
+            BTreeSet::from(["a".to_string(), "b".to_string(), "c".to_string()])
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_filters() {
+        let error = |filter: &str| Expression::parse(filter).unwrap_err().to_string();
+        assert_eq!(error(""), "unexpected end of filter");
+        assert_eq!(error("a"), "unexpected end of filter");
+        assert_eq!(error("a 1"), "expected an operator after `a`, found 1");
+        assert_eq!(error("a = b"), "expected a value, found `b`");
+        assert_eq!(error("= 1"), "expected a column name, found `=`");
+        assert_eq!(error("(a = 1"), "unexpected end of filter");
+        assert_eq!(error("(a = 1 b"), "expected `)`, found `b`");
+        assert_eq!(error("a = 1)"), "unexpected `)`");
+        assert_eq!(error("a = 1 b = 2"), "unexpected `b`");
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let nested = |depth: usize| format!("{}id = 1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(matching(&nested(MAX_NESTING)), [1]);
+        assert_eq!(
+            Expression::parse(&nested(MAX_NESTING + 1))
+                .unwrap_err()
+                .to_string(),
+            "filter nested deeper than 16 levels"
+        );
+        // The deepest expression allowed: every level negates a chain.
+        let mut deepest = "id = 1 AND id = 1".to_string();
+        for _ in 0..MAX_NESTING / 2 {
+            deepest = format!("NOT (id = 2 OR {})", deepest);
+        }
+        assert_eq!(matching(&deepest), [1]);
+        // Deep enough to overflow the stack without the limit.
+        assert!(Expression::parse(&nested(100_000)).is_err());
+        assert!(Expression::parse(&format!("{}id = 1", "NOT ".repeat(100_000))).is_err());
+        assert_eq!(
+            matching(&format!("{}id = 1", "NOT ".repeat(MAX_NESTING))),
+            [1]
+        );
+    }
+
+    #[test]
+    fn limits_comparisons() {
+        let chain = |comparisons: usize| vec!["id = 1"; comparisons].join(" OR ");
+        assert_eq!(matching(&chain(MAX_COMPARISONS)), [1]);
+        assert_eq!(
+            Expression::parse(&chain(MAX_COMPARISONS + 1))
+                .unwrap_err()
+                .to_string(),
+            "filter has more than 256 comparisons"
+        );
+        assert!(Expression::parse(&chain(100_000)).is_err());
+    }
+}
@@ -0,0 +1,283 @@
+use anyhow::ensure;
+use rustfft::{FftPlanner, num_complex::Complex};
+use serde::{Deserialize, Serialize};
+
+/// Floor applied before taking the log of mel energies.
+const LOG_FLOOR: f32 = 1e-10;
+
+/// Kind of feature matrix to compute.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FeatureKind {
+    #[default]
+    Logmel,
+    Mfcc,
+}
+
+impl FeatureKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeatureKind::Logmel => "logmel",
+            FeatureKind::Mfcc => "mfcc",
+        }
+    }
+}
+
+/// Largest accepted `n_fft`.
+const MAX_N_FFT: usize = 8192;
+/// Largest accepted `n_mels`.
+const MAX_N_MELS: usize = 512;
+/// Most power spectrogram values (frames times bins) computed for one clip, 128 MiB.
+const MAX_SPECTROGRAM_VALUES: usize = 1 << 25;
+
+/// Parameters of the feature extraction, mirroring the usual torchaudio/librosa knobs.
+#[derive(Clone, Debug, Deserialize)]
+pub struct FeatureParams {
+    #[serde(default)]
+    pub kind: FeatureKind,
+    #[serde(default = "default_n_fft")]
+    pub n_fft: usize,
+    #[serde(default = "default_hop_length")]
+    pub hop_length: usize,
+    #[serde(default = "default_n_mels")]
+    pub n_mels: usize,
+    #[serde(default = "default_n_mfcc")]
+    pub n_mfcc: usize,
+    #[serde(default)]
+    pub f_min: f32,
+    /// Defaults to the Nyquist frequency.
+    pub f_max: Option<f32>,
+}
+
+fn default_n_fft() -> usize {
+    400
+}
+
+fn default_hop_length() -> usize {
+    160
+}
+
+fn default_n_mels() -> usize {
+    80
+}
+
+fn default_n_mfcc() -> usize {
+    13
+}
+
+/// A frames x coefficients feature matrix.
+#[derive(Serialize)]
+pub struct Features {
+    pub kind: FeatureKind,
+    pub sample_rate: u32,
+    pub n_fft: usize,
+    pub hop_length: usize,
+    pub shape: [usize; 2],
+    pub data: Vec<Vec<f32>>,
+}
+
+/// Periodic Hann window.
+pub fn hann_window(size: usize) -> Vec<f32> {
+    (0..size)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / size as f32).cos())
+        .collect()
+}
+
+/// Power spectrogram (`|STFT|^2`) with centered, reflect-padded frames.
+///
+/// Returns one row of `n_fft / 2 + 1` bins per frame.
+pub fn stft_power(samples: &[f32], n_fft: usize, hop_length: usize) -> Vec<Vec<f32>> {
+    let pad = n_fft / 2;
+    let reflect = |i: isize| -> f32 {
+        let n = samples.len() as isize;
+        if n == 1 {
+            return samples[0];
+        }
+        let period = 2 * (n - 1);
+        let mut i = i.rem_euclid(period);
+        if i >= n {
+            i = period - i;
+        }
+        samples[i as usize]
+    };
+
+    let padded_len = samples.len() + 2 * pad;
+    if samples.is_empty() || padded_len < n_fft {
+        return Vec::new();
+    }
+    let frames = 1 + (padded_len - n_fft) / hop_length;
+
+    let window = hann_window(n_fft);
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(n_fft);
+    let mut buffer = vec![Complex::default(); n_fft];
+
+    (0..frames)
+        .map(|frame| {
+            let start = (frame * hop_length) as isize - pad as isize;
+            for (k, value) in buffer.iter_mut().enumerate() {
+                *value = Complex::new(reflect(start + k as isize) * window[k], 0.0);
+            }
+            fft.process(&mut buffer);
+            buffer[..=n_fft / 2].iter().map(|c| c.norm_sqr()).collect()
+        })
+        .collect()
+}
+
+fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+/// Triangular HTK-scale mel filterbank, `n_mels` rows of `n_fft / 2 + 1` weights.
+pub fn mel_filterbank(
+    sample_rate: u32,
+    n_fft: usize,
+    n_mels: usize,
+    f_min: f32,
+    f_max: f32,
+) -> Vec<Vec<f32>> {
+    let n_bins = n_fft / 2 + 1;
+    let bin_hz: Vec<f32> = (0..n_bins)
+        .map(|i| i as f32 * sample_rate as f32 / n_fft as f32)
+        .collect();
+
+    let (mel_min, mel_max) = (hz_to_mel(f_min), hz_to_mel(f_max));
+    let points: Vec<f32> = (0..n_mels + 2)
+        .map(|i| mel_to_hz(mel_min + (mel_max - mel_min) * i as f32 / (n_mels + 1) as f32))
+        .collect();
+
+    (0..n_mels)
+        .map(|m| {
+            let (left, center, right) = (points[m], points[m + 1], points[m + 2]);
+            bin_hz
+                .iter()
+                .map(|&f| {
+                    let rising = (f - left) / (center - left);
+                    let falling = (right - f) / (right - center);
+                    rising.min(falling).max(0.0)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Orthonormal DCT-II of a single vector, keeping the first `n_out` coefficients.
+fn dct_ortho(input: &[f32], n_out: usize) -> Vec<f32> {
+    let n = input.len() as f32;
+    (0..n_out)
+        .map(|k| {
+            let sum: f32 = input
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| {
+                    x * (std::f32::consts::PI * k as f32 * (2.0 * i as f32 + 1.0) / (2.0 * n)).cos()
+                })
+                .sum();
+            let scale = if k == 0 {
+                (1.0 / n).sqrt()
+            } else {
+                (2.0 / n).sqrt()
+            };
+            sum * scale
+        })
+        .collect()
+}
+
+/// Computes log-mel energies (natural log) or MFCCs for a mono signal.
+pub fn compute(
+    samples: &[f32],
+    sample_rate: u32,
+    params: &FeatureParams,
+) -> anyhow::Result<Features> {
+    ensure!(
+        (16..=MAX_N_FFT).contains(&params.n_fft),
+        "n_fft must be between 16 and {}",
+        MAX_N_FFT
+    );
+    ensure!(params.hop_length > 0, "hop_length must be positive");
+    ensure!(
+        (1..=MAX_N_MELS).contains(&params.n_mels),
+        "n_mels must be between 1 and {}",
+        MAX_N_MELS
+    );
+    ensure!(
+        (samples.len() / params.hop_length + 1) * (params.n_fft / 2 + 1) <= MAX_SPECTROGRAM_VALUES,
+        "hop_length {} is too small for a clip of {} samples",
+        params.hop_length,
+        samples.len()
+    );
+    ensure!(
+        params.kind != FeatureKind::Mfcc || (1..=params.n_mels).contains(&params.n_mfcc),
+        "n_mfcc must be between 1 and n_mels"
+    );
+    let nyquist = sample_rate as f32 / 2.0;
+    let f_max = params.f_max.unwrap_or(nyquist).min(nyquist);
+    ensure!(params.f_min >= 0.0, "f_min must not be negative");
+    ensure!(params.f_min < f_max, "f_min must be below f_max");
+
+    let power = stft_power(samples, params.n_fft, params.hop_length);
+    let filters = mel_filterbank(
+        sample_rate,
+        params.n_fft,
+        params.n_mels,
+        params.f_min,
+        f_max,
+    );
+
+    let data: Vec<Vec<f32>> = power
+        .iter()
+        .map(|frame| {
+            let log_mel: Vec<f32> = filters
+                .iter()
+                .map(|filter| {
+                    let energy: f32 = filter.iter().zip(frame).map(|(w, p)| w * p).sum();
+                    energy.max(LOG_FLOOR).ln()
+                })
+                .collect();
+            match params.kind {
+                FeatureKind::Logmel => log_mel,
+                FeatureKind::Mfcc => dct_ortho(&log_mel, params.n_mfcc),
+            }
+        })
+        .collect();
+
+    let width = match params.kind {
+        FeatureKind::Logmel => params.n_mels,
+        FeatureKind::Mfcc => params.n_mfcc,
+    };
+
+    Ok(Features {
+        kind: params.kind,
+        sample_rate,
+        n_fft: params.n_fft,
+        hop_length: params.hop_length,
+        shape: [data.len(), width],
+        data,
+    })
+}
+
+impl Features {
+    /// Serializes the matrix as a little-endian float32 NumPy `.npy` file.
+    pub fn to_npy(&self) -> Vec<u8> {
+        let mut header = format!(
+            "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}), }}",
+            self.shape[0], self.shape[1]
+        );
+        // Magic (6) + version (2) + header length (2) + header must align to 64 bytes.
+        let unpadded = 10 + header.len() + 1;
+        header.push_str(&" ".repeat(unpadded.next_multiple_of(64) - unpadded));
+        header.push('\n');
+
+        let mut out = Vec::with_capacity(10 + header.len() + self.shape[0] * self.shape[1] * 4);
+        out.extend_from_slice(b"\x93NUMPY\x01\x00");
+        out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        out.extend_from_slice(header.as_bytes());
+        for value in self.data.iter().flatten() {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+
+/// Name of the index in the temp folder, which is kept when the temp folder is cleared at
+/// startup.
+pub const FILE_NAME: &str = "folder_index.json";
+
+/// What the folder scan learns about a file without loading its audio.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileInfo {
+    pub rows: usize,
+    /// Total duration in seconds.
+    pub duration: f64,
+    /// Flattened column types, see [`crate::schema_check::flatten`].
+    pub schema: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Entry {
+    /// Modification time of the file when it was scanned, in nanoseconds.
+    modified: i64,
+    #[serde(flatten)]
+    info: FileInfo,
+}
+
+/// Row counts, durations and schemas of the data folder entries, persisted as JSON so a
+/// restart only rescans the files that changed.
+pub struct FolderIndex {
+    path: PathBuf,
+    entries: Mutex<BTreeMap<String, Entry>>,
+    /// Held while files are rescanned, so concurrent refreshes do not scan a file twice.
+    refreshing: Mutex<()>,
+}
+
+impl FolderIndex {
+    /// Loads the index at `path`; a missing or unreadable index starts empty.
+    pub fn load(path: PathBuf) -> Self {
+        let entries = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            entries: Mutex::new(entries),
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    /// Rescans the files whose modification time changed since they were scanned, drops
+    /// the files that are gone and saves the index if anything changed. Returns the
+    /// number of rescanned files.
+    pub fn refresh(
+        &self,
+        folder: &Path,
+        files: &[String],
+        scan: impl Fn(&str) -> anyhow::Result<FileInfo>,
+    ) -> anyhow::Result<usize> {
+        let _refreshing = self.refreshing.lock().unwrap();
+        let mut rescanned = 0;
+        for file in files {
+            let modified = modified_nanos(&folder.join(file))?;
+            let known = self.entries.lock().unwrap().get(file).map(|e| e.modified);
+            if known == Some(modified) {
+                continue;
+            }
+            let info = scan(file).with_context(|| format!("failed to scan {}", file))?;
+            self.entries
+                .lock()
+                .unwrap()
+                .insert(file.clone(), Entry { modified, info });
+            rescanned += 1;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|file, _| files.contains(file));
+        if rescanned > 0 || entries.len() != before {
+            let partial = self.path.with_extension("json.partial");
+            fs::write(&partial, serde_json::to_vec(&*entries)?)?;
+            fs::rename(&partial, &self.path)
+                .with_context(|| format!("failed to write {}", self.path.display()))?;
+        }
+        Ok(rescanned)
+    }
+
+    /// Number of files scanned so far.
+    pub fn scanned(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// What the last scan learned about a file.
+    pub fn get(&self, file: &str) -> Option<FileInfo> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(file).map(|entry| entry.info.clone())
+    }
+}
+
+fn modified_nanos(path: &Path) -> anyhow::Result<i64> {
+    let modified = path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(modified
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as i64)
+}
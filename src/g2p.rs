@@ -0,0 +1,69 @@
+use anyhow::{Context, bail};
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+};
+
+/// Stress and syllable marks, which are prosody rather than phonemes.
+const PROSODY_MARKS: [char; 3] = ['ˈ', 'ˌ', '.'];
+
+/// Grapheme-to-phoneme conversion through the `espeak-ng` command line.
+#[derive(Clone, Debug)]
+pub struct G2p {
+    /// Path to the `espeak-ng` executable.
+    pub espeak: PathBuf,
+    /// espeak-ng voice, e.g. `en-us` or `de`.
+    pub language: String,
+    /// Phonemes the language is expected to use, if known.
+    pub inventory: Option<BTreeSet<String>>,
+}
+
+impl G2p {
+    /// Reads the expected phoneme inventory, whitespace-separated IPA symbols.
+    pub fn read_inventory(path: &Path) -> anyhow::Result<BTreeSet<String>> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Ok(text.split_whitespace().map(str::to_string).collect())
+    }
+
+    /// Phonemizes the texts and returns every phoneme occurrence, in order.
+    pub fn phonemes(&self, texts: &[String]) -> anyhow::Result<Vec<String>> {
+        let mut child = Command::new(&self.espeak)
+            .args(["-q", "--ipa=3", "-v", &self.language])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to run {}", self.espeak.display()))?;
+
+        // Feed stdin from another thread so a full stdout pipe cannot deadlock us.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input: String = texts
+            .iter()
+            .map(|text| format!("{}\n", text.replace('\n', " ")))
+            .collect();
+        let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+        let output = child.wait_with_output()?;
+        writer
+            .join()
+            .expect("stdin writer panicked")
+            .context("failed to write to espeak-ng")?;
+
+        if !output.status.success() {
+            bail!(
+                "espeak-ng exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split(|c: char| c.is_whitespace() || c == '_')
+            .map(|phoneme| phoneme.replace(PROSODY_MARKS, ""))
+            .filter(|phoneme| !phoneme.is_empty())
+            .collect())
+    }
+}
@@ -0,0 +1,265 @@
+use crate::templates;
+use minijinja::context;
+use std::ops::Range;
+
+/// Renders a complete page with the `layout.html` template (Tailwind, theme handling, footer).
+///
+/// `head_extra` is inserted at the end of `<head>`, `content` becomes the body.
+pub fn render_page(title: &str, head_extra: &str, content: &str) -> String {
+    templates::render(
+        "layout.html",
+        context! {
+            title => title,
+            theme => crate::preferences::current().theme,
+            head_extra => templates::safe(head_extra),
+            content => templates::safe(content),
+        },
+    )
+}
+
+/// Top bar used by sub-pages: a back link on the left, the theme toggle on the right.
+pub fn page_header(back_href: &str, back_label: &str) -> String {
+    format!(
+        r#"<div class="flex justify-between items-center mb-4">
+            <a href="{}" class="text-blue-600 dark:text-blue-400 hover:underline">{}</a>
+            <button type="button" onclick="toggleTheme()" class="px-3 py-1 bg-gray-200 dark:bg-gray-700 rounded-md text-sm">
+                {}
+            </button>
+        </div>"#,
+        back_href,
+        back_label,
+        crate::i18n::t("Toggle Theme")
+    )
+}
+
+/// Escapes text for safe interpolation into HTML content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escapes text and wraps the case-insensitive occurrences of `query` in `<mark>`.
+pub fn highlight(text: &str, query: &str) -> String {
+    highlight_misspellings(text, query, &[])
+}
+
+/// Like [`highlight`], also underlining the `misspelled` byte ranges with a wavy red line.
+pub fn highlight_misspellings(text: &str, query: &str, misspelled: &[Range<usize>]) -> String {
+    let matches = crate::text::find_matches(text, query);
+    let mut bounds: Vec<usize> = matches
+        .iter()
+        .chain(misspelled)
+        .flat_map(|range| [range.start, range.end])
+        .chain([0, text.len()])
+        .collect();
+    bounds.sort_unstable();
+    bounds.dedup();
+    let title = crate::i18n::t("Possible misspelling");
+    let mut out = String::with_capacity(text.len());
+    for span in bounds.windows(2) {
+        let inside = |ranges: &[Range<usize>]| {
+            ranges
+                .iter()
+                .any(|range| range.start <= span[0] && span[1] <= range.end)
+        };
+        let mut part = escape(&text[span[0]..span[1]]);
+        if inside(misspelled) {
+            part = format!(
+                r#"<span class="underline decoration-wavy decoration-red-500" title="{}">{}</span>"#,
+                title, part
+            );
+        }
+        if inside(&matches) {
+            part = format!("<mark>{}</mark>", part);
+        }
+        out.push_str(&part);
+    }
+    out
+}
+
+/// Attributes for an element holding a transcription (with a leading space, or empty):
+/// `dir="rtl"` for right-to-left text, and for Arabic and CJK text a language tag and
+/// fonts and line breaking suited to the script.
+pub fn text_attributes(text: &str) -> String {
+    use crate::text::Script;
+    let dir = if crate::text::is_rtl(text) {
+        r#" dir="rtl""#
+    } else {
+        ""
+    };
+    let lang = match crate::text::script(text) {
+        Script::Arabic => {
+            r#" lang="ar" style="font-family: 'Noto Naskh Arabic', 'Geeza Pro', Tahoma, sans-serif; line-height: 1.8""#
+        }
+        Script::Hebrew => r#" lang="he""#,
+        Script::Chinese => {
+            r#" lang="zh" style="font-family: 'Noto Sans CJK SC', 'PingFang SC', 'Microsoft YaHei', sans-serif; line-break: strict; overflow-wrap: anywhere""#
+        }
+        Script::Japanese => {
+            r#" lang="ja" style="font-family: 'Noto Sans CJK JP', 'Hiragino Sans', 'Yu Gothic', sans-serif; line-break: strict; overflow-wrap: anywhere""#
+        }
+        Script::Korean => {
+            r#" lang="ko" style="font-family: 'Noto Sans CJK KR', 'Apple SD Gothic Neo', 'Malgun Gothic', sans-serif; word-break: keep-all; overflow-wrap: anywhere""#
+        }
+        Script::Other => "",
+    };
+    format!("{}{}", dir, lang)
+}
+
+/// Accessible name of a clip's player, read out by screen readers: the start of its
+/// transcription, so rows can be told apart without playing them.
+pub fn clip_label(transcription: &str) -> String {
+    const MAX_CHARS: usize = 120;
+    let text = transcription
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let text = match text.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    };
+    if text.is_empty() {
+        crate::i18n::t("Play clip").to_string()
+    } else {
+        crate::i18n::tf("Play clip: {text}", &[("text", &text)])
+    }
+}
+
+/// Percent-encodes text for use as a URL query parameter value.
+pub fn url_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Decodes `%XX` escapes, keeping malformed escapes as they are.
+pub fn url_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Level shown at the bottom of a [`sparkline`].
+const SPARKLINE_FLOOR_DBFS: f32 = -60.0;
+
+/// Renders dBFS levels as a filled SVG area, one unit wide per value and stretched to
+/// the element size.
+pub fn sparkline(levels_dbfs: &[f32]) -> String {
+    let height = 24.0;
+    let mut path = format!("M0,{}", height);
+    for (i, level) in levels_dbfs.iter().enumerate() {
+        let y = height * (level / SPARKLINE_FLOOR_DBFS).clamp(0.0, 1.0);
+        path.push_str(&format!(" L{},{:.1} L{},{:.1}", i, y, i + 1, y));
+    }
+    path.push_str(&format!(" L{},{} Z", levels_dbfs.len(), height));
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}" preserveAspectRatio="none"><path d="{}" fill="#3b82f6"/></svg>"##,
+        levels_dbfs.len().max(1),
+        height,
+        path
+    )
+}
+
+/// Renders a checkbox that toggles a boolean query parameter on the current page.
+///
+/// Relies on the `setParam` helper defined by the view page.
+pub fn flag_toggle(param: &str, label: &str, checked: bool) -> String {
+    format!(
+        r#"<label class="flex items-center gap-2"><input type="checkbox" onchange="setParam('{}', this.checked ? '1' : null)" {}><span>{}</span></label>"#,
+        param,
+        if checked { "checked" } else { "" },
+        label
+    )
+}
+
+/// Renders Markdown to HTML, escaping any raw HTML it contains and highlighting
+/// `query` in the text like [`highlight`].
+///
+/// Link and image targets other than `http(s)`, `mailto` and relative URLs are
+/// dropped, so the output is safe to embed.
+pub fn markdown(text: &str, query: &str) -> String {
+    use pulldown_cmark::{CowStr, Event, Parser, Tag};
+
+    fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+        let scheme = url
+            .split_once(':')
+            .map(|(scheme, _)| scheme)
+            .filter(|scheme| !scheme.contains(['/', '?', '#']));
+        match scheme {
+            None => url,
+            Some(scheme)
+                if ["http", "https", "mailto"].contains(&scheme.to_ascii_lowercase().as_str()) =>
+            {
+                url
+            }
+            Some(_) => CowStr::Borrowed("#"),
+        }
+    }
+
+    let events = Parser::new(text).map(|event| match event {
+        Event::Html(text) | Event::InlineHtml(text) | Event::Text(text) => {
+            Event::InlineHtml(highlight(&text, query).into())
+        }
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        event => event,
+    });
+    let mut out = String::with_capacity(text.len());
+    pulldown_cmark::html::push_html(&mut out, events);
+    out
+}
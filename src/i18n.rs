@@ -0,0 +1,70 @@
+use anyhow::{Context, bail};
+use std::{collections::HashMap, fmt::Display, fs, path::Path, sync::OnceLock};
+
+/// Built-in translations of the UI strings, keyed by the English text, which needs none.
+const BUILTIN: [(&str, &str); 1] = [("de", include_str!("../locales/de.json"))];
+
+struct Catalog {
+    lang: String,
+    messages: HashMap<String, String>,
+}
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// Selects the UI language: the built-in translations of `lang`, overridden by
+/// `<locale_dir>/<lang>.json` (an object mapping English strings to translations) when
+/// present, so teams can add a language or adjust wording without rebuilding.
+///
+/// Must be called before the first page is rendered; later calls are ignored.
+pub fn init(lang: &str, locale_dir: Option<&Path>) -> anyhow::Result<()> {
+    let mut messages = HashMap::new();
+    let builtin = BUILTIN.iter().find(|(name, _)| *name == lang);
+    if let Some((_, json)) = builtin {
+        messages = serde_json::from_str(json).expect("built-in translations are valid");
+    }
+    let custom = locale_dir
+        .map(|dir| dir.join(format!("{}.json", lang)))
+        .filter(|path| path.is_file());
+    match custom {
+        Some(path) => {
+            let json = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let overrides: HashMap<String, String> = serde_json::from_str(&json)
+                .with_context(|| format!("invalid translations in {}", path.display()))?;
+            messages.extend(overrides);
+        }
+        None if builtin.is_none() && lang != "en" => bail!(
+            "no translations for `{}`; add {}.json to a --locale-dir",
+            lang,
+            lang
+        ),
+        None => {}
+    }
+    let _ = CATALOG.set(Catalog {
+        lang: lang.to_string(),
+        messages,
+    });
+    Ok(())
+}
+
+/// Language tag of the UI, for `<html lang>`.
+pub fn lang() -> &'static str {
+    CATALOG.get().map_or("en", |catalog| catalog.lang.as_str())
+}
+
+/// Translates a UI string, falling back to the English text.
+pub fn t(text: &str) -> &str {
+    CATALOG
+        .get()
+        .and_then(|catalog| catalog.messages.get(text))
+        .map_or(text, String::as_str)
+}
+
+/// Translates a UI string with `{name}` placeholders, then fills them in.
+pub fn tf(text: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut out = t(text).to_string();
+    for (name, value) in args {
+        out = out.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    out
+}
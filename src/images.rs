@@ -0,0 +1,136 @@
+use crate::html;
+use polars::prelude::*;
+
+/// Extensions of image URLs shown as thumbnails.
+const IMAGE_EXTENSIONS: [&str; 6] = [".png", ".jpg", ".jpeg", ".gif", ".webp", ".svg"];
+
+/// How the images of a column are stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageSource {
+    /// Encoded image bytes, in a binary column or the `bytes` field of a struct column
+    /// (the Hugging Face `Image` feature), served by `/image`.
+    Bytes,
+    /// Links to images, shown directly.
+    Url,
+}
+
+/// A column detected as holding images.
+#[derive(Clone, Debug)]
+pub struct ImageColumn {
+    pub name: String,
+    pub source: ImageSource,
+}
+
+/// Finds the columns holding images, from their first non-null value: binary data
+/// starting with a PNG, JPEG, GIF or WebP signature, or image URLs.
+pub fn image_columns(df: &DataFrame) -> Vec<ImageColumn> {
+    df.columns()
+        .iter()
+        .filter(|column| !column.name().starts_with("audio_"))
+        .filter_map(|column| {
+            let source = match column.dtype() {
+                DataType::String => {
+                    let strings = column.str().ok()?;
+                    let first = strings.into_iter().flatten().next()?;
+                    is_image_url(first).then_some(ImageSource::Url)
+                }
+                DataType::Binary | DataType::Struct(_) => {
+                    let bytes = image_bytes_series(column).ok()?;
+                    let first = bytes.binary().ok()?.into_iter().flatten().next()?;
+                    sniff(first).map(|_| ImageSource::Bytes)
+                }
+                _ => None,
+            }?;
+            Some(ImageColumn {
+                name: column.name().to_string(),
+                source,
+            })
+        })
+        .collect()
+}
+
+/// Image `src` of one row of an image column, if the row has an image.
+pub fn image_src(
+    image: &ImageColumn,
+    column: &Column,
+    filename: &str,
+    row: usize,
+) -> Option<String> {
+    match image.source {
+        ImageSource::Url => match column.get(row).ok()? {
+            AnyValue::String(url) if is_image_url(url) => Some(url.to_string()),
+            _ => None,
+        },
+        ImageSource::Bytes => {
+            let bytes = image_bytes_series(column).ok()?;
+            bytes.binary().ok()?.get(row)?;
+            Some(format!(
+                "/image/{}/{}/{}",
+                html::url_encode(filename),
+                row,
+                html::url_encode(&image.name)
+            ))
+        }
+    }
+}
+
+/// Encoded image bytes of one row of a binary or `Image` struct column.
+pub fn image_bytes(column: &Column, row: usize) -> Option<Vec<u8>> {
+    let series = image_bytes_series(column).ok()?;
+    series.binary().ok()?.get(row).map(<[u8]>::to_vec)
+}
+
+fn image_bytes_series(column: &Column) -> PolarsResult<Series> {
+    match column.dtype() {
+        DataType::Struct(_) => column.struct_()?.field_by_name("bytes"),
+        _ => Ok(column.as_materialized_series().clone()),
+    }
+}
+
+/// MIME type of encoded image bytes, from their signature.
+pub fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+fn is_image_url(value: &str) -> bool {
+    if value.starts_with("data:image/") {
+        return true;
+    }
+    let path = value.split(['?', '#']).next().unwrap_or_default();
+    (value.starts_with("https://") || value.starts_with("http://"))
+        && IMAGE_EXTENSIONS
+            .iter()
+            .any(|extension| path.to_ascii_lowercase().ends_with(extension))
+}
+
+/// Renders linked thumbnails for the images of a row; `class` sizes them.
+pub fn thumbnails(images: &[(String, String)], class: &str) -> String {
+    if images.is_empty() {
+        return String::new();
+    }
+    let thumbnails: String = images
+        .iter()
+        .map(|(column, src)| {
+            format!(
+                r#"<a href="{src}" target="_blank" rel="noopener" onclick="event.stopPropagation()"><img src="{src}" alt="{alt}" title="{alt}" loading="lazy" class="object-contain rounded bg-gray-100 dark:bg-gray-900 {class}"></a>"#,
+                src = html::escape(src),
+                alt = html::escape(column),
+                class = class,
+            )
+        })
+        .collect();
+    format!(
+        r#"<div class="flex flex-wrap gap-2 my-1">{}</div>"#,
+        thumbnails
+    )
+}
@@ -0,0 +1,95 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// SHA-256 of the audio bytes of every row of a file, recorded by `--write-hashes` so a
+/// later version of the dataset can be checked against it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Hashes {
+    /// Hash of each row in row order; `None` where the audio could not be read.
+    pub rows: Vec<Option<String>>,
+}
+
+/// How a row differs from the recorded hashes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Its bytes differ; `moved_from` is the recorded row that had the new bytes, if any,
+    /// which tells a reordering apart from an edit.
+    Changed { moved_from: Option<usize> },
+    /// Its audio was readable before and is not anymore, or the other way round.
+    Unreadable,
+    /// The row is new.
+    Added,
+    /// The row was removed.
+    Removed,
+}
+
+impl Change {
+    pub fn describe(&self) -> String {
+        match self {
+            Change::Changed {
+                moved_from: Some(row),
+            } => format!("holds the audio of recorded row {}", row),
+            Change::Changed { moved_from: None } => "audio bytes changed".to_string(),
+            Change::Unreadable => "audio readable in only one version".to_string(),
+            Change::Added => "added".to_string(),
+            Change::Removed => "removed".to_string(),
+        }
+    }
+}
+
+/// Sidecar holding the [`Hashes`] of a data folder entry, next to it.
+pub fn sidecar_path(folder: &Path, filename: &str) -> PathBuf {
+    folder.join(format!("{}.hashes.json", filename))
+}
+
+/// Reads the sidecar of a file; `None` when there is none.
+pub fn read_sidecar(folder: &Path, filename: &str) -> anyhow::Result<Option<Hashes>> {
+    let sidecar = sidecar_path(folder, filename);
+    if !sidecar.is_file() {
+        return Ok(None);
+    }
+    let data =
+        fs::read(&sidecar).with_context(|| format!("failed to read {}", sidecar.display()))?;
+    serde_json::from_slice(&data)
+        .map(Some)
+        .with_context(|| format!("failed to parse {}", sidecar.display()))
+}
+
+/// Writes the sidecar of a file.
+pub fn write_sidecar(folder: &Path, filename: &str, hashes: &Hashes) -> anyhow::Result<PathBuf> {
+    let sidecar = sidecar_path(folder, filename);
+    let partial = sidecar.with_extension("json.partial");
+    fs::write(&partial, serde_json::to_vec_pretty(hashes)?)?;
+    fs::rename(&partial, &sidecar)?;
+    Ok(sidecar)
+}
+
+/// Rows whose current hash differs from the recorded one, in row order.
+pub fn compare(recorded: &Hashes, current: &Hashes) -> Vec<(usize, Change)> {
+    let recorded_rows: HashMap<&str, usize> = recorded
+        .rows
+        .iter()
+        .enumerate()
+        .filter_map(|(row, hash)| Some((hash.as_deref()?, row)))
+        .collect();
+    let rows = recorded.rows.len().max(current.rows.len());
+    (0..rows)
+        .filter_map(|row| {
+            let change = match (recorded.rows.get(row), current.rows.get(row)) {
+                (Some(before), Some(after)) if before == after => return None,
+                (Some(Some(_)), Some(Some(after))) => Change::Changed {
+                    moved_from: recorded_rows.get(after.as_str()).copied(),
+                },
+                (Some(_), Some(_)) => Change::Unreadable,
+                (None, _) => Change::Added,
+                (_, None) => Change::Removed,
+            };
+            Some((row, change))
+        })
+        .collect()
+}
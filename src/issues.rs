@@ -0,0 +1,64 @@
+use crate::stats;
+
+/// Characters of the transcription quoted in an issue.
+const SNIPPET_CHARS: usize = 80;
+
+/// A flagged row, handed over to whoever fixes the data.
+pub struct Issue {
+    pub file: String,
+    pub row: usize,
+    /// Absolute URL of the row page.
+    pub url: String,
+    /// Tags of the row, which reviewers use to say what is wrong.
+    pub reason: String,
+    pub transcription: String,
+}
+
+/// Start of a transcription on one line, cut at a character boundary.
+fn snippet(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+/// Markdown table cell: pipes are escaped so they do not split the cell.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// Renders the issues as a Markdown table, ready to paste into a GitHub or Jira issue.
+pub fn markdown(issues: &[Issue]) -> String {
+    let mut out = format!(
+        "# Flagged rows\n\n{} rows flagged for review.\n\n| File | Row | Reason | Transcription |\n|---|---|---|---|\n",
+        issues.len()
+    );
+    for issue in issues {
+        out.push_str(&format!(
+            "| {} | [#{}]({}) | {} | {} |\n",
+            cell(&issue.file),
+            issue.row,
+            issue.url,
+            cell(&issue.reason),
+            cell(&snippet(&issue.transcription))
+        ));
+    }
+    out
+}
+
+/// Renders the issues as CSV with a header, for importing into a tracker.
+pub fn csv(issues: &[Issue]) -> String {
+    let mut out = String::from("file,row,url,reason,transcription\n");
+    for issue in issues {
+        out.push_str(&format!(
+            "{},{},{},{},{}\n",
+            stats::csv_field(&issue.file),
+            issue.row,
+            stats::csv_field(&issue.url),
+            stats::csv_field(&issue.reason),
+            stats::csv_field(&snippet(&issue.transcription))
+        ));
+    }
+    out
+}
@@ -0,0 +1,86 @@
+use crate::acl::Permission;
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+/// State of a background job.
+#[derive(Clone, Debug)]
+pub enum JobStatus {
+    Running,
+    /// Finished, with the path of the produced file for exports.
+    Finished(Option<PathBuf>),
+    Failed(String),
+}
+
+/// A background job with progress reporting.
+pub struct Job {
+    pub id: usize,
+    pub description: String,
+    /// Dataset file the job works on.
+    pub file: String,
+    /// What the job does with the file; following its progress and downloading its
+    /// output need the same permission.
+    pub permission: Permission,
+    pub total: usize,
+    done: AtomicUsize,
+    status: Mutex<JobStatus>,
+}
+
+impl Job {
+    pub fn done(&self) -> usize {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    pub fn advance(&self, steps: usize) {
+        self.done.fetch_add(steps, Ordering::Relaxed);
+    }
+
+    pub fn status(&self) -> JobStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn finish(&self, result: anyhow::Result<Option<PathBuf>>) {
+        *self.status.lock().unwrap() = match result {
+            Ok(output) => JobStatus::Finished(output),
+            Err(err) => JobStatus::Failed(format!("{:#}", err)),
+        };
+    }
+}
+
+/// Registry of the jobs started since the server came up.
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    jobs: Arc<Mutex<Vec<Arc<Job>>>>,
+}
+
+impl JobRegistry {
+    /// Registers a new running job and returns it.
+    pub fn start(
+        &self,
+        description: String,
+        total: usize,
+        file: String,
+        permission: Permission,
+    ) -> Arc<Job> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = Arc::new(Job {
+            id: jobs.len(),
+            description,
+            file,
+            permission,
+            total,
+            done: AtomicUsize::new(0),
+            status: Mutex::new(JobStatus::Running),
+        });
+        jobs.push(job.clone());
+        job
+    }
+
+    pub fn get(&self, id: usize) -> Option<Arc<Job>> {
+        self.jobs.lock().unwrap().get(id).cloned()
+    }
+}
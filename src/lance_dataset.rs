@@ -0,0 +1,106 @@
+use anyhow::{Context, anyhow, ensure};
+use arrow_ipc::writer::FileWriter;
+use lance::dataset::Dataset;
+use polars::prelude::*;
+use std::{
+    fs::{self, File},
+    io::Cursor,
+    path::Path,
+    sync::Arc,
+    thread,
+};
+
+/// Column holding the audio of each row, as in the Parquet files of the data folder.
+const AUDIO_COLUMN: &str = "audio";
+
+/// Converts a Lance dataset directory to a Parquet file laid out like the Parquet files
+/// of the data folder, so it is browsed the same way.
+///
+/// Blob-encoded columns are read in full, and a binary `audio` column becomes the
+/// `audio.bytes` struct field the viewer expects.
+pub fn convert(dataset: &Path, out: &Path) -> anyhow::Result<()> {
+    let uri = dataset
+        .to_str()
+        .context("dataset path is not valid UTF-8")?
+        .to_string();
+    // Lance is asynchronous; callers may be on a runtime thread, so read the dataset on
+    // a thread with a runtime of its own.
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let mut df = thread::scope(|scope| {
+        scope
+            .spawn(|| runtime.block_on(read(&uri)))
+            .join()
+            .map_err(|_| anyhow!("reading the Lance dataset panicked"))
+    })??;
+
+    if df.column(AUDIO_COLUMN)?.dtype().is_binary() {
+        let bytes = df
+            .column(AUDIO_COLUMN)?
+            .as_materialized_series()
+            .clone()
+            .with_name("bytes".into());
+        let audio = StructChunked::from_series(AUDIO_COLUMN.into(), df.height(), [bytes].iter())?;
+        df.with_column(audio.into_series().into_column())?;
+    }
+
+    if let Some(parent) = out.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Concurrent requests may convert the same dataset; readers only ever see a whole file.
+    let partial = out.with_extension("parquet.partial");
+    ParquetWriter::new(File::create(&partial)?).finish(&mut df)?;
+    fs::rename(&partial, out)?;
+    Ok(())
+}
+
+/// Reads every row of a Lance dataset, with the bytes of blob columns.
+async fn read(uri: &str) -> anyhow::Result<DataFrame> {
+    let dataset = Arc::new(
+        Dataset::open(uri)
+            .await
+            .with_context(|| format!("failed to open {}", uri))?,
+    );
+    // Blob columns scan as their (position, size) descriptions.
+    let batch = dataset.scan().try_into_batch().await?;
+    let mut ipc = Vec::new();
+    let mut writer = FileWriter::try_new(&mut ipc, &batch.schema())?;
+    writer.write(&batch)?;
+    writer.finish()?;
+    drop(writer);
+    let mut df = IpcReader::new(Cursor::new(ipc)).finish()?;
+
+    let rows: Vec<u64> = (0..df.height() as u64).collect();
+    for field in dataset
+        .schema()
+        .fields
+        .iter()
+        .filter(|field| field.is_blob())
+    {
+        // Null blobs have no description and are left out of the blobs taken.
+        let present = df
+            .column(&field.name)?
+            .struct_()?
+            .field_by_name("position")?
+            .is_not_null();
+        let mut blobs = dataset
+            .take_blobs_by_indices(&rows, &field.name)
+            .await?
+            .into_iter();
+        let mut values: Vec<Option<Vec<u8>>> = Vec::with_capacity(rows.len());
+        for present in present.into_no_null_iter() {
+            let value = if present {
+                let blob = blobs.next().context("fewer blobs than rows")?;
+                // Null values may be written as empty blobs.
+                Some(blob.read().await?.to_vec()).filter(|bytes| !bytes.is_empty())
+            } else {
+                None
+            };
+            values.push(value);
+        }
+        ensure!(blobs.next().is_none(), "more blobs than rows");
+        df.with_column(Column::new(field.name.as_str().into(), values))?;
+    }
+    Ok(df)
+}
@@ -1,19 +1,110 @@
+mod access_log;
+mod acl;
+mod agreement;
+mod analysis;
+mod annotations;
+mod archive;
+mod asr;
+mod audit;
+mod augment;
+mod auth;
+mod blobs;
+mod blocklist;
+mod card;
+mod columns;
+mod commands;
+mod confidence;
+mod container;
+mod dsp;
+#[cfg(feature = "duckdb")]
+mod duckdb_engine;
+mod durations;
+mod embeddings;
+mod enrich;
+mod events;
+mod expansions;
+mod expression;
+mod features;
+mod folder_index;
+mod g2p;
+mod html;
+mod i18n;
+mod images;
+mod integrity;
+mod issues;
+mod jobs;
+#[cfg(feature = "lance")]
+mod lance_dataset;
+mod nested;
+mod notes;
+mod pitch;
+mod plugins;
+mod preferences;
+mod preview;
+mod projection;
+mod reports;
+mod row_cache;
+mod schedule;
+mod schema_check;
+mod scoring;
+mod search_index;
+mod segments;
+mod server;
+mod shortcuts;
+mod spellcheck;
+mod split;
+mod splits;
+mod stats;
+mod style;
+mod templates;
+mod text;
+mod wav;
+mod webhooks;
+
+use analysis::AnalysisCache;
+use annotations::{AnnotationStore, RowAnnotation};
+use axum::Form;
 use axum::{
-    body,
-    extract::{Path as AxumPath, Query, State},
-    http,
-    response::{self, Html},
-    routing::{Router, get},
+    RequestExt, body,
+    extract::{
+        MatchedPath, Path as AxumPath, Query, RawPathParams, Request, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http, middleware,
+    response::{self, Html, IntoResponse},
+    routing::{Router, get, post},
 };
+use blocklist::Blocklist;
 use clap::Parser;
+use commands::{CommandOutput, CommandRunner, RowCommand};
+use embeddings::Embedder;
+use events::AudioEvent;
+use expression::Expression;
+use folder_index::{FileInfo, FolderIndex};
+use jobs::{Job, JobRegistry, JobStatus};
+use minijinja::context;
+use plugins::{Plugin, PluginSpec};
+use polars::io::mmap::MmapBytesReader;
 use polars::prelude::*;
-use serde::Deserialize;
+use preview::PreviewConfig;
+use row_cache::RowCache;
+use scoring::Scorer;
+use search_index::{SearchHit, SearchIndex};
+use serde::{Deserialize, Deserializer};
 use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
     fs::{self, File},
-    io::BufReader,
+    hash::{DefaultHasher, Hash, Hasher},
+    net::SocketAddr,
     path::{Path, PathBuf},
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
 };
-use tokio::net::TcpListener;
+use text::{TextNormalizer, TextStep};
+use tokenizers::Tokenizer;
 use tokio_util::io;
 
 /// Command-line arguments for the application.
@@ -27,6 +118,285 @@ struct Args {
     /// The address to bind the server to.
     #[arg(short, long, default_value = "0.0.0.0:3000")]
     bind: String,
+    /// Log every request in the given format.
+    #[arg(long, value_enum)]
+    access_log: Option<access_log::Format>,
+    /// Append the access log to this file instead of printing it.
+    #[arg(long, requires = "access_log")]
+    access_log_file: Option<PathBuf>,
+    /// If the port is in use, try the next few ports and then any free port.
+    #[arg(long)]
+    port_fallback: bool,
+    /// Open the default browser at the server address once it is listening.
+    #[arg(long)]
+    open: bool,
+    /// Compare the schemas of all files, print the differences and exit (with status 1
+    /// when they differ) instead of serving.
+    #[arg(long)]
+    check_schema: bool,
+    /// Compute the statistics of every file, write them next to it as
+    /// `<file>.stats.json` and exit; the viewer then shows them without opening the file.
+    #[arg(long)]
+    write_stats: bool,
+    /// Write a Hugging Face dataset card (`README.md`) with the clips, hours, duration
+    /// buckets, languages and speakers of every file to this path and exit. The license
+    /// and the description are left as placeholders to fill in.
+    #[arg(long, value_name = "PATH")]
+    dataset_card: Option<PathBuf>,
+    /// Hash the audio bytes of every row, write the hashes next to each file as
+    /// `<file>.hashes.json` and exit; the row page then shows whether a clip changed since.
+    #[arg(long)]
+    write_hashes: bool,
+    /// Compare the audio of every row with the hashes written by `--write-hashes`, print
+    /// the rows that changed, moved, were added or removed and exit (with status 1 when
+    /// any file differs or has no hashes).
+    #[arg(long, conflicts_with = "write_hashes")]
+    verify_hashes: bool,
+    /// Column holding the language of each clip, tallied in the dataset card.
+    #[arg(long, default_value = "language")]
+    language_column: String,
+    /// Decode every clip, print the rows whose `duration` is off by more than
+    /// `--duration-tolerance` and exit (with status 1 when any is, unless
+    /// `--durations-out` is set). Non-WAV clips are decoded with `--ffmpeg`.
+    #[arg(long)]
+    recompute_durations: bool,
+    /// Difference in seconds between the stored and the decoded duration of a clip that
+    /// `--recompute-durations` accepts.
+    #[arg(long, default_value_t = 0.01)]
+    duration_tolerance: f64,
+    /// Write a copy of each Parquet file with the decoded durations to this folder when
+    /// recomputing durations; the data folder is never modified.
+    #[arg(long, requires = "recompute_durations")]
+    durations_out: Option<PathBuf>,
+    /// Write a copy of each Parquet file with the computed analysis as columns
+    /// (`peak_dbfs`, `loudness_lufs`, `noise_floor_dbfs`, `snr_db`, `speech_ratio`,
+    /// `clipped_runs`, and `token_count` with `--tokenizer`) to this folder and exit, so
+    /// they can be filtered on downstream; the data folder is never modified.
+    #[arg(long, value_name = "DIR")]
+    analysis_out: Option<PathBuf>,
+    /// OpenID Connect issuer URL; when set, every page requires a login.
+    #[arg(long, requires_all = ["oidc_client_id", "oidc_redirect_url"])]
+    oidc_issuer: Option<String>,
+    /// Client id registered with the OIDC provider.
+    #[arg(long)]
+    oidc_client_id: Option<String>,
+    /// Client secret for confidential clients; omit for public clients.
+    #[arg(long, env = "OIDC_CLIENT_SECRET", hide_env_values = true)]
+    oidc_client_secret: Option<String>,
+    /// Callback URL registered with the provider, e.g. https://viewer.example.com/auth/callback.
+    #[arg(long)]
+    oidc_redirect_url: Option<String>,
+    /// ID token or userinfo claim listing the user's groups.
+    #[arg(long, default_value = "groups")]
+    oidc_groups_claim: String,
+    /// Only members of these groups may view this dataset root (repeatable).
+    #[arg(long = "oidc-group", value_name = "GROUP")]
+    oidc_groups: Vec<String>,
+    /// JSON access control lists granting users and groups view, annotate and export
+    /// rights per file pattern (see the README).
+    #[arg(long, requires = "oidc_issuer")]
+    acl: Option<PathBuf>,
+    /// SQLite log of every clip streamed or downloaded, with the user, time, file and
+    /// row, exported from `/audit`.
+    #[arg(long)]
+    audit_log: Option<PathBuf>,
+    /// User name, `group:NAME` or `*` allowed to export the audit log when login is
+    /// enabled (repeatable).
+    #[arg(long = "audit-admin", value_name = "PRINCIPAL", requires = "audit_log")]
+    audit_admins: Vec<String>,
+    /// Key signing the preferences cookie (theme, page size, hidden columns); without
+    /// one, preferences are forgotten on restart.
+    #[arg(long, env = "PREFERENCES_SECRET", hide_env_values = true)]
+    preferences_secret: Option<String>,
+    /// Serve low-bitrate Opus previews to the table players, and Opus on `/audio` to
+    /// clients preferring WebM or Ogg audio (requires ffmpeg).
+    #[arg(long)]
+    preview: bool,
+    /// Bitrate of the Opus previews.
+    #[arg(long, default_value = "32k")]
+    preview_bitrate: String,
+    /// Path to the ffmpeg executable used for transcoding.
+    #[arg(long, default_value = "ffmpeg")]
+    ffmpeg: PathBuf,
+    /// Path to the espeak-ng executable used by the phoneme report.
+    #[arg(long, default_value = "espeak-ng")]
+    espeak: PathBuf,
+    /// espeak-ng voice used to phonemize transcriptions, e.g. en-us or de.
+    #[arg(long, default_value = "en-us")]
+    g2p_language: String,
+    /// File listing the expected phonemes (IPA, whitespace-separated) for coverage.
+    #[arg(long)]
+    phoneme_inventory: Option<PathBuf>,
+    /// Text normalization steps, applied in order, for the "Show normalized" toggle.
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [TextStep::ExpandNumbers, TextStep::Lowercase, TextStep::StripPunctuation]
+    )]
+    text_normalization: Vec<TextStep>,
+    /// How Parquet files are read: memory-mapped (shared through the page cache), or
+    /// copied into a buffer first (safer on network filesystems where files may change).
+    #[arg(long, value_enum, default_value = "mmap")]
+    parquet_read: ParquetRead,
+    /// Engine evaluating filter expressions; DuckDB (built with `--features duckdb`)
+    /// scans the Parquet files with its own predicate pushdown.
+    #[arg(long, value_enum, default_value = "polars")]
+    engine: Engine,
+    /// What the file view players load before they are played: nothing, the duration,
+    /// or the whole clip.
+    #[arg(long, value_enum, default_value = "none")]
+    audio_preload: AudioPreload,
+    /// Players created with a file view page; the other rows get a button creating theirs,
+    /// which keeps large pages from preloading every clip. All rows by default.
+    #[arg(long)]
+    players_per_page: Option<usize>,
+    /// Let clips play at the same time, e.g. to compare overlapping clips, instead of
+    /// pausing the other players when one starts.
+    #[arg(long)]
+    concurrent_playback: bool,
+    /// HuggingFace `tokenizer.json` used to add a token count column (special tokens included).
+    #[arg(long)]
+    tokenizer: Option<PathBuf>,
+    /// Allowed characters; anything else is flagged in the character set report.
+    #[arg(long)]
+    alphabet: Option<String>,
+    /// File with one blocked word or phrase per line (`#` starts a comment); rows
+    /// containing one get a warning badge and can be filtered in or out.
+    #[arg(long)]
+    blocklist: Option<PathBuf>,
+    /// Word list transcriptions are spell-checked against, one word per line or a
+    /// Hunspell `.dic` file; words missing from it are underlined and counted in
+    /// `/spelling`. Can be repeated to add e.g. domain terms to a language dictionary.
+    #[arg(long = "dictionary", value_name = "PATH")]
+    dictionaries: Vec<PathBuf>,
+    /// Quality model endpoint; each clip is POSTed as `audio/wav` and the returned
+    /// score is shown as a sortable column.
+    #[arg(long)]
+    score_url: Option<String>,
+    /// JSON field holding the score in the endpoint response (dots for nested fields).
+    #[arg(long, default_value = "score")]
+    score_field: String,
+    /// Maximum number of concurrent requests when scoring a whole file.
+    #[arg(long, default_value_t = 4)]
+    score_concurrency: usize,
+    /// Speaker-embedding model endpoint; each clip is POSTed as `audio/wav` and the
+    /// returned vector feeds the speaker report.
+    #[arg(long)]
+    embedding_url: Option<String>,
+    /// JSON field holding the embedding in the endpoint response (dots for nested fields).
+    #[arg(long, default_value = "embedding")]
+    embedding_field: String,
+    /// Maximum number of concurrent requests when embedding the dataset.
+    #[arg(long, default_value_t = 4)]
+    embedding_concurrency: usize,
+    /// Column holding the speaker label compared against the embeddings.
+    #[arg(long, default_value = "speaker_id")]
+    speaker_column: String,
+    /// Column holding machine transcripts (pseudo-labels) compared against the human
+    /// `transcription` in the label agreement report.
+    #[arg(long, default_value = "pseudo_label")]
+    pseudo_label_column: String,
+    /// Command runnable from the row detail page, as `NAME=COMMAND` (run with `sh -c`).
+    /// `{path}`, `{file}` and `{row}` are replaced by the quoted WAV path, file name
+    /// and row index. Can be repeated.
+    #[arg(long = "command", value_name = "NAME=COMMAND")]
+    commands: Vec<RowCommand>,
+    /// Speech recognition endpoint re-transcribing clips from the row detail page, as
+    /// `NAME=URL`; each clip is POSTed as `audio/wav`. Every hypothesis is kept in the
+    /// annotations per model. Can be repeated.
+    #[arg(long = "asr", value_name = "NAME=URL")]
+    asr_models: Vec<asr::AsrModel>,
+    /// JSON field holding the transcription in the ASR response (dots for nested fields);
+    /// plain-text responses are used as they are.
+    #[arg(long, default_value = "text")]
+    asr_field: String,
+    /// Seconds after which a row command is killed.
+    #[arg(long, default_value_t = 60)]
+    command_timeout: u64,
+    /// Keep the last output of each command per row and show it on the detail page.
+    #[arg(long)]
+    cache_command_output: bool,
+    /// JSON file holding tags, flags, review marks and transcription edits
+    /// [default: FOLDER/annotations.json].
+    #[arg(long)]
+    annotations: Option<PathBuf>,
+    /// JSON file holding the notes pinned to files [default: FOLDER/notes.json].
+    #[arg(long)]
+    notes: Option<PathBuf>,
+    /// URL POSTed a JSON notification when rows are flagged, a transcription is edited
+    /// or an annotation batch completes, as `URL` or `EVENTS=URL` with a comma-separated
+    /// subset of `flagged`, `edited` and `batch_completed`. Can be repeated.
+    #[arg(long = "webhook", value_name = "[EVENTS=]URL")]
+    webhooks: Vec<webhooks::Webhook>,
+    /// Directory with templates overriding the built-in ones (`layout.html`,
+    /// `index.html`, `file_header.html`, `file_row.html`).
+    #[arg(long)]
+    templates: Option<PathBuf>,
+    /// Instance name shown in the page header and titles, e.g. "AcmeCorp Speech Dataset Browser".
+    #[arg(long)]
+    instance_name: Option<String>,
+    /// URL of a logo shown next to the instance name.
+    #[arg(long)]
+    logo_url: Option<String>,
+    /// Footer text shown on every page instead of the project link.
+    #[arg(long)]
+    footer_text: Option<String>,
+    /// UI language, e.g. `de`; English and German are built in.
+    #[arg(long, default_value = "en")]
+    lang: String,
+    /// Directory with `<lang>.json` files mapping English UI strings to translations,
+    /// adding languages or overriding built-in wording.
+    #[arg(long)]
+    locale_dir: Option<PathBuf>,
+    /// WebAssembly plugin adding a column (HTML and/or a sortable score), as
+    /// `NAME=PATH` to a `.wasm` or `.wat` module. Can be repeated.
+    #[arg(long = "plugin", value_name = "NAME=PATH")]
+    plugins: Vec<PluginSpec>,
+    /// Render transcriptions as Markdown (raw HTML stays escaped) instead of plain text.
+    #[arg(long)]
+    markdown: bool,
+    /// Tag clips as speech, music, noise or silence with heuristic detectors; every clip
+    /// of a file is decoded the first time the file is viewed.
+    #[arg(long)]
+    audio_events: bool,
+    /// Show the estimated noise floor as a column and its distribution in the metadata
+    /// details; every clip of a file is decoded the first time the file is viewed.
+    #[arg(long)]
+    noise_floor: bool,
+    /// Folder of WAV noise recordings offered for mixing into clips at a chosen SNR on the
+    /// row page and with `/augment/{file}/{row}?noise=NAME&noise_snr=10`.
+    #[arg(long)]
+    noise_dir: Option<PathBuf>,
+    /// Folder of WAV room impulse responses offered for convolving clips with on the row
+    /// page and with `/augment/{file}/{row}?rir=NAME`, to audition far-field conditions.
+    #[arg(long)]
+    rir_dir: Option<PathBuf>,
+    /// JSON object rebinding row page keyboard shortcuts for everyone, e.g.
+    /// `{"play": "space"}`; reviewers can still rebind keys for their own browser.
+    #[arg(long)]
+    key_bindings: Option<PathBuf>,
+    /// JSON object choosing how dataset columns are shown in the file view and on the row
+    /// page, by column name: `text`, `badge` (with color `thresholds` or string `colors`),
+    /// `link`, `duration`, `code` or `hidden`.
+    #[arg(long)]
+    column_renderers: Option<PathBuf>,
+    /// Speaking rates (words per minute) below this are highlighted as outliers.
+    #[arg(long, default_value_t = 80.0)]
+    min_wpm: f64,
+    /// Speaking rates (words per minute) above this are highlighted as outliers.
+    #[arg(long, default_value_t = 250.0)]
+    max_wpm: f64,
+    /// SQLite full-text index of every transcription backing `/search`; it is built at
+    /// startup and files are reindexed when they change. Without it, searches scan the
+    /// Parquet files.
+    #[arg(long)]
+    search_index: Option<PathBuf>,
+    /// Rescan the data folder this often (e.g. `30m`, `1h`, `1d`), refreshing the folder
+    /// index, the search index and the stats sidecars of new or changed files, for
+    /// folders that pipelines keep adding shards to.
+    #[arg(long, value_name = "PERIOD")]
+    reindex_every: Option<schedule::Every>,
 }
 
 /// Application state shared across handlers.
@@ -34,13 +404,572 @@ struct Args {
 struct AppState {
     folder: PathBuf,
     tmp_folder: PathBuf,
+    preview: Option<PreviewConfig>,
+    analysis: AnalysisCache,
+    text_normalizer: TextNormalizer,
+    tokenizer: Option<Arc<Tokenizer>>,
+    alphabet: Option<Arc<HashSet<char>>>,
+    blocklist: Option<Arc<Blocklist>>,
+    dictionary: Option<Arc<spellcheck::Dictionary>>,
+    scorer: Option<Scorer>,
+    embedder: Option<Embedder>,
+    speaker_column: String,
+    commands: Option<CommandRunner>,
+    transcriber: Option<asr::Transcriber>,
+    annotations: AnnotationStore,
+    notes: notes::NoteStore,
+    webhooks: Option<webhooks::Webhooks>,
+    jobs: JobRegistry,
+    plugins: Arc<Vec<Plugin>>,
+    markdown: bool,
+    audio_events: bool,
+    noise_floor: bool,
+    noise_bank: Option<Arc<augment::SoundBank>>,
+    rir_bank: Option<Arc<augment::SoundBank>>,
+    /// Row page keyboard shortcuts configured with `--key-bindings`, by action name.
+    key_bindings: Arc<BTreeMap<String, String>>,
+    /// Words-per-minute range outside which clips count as outliers.
+    wpm_range: (f64, f64),
+    search_index: Option<Arc<SearchIndex>>,
+    folder_index: Arc<FolderIndex>,
+    filtered: Arc<RowCache<FilterKey, FilteredRows>>,
+    token_counts: Arc<RowCache<TokenKey, Vec<usize>>>,
+    /// Number of completed non-GET requests, part of the page ETags.
+    mutations: Arc<AtomicU64>,
+    oidc: Option<Arc<auth::Oidc>>,
+    acl: Option<Arc<acl::Acl>>,
+    audit: Option<Arc<audit::AuditLog>>,
+    audit_admins: Arc<Vec<String>>,
+    preferences: Arc<preferences::Signer>,
+    g2p: Arc<g2p::G2p>,
+    playback: Playback,
 }
 
 /// Represents pagination query parameters.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 struct Pagination {
     page: Option<usize>,
     page_size: Option<usize>,
+    /// Column to sort by, prefixed with `-` for descending order.
+    sort: Option<String>,
+    /// Play clips with loudness normalization applied.
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    normalize: bool,
+    /// Show the normalized transcription under the original.
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    show_normalized: bool,
+    /// Row to seek to: the file view redirects to the page holding it in the current
+    /// order and highlights it.
+    row: Option<usize>,
+}
+
+impl Pagination {
+    /// Query string fragment (starting with `&`) carrying the view options
+    /// that must survive pagination.
+    fn extra_query(&self) -> String {
+        let mut query = String::new();
+        if self.normalize {
+            query.push_str("&normalize=1");
+        }
+        if self.show_normalized {
+            query.push_str("&show_normalized=1");
+        }
+        if let Some(sort) = &self.sort {
+            query.push_str(&format!("&sort={}", html::url_encode(sort)));
+        }
+        query
+    }
+}
+
+/// Row filter of the file view, also selecting the rows of batch operations.
+#[derive(Deserialize, Debug, Clone, Default)]
+struct RowFilter {
+    /// Case-insensitive substring of the transcription.
+    #[serde(default)]
+    q: String,
+    /// Only rows carrying this tag.
+    #[serde(default)]
+    tag: String,
+    /// `flagged`, `reviewed` or `unreviewed`.
+    #[serde(default)]
+    status: String,
+    /// Audio event tag, see [`AudioEvent::as_str`]; checked by [`filter_rows`].
+    #[serde(default)]
+    event: String,
+    /// `only` or `exclude` the rows containing a `--blocklist` entry; checked by
+    /// [`filter_rows`].
+    #[serde(default)]
+    blocklist: String,
+    /// Casing or punctuation class, see [`style::Class::as_str`].
+    #[serde(default)]
+    style: String,
+    /// Minimum duration in seconds.
+    #[serde(default)]
+    min_duration: String,
+    /// Maximum duration in seconds.
+    #[serde(default)]
+    max_duration: String,
+    /// Minimum of the lowest confidence of a row, see [`confidence::lowest`].
+    #[serde(default)]
+    min_confidence: String,
+    /// Maximum of the lowest confidence of a row.
+    #[serde(default)]
+    max_confidence: String,
+    /// Pseudo-label agreement bucket, see [`agreement::BUCKETS`].
+    #[serde(default)]
+    wer: String,
+    /// Sampling rate in Hz, or `unknown` for rows without one.
+    #[serde(default)]
+    rate: String,
+    /// Filter expression over any column, see [`Expression`].
+    #[serde(default)]
+    filter: String,
+    /// Accepted values per facet column, from repeated `facet.COLUMN=VALUE` parameters.
+    #[serde(skip)]
+    facets: BTreeMap<String, BTreeSet<String>>,
+    /// Rows matching `filter`, set by [`RowFilter::evaluate`].
+    #[serde(skip)]
+    mask: Option<Arc<Vec<bool>>>,
+}
+
+impl RowFilter {
+    /// Adds the facet selections found in the raw query parameters.
+    fn with_facets(mut self, params: &[(String, String)]) -> Self {
+        for (name, value) in params {
+            if let Some(column) = name.strip_prefix("facet.") {
+                self.facets
+                    .entry(column.to_string())
+                    .or_default()
+                    .insert(value.clone());
+            }
+        }
+        self
+    }
+
+    /// Evaluates the filter expression and duration bounds against a file; when the
+    /// expression is invalid, no row matches.
+    fn evaluate(&mut self, path: &Path, files: &[Audio]) -> anyhow::Result<()> {
+        let result = self.predicate().and_then(|predicate| match predicate {
+            Some(predicate) => predicate_mask(predicate, path, files).map(Some),
+            None => Ok(None),
+        });
+        self.mask = match &result {
+            Ok(mask) => mask.clone().map(Arc::new),
+            Err(_) => Some(Arc::new(vec![false; files.len()])),
+        };
+        result.map(|_| ())
+    }
+
+    /// The filter expression combined with the duration bounds, if any is set.
+    fn predicate(&self) -> anyhow::Result<Option<Expression>> {
+        let bound = |value: &str| value.trim().parse::<f64>().ok();
+        let bounds = [
+            bound(&self.min_duration).map(|min| {
+                (
+                    col("duration").gt_eq(lit(min)),
+                    format!("\"duration\" >= {}", min),
+                )
+            }),
+            bound(&self.max_duration).map(|max| {
+                (
+                    col("duration").lt_eq(lit(max)),
+                    format!("\"duration\" <= {}", max),
+                )
+            }),
+        ];
+        let mut predicate = match self.filter.trim() {
+            "" => None,
+            filter => Some(Expression::parse(filter)?),
+        };
+        for (expr, sql) in bounds.into_iter().flatten() {
+            let bound = Expression::new(expr, sql, "duration");
+            predicate = Some(match predicate {
+                Some(predicate) => predicate.and(bound),
+                None => bound,
+            });
+        }
+        Ok(predicate)
+    }
+
+    fn is_active(&self) -> bool {
+        self.fields().iter().any(|(_, value)| !value.is_empty()) || !self.facets.is_empty()
+    }
+
+    /// Matches a row, with `audio.transcription` already corrected.
+    /// Duration bounds and the filter expression are checked through the mask set by
+    /// [`RowFilter::evaluate`].
+    fn matches(&self, row: usize, audio: &Audio, annotation: &RowAnnotation) -> bool {
+        self.mask.as_ref().is_none_or(|mask| mask[row])
+            && (self.q.is_empty()
+                || audio
+                    .transcription
+                    .to_lowercase()
+                    .contains(&self.q.to_lowercase()))
+            && (self.tag.is_empty() || annotation.tags.contains(&self.tag))
+            && match self.status.as_str() {
+                "flagged" => annotation.flagged,
+                "reviewed" => annotation.reviewed,
+                "unreviewed" => !annotation.reviewed,
+                "commented" => !annotation.comments.is_empty(),
+                _ => true,
+            }
+            && (self.style.is_empty()
+                || style::Class::parse(&self.style)
+                    .is_some_and(|class| class.matches(&audio.transcription)))
+            && self.facets.iter().all(|(column, values)| {
+                audio
+                    .facets
+                    .get(column)
+                    .is_some_and(|value| values.contains(value))
+            })
+            && self.matches_confidence(audio)
+            && (self.rate.is_empty() || rate_label(audio.sample_rate) == self.rate)
+            && (self.wer.is_empty()
+                || audio
+                    .pseudo_label_wer()
+                    .is_some_and(|wer| agreement::BUCKETS[agreement::bucket(wer)].name == self.wer))
+    }
+
+    /// Whether the lowest confidence of a row is within the bounds; rows without a
+    /// confidence only match when no bound is set.
+    fn matches_confidence(&self, audio: &Audio) -> bool {
+        let bound = |value: &str| value.trim().parse::<f64>().ok();
+        let (min, max) = (bound(&self.min_confidence), bound(&self.max_confidence));
+        if min.is_none() && max.is_none() {
+            return true;
+        }
+        confidence::lowest(&audio.confidences).is_some_and(|lowest| {
+            min.is_none_or(|min| lowest >= min) && max.is_none_or(|max| lowest <= max)
+        })
+    }
+
+    /// Scalar parameters as `(name, value)` pairs.
+    fn fields(&self) -> [(&str, &str); 13] {
+        [
+            ("q", &self.q),
+            ("tag", &self.tag),
+            ("status", &self.status),
+            ("event", &self.event),
+            ("blocklist", &self.blocklist),
+            ("style", &self.style),
+            ("min_duration", &self.min_duration),
+            ("max_duration", &self.max_duration),
+            ("min_confidence", &self.min_confidence),
+            ("max_confidence", &self.max_confidence),
+            ("wer", &self.wer),
+            ("rate", &self.rate),
+            ("filter", &self.filter),
+        ]
+    }
+
+    /// Non-empty parameters, facets included, as `(name, value)` pairs.
+    fn params(&self) -> Vec<(String, &str)> {
+        let fields = self
+            .fields()
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(name, value)| (name.to_string(), value));
+        let facets = self.facets.iter().flat_map(|(column, values)| {
+            values
+                .iter()
+                .map(move |value| (format!("facet.{}", column), value.as_str()))
+        });
+        fields.chain(facets).collect()
+    }
+
+    /// Query string fragment (starting with `&`) reproducing the filter.
+    fn query(&self) -> String {
+        self.params()
+            .iter()
+            .map(|(name, value)| format!("&{}={}", html::url_encode(name), html::url_encode(value)))
+            .collect()
+    }
+
+    /// Hidden form inputs reproducing the filter.
+    fn hidden_inputs(&self) -> String {
+        self.params()
+            .iter()
+            .map(|(name, value)| hidden_input(name, value))
+            .collect()
+    }
+}
+
+/// Evaluates a filter predicate, returning whether each row matches.
+///
+/// The predicate is pushed down to a lazy Parquet scan, so row groups whose statistics
+/// rule out a match are skipped. Predicates on `transcription` must see the corrected
+/// text instead, so they are evaluated on the referenced columns of the whole file.
+/// With `--engine duckdb`, DuckDB scans the file instead of Polars.
+fn predicate_mask(
+    predicate: Expression,
+    path: &Path,
+    files: &[Audio],
+) -> anyhow::Result<Vec<bool>> {
+    if predicate.columns.contains("transcription") {
+        return corrected_predicate_mask(predicate, path, files);
+    }
+    #[cfg(feature = "duckdb")]
+    if ENGINE.get() == Some(&Engine::Duckdb) {
+        return duckdb_engine::predicate_mask(&predicate.sql, path, files.len());
+    }
+    let args = ScanArgsParquet {
+        row_index: Some(RowIndex {
+            name: "row".into(),
+            offset: 0,
+        }),
+        ..Default::default()
+    };
+    let matching = LazyFrame::scan_parquet(PlRefPath::try_from_path(path)?, args)?
+        .filter(predicate.expr)
+        .select([col("row")])
+        .collect()?;
+    let mut mask = vec![false; files.len()];
+    for row in matching.column("row")?.idx()?.into_no_null_iter() {
+        mask[row as usize] = true;
+    }
+    Ok(mask)
+}
+
+/// Evaluates a predicate with `transcription` replaced by the corrected text.
+fn corrected_predicate_mask(
+    predicate: Expression,
+    path: &Path,
+    files: &[Audio],
+) -> anyhow::Result<Vec<bool>> {
+    let mut columns = vec!["duration".to_string()];
+    columns.extend(
+        predicate
+            .columns
+            .iter()
+            .filter(|column| !["duration", "transcription"].contains(&column.as_str()))
+            .cloned(),
+    );
+    let mut df = parquet_reader(path)?.with_columns(Some(columns)).finish()?;
+    df.with_column(Column::new(
+        "transcription".into(),
+        files
+            .iter()
+            .map(|audio| audio.transcription.as_str())
+            .collect::<Vec<_>>(),
+    ))?;
+    let mask = df.lazy().select([predicate.expr.alias("mask")]).collect()?;
+    Ok(mask
+        .column("mask")?
+        .bool()?
+        .into_iter()
+        .map(|matched| matched.unwrap_or(false))
+        .collect())
+}
+
+/// Hidden form input carrying a query parameter.
+fn hidden_input(name: &str, value: &str) -> String {
+    format!(
+        r#"<input type="hidden" name="{}" value="{}">"#,
+        html::escape(name),
+        html::escape(value)
+    )
+}
+
+/// Action applied by a batch operation.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum BatchAction {
+    Tag,
+    Untag,
+    Flag,
+    Unflag,
+    Review,
+    Unreview,
+    Export,
+}
+
+/// Query parameters of a batch operation; the rows are selected by a [`RowFilter`].
+#[derive(Deserialize, Debug)]
+struct BatchQuery {
+    action: BatchAction,
+    /// Tag name for `tag` and `untag`.
+    #[serde(default)]
+    value: String,
+}
+
+impl BatchAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BatchAction::Tag => "tag",
+            BatchAction::Untag => "untag",
+            BatchAction::Flag => "flag",
+            BatchAction::Unflag => "unflag",
+            BatchAction::Review => "review",
+            BatchAction::Unreview => "unreview",
+            BatchAction::Export => "export",
+        }
+    }
+}
+
+impl BatchQuery {
+    fn describe(&self) -> String {
+        match self.action {
+            BatchAction::Tag => format!("Add tag \"{}\"", self.value),
+            BatchAction::Untag => format!("Remove tag \"{}\"", self.value),
+            BatchAction::Flag => "Flag".to_string(),
+            BatchAction::Unflag => "Unflag".to_string(),
+            BatchAction::Review => "Mark reviewed".to_string(),
+            BatchAction::Unreview => "Mark unreviewed".to_string(),
+            BatchAction::Export => "Export".to_string(),
+        }
+    }
+}
+
+/// Query parameters of the character set report.
+#[derive(Deserialize, Debug)]
+struct CharsetQuery {
+    /// Also list word frequencies.
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    words: bool,
+    /// Maximum number of words to list.
+    limit: Option<usize>,
+}
+
+/// Query parameters of the n-gram report.
+#[derive(Deserialize, Debug)]
+struct NgramQuery {
+    /// Number of n-grams listed per order.
+    k: Option<usize>,
+    /// Count n-grams over normalized text (see `--text-normalization`).
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    normalized: bool,
+}
+
+/// Query parameters of the spelling report.
+#[derive(Deserialize, Debug)]
+struct SpellingQuery {
+    /// Number of words listed.
+    k: Option<usize>,
+}
+
+/// Query parameters of the duplicate transcription report.
+#[derive(Deserialize, Debug)]
+struct DuplicatesQuery {
+    /// Minimum number of occurrences for a transcription to be reported.
+    min_count: Option<usize>,
+    /// Group transcriptions by their normalized form (see `--text-normalization`).
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    normalized: bool,
+}
+
+/// Query parameters of the completeness report.
+#[derive(Deserialize, Debug)]
+struct CompletenessQuery {
+    /// Columns with a larger percentage of missing values are highlighted.
+    max_missing: Option<f64>,
+}
+
+/// Query parameters of the speaking rate report; both default to the `--min-wpm` and
+/// `--max-wpm` options.
+#[derive(Deserialize, Debug)]
+struct WpmQuery {
+    min_wpm: Option<f64>,
+    max_wpm: Option<f64>,
+    /// Downloads the histogram and per-file counts instead of the page.
+    format: Option<stats::Format>,
+}
+
+/// Encoding of the audit log export; CSV by default.
+#[derive(Deserialize, Debug)]
+struct AuditFormatQuery {
+    format: Option<stats::Format>,
+}
+
+/// Query parameters of the speaker report.
+#[derive(Deserialize, Debug)]
+struct SpeakersQuery {
+    /// Cosine similarity at which two voices count as the same speaker.
+    threshold: Option<f32>,
+}
+
+/// Query parameters of the text leakage check.
+#[derive(Deserialize, Debug)]
+struct TextLeakageQuery {
+    /// Largest normalized edit distance at which two transcriptions match.
+    max_distance: Option<f64>,
+    /// Transcriptions with fewer words are not compared.
+    min_words: Option<usize>,
+}
+
+/// Query parameters of the embedding map.
+#[derive(Deserialize, Debug)]
+struct MapQuery {
+    #[serde(default)]
+    method: projection::Method,
+    /// t-SNE perplexity, roughly the number of neighbours each point keeps close.
+    perplexity: Option<f32>,
+    /// Color points by `file` instead of by speaker label.
+    color: Option<String>,
+}
+
+/// Query parameters of the phoneme coverage report.
+#[derive(Deserialize, Debug)]
+struct PhonemeQuery {
+    /// Phonemes seen fewer times than this are reported as rare.
+    min_count: Option<usize>,
+    /// Phonemize normalized text (see `--text-normalization`), which spells out numbers.
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    normalized: bool,
+}
+
+/// Query parameters of the global search.
+#[derive(Deserialize, Debug)]
+struct SearchQuery {
+    /// Case-insensitive substring of the transcription.
+    #[serde(default)]
+    q: String,
+}
+
+/// Query parameters accepted by the audio endpoint.
+#[derive(Deserialize, Debug)]
+struct AudioQuery {
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    normalize: bool,
+    /// Trims the clip to start at this many seconds.
+    start: Option<f64>,
+    /// Trims the clip to end at this many seconds.
+    end: Option<f64>,
+    /// Serves the bytes stored in the dataset instead, see [`stored_audio`].
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    raw: bool,
+}
+
+/// Query parameters accepted by the download endpoint.
+#[derive(Deserialize, Debug)]
+struct DownloadQuery {
+    /// Serves the bytes stored in the dataset instead, see [`stored_audio`].
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    raw: bool,
+}
+
+/// Output encoding for the feature endpoint.
+#[derive(Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum FeatureFormat {
+    #[default]
+    Json,
+    Npy,
+}
+
+/// Query parameters selecting the feature output encoding.
+#[derive(Deserialize, Debug)]
+struct FeatureFormatQuery {
+    #[serde(default)]
+    format: FeatureFormat,
+}
+
+/// Deserializes HTML-style boolean flags (`1`, `true`, `on`).
+fn deserialize_flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(matches!(
+        value.as_deref(),
+        Some("1" | "true" | "on" | "yes")
+    ))
 }
 
 #[derive(Clone)]
@@ -48,14 +977,143 @@ struct Audio {
     path: PathBuf,
     duration: f64,
     transcription: String,
+    /// Values of the low-cardinality columns offered as facets.
+    facets: BTreeMap<String, String>,
+    /// Transcript segments of a long recording, from the `segments` column.
+    segments: Vec<segments::Segment>,
+    /// Column name and `src` of each image of the row.
+    images: Vec<(String, String)>,
+    /// Values of the struct and list columns (alignments, metadata dicts, ...).
+    nested: Vec<(String, serde_json::Value)>,
+    /// Values of the confidence columns, see [`confidence::columns`].
+    confidences: Vec<(String, Option<f64>)>,
+    /// Machine transcript, from the `--pseudo-label-column`.
+    pseudo_label: Option<String>,
+    /// Size of the stored audio in bytes; tiny payloads are usually truncated or empty.
+    size: Option<usize>,
+    /// Values of the shown columns with a `--column-renderers` entry.
+    columns: Vec<(String, serde_json::Value)>,
+    /// Sampling rate in Hz, from the WAV header of embedded audio or else from a
+    /// `sampling_rate` column.
+    sample_rate: Option<u32>,
 }
 
-fn extract_parquet(path: &Path) -> PolarsResult<DataFrame> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+impl Audio {
+    /// Word error rate of the pseudo-label against the (corrected) transcription.
+    fn pseudo_label_wer(&self) -> Option<f64> {
+        self.pseudo_label
+            .as_deref()
+            .map(|label| agreement::word_error_rate(&self.transcription, label))
+    }
+}
+
+/// Columns with more distinct values than this are not offered as facets.
+const MAX_FACET_VALUES: usize = 50;
+
+/// Names of the categorical columns (strings, booleans and integers with few distinct
+/// values) that can be used as facets.
+fn facet_columns(df: &DataFrame) -> PolarsResult<Vec<String>> {
+    let mut columns = Vec::new();
+    for column in df.columns() {
+        let name = column.name().as_str();
+        let dtype = column.dtype();
+        if name == "duration"
+            || name == "transcription"
+            || PSEUDO_LABEL_COLUMN
+                .get()
+                .is_some_and(|column| column == name)
+            || name.starts_with("audio_")
+            || COLUMN_RENDERERS
+                .get()
+                .is_some_and(|renderers| renderers.is_hidden(name))
+            || !(matches!(dtype, DataType::String | DataType::Boolean) || dtype.is_integer())
+        {
+            continue;
+        }
+        let distinct = column.n_unique()?;
+        if distinct <= MAX_FACET_VALUES && distinct < df.height() {
+            columns.push(name.to_string());
+        }
+    }
+    Ok(columns)
+}
+
+/// Strategy for reading Parquet files, see `--parquet-read`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum ParquetRead {
+    Mmap,
+    Buffered,
+}
+
+static PARQUET_READ: OnceLock<ParquetRead> = OnceLock::new();
 
-    let reader_pq = ParquetReader::new(reader);
-    reader_pq
+/// Query engine evaluating filter expressions, see `--engine`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum Engine {
+    Polars,
+    /// Requires building with `--features duckdb`.
+    Duckdb,
+}
+
+static ENGINE: OnceLock<Engine> = OnceLock::new();
+
+/// `preload` of the file view players, see `--audio-preload`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum AudioPreload {
+    None,
+    Metadata,
+    Auto,
+}
+
+impl AudioPreload {
+    fn attribute(self) -> &'static str {
+        match self {
+            AudioPreload::None => "none",
+            AudioPreload::Metadata => "metadata",
+            AudioPreload::Auto => "auto",
+        }
+    }
+}
+
+/// How the file view players behave, from `--audio-preload`, `--players-per-page` and
+/// `--concurrent-playback`.
+#[derive(Clone, Copy, Debug)]
+struct Playback {
+    preload: AudioPreload,
+    players_per_page: Option<usize>,
+    concurrent: bool,
+}
+
+impl Playback {
+    /// Script telling the page scripts whether starting a player pauses the others.
+    fn script(&self) -> String {
+        format!(
+            "\n    <script>var concurrentPlayback = {};</script>",
+            self.concurrent
+        )
+    }
+}
+
+/// Column holding the pseudo-labels, see `--pseudo-label-column`.
+static PSEUDO_LABEL_COLUMN: OnceLock<String> = OnceLock::new();
+
+/// Renderers of dataset columns, see `--column-renderers`.
+static COLUMN_RENDERERS: OnceLock<columns::Renderers> = OnceLock::new();
+
+/// Opens a Parquet file with the configured [`ParquetRead`] strategy.
+///
+/// Polars memory-maps readers that expose their `File`; the buffered mode hands it an
+/// in-memory copy instead.
+fn parquet_reader(path: &Path) -> PolarsResult<ParquetReader<Box<dyn MmapBytesReader>>> {
+    let reader: Box<dyn MmapBytesReader> = match PARQUET_READ.get() {
+        Some(ParquetRead::Buffered) => Box::new(std::io::Cursor::new(fs::read(path)?)),
+        _ => Box::new(File::open(path)?),
+    };
+    Ok(ParquetReader::new(reader))
+}
+
+fn extract_parquet(path: &Path) -> PolarsResult<DataFrame> {
+    parquet_reader(path)?
         .finish()?
         // Unnest the 'audio' struct column. This creates new columns.
         .unnest(["audio"], None)
@@ -137,6 +1195,12 @@ impl Histogram {
 
     /// Renders the histogram as a formatted string.
     fn render(&self, field: &str) -> String {
+        self.render_marked(field, |_, _| false)
+    }
+
+    /// Like [`Histogram::render`], with a `!` after the bars of the bins for which
+    /// `marked(start, end)` holds.
+    fn render_marked(&self, field: &str, marked: impl Fn(f64, f64) -> bool) -> String {
         let mut output = String::new();
         output.push_str(&format!(
             "Histogram of {}: {} values\n",
@@ -155,17 +1219,150 @@ impl Histogram {
             };
             let bar = std::iter::repeat_n(self.bar_char, bar_length).collect::<String>();
             let range_str = format!("[{:.2} - {:.2})", start, end);
-            output.push_str(&format!("{}\t{:>8}\t{}\n", range_str, count, bar));
+            let mark = if marked(*start, *end) { " !" } else { "" };
+            output.push_str(&format!("{}\t{:>8}\t{}{}\n", range_str, count, bar, mark));
         }
 
         output
     }
+
+    /// The bins as downloadable records.
+    fn records(&self, chart: &str) -> Vec<stats::Record> {
+        self.bins
+            .iter()
+            .map(|&(start, end, count)| stats::Record::bin(chart, start, end, count))
+            .collect()
+    }
+
+    /// The rendered histogram with its bins.
+    fn chart(&self, field: &str) -> stats::Chart {
+        stats::Chart {
+            text: self.render(field),
+            records: self.records(field),
+        }
+    }
 }
 
-fn plot_durations(data: &[f64]) -> String {
+fn plot_durations(data: &[f64]) -> stats::Chart {
     let hist = Histogram::new(data, 4, 20, '*');
 
-    hist.render("durations")
+    hist.chart("durations")
+}
+
+/// Quantile of sorted values, interpolating linearly between the closest ranks.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let rank = q * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+}
+
+/// A text box-and-whisker plot, complementing the histograms with quantiles: whiskers
+/// reach the furthest values within 1.5 IQR of the box, values beyond are drawn as `o`.
+fn plot_box(values: &[f64], field: &str) -> stats::Chart {
+    const WIDTH: usize = 60;
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+    let [p5, q1, median, q3, p95] = [0.05, 0.25, 0.5, 0.75, 0.95].map(|q| quantile(&sorted, q));
+    let iqr = q3 - q1;
+    let low = sorted
+        .iter()
+        .copied()
+        .find(|&v| v >= q1 - 1.5 * iqr)
+        .unwrap_or(min);
+    let high = sorted
+        .iter()
+        .copied()
+        .rfind(|&v| v <= q3 + 1.5 * iqr)
+        .unwrap_or(max);
+    let column = |v: f64| {
+        if max > min {
+            ((v - min) / (max - min) * (WIDTH - 1) as f64).round() as usize
+        } else {
+            0
+        }
+    };
+
+    let mut line = vec![' '; WIDTH];
+    for c in &mut line[column(low)..=column(high)] {
+        *c = '-';
+    }
+    for c in &mut line[column(q1)..=column(q3)] {
+        *c = '=';
+    }
+    let outliers = sorted.iter().filter(|&&v| v < low || v > high);
+    for &v in outliers.clone() {
+        line[column(v)] = 'o';
+    }
+    line[column(low)] = '|';
+    line[column(high)] = '|';
+    line[column(q1)] = '[';
+    line[column(q3)] = ']';
+    line[column(median)] = '#';
+
+    let outlier_count = outliers.count();
+    let text = format!(
+        "Box plot of {}: {} values, {} outliers\n{}\n{:<w$}{:>w$}\nmin {:.2}  p5 {:.2}  q1 {:.2}  median {:.2}  q3 {:.2}  p95 {:.2}  max {:.2}\n",
+        field,
+        sorted.len(),
+        outlier_count,
+        line.into_iter().collect::<String>(),
+        format!("{:.2}", min),
+        format!("{:.2}", max),
+        min,
+        p5,
+        q1,
+        median,
+        q3,
+        p95,
+        max,
+        w = WIDTH / 2
+    );
+    let records = [
+        ("min", min),
+        ("p5", p5),
+        ("q1", q1),
+        ("median", median),
+        ("q3", q3),
+        ("p95", p95),
+        ("max", max),
+        ("lower_whisker", low),
+        ("upper_whisker", high),
+        ("outliers", outlier_count as f64),
+    ]
+    .iter()
+    .map(|&(statistic, value)| stats::Record::value(field, statistic, value))
+    .collect();
+    stats::Chart { text, records }
+}
+
+/// Speaking rate of a clip in words per minute, `None` for clips without a duration.
+fn words_per_minute(duration: f64, transcription: &str) -> Option<f64> {
+    (duration > 0.0).then(|| transcription.split_whitespace().count() as f64 / duration * 60.0)
+}
+
+/// Histogram of speaking rates, marking the bins reaching outside `(min, max)` and
+/// counting the clips outside it.
+fn plot_wpm(rates: &[f64], (min, max): (f64, f64)) -> stats::Chart {
+    const FIELD: &str = "words per minute";
+    let hist = Histogram::new(rates, 8, 20, '*');
+    let slower = rates.iter().filter(|&&rate| rate < min).count();
+    let faster = rates.iter().filter(|&&rate| rate > max).count();
+    let mut text = hist.render_marked(FIELD, |start, end| start < min || end > max);
+    text.push_str(&format!(
+        "! outside {} - {} wpm: {} slower, {} faster\n",
+        min, max, slower, faster
+    ));
+    let mut records = hist.records(FIELD);
+    records.push(stats::Record {
+        end: Some(min),
+        ..stats::Record::value(FIELD, "slower", slower as f64)
+    });
+    records.push(stats::Record {
+        start: Some(max),
+        ..stats::Record::value(FIELD, "faster", faster as f64)
+    });
+    stats::Chart { text, records }
 }
 
 /// A simple text-based histogram for integer values, rendered as a string using ASCII bars.
@@ -199,11 +1396,9 @@ impl IntHistogram {
             if value < min || value > max {
                 continue;
             }
-            let bin_idx = if bin_width > 0 {
-                ((value - min) / bin_width).min(num_bins - 1)
-            } else {
-                0
-            };
+            let bin_idx = (value - min)
+                .checked_div(bin_width)
+                .map_or(0, |idx| idx.min(num_bins - 1));
             bin_counts[bin_idx] += 1;
         }
 
@@ -248,410 +1443,7480 @@ impl IntHistogram {
         }
         output
     }
+
+    /// The rendered histogram with its bins.
+    fn chart(&self, field: &str) -> stats::Chart {
+        stats::Chart {
+            text: self.render(field),
+            records: self
+                .bins
+                .iter()
+                .map(|&(start, end, count)| {
+                    stats::Record::bin(field, start as f64, end as f64, count)
+                })
+                .collect(),
+        }
+    }
 }
 
-fn plot_transcription_lengths(data: &[usize]) -> String {
+fn plot_transcription_lengths(data: &[usize]) -> stats::Chart {
     let hist = IntHistogram::new(data, 4, 20, '*');
-    hist.render("transcription lengths")
+    hist.chart("transcription lengths")
 }
 
-fn extract_parquet_file(tmp_folder: &Path, folder: &Path, filename: &str) -> Vec<Audio> {
-    let file_path = folder.join(filename);
-
-    let df = extract_parquet(&file_path).unwrap();
-
-    // Save data frame to temp folder
-    let tmp_folder_subdir = tmp_folder.join(filename);
+/// Reads only the transcription column of a Parquet file.
+fn load_transcriptions(path: &Path) -> PolarsResult<Vec<String>> {
+    let df = parquet_reader(path)?
+        .with_columns(Some(vec!["transcription".to_string()]))
+        .finish()?;
+    let col = df.column("transcription")?.cast(&DataType::String)?;
+    Ok(col
+        .str()?
+        .into_iter()
+        .map(|s| s.unwrap_or_default().to_string())
+        .collect())
+}
 
-    if !tmp_folder_subdir.exists() {
-        fs::create_dir(&tmp_folder_subdir).unwrap();
-    }
+/// Lists the Parquet files, zip datasets and Lance datasets in the data folder, sorted
+/// by name.
+fn list_parquet_files(folder: &Path) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir(folder)
+        .unwrap_or_else(|_| fs::read_dir(".").unwrap()) // Fallback to current directory if specified folder is invalid
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            matches!(
+                entry.path().extension().and_then(|s| s.to_str()),
+                Some("parquet" | "zip")
+            ) || entry.file_name().to_str().is_some_and(is_lance)
+        })
+        .filter_map(|entry| entry.file_name().to_str().map(|s| s.to_string()))
+        .collect();
+    files.sort();
+    files
+}
 
-    let col_d = df.column("duration").unwrap();
-    let col_t = df.column("transcription").unwrap();
+/// Whether the user of the current request may do `permission` with a file; always true
+/// without access control lists.
+fn allows(state: &AppState, file: &str, permission: acl::Permission) -> bool {
+    let Some(acl) = &state.acl else {
+        return true;
+    };
+    acl::current_session().is_some_and(|session| acl.allows(&session, file, permission))
+}
 
-    let col = df.column("audio_bytes").unwrap();
-    let binary_arr = col.binary().unwrap();
+/// The dataset files the user of the current request may view, for the file list and
+/// the corpus-wide pages.
+fn visible_files(state: &AppState) -> Vec<String> {
+    list_parquet_files(&state.folder)
+        .into_iter()
+        .filter(|file| allows(state, file, acl::Permission::View))
+        .collect()
+}
 
-    let mut created_files = vec![];
+/// Checks that `filename` names a Parquet file, zip dataset or Lance dataset directly
+/// inside the data folder.
+fn is_valid_parquet(state: &AppState, filename: &str) -> bool {
+    let path = state.folder.join(filename);
+    if filename.contains(['/', '\\']) {
+        return false;
+    }
+    if is_lance(filename) {
+        return path.is_dir();
+    }
+    (filename.ends_with(".parquet") || archive::is_archive(filename)) && path.is_file()
+}
 
-    for i in 0..df.height() {
-        let path = tmp_folder_subdir.join(format!("{}.wav", i));
+/// Whether a data folder entry is a Lance dataset directory; only builds with
+/// `--features lance` read them.
+fn is_lance(filename: &str) -> bool {
+    cfg!(feature = "lance") && filename.ends_with(".lance")
+}
 
-        if !path.exists() {
-            let audio_bytes = binary_arr.get(i).unwrap().to_vec();
-            let mut file = File::create(path.clone()).unwrap();
-            std::io::copy(&mut &audio_bytes[..], &mut file).unwrap();
+/// Path of the Parquet file holding the rows of a data folder entry: the file itself, the
+/// manifest of a zip dataset, or the conversion of a Lance dataset, (re)built when
+/// missing or older than the archive or dataset.
+fn dataset_path(tmp_folder: &Path, folder: &Path, filename: &str) -> PathBuf {
+    let path = folder.join(filename);
+    #[cfg(feature = "lance")]
+    if is_lance(filename) {
+        let converted = tmp_folder.join(filename).join("data.parquet");
+        let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+        // Every commit to a Lance dataset adds a manifest to `_versions`.
+        let versions = path.join("_versions");
+        if modified(&converted).is_none_or(|built| modified(&versions).is_some_and(|m| m > built)) {
+            let _ = fs::remove_dir_all(tmp_folder.join(filename));
+            if let Err(err) = lance_dataset::convert(&path, &converted) {
+                eprintln!("Failed to read {}: {:#}", filename, err);
+            }
         }
+        return converted;
+    }
+    if !archive::is_archive(filename) {
+        return path;
+    }
+    let manifest = tmp_folder.join(filename).join("metadata.parquet");
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    if modified(&manifest).is_none_or(|built| modified(&path).is_some_and(|m| m > built)) {
+        // Clips extracted from an older archive are stale too.
+        let _ = fs::remove_dir_all(tmp_folder.join(filename));
+        if let Err(err) = archive::build_manifest(&path, &manifest) {
+            eprintln!("Failed to read {}: {:#}", filename, err);
+        }
+    }
+    manifest
+}
 
-        let duration = col_d.get(i).unwrap().extract::<f64>().unwrap();
-        let transcription = if let AnyValue::String(s) = col_t.get(i).unwrap() {
-            s.to_string()
-        } else {
-            col_t.get(i).unwrap().to_string()
-        };
-
-        let audio = Audio {
-            path,
-            duration,
-            transcription,
-        };
+/// Extracts the audio of the given rows of a zip dataset next to the clips of Parquet
+/// files, so only the clips that are listened to or analyzed leave the archive.
+fn extract_archived_rows(state: &AppState, filename: &str, rows: impl IntoIterator<Item = usize>) {
+    if !archive::is_archive(filename) {
+        return;
+    }
+    let manifest = dataset_path(&state.tmp_folder, &state.folder, filename);
+    let extracted = load_string_column(&manifest, archive::FILE_NAME_COLUMN)
+        .map_err(anyhow::Error::from)
+        .and_then(|entries| {
+            let entries: Vec<(String, PathBuf)> = rows
+                .into_iter()
+                .filter_map(|row| {
+                    let entry = entries.get(row)?.clone()?;
+                    let out = state.tmp_folder.join(filename).join(format!("{}.wav", row));
+                    Some((entry, out))
+                })
+                .collect();
+            archive::extract(
+                &state.folder.join(filename),
+                &entries,
+                &blobs::BlobStore::new(&state.tmp_folder),
+            )
+        });
+    if let Err(err) = extracted {
+        eprintln!("Failed to extract audio from {}: {:#}", filename, err);
+    }
+}
 
-        created_files.push(audio);
+/// Loads transcriptions for one file, or for every file when `filename` is `None`,
+/// grouped by file name.
+fn load_corpus(
+    state: &AppState,
+    filename: Option<&str>,
+) -> Result<reports::Corpus, (http::StatusCode, String)> {
+    let mut corpus = Vec::new();
+    for file in report_files(state, filename)? {
+        let loaded = load_transcriptions(&dataset_path(&state.tmp_folder, &state.folder, &file))
+            .map_err(|err| {
+                (
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to read {}: {}", file, err),
+                )
+            })?;
+        corpus.push((file, loaded));
     }
+    Ok(corpus)
+}
 
-    created_files
+/// Like [`load_corpus`], but flattened into a single list of transcriptions.
+fn load_corpus_transcriptions(
+    state: &AppState,
+    filename: Option<&str>,
+) -> Result<Vec<String>, (http::StatusCode, String)> {
+    Ok(load_corpus(state, filename)?
+        .into_iter()
+        .flat_map(|(_, texts)| texts)
+        .collect())
 }
 
-/// Formats a duration in seconds into a human-readable string (MM:SS.ms or HH:MM:SS.ms).
-fn format_duration(seconds: f64) -> String {
-    let total_seconds = seconds.floor() as u64;
-    let hours = total_seconds / 3600;
-    let minutes = (total_seconds % 3600) / 60;
-    let secs = total_seconds % 60;
-    let millis = (seconds.fract() * 1000.0).round() as u64;
+/// Links to the text reports, scoped to one file or to the whole corpus.
+fn report_links(filename: Option<&str>) -> String {
+    let suffix = filename
+        .map(|f| format!("/{}", html::url_encode(f)))
+        .unwrap_or_default();
+    [
+        ("charset", "Character set"),
+        ("ngrams", "N-grams"),
+        ("duplicates", "Duplicates"),
+        ("phonemes", "Phonemes"),
+        ("speakers", "Speakers"),
+        ("map", "Embedding map"),
+        ("completeness", "Completeness"),
+        ("schema", "Schema"),
+        ("wpm", "Speaking rate"),
+        ("agreement", "Label agreement"),
+        ("encodings", "Encodings"),
+        ("spelling", "Spelling"),
+        ("expansions", "Expansions"),
+        ("style", "Style"),
+    ]
+    .iter()
+    .map(|(route, label)| {
+        format!(
+            r#"<a href="/{}{}" class="text-blue-600 dark:text-blue-400 hover:underline">{}</a>"#,
+            route,
+            suffix,
+            i18n::t(label)
+        )
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
 
-    if hours > 0 {
-        format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, secs, millis)
-    } else {
-        format!("{:02}:{:02}.{:03}", minutes, secs, millis)
-    }
+/// Runs blocking dataset work (Parquet decoding, WAV extraction, reports) on the
+/// blocking thread pool, so it does not stall the async runtime and the audio streams
+/// it serves. Panics are propagated to the caller.
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> T {
+    let (preferences, session) = (preferences::current(), acl::current_session());
+    tokio::task::spawn_blocking(move || {
+        acl::sync_scope(session, || preferences::sync_scope(preferences, work))
+    })
+    .await
+    .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))
 }
 
-/// Serves the list of Parquet files in the folder.
-async fn list_files(State(state): State<AppState>) -> Html<String> {
-    let files: Vec<String> = fs::read_dir(&state.folder)
-        .unwrap_or_else(|_| fs::read_dir(".").unwrap()) // Fallback to current directory if specified folder is invalid
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.path().extension().and_then(|s| s.to_str()) == Some("parquet"))
-        .filter_map(|entry| entry.file_name().to_str().map(|s| s.to_string()))
-        .collect();
+/// Wraps a report body in the standard sub-page layout.
+fn render_report(title: &str, filename: Option<&str>, body: &str) -> Html<String> {
+    let (back_href, back_label, scope) = match filename {
+        Some(f) => (
+            format!("/view/{}", html::url_encode(f)),
+            i18n::tf("Back to {name}", &[("name", &f)]),
+            f.to_string(),
+        ),
+        None => (
+            "/".to_string(),
+            i18n::t("Back to list").to_string(),
+            i18n::t("All files").to_string(),
+        ),
+    };
+    let content = format!(
+        r#"
+    <div class="max-w-6xl mx-auto bg-white dark:bg-gray-800 shadow-md rounded-lg p-6 relative">
+        {}
+        <h1 class="text-2xl font-bold mb-1">{}</h1>
+        <p class="mb-4 text-gray-500 dark:text-gray-400">{}</p>
+        {}
+    </div>"#,
+        html::page_header(&back_href, &html::escape(&back_label)),
+        i18n::t(title),
+        html::escape(&scope),
+        body
+    );
+    Html(html::render_page(
+        &format!("{} - {}", i18n::t(title), scope),
+        "",
+        &content,
+    ))
+}
+
+/// Counts the missing values of every column of a file; struct columns such as `audio`
+/// are reported per field.
+fn column_completeness(path: &Path) -> PolarsResult<Vec<reports::ColumnCompleteness>> {
+    let df = parquet_reader(path)?.finish()?;
+    let mut columns = Vec::new();
+    for column in df.columns() {
+        let series = column.as_materialized_series();
+        if let DataType::Struct(_) = series.dtype() {
+            for field in series.struct_()?.fields_as_series() {
+                let name = format!("{}.{}", series.name(), field.name());
+                columns.push(series_completeness(name, &field)?);
+            }
+        } else {
+            columns.push(series_completeness(series.name().to_string(), series)?);
+        }
+    }
+    Ok(columns)
+}
+
+fn series_completeness(name: String, series: &Series) -> PolarsResult<reports::ColumnCompleteness> {
+    let nans = if series.dtype().is_float() {
+        let values = series.cast(&DataType::Float64)?;
+        values
+            .f64()?
+            .into_iter()
+            .flatten()
+            .filter(|v| v.is_nan())
+            .count()
+    } else {
+        0
+    };
+    let empty = match series.dtype() {
+        DataType::String => series
+            .str()?
+            .into_iter()
+            .flatten()
+            .filter(|s| s.trim().is_empty())
+            .count(),
+        DataType::Binary => series
+            .binary()?
+            .into_iter()
+            .flatten()
+            .filter(|b| b.is_empty())
+            .count(),
+        DataType::List(_) => series
+            .list()?
+            .into_iter()
+            .flatten()
+            .filter(|list| list.is_empty())
+            .count(),
+        _ => 0,
+    };
+    Ok(reports::ColumnCompleteness {
+        name,
+        dtype: series.dtype().to_string(),
+        files: 1,
+        rows: series.len(),
+        nulls: series.null_count(),
+        nans,
+        empty,
+    })
+}
+
+/// Serves null, NaN and empty value counts per column.
+async fn completeness_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+    Query(query): Query<CompletenessQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let files = report_files(&state, filename.as_deref())?;
+        let mut columns: Vec<reports::ColumnCompleteness> = Vec::new();
+        for file in &files {
+            let path = dataset_path(&state.tmp_folder, &state.folder, file);
+            let counts = column_completeness(&path).map_err(|err| {
+                (
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to read {}: {}", file, err),
+                )
+            })?;
+            for count in counts {
+                match columns.iter_mut().find(|column| column.name == count.name) {
+                    Some(column) => {
+                        column.files += 1;
+                        column.rows += count.rows;
+                        column.nulls += count.nulls;
+                        column.nans += count.nans;
+                        column.empty += count.empty;
+                    }
+                    None => columns.push(count),
+                }
+            }
+        }
+        let body =
+            reports::render_completeness(&columns, files.len(), query.max_missing.unwrap_or(5.0));
+        Ok(render_report("Completeness", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Serves the words-per-minute distribution of the files, with the clips outside the
+/// expected range counted per file.
+async fn wpm_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+    Query(query): Query<WpmQuery>,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let range = (
+            query.min_wpm.unwrap_or(state.wpm_range.0),
+            query.max_wpm.unwrap_or(state.wpm_range.1),
+        );
+        let mut rates = Vec::new();
+        for file in report_files(&state, filename.as_deref())? {
+            let path = dataset_path(&state.tmp_folder, &state.folder, &file);
+            let rows = load_durations_and_transcriptions(&path).map_err(|err| {
+                (
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to read {}: {}", file, err),
+                )
+            })?;
+            let file_rates: Vec<f64> = rows
+                .iter()
+                .filter_map(|(duration, text)| words_per_minute(*duration, text))
+                .collect();
+            rates.push((file, file_rates));
+        }
+        let all: Vec<f64> = rates.iter().flat_map(|(_, r)| r.iter().copied()).collect();
+        let chart = if all.is_empty() {
+            stats::Chart {
+                text: String::new(),
+                records: Vec::new(),
+            }
+        } else {
+            plot_wpm(&all, range)
+        };
+        if let Some(format) = query.format {
+            let mut records = chart.records;
+            for (file, file_rates) in &rates {
+                let mut sorted = file_rates.clone();
+                sorted.sort_by(f64::total_cmp);
+                let chart = format!("words per minute in {}", file);
+                records.push(stats::Record::value(&chart, "clips", sorted.len() as f64));
+                if let Some(&median) = sorted.get(sorted.len() / 2) {
+                    records.push(stats::Record::value(&chart, "median", median));
+                }
+                let slower = sorted.iter().filter(|&&rate| rate < range.0).count();
+                let faster = sorted.iter().filter(|&&rate| rate > range.1).count();
+                records.push(stats::Record::value(&chart, "slower", slower as f64));
+                records.push(stats::Record::value(&chart, "faster", faster as f64));
+            }
+            let name = match &filename {
+                Some(file) => format!("{}-wpm", file),
+                None => "wpm".to_string(),
+            };
+            return Ok(stats_download(&records, format, &name));
+        }
+        let links = stats::download_links(&format!(
+            "/wpm{}?min_wpm={}&max_wpm={}",
+            filename
+                .as_ref()
+                .map(|f| format!("/{}", html::url_encode(f)))
+                .unwrap_or_default(),
+            range.0,
+            range.1
+        ));
+        let body = reports::render_wpm(&chart.text, &rates, range) + &links;
+        Ok(render_report("Speaking rate", filename.as_deref(), &body).into_response())
+    })
+    .await
+}
+
+/// Serves the distribution of audio encodings (codec, sample format and bit depth) of
+/// the files, read from the clip headers, with the clips deviating from the dominant one.
+async fn encoding_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let mut rows = Vec::new();
+        for file in report_files(&state, filename.as_deref())? {
+            let files = extract_parquet_file(&state.tmp_folder, &state.folder, &file);
+            extract_archived_rows(&state, &file, 0..files.len());
+            for (row, audio) in files.iter().enumerate() {
+                let encoding = container::read_header(&audio.path)
+                    .map(|header| container::encoding(&header))
+                    .unwrap_or_else(|_| "unreadable".to_string());
+                rows.push((file.clone(), row, encoding));
+            }
+        }
+        let body = reports::render_encodings(&rows);
+        Ok(render_report("Encodings", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Summarizes how well the human transcriptions of one or all files agree with their
+/// pseudo-labels, by word error rate bucket.
+async fn agreement_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let column = PSEUDO_LABEL_COLUMN.get().map_or("", String::as_str);
+        let mut files = Vec::new();
+        let mut unlabeled = Vec::new();
+        for file in report_files(&state, filename.as_deref())? {
+            let path = dataset_path(&state.tmp_folder, &state.folder, &file);
+            let Ok(labels) = load_string_column(&path, column) else {
+                unlabeled.push(file);
+                continue;
+            };
+            let rows = load_durations_and_transcriptions(&path).map_err(|err| {
+                (
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to read {}: {}", file, err),
+                )
+            })?;
+            let corrections = state.annotations.file(&file);
+            let mut agreement = reports::FileAgreement {
+                file: file.clone(),
+                rows: 0,
+                errors: 0,
+                words: 0,
+                buckets: [0; agreement::BUCKETS.len()],
+            };
+            for (row, ((_, original), label)) in rows.iter().zip(labels).enumerate() {
+                let Some(label) = label else { continue };
+                let transcription = corrections
+                    .get(&row)
+                    .and_then(|annotation| annotation.transcription())
+                    .unwrap_or(original);
+                let (errors, words) = agreement::word_errors(transcription, &label);
+                agreement.rows += 1;
+                agreement.errors += errors;
+                agreement.words += words;
+                agreement.buckets[agreement::bucket(errors as f64 / words.max(1) as f64)] += 1;
+            }
+            files.push(agreement);
+        }
+        let body = reports::render_agreement(column, &files, &unlabeled);
+        Ok(render_report("Label agreement", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Reads the row count, total duration and flattened schema of a file for the
+/// [`FolderIndex`], loading only its `duration` column.
+fn scan_file(tmp_folder: &Path, folder: &Path, file: &str) -> anyhow::Result<FileInfo> {
+    let path = dataset_path(tmp_folder, folder, file);
+    let schema = parquet_reader(&path)
+        .and_then(|mut reader| reader.schema())
+        .map_err(|err| anyhow::anyhow!("failed to read the schema of {}: {}", file, err))?;
+    let df = parquet_reader(&path)
+        .and_then(|reader| {
+            reader
+                .with_columns(Some(vec!["duration".to_string()]))
+                .finish()
+        })
+        .map_err(|err| anyhow::anyhow!("failed to read the durations of {}: {}", file, err))?;
+    let durations = df.column("duration")?.cast(&DataType::Float64)?;
+    Ok(FileInfo {
+        rows: df.height(),
+        duration: durations.f64()?.sum().unwrap_or_default(),
+        schema: schema_check::flatten(
+            schema
+                .iter_values()
+                .map(|field| (field.name.to_string(), DataType::from_arrow_field(field))),
+        ),
+    })
+}
+
+/// Brings the folder index up to date with the data folder.
+fn refresh_folder_index(
+    tmp_folder: &Path,
+    folder: &Path,
+    index: &FolderIndex,
+) -> anyhow::Result<usize> {
+    index.refresh(folder, &list_parquet_files(folder), |file| {
+        scan_file(tmp_folder, folder, file)
+    })
+}
+
+/// Refreshes the folder index, the search index and the stats sidecars that are
+/// missing or older than their file, logging what changed.
+fn reindex(state: &AppState) {
+    match refresh_folder_index(&state.tmp_folder, &state.folder, &state.folder_index) {
+        Ok(rescanned) => println!("Folder index up to date ({} files scanned)", rescanned),
+        Err(err) => eprintln!("Failed to scan the data folder: {:#}", err),
+    }
+    if let Some(index) = &state.search_index {
+        match refresh_search_index(state, index) {
+            Ok(reindexed) => println!("Search index up to date ({} files reindexed)", reindexed),
+            Err(err) => eprintln!("Failed to refresh the search index: {:#}", err),
+        }
+    }
+    let mut written = 0;
+    for file in list_parquet_files(&state.folder) {
+        if stats::read_sidecar(&state.folder, &file).is_some() {
+            continue;
+        }
+        let summary = compute_summary(state, &file);
+        match stats::write_sidecar(&state.folder, &file, &summary) {
+            Ok(_) => written += 1,
+            Err(err) => eprintln!("Failed to write the statistics of {}: {:#}", file, err),
+        }
+    }
+    println!("Statistics up to date ({} sidecars written)", written);
+}
+
+/// Flattened column types of every file, for [`schema_check::compare`].
+fn file_schemas(
+    tmp_folder: &Path,
+    folder: &Path,
+    index: &FolderIndex,
+    files: &[String],
+) -> anyhow::Result<Vec<Vec<(String, String)>>> {
+    refresh_folder_index(tmp_folder, folder, index)?;
+    files
+        .iter()
+        .map(|file| {
+            index
+                .get(file)
+                .map(|info| info.schema)
+                .ok_or_else(|| anyhow::anyhow!("{} is not in the folder index", file))
+        })
+        .collect()
+}
+
+/// Serves the schema comparison of the files.
+async fn schema_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let files = report_files(&state, filename.as_deref())?;
+        let schemas = file_schemas(
+            &state.tmp_folder,
+            &state.folder,
+            &state.folder_index,
+            &files,
+        )
+        .map_err(|err| {
+            (
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("{:#}", err),
+            )
+        })?;
+        let body = reports::render_schemas(&files, &schema_check::compare(&schemas));
+        Ok(render_report("Schema", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Serves the character (and optionally word) frequency report.
+async fn charset_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+    Query(query): Query<CharsetQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let texts = load_corpus_transcriptions(&state, filename.as_deref())?;
+        let body = reports::render_charset(
+            &texts,
+            state.alphabet.as_deref(),
+            query.words,
+            query.limit.unwrap_or(500),
+        );
+        Ok(render_report("Character set", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Serves the word n-gram frequency report.
+async fn ngram_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+    Query(query): Query<NgramQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let mut texts = load_corpus_transcriptions(&state, filename.as_deref())?;
+        if query.normalized {
+            for text in &mut texts {
+                *text = state.text_normalizer.normalize(text);
+            }
+        }
+        let body = reports::render_ngrams(&texts, query.k.unwrap_or(50));
+        Ok(render_report("N-grams", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Serves the most frequent likely misspellings according to `--dictionary`.
+async fn spelling_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+    Query(query): Query<SpellingQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let dictionary = state.dictionary.clone().ok_or((
+        http::StatusCode::NOT_FOUND,
+        "No --dictionary is configured".to_string(),
+    ))?;
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let corpus = load_corpus(&state, filename.as_deref())?;
+        let body = reports::render_spelling(&corpus, &dictionary, query.k.unwrap_or(100));
+        Ok(render_report("Spelling", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Serves the consistency of number, currency and abbreviation forms.
+async fn expansion_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let corpus = load_corpus(&state, filename.as_deref())?;
+        let body = reports::render_expansions(&corpus);
+        Ok(render_report("Expansions", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Serves the casing and punctuation classes of the transcriptions.
+async fn style_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let corpus = load_corpus(&state, filename.as_deref())?;
+        let body = reports::render_style(&corpus);
+        Ok(render_report("Style", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Link to the split comparison, when the file names name at least two splits.
+fn splits_link(files: &[String]) -> String {
+    if splits::group(files).len() < 2 {
+        return String::new();
+    }
+    format!(
+        r#" <a href="/splits" class="text-blue-600 dark:text-blue-400 hover:underline">{}</a>"#,
+        i18n::t("Splits")
+    )
+}
+
+/// Reads the durations, transcriptions and speakers of the files of a split.
+fn load_split(
+    state: &AppState,
+    name: &'static str,
+    files: Vec<String>,
+) -> Result<splits::Split, (http::StatusCode, String)> {
+    let mut split = splits::Split {
+        name,
+        speakers: Some(Vec::new()),
+        ..splits::Split::default()
+    };
+    for file in &files {
+        let path = dataset_path(&state.tmp_folder, &state.folder, file);
+        let rows = load_durations_and_transcriptions(&path).map_err(|err| {
+            (
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read {}: {}", file, err),
+            )
+        })?;
+        split.speakers = match (
+            split.speakers,
+            load_string_column(&path, &state.speaker_column),
+        ) {
+            (Some(mut speakers), Ok(labels)) => {
+                speakers.extend(labels.into_iter().map(Option::unwrap_or_default));
+                Some(speakers)
+            }
+            _ => None,
+        };
+        for (duration, transcription) in rows {
+            split.durations.push(duration);
+            split.transcriptions.push(transcription);
+        }
+    }
+    split.files = files;
+    Ok(split)
+}
+
+/// Serves the comparison of the train, validation and test splits recognized in the
+/// file names.
+async fn splits_report(
+    State(state): State<AppState>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    blocking(move || {
+        let files = visible_files(&state);
+        let groups = splits::group(&files);
+        if groups.len() < 2 {
+            return Err((
+                http::StatusCode::NOT_FOUND,
+                "No two of train, validation and test are recognized in the file names".to_string(),
+            ));
+        }
+        let splits = groups
+            .into_iter()
+            .map(|(name, files)| load_split(&state, name, files))
+            .collect::<Result<Vec<_>, _>>()?;
+        let unassigned: Vec<String> = files
+            .into_iter()
+            .filter(|file| splits::recognize(file).is_none())
+            .collect();
+        let body = reports::render_splits(&splits, &unassigned, &state.speaker_column);
+        Ok(render_report("Splits", None, &body))
+    })
+    .await
+}
+
+/// Serves the speaker leakage check: speaker labels, and with `--embedding-url` similar
+/// voices, found in more than one split.
+async fn speaker_leakage_report(
+    State(state): State<AppState>,
+    Query(query): Query<SpeakersQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    blocking(move || {
+        let groups = splits::group(&visible_files(&state));
+        if groups.len() < 2 {
+            return Err((
+                http::StatusCode::NOT_FOUND,
+                "No two of train, validation and test are recognized in the file names".to_string(),
+            ));
+        }
+        let mut clips = Vec::new();
+        let mut labeled = true;
+        for (split, files) in groups {
+            for file in files {
+                let path = dataset_path(&state.tmp_folder, &state.folder, &file);
+                let labels = load_string_column(&path, &state.speaker_column).ok();
+                labeled &= labels.is_some();
+                // Only the embedding check needs the audio.
+                let paths: Option<Vec<PathBuf>> = state.embedder.as_ref().map(|_| {
+                    extract_parquet_file(&state.tmp_folder, &state.folder, &file)
+                        .into_iter()
+                        .map(|audio| audio.path)
+                        .collect()
+                });
+                let rows = match (&labels, &paths) {
+                    (Some(labels), _) => labels.len(),
+                    (None, Some(paths)) => paths.len(),
+                    (None, None) => continue,
+                };
+                for row in 0..rows {
+                    clips.push(splits::Clip {
+                        split,
+                        file: file.clone(),
+                        row,
+                        speaker: labels
+                            .as_ref()
+                            .and_then(|labels| labels[row].clone())
+                            .unwrap_or_default(),
+                        embedding: state
+                            .embedder
+                            .as_ref()
+                            .zip(paths.as_ref())
+                            .and_then(|(embedder, paths)| embedder.cached(paths.get(row)?)),
+                    });
+                }
+            }
+        }
+        let body = reports::render_speaker_leakage(
+            &clips,
+            labeled,
+            &state.speaker_column,
+            state
+                .embedder
+                .as_ref()
+                .map(|_| query.threshold.unwrap_or(0.75)),
+        );
+        Ok(render_report("Speaker leakage", None, &body))
+    })
+    .await
+}
+
+/// Serves the text leakage check: transcriptions of the validation and test splits that
+/// are identical or nearly identical to one of an earlier split.
+async fn text_leakage_report(
+    State(state): State<AppState>,
+    Query(query): Query<TextLeakageQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    blocking(move || {
+        let groups = splits::group(&visible_files(&state));
+        if groups.len() < 2 {
+            return Err((
+                http::StatusCode::NOT_FOUND,
+                "No two of train, validation and test are recognized in the file names".to_string(),
+            ));
+        }
+        let mut lines = Vec::new();
+        for (split, files) in groups {
+            for file in files {
+                let path = dataset_path(&state.tmp_folder, &state.folder, &file);
+                let rows = load_durations_and_transcriptions(&path).map_err(|err| {
+                    (
+                        http::StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to read {}: {}", file, err),
+                    )
+                })?;
+                lines.extend(
+                    rows.into_iter()
+                        .enumerate()
+                        .map(|(row, (_, text))| splits::Line {
+                            split,
+                            file: file.clone(),
+                            row,
+                            text,
+                        }),
+                );
+            }
+        }
+        let body = reports::render_text_leakage(
+            &lines,
+            query.max_distance.unwrap_or(0.1).clamp(0.0, 1.0),
+            query.min_words.unwrap_or(3),
+        );
+        Ok(render_report("Text leakage", None, &body))
+    })
+    .await
+}
+
+/// Serves groups of transcriptions that occur at least `min_count` times.
+async fn duplicates_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+    Query(query): Query<DuplicatesQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let corpus = load_corpus(&state, filename.as_deref())?;
+        let min_count = query.min_count.unwrap_or(2).max(2);
+        let body = if query.normalized {
+            reports::render_duplicates(&corpus, min_count, |t| state.text_normalizer.normalize(t))
+        } else {
+            reports::render_duplicates(&corpus, min_count, |t| t.to_string())
+        };
+        Ok(render_report("Duplicates", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Serves phoneme frequencies and inventory coverage from the G2P backend.
+async fn phoneme_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+    Query(query): Query<PhonemeQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let mut texts = load_corpus_transcriptions(&state, filename.as_deref())?;
+        if query.normalized {
+            for text in &mut texts {
+                *text = state.text_normalizer.normalize(text);
+            }
+        }
+        let phonemes = state.g2p.phonemes(&texts).map_err(|err| {
+            (
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("{:#}", err),
+            )
+        })?;
+        let body = reports::render_phonemes(
+            texts.len(),
+            &phonemes,
+            &state.g2p.language,
+            state.g2p.inventory.as_ref(),
+            query.min_count.unwrap_or(10),
+        );
+        Ok(render_report("Phonemes", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Reads a column as optional strings, casting non-string columns.
+fn load_string_column(path: &Path, column: &str) -> PolarsResult<Vec<Option<String>>> {
+    let df = parquet_reader(path)?
+        .with_columns(Some(vec![column.to_string()]))
+        .finish()?;
+    let col = df.column(column)?.cast(&DataType::String)?;
+    Ok(col
+        .str()?
+        .into_iter()
+        .map(|s| s.map(str::to_string))
+        .collect())
+}
+
+/// Files covered by a report: the given one, or all of them.
+fn report_files(
+    state: &AppState,
+    filename: Option<&str>,
+) -> Result<Vec<String>, (http::StatusCode, String)> {
+    match filename {
+        Some(name) if is_valid_parquet(state, name) => Ok(vec![name.to_string()]),
+        Some(_) => Err((http::StatusCode::NOT_FOUND, "File not found".to_string())),
+        None => Ok(visible_files(state)),
+    }
+}
+
+/// Clips of one or all files that have a cached embedding, and the number of clips.
+fn embedded_clips(
+    state: &AppState,
+    embedder: &Embedder,
+    filename: Option<&str>,
+) -> Result<(Vec<reports::SpeakerClip>, usize), (http::StatusCode, String)> {
+    let mut clips = Vec::new();
+    let mut total = 0;
+    for file in report_files(state, filename)? {
+        let audios = extract_parquet_file(&state.tmp_folder, &state.folder, &file);
+        let speakers = load_string_column(
+            &dataset_path(&state.tmp_folder, &state.folder, &file),
+            &state.speaker_column,
+        )
+        .map_err(|err| {
+            (
+                http::StatusCode::BAD_REQUEST,
+                format!(
+                    "Failed to read {} from {}: {}",
+                    state.speaker_column, file, err
+                ),
+            )
+        })?;
+        total += audios.len();
+        for (row, (audio, speaker)) in audios.into_iter().zip(speakers).enumerate() {
+            if let Some(embedding) = embedder.cached(&audio.path) {
+                clips.push(reports::SpeakerClip {
+                    file: file.clone(),
+                    row,
+                    speaker: speaker.unwrap_or_default(),
+                    transcription: audio.transcription,
+                    embedding,
+                });
+            }
+        }
+    }
+    Ok((clips, total))
+}
+
+/// Serves the speaker consistency report built from the cached clip embeddings.
+async fn speakers_report(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+    Query(query): Query<SpeakersQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let embedder = state.embedder.clone().ok_or((
+        http::StatusCode::NOT_FOUND,
+        "Speaker embeddings are not configured".to_string(),
+    ))?;
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let (clips, total) = embedded_clips(&state, &embedder, filename.as_deref())?;
+        let action = match &filename {
+            Some(file) => format!("/speakers/{}", html::url_encode(file)),
+            None => "/speakers".to_string(),
+        };
+        let body = format!(
+            r#"<form method="post" action="{}" class="mb-4"><button class="px-3 py-1 bg-blue-500 text-white rounded-md text-sm">Compute embeddings</button></form>{}"#,
+            action,
+            reports::render_speakers(&clips, total, query.threshold.unwrap_or(0.75))
+        );
+        Ok(render_report("Speakers", filename.as_deref(), &body))
+    })
+    .await
+}
+
+/// Draws the embedding map: colored points that play their clip on click, show the
+/// transcription on hover and open the row on double click.
+const MAP_SCRIPT: &str = r#"
+    <script>
+        document.addEventListener('DOMContentLoaded', function() {
+            var svg = document.getElementById('embedding-map');
+            var info = document.getElementById('map-info');
+            var player = new Audio();
+            var ns = 'http://www.w3.org/2000/svg';
+            var xs = MAP_POINTS.map(function(p) { return p.x; });
+            var ys = MAP_POINTS.map(function(p) { return p.y; });
+            var minX = Math.min.apply(null, xs), maxX = Math.max.apply(null, xs);
+            var minY = Math.min.apply(null, ys), maxY = Math.max.apply(null, ys);
+            MAP_POINTS.forEach(function(p) {
+                var circle = document.createElementNS(ns, 'circle');
+                circle.setAttribute('cx', 2 + 96 * (p.x - minX) / ((maxX - minX) || 1));
+                circle.setAttribute('cy', 2 + 96 * (p.y - minY) / ((maxY - minY) || 1));
+                circle.setAttribute('r', 0.8);
+                circle.setAttribute('fill', p.color);
+                circle.setAttribute('class', 'cursor-pointer hover:stroke-black dark:hover:stroke-white');
+                circle.setAttribute('stroke-width', 0.3);
+                circle.addEventListener('mouseenter', function() {
+                    info.textContent = p.file + ' #' + p.row + ' (' + p.label + '): ' + p.text;
+                });
+                circle.addEventListener('click', function() {
+                    player.src = '/audio/' + encodeURIComponent(p.file) + '/' + p.row;
+                    player.play();
+                });
+                circle.addEventListener('dblclick', function() {
+                    location = '/row/' + encodeURIComponent(p.file) + '/' + p.row;
+                });
+                svg.appendChild(circle);
+            });
+        });
+    </script>"#;
+
+/// Colors cycled through for map labels.
+const MAP_COLORS: [&str; 10] = [
+    "#3b82f6", "#ef4444", "#22c55e", "#f59e0b", "#a855f7", "#06b6d4", "#ec4899", "#84cc16",
+    "#f97316", "#64748b",
+];
+
+/// One point of the embedding map.
+#[derive(serde::Serialize)]
+struct MapPoint<'a> {
+    x: f32,
+    y: f32,
+    file: &'a str,
+    row: usize,
+    label: &'a str,
+    text: &'a str,
+    color: &'a str,
+}
+
+/// Serves a 2D map (t-SNE or PCA) of the cached clip embeddings.
+async fn embedding_map(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+    Query(query): Query<MapQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let embedder = state.embedder.clone().ok_or((
+        http::StatusCode::NOT_FOUND,
+        "Speaker embeddings are not configured".to_string(),
+    ))?;
+    let filename = filename.map(|AxumPath(f)| f);
+    blocking(move || {
+        let (clips, total) = embedded_clips(&state, &embedder, filename.as_deref())?;
+        let by_file = query.color.as_deref() == Some("file");
+        let label = |clip: &reports::SpeakerClip| -> String {
+            if by_file {
+                clip.file.clone()
+            } else {
+                clip.speaker.clone()
+            }
+        };
+        let labels: BTreeSet<String> = clips.iter().map(label).collect();
+        let color = |label: &str| {
+            let index = labels.iter().position(|l| l == label).unwrap_or(0);
+            MAP_COLORS[index % MAP_COLORS.len()]
+        };
+
+        let vectors: Vec<&[f32]> = clips.iter().map(|clip| clip.embedding.as_slice()).collect();
+        let coordinates =
+            projection::project(&vectors, query.method, query.perplexity.unwrap_or(30.0));
+        let clip_labels: Vec<String> = clips.iter().map(label).collect();
+        let points: Vec<MapPoint> = clips
+            .iter()
+            .zip(&coordinates)
+            .zip(&clip_labels)
+            .map(|((clip, [x, y]), label)| MapPoint {
+                x: *x,
+                y: *y,
+                file: &clip.file,
+                row: clip.row,
+                label,
+                text: &clip.transcription,
+                color: color(label),
+            })
+            .collect();
+        // Keep `</script>` inside transcriptions from closing the data script.
+        let data = serde_json::to_string(&points)
+            .unwrap_or_else(|_| "[]".to_string())
+            .replace("</", "<\\/");
+
+        let legend = labels
+            .iter()
+            .map(|label| {
+                let count = clip_labels.iter().filter(|l| *l == label).count();
+                format!(
+                    r#"<span class="inline-flex items-center gap-1"><span class="inline-block w-3 h-3 rounded-full" style="background: {}"></span>{} ({})</span>"#,
+                    color(label),
+                    html::escape(label),
+                    count
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let method = if query.method == projection::Method::Pca
+            || clips.len() > projection::MAX_TSNE_POINTS
+        {
+            "PCA"
+        } else {
+            "t-SNE"
+        };
+        let body = format!(
+            r#"<p class="mb-2">{} of {} clips embedded, projected with {}. Click a point to play it, double click to open the row.</p>
+        <div class="mb-2 flex flex-wrap gap-3 text-sm">{}</div>
+        <svg id="embedding-map" viewBox="0 0 100 100" class="w-full max-w-3xl aspect-square rounded bg-gray-50 dark:bg-gray-900"></svg>
+        <p id="map-info" class="mt-2 text-sm min-h-[1.5rem]"></p>
+        <script>var MAP_POINTS = {};</script>"#,
+            clips.len(),
+            total,
+            method,
+            legend,
+            data
+        );
+        let (back_href, back_label) = match &filename {
+            Some(file) => (
+                format!("/view/{}", html::url_encode(file)),
+                i18n::tf("Back to {name}", &[("name", &file)]),
+            ),
+            None => ("/".to_string(), i18n::t("Back to list").to_string()),
+        };
+        let content = format!(
+            r#"
+    <div class="max-w-6xl mx-auto bg-white dark:bg-gray-800 shadow-md rounded-lg p-6 relative">
+        {}
+        <h1 class="text-2xl font-bold mb-1">Embedding map</h1>
+        <p class="mb-4 text-gray-500 dark:text-gray-400">{}</p>
+        {}
+    </div>"#,
+            html::page_header(&back_href, &html::escape(&back_label)),
+            html::escape(filename.as_deref().unwrap_or("All files")),
+            body
+        );
+        Ok(Html(html::render_page("Embedding map", MAP_SCRIPT, &content)))
+    })
+    .await
+}
+
+/// Embeds every clip of one or all files, then shows the speaker report.
+async fn embed_speakers(
+    State(state): State<AppState>,
+    filename: Option<AxumPath<String>>,
+) -> Result<response::Redirect, (http::StatusCode, String)> {
+    let embedder = state.embedder.clone().ok_or((
+        http::StatusCode::NOT_FOUND,
+        "Speaker embeddings are not configured".to_string(),
+    ))?;
+    let filename = filename.map(|AxumPath(f)| f);
+    let paths = {
+        let (state, filename) = (state.clone(), filename.clone());
+        blocking(move || {
+            Ok::<_, (http::StatusCode, String)>(
+                report_files(&state, filename.as_deref())?
+                    .iter()
+                    .flat_map(|file| {
+                        let audios = extract_parquet_file(&state.tmp_folder, &state.folder, file);
+                        extract_archived_rows(&state, file, 0..audios.len());
+                        audios
+                    })
+                    .map(|audio| audio.path)
+                    .collect(),
+            )
+        })
+        .await?
+    };
+    let (embedded, errors) = embedder.embed_all(paths).await;
+    if let Some(first) = errors.first() {
+        return Err((
+            http::StatusCode::BAD_GATEWAY,
+            format!(
+                "Embedded {} of {} rows. First error: {:#}",
+                embedded,
+                embedded + errors.len(),
+                first
+            ),
+        ));
+    }
+    Ok(response::Redirect::to(&match filename {
+        Some(file) => format!("/speakers/{}", html::url_encode(&file)),
+        None => "/speakers".to_string(),
+    }))
+}
+
+/// Count of clips per audio event tag, with undecodable clips last.
+fn plot_audio_events(events: &[Option<AudioEvent>]) -> stats::Chart {
+    let mut counts: Vec<(&str, usize)> = AudioEvent::ALL
+        .iter()
+        .map(|tag| {
+            let count = events.iter().filter(|event| **event == Some(*tag)).count();
+            (tag.label(), count)
+        })
+        .collect();
+    counts.push((
+        "Undecodable",
+        events.iter().filter(|event| event.is_none()).count(),
+    ));
+    let max_count = counts.iter().map(|(_, count)| *count).max().unwrap_or(0);
+
+    let mut output = format!("Audio events: {} clips\n", events.len());
+    output.push_str("Event\t\t\tFrequency\n");
+    output.push_str(&"-".repeat(40));
+    output.push('\n');
+    let mut records = Vec::new();
+    for (label, count) in counts {
+        records.push(stats::Record::value("audio events", label, count as f64));
+        let bar_length = if max_count > 0 {
+            ((count as f64 / max_count as f64) * 20.0).round() as usize
+        } else {
+            0
+        };
+        output.push_str(&format!(
+            "{:<16}\t{:>8}\t{}\n",
+            label,
+            count,
+            "*".repeat(bar_length)
+        ));
+    }
+    stats::Chart {
+        text: output,
+        records,
+    }
+}
+
+fn plot_token_counts(data: &[usize]) -> stats::Chart {
+    let hist = IntHistogram::new(data, 4, 20, '*');
+    hist.chart("token counts")
+}
+
+/// Token count of every transcription, with `--tokenizer`.
+fn count_tokens(state: &AppState, files: &[Audio]) -> Option<Vec<usize>> {
+    state.tokenizer.as_ref().map(|tokenizer| {
+        files
+            .iter()
+            .map(|audio| {
+                tokenizer
+                    .encode(audio.transcription.as_str(), true)
+                    .map(|encoding| encoding.len())
+                    .unwrap_or(0)
+            })
+            .collect()
+    })
+}
+
+/// Identifies the token counts of a file: its version and the annotation version. The
+/// tokenizer is fixed for the life of the server.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TokenKey {
+    filename: String,
+    modified: Option<std::time::SystemTime>,
+    annotations: u64,
+}
+
+/// Token count of every (corrected) transcription of a file, reusing the counts while
+/// the file and its annotations are unchanged.
+fn cached_token_counts(
+    state: &AppState,
+    filename: &str,
+    files: &[Audio],
+) -> Option<Arc<Vec<usize>>> {
+    state.tokenizer.as_ref().map(|_| {
+        let path = dataset_path(&state.tmp_folder, &state.folder, filename);
+        let key = TokenKey {
+            filename: filename.to_string(),
+            modified: fs::metadata(&path).and_then(|m| m.modified()).ok(),
+            annotations: state.annotations.version(),
+        };
+        state
+            .token_counts
+            .get_or_insert_with(key, || count_tokens(state, files).unwrap_or_default())
+    })
+}
+
+/// The charts of the metadata details of a file, over all its rows.
+fn file_charts(
+    state: &AppState,
+    files: &[Audio],
+    token_counts: Option<&[usize]>,
+) -> Vec<stats::Chart> {
+    let durations: Vec<f64> = files.iter().map(|a| a.duration).collect();
+    let transcriptions: Vec<usize> = files.iter().map(|a| a.transcription.len()).collect();
+    let lengths: Vec<f64> = transcriptions.iter().map(|&len| len as f64).collect();
+    let mut charts = vec![
+        plot_durations(&durations),
+        plot_box(&durations, "durations"),
+        plot_transcription_lengths(&transcriptions),
+        plot_box(&lengths, "transcription lengths"),
+    ];
+    let sizes: Vec<f64> = files
+        .iter()
+        .filter_map(|audio| audio.size)
+        .map(|size| size as f64 / 1024.0)
+        .collect();
+    if !sizes.is_empty() {
+        charts.push(Histogram::new(&sizes, 10, 20, '*').chart("audio sizes (KiB)"));
+    }
+    let rates: Vec<f64> = files
+        .iter()
+        .filter_map(|audio| words_per_minute(audio.duration, &audio.transcription))
+        .collect();
+    if !rates.is_empty() {
+        charts.push(plot_wpm(&rates, state.wpm_range));
+    }
+    if let Some(counts) = token_counts {
+        charts.push(plot_token_counts(counts));
+    }
+    for (i, (name, _)) in files
+        .first()
+        .map(|audio| audio.confidences.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+    {
+        let values: Vec<f64> = files
+            .iter()
+            .filter_map(|audio| audio.confidences[i].1)
+            .collect();
+        if !values.is_empty() {
+            let hist = Histogram::new(&values, 10, 20, '*');
+            let field = format!("{} (confidence)", name);
+            charts.push(stats::Chart {
+                text: hist.render_marked(&field, |start, _| start < confidence::LOW),
+                records: hist.records(&field),
+            });
+        }
+    }
+    if state.noise_floor {
+        let floors: Vec<f64> = files
+            .iter()
+            .filter_map(|audio| state.analysis.levels(&audio.path))
+            .map(|levels| levels.noise_floor_dbfs as f64)
+            .collect();
+        if !floors.is_empty() {
+            charts.push(Histogram::new(&floors, 6, 20, '*').chart("noise floors (dBFS)"));
+        }
+    }
+    if state.audio_events {
+        let events: Vec<Option<AudioEvent>> = files
+            .iter()
+            .map(|audio| state.analysis.events(&audio.path))
+            .collect();
+        charts.push(plot_audio_events(&events));
+    }
+    charts
+}
+
+/// Query parameters of the file statistics page.
+#[derive(Deserialize, Debug)]
+struct StatsQuery {
+    /// Downloads the data behind the charts instead of the page.
+    format: Option<stats::Format>,
+}
+
+/// Computes the statistics of a whole file, decoding every clip when `--audio-events`
+/// or `--noise-floor` is set.
+fn compute_summary(state: &AppState, filename: &str) -> stats::Summary {
+    let mut files = extract_parquet_file(&state.tmp_folder, &state.folder, filename);
+    apply_corrections(&mut files, &state.annotations.file(filename));
+    if state.audio_events || state.noise_floor {
+        extract_archived_rows(state, filename, 0..files.len());
+    }
+    let token_counts = count_tokens(state, &files);
+    stats::Summary {
+        rows: files.len(),
+        duration: files.iter().map(|audio| audio.duration).sum(),
+        charts: file_charts(state, &files, token_counts.as_deref()),
+    }
+}
+
+/// Hashes of the audio of every row of a file, extracting the clips of zip datasets.
+fn audio_hashes(state: &AppState, filename: &str) -> integrity::Hashes {
+    let files = extract_parquet_file(&state.tmp_folder, &state.folder, filename);
+    extract_archived_rows(state, filename, 0..files.len());
+    integrity::Hashes {
+        rows: files
+            .iter()
+            .map(|audio| fs::read(&audio.path).ok().map(|bytes| blobs::hash(&bytes)))
+            .collect(),
+    }
+}
+
+/// Durations of the clips of a file and their languages and speakers, for the dataset
+/// card; a language or speaker column the file lacks is left out.
+fn card_split(state: &AppState, filename: &str, language_column: &str) -> card::Split {
+    let durations: Vec<f64> = extract_parquet_file(&state.tmp_folder, &state.folder, filename)
+        .iter()
+        .map(|audio| audio.duration)
+        .collect();
+    let path = dataset_path(&state.tmp_folder, &state.folder, filename);
+    let tally = |column: &str| {
+        load_string_column(&path, column)
+            .ok()
+            .map(|values| card::tally(&values, &durations))
+    };
+    card::Split {
+        name: Path::new(filename)
+            .file_stem()
+            .map_or(filename.to_string(), |stem| {
+                stem.to_string_lossy().into_owned()
+            }),
+        languages: tally(language_column),
+        speakers: tally(&state.speaker_column),
+        durations,
+    }
+}
+
+/// Attachment holding chart records in the requested format.
+fn stats_download(
+    records: &[stats::Record],
+    format: stats::Format,
+    name: &str,
+) -> response::Response {
+    let (content_type, extension, body) = stats::encode(records, format);
+    response::Response::builder()
+        .header(http::header::CONTENT_TYPE, content_type)
+        .header(
+            http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.{}\"", name, extension),
+        )
+        .body(body::Body::from(body))
+        .unwrap()
+}
+
+/// Serves the statistics of a file, or the data behind its charts, from its sidecar
+/// when one is up to date.
+async fn file_stats(
+    State(state): State<AppState>,
+    AxumPath(filename): AxumPath<String>,
+    Query(query): Query<StatsQuery>,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    if !is_valid_parquet(&state, &filename) {
+        return Err((http::StatusCode::NOT_FOUND, "File not found".to_string()));
+    }
+    blocking(move || {
+        let (summary, cached) = match stats::read_sidecar(&state.folder, &filename) {
+            Some(summary) => (summary, true),
+            None => (compute_summary(&state, &filename), false),
+        };
+        if let Some(format) = query.format {
+            let records: Vec<stats::Record> = summary
+                .charts
+                .into_iter()
+                .flat_map(|chart| chart.records)
+                .collect();
+            return Ok(stats_download(
+                &records,
+                format,
+                &format!("{}-stats", filename),
+            ));
+        }
+        let mut body = format!(
+            r#"<p class="mb-4">{}{}.</p>"#,
+            summary.label(),
+            if cached {
+                " (precomputed with <code>--write-stats</code>)"
+            } else {
+                ""
+            }
+        );
+        for chart in &summary.charts {
+            body.push_str(&format!(
+                r#"<pre class="mb-4 text-sm whitespace-pre-wrap"><code>{}</code></pre>"#,
+                html::escape(&chart.text)
+            ));
+        }
+        body.push_str(&stats::download_links(&format!(
+            "/stats/{}",
+            html::url_encode(&filename)
+        )));
+        Ok(render_report("Statistics", Some(&filename), &body).into_response())
+    })
+    .await
+}
+
+/// Lists the rows of a data folder entry, extracting the audio of Parquet files.
+///
+/// The audio of zip datasets stays in the archive until [`extract_archived_rows`].
+fn extract_parquet_file(tmp_folder: &Path, folder: &Path, filename: &str) -> Vec<Audio> {
+    let file_path = dataset_path(tmp_folder, folder, filename);
+
+    let archived = archive::is_archive(filename);
+    let df = if archived {
+        parquet_reader(&file_path).and_then(|reader| reader.finish())
+    } else {
+        extract_parquet(&file_path)
+    }
+    .unwrap();
+
+    // Save data frame to temp folder
+    let tmp_folder_subdir = tmp_folder.join(filename);
+
+    if !tmp_folder_subdir.exists() {
+        fs::create_dir(&tmp_folder_subdir).unwrap();
+    }
+
+    let col_d = df.column("duration").unwrap();
+    let col_t = df.column("transcription").unwrap();
+
+    let binary_arr = (!archived).then(|| df.column("audio_bytes").unwrap().binary().unwrap());
+    let col_segments = df.column(segments::COLUMN).ok();
+    let image_cols: Vec<(images::ImageColumn, &Column)> = images::image_columns(&df)
+        .into_iter()
+        .map(|image| {
+            let col = df.column(&image.name).unwrap();
+            (image, col)
+        })
+        .collect();
+    let facet_cols: Vec<&Column> = facet_columns(&df)
+        .unwrap()
+        .iter()
+        .map(|name| df.column(name).unwrap())
+        .collect();
+    let mut rendered: Vec<&str> = image_cols
+        .iter()
+        .map(|(image, _)| image.name.as_str())
+        .collect();
+    rendered.push(segments::COLUMN);
+    let renderers = COLUMN_RENDERERS.get();
+    let rendered_cols: Vec<&Column> = renderers
+        .into_iter()
+        .flat_map(columns::Renderers::shown)
+        .filter_map(|name| df.column(name).ok())
+        .collect();
+    rendered.extend(rendered_cols.iter().map(|col| col.name().as_str()));
+    rendered.extend(
+        df.get_column_names()
+            .into_iter()
+            .map(|name| name.as_str())
+            .filter(|name| renderers.is_some_and(|renderers| renderers.is_hidden(name))),
+    );
+    let nested_cols: Vec<&Column> = nested::nested_columns(&df, &rendered)
+        .iter()
+        .map(|name| df.column(name).unwrap())
+        .collect();
+    let confidence_cols: Vec<&Column> = confidence::columns(&df)
+        .iter()
+        .map(|name| df.column(name).unwrap())
+        .collect();
+    let col_pseudo_label = PSEUDO_LABEL_COLUMN
+        .get()
+        .and_then(|name| df.column(name).ok());
+    let col_sample_rate = ["audio_sampling_rate", "sampling_rate"]
+        .iter()
+        .find_map(|name| df.column(name).ok()?.cast(&DataType::UInt32).ok());
+    let col_size = archived
+        .then(|| {
+            df.column(archive::SIZE_COLUMN)
+                .ok()?
+                .cast(&DataType::UInt64)
+                .ok()
+        })
+        .flatten();
+
+    let blobs = blobs::BlobStore::new(tmp_folder);
+    let mut created_files = vec![];
+
+    for i in 0..df.height() {
+        let path = tmp_folder_subdir.join(format!("{}.wav", i));
+
+        if let Some(binary_arr) = binary_arr
+            && !path.exists()
+        {
+            blobs.write(binary_arr.get(i).unwrap(), &path).unwrap();
+        }
+
+        // Archive manifests leave the durations of undecodable entries null.
+        let duration = col_d.get(i).unwrap().extract::<f64>().unwrap_or_default();
+        let transcription = if let AnyValue::String(s) = col_t.get(i).unwrap() {
+            s.to_string()
+        } else {
+            col_t.get(i).unwrap().to_string()
+        };
+
+        let facets = facet_cols
+            .iter()
+            .map(|col| {
+                let value = match col.get(i).unwrap() {
+                    AnyValue::String(s) => s.to_string(),
+                    value => value.to_string(),
+                };
+                (col.name().to_string(), value)
+            })
+            .collect();
+
+        let segments = col_segments
+            .map(|col| segments::parse(col.get(i).unwrap()))
+            .unwrap_or_default();
+
+        let images = image_cols
+            .iter()
+            .filter_map(|(image, col)| {
+                images::image_src(image, col, filename, i).map(|src| (image.name.clone(), src))
+            })
+            .collect();
+
+        let nested = nested_cols
+            .iter()
+            .map(|col| (col.name().to_string(), nested::to_json(col.get(i).unwrap())))
+            .collect();
+
+        let confidences = confidence_cols
+            .iter()
+            .map(|col| {
+                (
+                    col.name().to_string(),
+                    confidence::parse(col.get(i).unwrap()),
+                )
+            })
+            .collect();
+
+        let size = match (binary_arr, &col_size) {
+            (Some(binary_arr), _) => binary_arr.get(i).map(<[u8]>::len),
+            (None, Some(col)) => col.u64().unwrap().get(i).map(|size| size as usize),
+            (None, None) => None,
+        };
+
+        let pseudo_label = col_pseudo_label.and_then(|col| match col.get(i).unwrap() {
+            AnyValue::String(s) => Some(s.to_string()),
+            AnyValue::Null => None,
+            value => Some(value.to_string()),
+        });
+
+        let columns = rendered_cols
+            .iter()
+            .map(|col| (col.name().to_string(), nested::to_json(col.get(i).unwrap())))
+            .collect();
+
+        let sample_rate = binary_arr
+            .and_then(|binary_arr| binary_arr.get(i))
+            .and_then(wav::sample_rate)
+            .or_else(|| col_sample_rate.as_ref()?.u32().unwrap().get(i));
+
+        let audio = Audio {
+            path,
+            duration,
+            transcription,
+            facets,
+            segments,
+            images,
+            nested,
+            confidences,
+            pseudo_label,
+            size,
+            columns,
+            sample_rate,
+        };
+
+        created_files.push(audio);
+    }
+
+    created_files
+}
+
+/// Formats a duration in seconds into a human-readable string (MM:SS.ms or HH:MM:SS.ms).
+fn format_duration(seconds: f64) -> String {
+    let total_seconds = seconds.floor() as u64;
+    let hours = total_seconds / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let secs = total_seconds % 60;
+    let millis = (seconds.fract() * 1000.0).round() as u64;
+
+    if hours > 0 {
+        format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, secs, millis)
+    } else {
+        format!("{:02}:{:02}.{:03}", minutes, secs, millis)
+    }
+}
+
+/// Formats a byte count with a binary unit, e.g. `31.3 KiB`.
+fn format_size(bytes: usize) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// Serves the list of Parquet files in the folder.
+async fn list_files(State(state): State<AppState>) -> Html<String> {
+    let files = visible_files(&state);
+    let summaries: BTreeMap<String, String> = files
+        .iter()
+        .filter_map(|file| {
+            let info = state.folder_index.get(file)?;
+            Some((file.clone(), stats::size_label(info.rows, info.duration)))
+        })
+        .collect();
+
+    let notes: BTreeMap<String, String> = state
+        .notes
+        .all()
+        .into_iter()
+        .map(|(file, note)| (file, note.text))
+        .collect();
+
+    let links = report_links(None) + &splits_link(&files);
+    let content = templates::render(
+        "index.html",
+        context! {
+            files => files,
+            summaries => summaries,
+            notes => notes,
+            report_links => templates::safe(links),
+        },
+    );
+
+    Html(html::render_page(i18n::t("Parquet Files"), "", &content))
+}
+
+/// Most matches listed by the global search.
+const MAX_SEARCH_RESULTS: usize = 500;
+
+/// Decodes every clip of every file and prints the rows whose stored duration differs
+/// from the decoded one by more than `tolerance` seconds; returns their number.
+///
+/// With `out`, a copy of each Parquet file with the decoded durations is written there.
+/// Zip datasets are only checked, since their durations live in the metadata table.
+fn recompute_durations(
+    state: &AppState,
+    ffmpeg: &Path,
+    tolerance: f64,
+    out: Option<&Path>,
+) -> anyhow::Result<usize> {
+    if let Some(out) = out {
+        fs::create_dir_all(out)?;
+        anyhow::ensure!(
+            fs::canonicalize(out)? != fs::canonicalize(&state.folder)?,
+            "--durations-out must not be the data folder"
+        );
+    }
+    let mut total_off = 0;
+    for file in list_parquet_files(&state.folder) {
+        let files = extract_parquet_file(&state.tmp_folder, &state.folder, &file);
+        extract_archived_rows(state, &file, 0..files.len());
+        let mut off = 0;
+        let mut durations = Vec::with_capacity(files.len());
+        for (row, audio) in files.iter().enumerate() {
+            let duration = match durations::decode(&audio.path, ffmpeg) {
+                Ok(duration) => duration,
+                Err(err) => {
+                    eprintln!("{} #{}: {:#}", file, row, err);
+                    audio.duration
+                }
+            };
+            if (duration - audio.duration).abs() > tolerance {
+                println!(
+                    "{} #{}: stored {:.3}s, decoded {:.3}s",
+                    file, row, audio.duration, duration
+                );
+                off += 1;
+            }
+            durations.push(duration);
+        }
+        println!(
+            "{}: {} of {} durations off by more than {}s",
+            file,
+            off,
+            files.len(),
+            tolerance
+        );
+        total_off += off;
+        match out {
+            Some(_) if archive::is_archive(&file) || is_lance(&file) => {
+                println!("{}: zip and Lance datasets are not rewritten", file)
+            }
+            Some(out) => {
+                let corrected = out.join(&file);
+                durations::write_corrected(&state.folder.join(&file), &corrected, durations)?;
+                println!("{} -> {}", file, corrected.display());
+            }
+            None => {}
+        }
+    }
+    Ok(total_off)
+}
+
+/// Analyzes every clip and writes enriched copies of the Parquet files to `out`, see
+/// `--analysis-out`.
+fn write_analysis_columns(state: &AppState, out: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(out)?;
+    anyhow::ensure!(
+        fs::canonicalize(out)? != fs::canonicalize(&state.folder)?,
+        "--analysis-out must not be the data folder"
+    );
+    for file in list_parquet_files(&state.folder) {
+        if archive::is_archive(&file) || is_lance(&file) {
+            println!("{}: zip and Lance datasets are not rewritten", file);
+            continue;
+        }
+        let files = extract_parquet_file(&state.tmp_folder, &state.folder, &file);
+        let levels: Vec<Option<analysis::LevelAnalysis>> = files
+            .iter()
+            .map(|audio| state.analysis.levels(&audio.path))
+            .collect();
+        let failed = levels.iter().filter(|levels| levels.is_none()).count();
+        let enriched = out.join(&file);
+        enrich::write(
+            &state.folder.join(&file),
+            &enriched,
+            enrich::columns(&levels, count_tokens(state, &files).as_deref()),
+        )?;
+        println!(
+            "{} -> {} ({} rows, {} not decodable)",
+            file,
+            enriched.display(),
+            files.len(),
+            failed
+        );
+    }
+    Ok(())
+}
+
+/// Reads the duration and transcription columns of a Parquet file.
+fn load_durations_and_transcriptions(path: &Path) -> PolarsResult<Vec<(f64, String)>> {
+    let df = parquet_reader(path)?
+        .with_columns(Some(vec![
+            "duration".to_string(),
+            "transcription".to_string(),
+        ]))
+        .finish()?;
+    let durations = df.column("duration")?.cast(&DataType::Float64)?;
+    let transcriptions = df.column("transcription")?.cast(&DataType::String)?;
+    Ok(durations
+        .f64()?
+        .into_iter()
+        .zip(transcriptions.str()?)
+        .map(|(duration, text)| {
+            (
+                duration.unwrap_or_default(),
+                text.unwrap_or_default().to_string(),
+            )
+        })
+        .collect())
+}
+
+/// Searches the (corrected) transcriptions of every file, returning the first
+/// [`MAX_SEARCH_RESULTS`] matches and the total number of matches.
+fn search_corpus(
+    state: &AppState,
+    query: &str,
+) -> Result<(Vec<SearchHit>, usize), (http::StatusCode, String)> {
+    let (hits, total) = match &state.search_index {
+        Some(index) => search_indexed(state, index, query).map_err(|err| {
+            (
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("{:#}", err),
+            )
+        })?,
+        None => scan_corpus(state, query)?,
+    };
+    // The players stream the extracted WAV files.
+    let files: BTreeSet<&str> = hits.iter().map(|hit| hit.file.as_str()).collect();
+    for file in files {
+        extract_parquet_file(&state.tmp_folder, &state.folder, file);
+    }
+    Ok((hits, total))
+}
+
+/// Brings the search index up to date with the data folder.
+fn refresh_search_index(state: &AppState, index: &SearchIndex) -> anyhow::Result<usize> {
+    index.refresh(&state.folder, &list_parquet_files(&state.folder), |file| {
+        Ok(load_durations_and_transcriptions(&dataset_path(
+            &state.tmp_folder,
+            &state.folder,
+            file,
+        ))?)
+    })
+}
+
+/// Searches through the index, which holds the Parquet text; edited rows are matched
+/// against their corrected transcription instead.
+fn search_indexed(
+    state: &AppState,
+    index: &SearchIndex,
+    query: &str,
+) -> anyhow::Result<(Vec<SearchHit>, usize)> {
+    refresh_search_index(state, index)?;
+    let files = visible_files(state);
+    let corrections: Vec<(String, usize, String)> = files
+        .iter()
+        .flat_map(|file| {
+            state
+                .annotations
+                .file(file)
+                .into_iter()
+                .filter_map(move |(row, annotation)| {
+                    let text = annotation.transcription()?.to_string();
+                    Some((file.clone(), row, text))
+                })
+        })
+        .collect();
+
+    let (mut hits, mut total) =
+        index.search(query, &files, MAX_SEARCH_RESULTS + corrections.len())?;
+    let corrected: HashSet<(&str, usize)> = corrections
+        .iter()
+        .map(|(file, row, _)| (file.as_str(), *row))
+        .collect();
+    hits.retain(|hit| !corrected.contains(&(hit.file.as_str(), hit.row)));
+
+    let needle = query.to_lowercase();
+    for (file, row, text) in &corrections {
+        let Some((duration, original_matches)) = index.row(query, file, *row)? else {
+            continue;
+        };
+        if original_matches {
+            total -= 1;
+        }
+        if text.to_lowercase().contains(&needle) {
+            total += 1;
+            hits.push(SearchHit {
+                file: file.clone(),
+                row: *row,
+                duration,
+                transcription: text.clone(),
+            });
+        }
+    }
+    hits.sort_by(|a, b| (&a.file, a.row).cmp(&(&b.file, b.row)));
+    hits.truncate(MAX_SEARCH_RESULTS);
+    Ok((hits, total))
+}
+
+/// Searches by reading the transcriptions of every file.
+fn scan_corpus(
+    state: &AppState,
+    query: &str,
+) -> Result<(Vec<SearchHit>, usize), (http::StatusCode, String)> {
+    let needle = query.to_lowercase();
+    let mut hits = Vec::new();
+    let mut total = 0;
+    for file in visible_files(state) {
+        let mut rows = load_durations_and_transcriptions(&dataset_path(
+            &state.tmp_folder,
+            &state.folder,
+            &file,
+        ))
+        .map_err(|err| {
+            (
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read {}: {}", file, err),
+            )
+        })?;
+        for (&row, annotation) in state.annotations.file(&file).iter() {
+            if let (Some((_, text)), Some(corrected)) =
+                (rows.get_mut(row), annotation.transcription())
+            {
+                *text = corrected.to_string();
+            }
+        }
+
+        for (row, (duration, transcription)) in rows.into_iter().enumerate() {
+            if !transcription.to_lowercase().contains(&needle) {
+                continue;
+            }
+            total += 1;
+            if hits.len() < MAX_SEARCH_RESULTS {
+                hits.push(SearchHit {
+                    file: file.clone(),
+                    row,
+                    duration,
+                    transcription,
+                });
+            }
+        }
+    }
+    Ok((hits, total))
+}
+
+/// Searches transcriptions across every Parquet file in the folder.
+async fn search(
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    blocking(move || {
+        let q = query.q.trim();
+        let form = format!(
+            r#"<form method="get" action="/search" class="mb-4 flex gap-2">
+            <input type="search" name="q" value="{}" placeholder="{placeholder}" aria-label="{placeholder}" autofocus class="flex-1 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md p-1">
+            <button class="px-3 py-1 bg-blue-500 text-white rounded-md">{}</button>
+        </form>"#,
+            html::escape(q),
+            i18n::t("Search"),
+            placeholder = i18n::t("Search all transcriptions"),
+        );
+        if q.is_empty() {
+            return Ok(render_report("Search", None, &form));
+        }
+
+        let (hits, total) = search_corpus(&state, q)?;
+        let rows: String = hits
+            .iter()
+            .map(|hit| {
+                format!(
+                    r#"<tr class="border-b dark:border-gray-700">
+                <td class="px-4 py-2"><a href="/row/{file}/{row}" class="text-blue-600 dark:text-blue-400 hover:underline">{file_label} #{row}</a></td>
+                <td class="px-4 py-2 text-right">{duration}</td>
+                <td class="px-4 py-2"{attributes}>{transcription}</td>
+                <td class="px-4 py-2"><audio controls preload="none" src="/audio/{file}/{row}" aria-label="{label}" class="h-8"></audio></td>
+            </tr>"#,
+                    file = html::url_encode(&hit.file),
+                    file_label = html::escape(&hit.file),
+                    row = hit.row,
+                    duration = format_duration(hit.duration),
+                    transcription = html::highlight(&hit.transcription, q),
+                    attributes = html::text_attributes(&hit.transcription),
+                    label = html::escape(&html::clip_label(&hit.transcription)),
+                )
+            })
+            .collect();
+        let summary = if total > hits.len() {
+            format!(
+                "{} matching rows, showing the first {}.",
+                total,
+                hits.len()
+            )
+        } else {
+            format!("{} matching rows.", total)
+        };
+        let body = format!(
+            r#"{}
+        <p class="mb-2">{}</p>
+        <table class="min-w-full text-sm">
+            <thead><tr class="border-b-2 dark:border-gray-700"><th class="px-4 py-2 text-left">Row</th><th class="px-4 py-2 text-right">Duration</th><th class="px-4 py-2 text-left">Transcription</th><th class="px-4 py-2 text-left">Audio</th></tr></thead>
+            <tbody>{}</tbody>
+        </table>"#,
+            form, summary, rows
+        );
+        Ok(render_report("Search", None, &body))
+    })
+    .await
+}
+
+/// Renders the `<source>` elements for a clip's player.
+///
+/// Previews are transcoded from the original, so normalized playback uses the WAV path.
+fn audio_sources(state: &AppState, filename: &str, index: &str, normalize: bool) -> String {
+    let (filename, index) = (html::url_encode(filename), html::url_encode(index));
+    let audio_src = if normalize {
+        format!("/audio/{}/{}?normalize=1", filename, index)
+    } else {
+        format!("/audio/{}/{}", filename, index)
+    };
+    if state.preview.is_some() && !normalize {
+        format!(
+            r#"<source src="/preview/{}/{}" type="audio/ogg; codecs=opus"><source src="{}" type="audio/wav">"#,
+            filename, index, audio_src
+        )
+    } else {
+        format!(r#"<source src="{}" type="audio/wav">"#, audio_src)
+    }
+}
+
+/// Row indices of `files` in the order requested by `sort` (`column` or `-column`).
+///
+/// Rows without a value for the column, such as unscored clips, are placed last.
+fn sort_rows(
+    files: &[Audio],
+    analysis: &AnalysisCache,
+    token_counts: Option<&[usize]>,
+    scorer: Option<&Scorer>,
+    plugin_scores: Option<&[Option<f64>]>,
+    sort: Option<&str>,
+) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..files.len()).collect();
+    let Some(sort) = sort else {
+        return order;
+    };
+    let (column, descending) = match sort.strip_prefix('-') {
+        Some(column) => (column, true),
+        None => (sort, false),
+    };
+
+    let keys: Vec<Option<f64>> = files
+        .iter()
+        .enumerate()
+        .map(|(i, audio)| match column {
+            "duration" => Some(audio.duration),
+            "size" => audio.size.map(|size| size as f64),
+            "transcription" => Some(audio.transcription.chars().count() as f64),
+            "wpm" => words_per_minute(audio.duration, &audio.transcription),
+            "wer" => audio.pseudo_label_wer(),
+            "tokens" => token_counts.map(|counts| counts[i] as f64),
+            "noise_floor" => analysis
+                .levels(&audio.path)
+                .map(|levels| levels.noise_floor_dbfs as f64),
+            "score" => scorer.and_then(|scorer| scorer.cached(&audio.path)),
+            column if column.starts_with("plugin-") => plugin_scores.and_then(|scores| scores[i]),
+            column if column.starts_with("confidence-") => audio
+                .confidences
+                .iter()
+                .find(|(name, _)| column["confidence-".len()..] == *name)
+                .and_then(|(_, value)| *value),
+            _ => None,
+        })
+        .collect();
+    order.sort_by(|&a, &b| match (keys[a], keys[b]) {
+        (Some(x), Some(y)) if descending => y.total_cmp(&x),
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    order
+}
+
+/// Transcription as HTML: escaped text, or Markdown with `--markdown`, with the
+/// matches of the search query highlighted.
+fn render_transcription(state: &AppState, transcription: &str, query: &str) -> String {
+    if state.markdown {
+        html::markdown(transcription, query)
+    } else if let Some(dictionary) = &state.dictionary {
+        html::highlight_misspellings(transcription, query, &dictionary.suspects(transcription))
+    } else {
+        html::highlight(transcription, query)
+    }
+}
+
+/// Cell of a column with a `--column-renderers` entry.
+fn render_column(name: &str, value: &serde_json::Value) -> String {
+    COLUMN_RENDERERS
+        .get()
+        .and_then(|renderers| renderers.get(name))
+        .unwrap_or(&columns::Renderer::Text)
+        .render(value)
+}
+
+/// Runs a plugin on a row.
+fn run_plugin(
+    plugin: &Plugin,
+    filename: &str,
+    row: usize,
+    audio: &Audio,
+) -> anyhow::Result<plugins::PluginOutput> {
+    plugin.run(&plugins::RowData {
+        file: filename,
+        row,
+        duration: audio.duration,
+        transcription: &audio.transcription,
+    })
+}
+
+/// Cell content of a plugin: its HTML if it renders one, otherwise its score.
+fn plugin_cell(plugin: &Plugin, filename: &str, row: usize, audio: &Audio) -> String {
+    match run_plugin(plugin, filename, row, audio) {
+        Ok(plugins::PluginOutput {
+            html: Some(html), ..
+        }) => html,
+        Ok(plugins::PluginOutput {
+            score: Some(score), ..
+        }) => format!("{:.3}", score),
+        Ok(_) => "&mdash;".to_string(),
+        Err(err) => format!(
+            r#"<span class="text-red-700 dark:text-red-400" title="{}">error</span>"#,
+            html::escape(&format!("{:#}", err))
+        ),
+    }
+}
+
+/// Table header linking to the view sorted by `column`; clicking again reverses the order.
+fn sort_header(
+    filename: &str,
+    pagination: &Pagination,
+    label: &str,
+    column: &str,
+    align: &str,
+    filter_query: &str,
+) -> String {
+    let descending = format!("-{}", column);
+    let (next, arrow) = match pagination.sort.as_deref() {
+        Some(sort) if sort == column => (descending.as_str(), " &uarr;"),
+        Some(sort) if sort == descending => (column, " &darr;"),
+        _ => (column, ""),
+    };
+    let sorted = Pagination {
+        sort: Some(next.to_string()),
+        ..pagination.clone()
+    };
+    format!(
+        r#"<th class="px-4 py-2 text-{} font-semibold"><a href="/view/{}?page=1&page_size={}{}" class="hover:underline">{}{}</a></th>"#,
+        align,
+        html::url_encode(filename),
+        pagination.page_size.unwrap_or(10),
+        html::escape(&(sorted.extra_query() + filter_query)),
+        label,
+        arrow
+    )
+}
+
+/// Download links for the annotated rows of a file, if there are any.
+fn annotated_export_links(filename: &str, annotated: usize) -> String {
+    if annotated == 0 {
+        return String::new();
+    }
+    format!(
+        r#" <span class="text-gray-500 dark:text-gray-400">{} annotated rows:</span> <a href="/export/{}" class="text-blue-600 dark:text-blue-400 hover:underline">Parquet</a> <a href="/export/{}?format=jsonl" class="text-blue-600 dark:text-blue-400 hover:underline">JSONL</a>"#,
+        annotated,
+        html::url_encode(filename),
+        html::url_encode(filename)
+    )
+}
+
+/// Download links for the flagged rows of a file as an issue list, if there are any.
+fn issue_links(filename: &str, flagged: usize) -> String {
+    if flagged == 0 {
+        return String::new();
+    }
+    format!(
+        r#" <span class="text-gray-500 dark:text-gray-400">{}</span> <a href="/issues/{}" class="text-blue-600 dark:text-blue-400 hover:underline">Markdown</a> <a href="/issues/{}?format=csv" class="text-blue-600 dark:text-blue-400 hover:underline">CSV</a>"#,
+        i18n::tf(
+            "{count} flagged rows as issues:",
+            &[("count", &flagged.to_string())]
+        ),
+        html::url_encode(filename),
+        html::url_encode(filename)
+    )
+}
+
+/// Warning badge listing the `--blocklist` entries found in a transcription.
+fn blocklist_badge(state: &AppState, transcription: &str) -> String {
+    let Some(blocklist) = &state.blocklist else {
+        return String::new();
+    };
+    let matches = blocklist.matches(transcription);
+    if matches.is_empty() {
+        return String::new();
+    }
+    format!(
+        r#"<div class="mt-1 text-xs"><span class="px-2 rounded bg-orange-100 text-orange-800 dark:bg-orange-900 dark:text-orange-200" title="{}">&#9888; {}</span></div>"#,
+        html::escape(&matches.join(", ")),
+        i18n::t("blocklisted")
+    )
+}
+
+/// Tag, flag and review badges shown under a transcription.
+fn annotation_badges(annotation: &RowAnnotation) -> String {
+    let mut badges = Vec::new();
+    if annotation.flagged {
+        badges.push(format!(r#"<span class="px-2 rounded bg-red-100 text-red-800 dark:bg-red-900 dark:text-red-200">{}</span>"#, i18n::t("flagged")));
+    }
+    if !annotation.revisions.is_empty() {
+        badges.push(format!(r#"<span class="px-2 rounded bg-yellow-100 text-yellow-800 dark:bg-yellow-900 dark:text-yellow-200">{}</span>"#, i18n::t("edited")));
+    }
+    if annotation.reviewed {
+        badges.push(format!(r#"<span class="px-2 rounded bg-green-100 text-green-800 dark:bg-green-900 dark:text-green-200">{}</span>"#, i18n::t("reviewed")));
+    }
+    if !annotation.comments.is_empty() {
+        badges.push(format!(r#"<span class="px-2 rounded bg-purple-100 text-purple-800 dark:bg-purple-900 dark:text-purple-200">{}</span>"#, i18n::tf("{count} comments", &[("count", &annotation.comments.len().to_string())])));
+    }
+    for tag in &annotation.tags {
+        badges.push(format!(
+            r#"<span class="px-2 rounded bg-blue-100 text-blue-800 dark:bg-blue-900 dark:text-blue-200">{}</span>"#,
+            html::escape(tag)
+        ));
+    }
+    if badges.is_empty() {
+        return String::new();
+    }
+    format!(
+        r#"<div class="mt-1 flex flex-wrap gap-1 text-xs">{}</div>"#,
+        badges.concat()
+    )
+}
+
+/// Search/filter form of the file view, followed by the batch action form for the matching rows.
+fn filter_bar(
+    state: &AppState,
+    filename: &str,
+    pagination: &Pagination,
+    filter: &RowFilter,
+    filter_error: Option<&str>,
+    matching: usize,
+    files: &[Audio],
+) -> String {
+    let filename = html::url_encode(filename);
+    let input_class =
+        "bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md p-1";
+    let keep = pagination_inputs(pagination)
+        + &RowFilter {
+            facets: filter.facets.clone(),
+            ..RowFilter::default()
+        }
+        .hidden_inputs();
+
+    let status_options = [
+        ("", "Any status"),
+        ("flagged", "Flagged"),
+        ("reviewed", "Reviewed"),
+        ("unreviewed", "Unreviewed"),
+        ("commented", "Commented"),
+    ]
+    .iter()
+    .map(|(value, label)| {
+        format!(
+            r#"<option value="{}" {}>{}</option>"#,
+            value,
+            if filter.status == *value {
+                "selected"
+            } else {
+                ""
+            },
+            i18n::t(label)
+        )
+    })
+    .collect::<String>();
+
+    let event_select = if state.audio_events {
+        let options = std::iter::once(("", i18n::t("Any event")))
+            .chain(
+                AudioEvent::ALL
+                    .iter()
+                    .map(|event| (event.as_str(), i18n::t(event.label()))),
+            )
+            .map(|(value, label)| {
+                format!(
+                    r#"<option value="{}" {}>{}</option>"#,
+                    value,
+                    if filter.event == value {
+                        "selected"
+                    } else {
+                        ""
+                    },
+                    label
+                )
+            })
+            .collect::<String>();
+        format!(
+            r#"
+                <select name="event" class="{}" aria-label="{}">{}</select>"#,
+            input_class,
+            i18n::t("Event"),
+            options
+        )
+    } else {
+        String::new()
+    };
+
+    let style_options = std::iter::once(("", i18n::t("Any style")))
+        .chain(
+            style::Class::ALL
+                .iter()
+                .map(|class| (class.as_str(), i18n::t(class.label()))),
+        )
+        .map(|(value, label)| {
+            format!(
+                r#"<option value="{}" {}>{}</option>"#,
+                value,
+                if filter.style == value {
+                    "selected"
+                } else {
+                    ""
+                },
+                label
+            )
+        })
+        .collect::<String>();
+
+    let confidence = files
+        .first()
+        .is_some_and(|audio| !audio.confidences.is_empty());
+    let confidence_inputs = if confidence {
+        format!(
+            r#"
+                <input type="number" name="min_confidence" value="{}" min="0" max="1" step="any" placeholder="{}" class="{input_class} w-24" title="{}" aria-label="{}">
+                <input type="number" name="max_confidence" value="{}" min="0" max="1" step="any" placeholder="{}" class="{input_class} w-24" title="{}" aria-label="{}">"#,
+            html::escape(&filter.min_confidence),
+            i18n::t("Min conf"),
+            i18n::t("Lowest confidence of the row"),
+            i18n::t("Min conf"),
+            html::escape(&filter.max_confidence),
+            i18n::t("Max conf"),
+            i18n::t("Lowest confidence of the row"),
+            i18n::t("Max conf"),
+        )
+    } else {
+        String::new()
+    };
+
+    let blocklist_select = if state.blocklist.is_some() {
+        let options = [
+            ("", "Any words"),
+            ("only", "Blocklisted"),
+            ("exclude", "Without blocklisted"),
+        ]
+        .iter()
+        .map(|(value, label)| {
+            format!(
+                r#"<option value="{}" {}>{}</option>"#,
+                value,
+                if filter.blocklist == *value {
+                    "selected"
+                } else {
+                    ""
+                },
+                i18n::t(label)
+            )
+        })
+        .collect::<String>();
+        format!(
+            r#"
+                <select name="blocklist" class="{}" aria-label="{}">{}</select>"#,
+            input_class,
+            i18n::t("Blocklisted"),
+            options
+        )
+    } else {
+        String::new()
+    };
+
+    let wer_select = if files.iter().any(|audio| audio.pseudo_label.is_some()) {
+        let options = std::iter::once(("", i18n::t("Any WER")))
+            .chain(
+                agreement::BUCKETS
+                    .iter()
+                    .map(|bucket| (bucket.name, bucket.label)),
+            )
+            .map(|(value, label)| {
+                format!(
+                    r#"<option value="{}" {}>{}</option>"#,
+                    value,
+                    if filter.wer == value { "selected" } else { "" },
+                    label
+                )
+            })
+            .collect::<String>();
+        format!(
+            r#"
+                <select name="wer" class="{}" title="{label}" aria-label="{label}">{}</select>"#,
+            input_class,
+            options,
+            label = i18n::t("Word error rate of the pseudo-label"),
+        )
+    } else {
+        String::new()
+    };
+
+    let rates = sample_rate_counts(files);
+    let rate_select = if rates.len() > 1 || !filter.rate.is_empty() {
+        let options = std::iter::once((String::new(), i18n::t("Any rate").to_string()))
+            .chain(
+                rates
+                    .iter()
+                    .map(|(rate, _)| (rate_label(*rate), rate_name(*rate))),
+            )
+            .map(|(value, label)| {
+                format!(
+                    r#"<option value="{}" {}>{}</option>"#,
+                    value,
+                    if filter.rate == value { "selected" } else { "" },
+                    label
+                )
+            })
+            .collect::<String>();
+        format!(
+            r#"
+                <select name="rate" class="{}" aria-label="{}">{}</select>"#,
+            input_class,
+            i18n::t("Sampling rate"),
+            options
+        )
+    } else {
+        String::new()
+    };
+
+    let action_options = [
+        ("tag", "Add tag"),
+        ("untag", "Remove tag"),
+        ("flag", "Flag"),
+        ("unflag", "Unflag"),
+        ("review", "Mark reviewed"),
+        ("unreview", "Mark unreviewed"),
+        ("export", "Export as Parquet"),
+    ]
+    .iter()
+    .map(|(value, label)| format!(r#"<option value="{}">{}</option>"#, value, i18n::t(label)))
+    .collect::<String>();
+
+    format!(
+        r#"<div class="mb-4 flex flex-col gap-2 text-sm">
+            <form method="get" action="/view/{filename}" class="flex flex-wrap items-center gap-2">{keep}
+                <input type="search" name="q" value="{q}" placeholder="{search_placeholder}" aria-label="{search_placeholder}" class="{input_class}">
+                <input type="text" name="tag" value="{tag}" placeholder="{tag_placeholder}" aria-label="{tag_placeholder}" class="{input_class} w-32">
+                <select name="status" class="{input_class}" aria-label="{status_label}">{status_options}</select>{event_select}{blocklist_select}
+                <select name="style" class="{input_class}" aria-label="{style_label}">{style_options}</select>
+                <input type="number" name="min_duration" value="{min_duration}" min="0" step="any" placeholder="{min_placeholder}" aria-label="{min_placeholder}" class="{input_class} w-20">
+                <input type="number" name="max_duration" value="{max_duration}" min="0" step="any" placeholder="{max_placeholder}" aria-label="{max_placeholder}" class="{input_class} w-20">{confidence_inputs}{wer_select}{rate_select}
+                <button class="px-3 py-1 bg-blue-500 text-white rounded-md">{filter_label}</button>
+                <a href="/view/{filename}" class="text-blue-600 dark:text-blue-400 hover:underline">{clear_label}</a>
+                <input type="text" name="filter" value="{expression}" placeholder="duration&gt;10 AND speaker_id='spk1'" aria-label="{expression_label}" class="{input_class} w-full font-mono">
+            </form>{error}
+            <form method="get" action="/batch/{filename}" class="flex flex-wrap items-center gap-2">{filter_inputs}
+                <span class="font-semibold">{apply_label}</span>
+                <select name="action" class="{input_class}" aria-label="{action_label}">{action_options}</select>
+                <input type="text" name="value" placeholder="{tag_name_placeholder}" aria-label="{tag_name_placeholder}" class="{input_class} w-32">
+                <button class="px-3 py-1 bg-gray-200 dark:bg-gray-700 rounded-md">{apply_button}&hellip;</button>
+            </form>
+        </div>"#,
+        q = html::escape(&filter.q),
+        tag = html::escape(&filter.tag),
+        search_placeholder = i18n::t("Search transcriptions"),
+        tag_placeholder = i18n::t("Tag"),
+        status_label = i18n::t("Status"),
+        style_label = i18n::t("Casing and punctuation"),
+        expression_label = i18n::t("Filter expression"),
+        action_label = i18n::t("Action"),
+        min_placeholder = i18n::t("Min s"),
+        max_placeholder = i18n::t("Max s"),
+        filter_label = i18n::t("Filter"),
+        clear_label = i18n::t("Clear"),
+        apply_label = i18n::tf(
+            "Apply to all {count} matching rows:",
+            &[("count", &matching)]
+        ),
+        tag_name_placeholder = i18n::t("Tag name"),
+        apply_button = i18n::t("Apply"),
+        min_duration = html::escape(&filter.min_duration),
+        expression = html::escape(&filter.filter),
+        error = filter_error
+            .map(|err| format!(
+                r#"<p class="text-red-700 dark:text-red-400">{} {}</p>"#,
+                i18n::t("Invalid filter:"),
+                html::escape(err)
+            ))
+            .unwrap_or_default(),
+        max_duration = html::escape(&filter.max_duration),
+        filter_inputs = filter.hidden_inputs(),
+    )
+}
+
+/// Filter value of a sampling rate: the rate in Hz, or `unknown`.
+fn rate_label(rate: Option<u32>) -> String {
+    rate.map_or("unknown".to_string(), |rate| rate.to_string())
+}
+
+/// Display name of a sampling rate, e.g. `16000 Hz`.
+fn rate_name(rate: Option<u32>) -> String {
+    rate.map_or(i18n::t("Unknown rate").to_string(), |rate| {
+        format!("{} Hz", rate)
+    })
+}
+
+/// Number of rows per sampling rate, ascending, with rows of unknown rate last.
+fn sample_rate_counts(files: &[Audio]) -> Vec<(Option<u32>, usize)> {
+    let mut counts: BTreeMap<Option<u32>, usize> = BTreeMap::new();
+    for audio in files {
+        *counts.entry(audio.sample_rate).or_default() += 1;
+    }
+    let mut counts: Vec<(Option<u32>, usize)> = counts.into_iter().collect();
+    counts.sort_by_key(|(rate, _)| (rate.is_none(), *rate));
+    counts
+}
+
+/// Warning shown above the file view when its clips have more than one known sampling
+/// rate, which silently breaks training, with a link filtering to each rate.
+fn sample_rate_banner(
+    filename: &str,
+    pagination: &Pagination,
+    filter: &RowFilter,
+    files: &[Audio],
+) -> String {
+    let counts = sample_rate_counts(files);
+    let known = counts.iter().filter(|(rate, _)| rate.is_some()).count();
+    if known < 2 {
+        return String::new();
+    }
+    let links = counts
+        .iter()
+        .map(|&(rate, count)| {
+            let value = rate_label(rate);
+            let query = RowFilter {
+                rate: value.clone(),
+                ..filter.clone()
+            }
+            .query();
+            format!(
+                r#"<a href="/view/{}?page_size={}{}" class="px-2 py-1 rounded border border-yellow-400 dark:border-yellow-600 hover:bg-yellow-200 dark:hover:bg-yellow-800{}">{}</a>"#,
+                html::url_encode(filename),
+                pagination.page_size.unwrap_or(10),
+                html::escape(&(pagination.extra_query() + &query)),
+                if filter.rate == value { " font-semibold" } else { "" },
+                i18n::tf(
+                    "{rate}: {count} rows",
+                    &[("rate", &rate_name(rate)), ("count", &count)]
+                )
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"<div class="mb-4 p-3 rounded border-l-4 border-yellow-500 bg-yellow-100 text-yellow-900 dark:bg-yellow-900 dark:text-yellow-100 text-sm" role="alert"><p class="mb-2"><strong>{}</strong> {}</p><div class="flex flex-wrap gap-2">{}</div></div>"#,
+        i18n::t("Mixed sampling rates."),
+        i18n::tf(
+            "The clips of this file have {count} different sampling rates; resample them to one rate before training.",
+            &[("count", &known)]
+        ),
+        links
+    )
+}
+
+/// Hidden inputs keeping the page size, sort order and view toggles across form submits.
+fn pagination_inputs(pagination: &Pagination) -> String {
+    let mut inputs = hidden_input("page_size", &pagination.page_size.unwrap_or(10).to_string());
+    if let Some(sort) = &pagination.sort {
+        inputs.push_str(&hidden_input("sort", sort));
+    }
+    if pagination.normalize {
+        inputs.push_str(&hidden_input("normalize", "1"));
+    }
+    if pagination.show_normalized {
+        inputs.push_str(&hidden_input("show_normalized", "1"));
+    }
+    inputs
+}
+
+/// Value counts of one facet column.
+struct Facet {
+    column: String,
+    /// `(value, rows)`, by descending count.
+    values: Vec<(String, usize)>,
+}
+
+/// Counts the values of every facet column among the rows matching `filter`, ignoring
+/// the selection of that column itself so its other values stay selectable.
+fn facet_counts(
+    files: &[Audio],
+    filter: &RowFilter,
+    annotation: impl Fn(usize) -> RowAnnotation,
+) -> Vec<Facet> {
+    let Some(first) = files.first() else {
+        return Vec::new();
+    };
+    first
+        .facets
+        .keys()
+        .map(|column| {
+            let mut others = filter.clone();
+            let selected = others.facets.remove(column).unwrap_or_default();
+            let mut counts: BTreeMap<String, usize> =
+                selected.into_iter().map(|value| (value, 0)).collect();
+            for (row, audio) in files.iter().enumerate() {
+                if others.matches(row, audio, &annotation(row)) {
+                    *counts.entry(audio.facets[column].clone()).or_default() += 1;
+                }
+            }
+            let mut values: Vec<(String, usize)> = counts.into_iter().collect();
+            values.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+            Facet {
+                column: column.clone(),
+                values,
+            }
+        })
+        .collect()
+}
+
+/// Sidebar with a checkbox per facet value; checking one reloads the view.
+fn facet_sidebar(
+    filename: &str,
+    pagination: &Pagination,
+    filter: &RowFilter,
+    facets: &[Facet],
+) -> String {
+    if facets.is_empty() {
+        return String::new();
+    }
+    let groups = facets
+        .iter()
+        .map(|facet| {
+            let selected = filter.facets.get(&facet.column);
+            let options = facet
+                .values
+                .iter()
+                .map(|(value, count)| {
+                    format!(
+                        r#"<label class="flex items-center gap-2"><input type="checkbox" name="facet.{}" value="{}" onchange="this.form.submit()" {}><span class="flex-1 truncate">{}</span><span class="text-gray-500 dark:text-gray-400">{}</span></label>"#,
+                        html::escape(&facet.column),
+                        html::escape(value),
+                        if selected.is_some_and(|values| values.contains(value)) {
+                            "checked"
+                        } else {
+                            ""
+                        },
+                        html::escape(value),
+                        count
+                    )
+                })
+                .collect::<String>();
+            format!(
+                r#"<fieldset class="mb-4"><legend class="font-semibold mb-1">{}</legend>{}</fieldset>"#,
+                html::escape(&facet.column),
+                options
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"<aside class="md:w-56 shrink-0 text-sm">
+            <form method="get" action="/view/{}">{}{}{}</form>
+        </aside>"#,
+        html::url_encode(filename),
+        pagination_inputs(pagination),
+        RowFilter {
+            facets: BTreeMap::new(),
+            ..filter.clone()
+        }
+        .hidden_inputs(),
+        groups
+    )
+}
+
+/// Scripts for the file view: query parameter helper, single active player and
+/// "show more" toggles for clamped transcriptions.
+/// Menu choosing which optional columns the file view shows; the choice is stored in
+/// the preferences cookie.
+fn column_menu(columns: &[(String, String)], preferences: &preferences::Preferences) -> String {
+    if columns.is_empty() {
+        return String::new();
+    }
+    let items = columns
+        .iter()
+        .map(|(key, label)| {
+            format!(
+                r#"<label class="flex items-center gap-2 whitespace-nowrap"><input type="checkbox" data-column="{}" onchange="savePreference(this.checked ? 'show' : 'hide', this.dataset.column).finally(function() {{ location.reload(); }})" {}><span>{}</span></label>"#,
+                html::escape(key),
+                if preferences.shows(key) { "checked" } else { "" },
+                html::escape(label)
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"<details class="relative"><summary class="cursor-pointer">{}</summary><div class="absolute z-10 mt-1 p-2 flex flex-col gap-1 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md shadow">{}</div></details>"#,
+        i18n::t("Columns"),
+        items
+    )
+}
+
+/// Extra cell of a file view row. Below the `md` breakpoint rows are laid out as cards,
+/// where the cell becomes a line with its label on the left and the value on the right.
+fn row_cell(label: &str, align_right: bool, content: &str) -> String {
+    format!(
+        r#"<td class="flex justify-between items-start gap-4 md:table-cell px-4 py-1 md:py-4{}"><span class="md:hidden font-semibold text-gray-500 dark:text-gray-400">{}</span>{}</td>"#,
+        if align_right { " md:text-right" } else { "" },
+        label,
+        content
+    )
+}
+
+const VIEW_HEAD_SCRIPTS: &str = r#"
+    <script>
+        function setParam(name, value) {
+            var url = new URL(location.href);
+            if (value === null) {
+                url.searchParams.delete(name);
+            } else {
+                url.searchParams.set(name, value);
+            }
+            location = url;
+        }
+        document.addEventListener('play', function(e) {
+            if (concurrentPlayback) {
+                return;
+            }
+            var audios = document.getElementsByTagName('audio');
+            for (var i = 0, len = audios.length; i < len; i++) {
+                if (audios[i] != e.target) {
+                    audios[i].pause();
+                }
+            }
+        }, true);
+        function toggleClamp(button) {
+            var text = button.previousElementSibling;
+            var clamped = text.classList.toggle('line-clamp-3');
+            button.textContent = clamped ? button.dataset.more : button.dataset.less;
+            button.setAttribute('aria-expanded', clamped ? 'false' : 'true');
+        }
+        // Creates the player of a row past `--players-per-page` from its template.
+        function loadPlayer(row) {
+            var template = row.querySelector('template[data-player]');
+            if (template) {
+                template.nextElementSibling.remove();
+                template.replaceWith(template.content);
+            }
+            return row.querySelector('audio');
+        }
+        // Clicking a row, or pressing Enter or Space while it has focus, plays or pauses it.
+        function toggleRowAudio(row) {
+            var audio = loadPlayer(row);
+            if (audio.paused) {
+                audio.play();
+            } else {
+                audio.pause();
+            }
+        }
+        function rowKeyDown(event, row) {
+            if (event.target === row && (event.key === 'Enter' || event.key === ' ')) {
+                event.preventDefault();
+                toggleRowAudio(row);
+            }
+        }
+        document.addEventListener('DOMContentLoaded', function() {
+            document.querySelectorAll('.line-clamp-3').forEach(function(text) {
+                if (text.scrollHeight > text.clientHeight) {
+                    text.nextElementSibling.classList.remove('hidden');
+                }
+            });
+        });
+    </script>"#;
+
+/// Remembers the clips played in this browser tab (`sessionStorage`, as `file/row`
+/// keys from `data-clip`) and dims and checks their rows, so a long listening pass shows
+/// what has been heard after moving between pages.
+const HISTORY_SCRIPT: &str = r#"
+    <script>
+        function playedClips() {
+            try {
+                return JSON.parse(sessionStorage.getItem('playedClips')) || [];
+            } catch (e) {
+                return [];
+            }
+        }
+        function markPlayed(element) {
+            var mark = element.querySelector('.played-mark');
+            if (mark) {
+                element.classList.add('opacity-60');
+                mark.classList.remove('hidden');
+            }
+        }
+        document.addEventListener('play', function(e) {
+            var clip = e.target.closest && e.target.closest('[data-clip]');
+            if (!clip) {
+                return;
+            }
+            var played = playedClips();
+            if (played.indexOf(clip.dataset.clip) < 0) {
+                played.push(clip.dataset.clip);
+                sessionStorage.setItem('playedClips', JSON.stringify(played));
+            }
+            markPlayed(clip);
+        }, true);
+        document.addEventListener('DOMContentLoaded', function() {
+            var played = playedClips();
+            document.querySelectorAll('[data-clip]').forEach(function(clip) {
+                if (played.indexOf(clip.dataset.clip) >= 0) {
+                    markPlayed(clip);
+                }
+            });
+        });
+    </script>"#;
+
+/// Runs the row page actions bound to keys in the shortcut overlay, and saves keys
+/// rebound there to the preferences.
+const SHORTCUT_SCRIPT: &str = r#"
+    <script>
+        var shortcutActions = {
+            play: function() {
+                var audio = document.querySelector('audio[data-clip]');
+                audio.paused ? audio.play() : audio.pause();
+            },
+            back: function() {
+                var audio = document.querySelector('audio[data-clip]');
+                audio.currentTime = Math.max(0, audio.currentTime - 5);
+            },
+            forward: function() {
+                document.querySelector('audio[data-clip]').currentTime += 5;
+            },
+            previous: function() {
+                var link = document.getElementById('previous-row');
+                if (link) {
+                    location.href = link.href;
+                }
+            },
+            next: function() {
+                var link = document.getElementById('next-row');
+                if (link) {
+                    location.href = link.href;
+                }
+            },
+            edit: function() {
+                document.querySelector('form[data-live-row] textarea').focus();
+            },
+            save: function() {
+                document.querySelector('form[data-live-row]').requestSubmit();
+            },
+            loop_a: function() { setLoopPoint('a'); },
+            loop_b: function() { setLoopPoint('b'); },
+            loop: function() { document.getElementById('loop-enabled').click(); },
+            comment: function() { document.getElementById('new-comment').focus(); },
+            help: function() { toggleShortcuts(); }
+        };
+        function keyName(event) {
+            var name = (event.ctrlKey ? 'ctrl+' : '') + (event.altKey ? 'alt+' : '') + (event.metaKey ? 'meta+' : '');
+            // Shift is part of printable keys already, e.g. `?`.
+            if (event.shiftKey && event.key.length > 1) {
+                name += 'shift+';
+            }
+            return name + (event.key === ' ' ? 'space' : event.key.toLowerCase());
+        }
+        function toggleShortcuts() {
+            var overlay = document.getElementById('shortcuts');
+            overlay.classList.toggle('hidden');
+            document.getElementById('shortcut-error').textContent = '';
+            if (!overlay.classList.contains('hidden')) {
+                overlay.querySelector('[data-bind]').focus();
+            }
+        }
+        function saveShortcut(action, key) {
+            var error = document.getElementById('shortcut-error');
+            fetch('/preferences', {
+                method: 'POST',
+                headers: {'Content-Type': 'application/x-www-form-urlencoded'},
+                body: new URLSearchParams([['bind', action], ['key', key]])
+            }).then(function(response) {
+                if (!response.ok) {
+                    return response.text().then(function(text) { error.textContent = text; });
+                }
+                var input = document.querySelector('[data-bind="' + action + '"]');
+                input.value = key || input.dataset.default;
+                error.textContent = '';
+            });
+        }
+        function resetShortcut(action) {
+            saveShortcut(action, '');
+        }
+        document.addEventListener('keydown', function(event) {
+            if (['Control', 'Alt', 'Shift', 'Meta'].indexOf(event.key) >= 0) {
+                return;
+            }
+            var overlay = document.getElementById('shortcuts');
+            var target = event.target;
+            if (target.dataset && target.dataset.bind && event.key !== 'Tab') {
+                event.preventDefault();
+                if (event.key === 'Escape') {
+                    toggleShortcuts();
+                } else {
+                    saveShortcut(target.dataset.bind, keyName(event));
+                }
+                return;
+            }
+            if (event.key === 'Escape' && !overlay.classList.contains('hidden')) {
+                toggleShortcuts();
+                return;
+            }
+            var modified = event.ctrlKey || event.altKey || event.metaKey;
+            if (!modified && target.closest && target.closest('input, textarea, select, [contenteditable]')) {
+                return;
+            }
+            var name = keyName(event);
+            var bound = Array.prototype.find.call(overlay.querySelectorAll('[data-bind]'), function(input) {
+                return input.value === name;
+            });
+            if (bound) {
+                event.preventDefault();
+                shortcutActions[bound.dataset.bind]();
+            }
+        });
+    </script>"#;
+
+/// Follows the annotation changes of the file named by `data-live-file` over `/live`:
+/// table rows get their new badges and transcription, and an open editor takes the new
+/// text, or warns about the conflict when it holds unsaved edits.
+const LIVE_SCRIPT: &str = r#"
+    <script>
+        function connectLive(file) {
+            var scheme = location.protocol === 'https:' ? 'wss://' : 'ws://';
+            var socket = new WebSocket(scheme + location.host + '/live/' + encodeURIComponent(file));
+            socket.onmessage = function(event) {
+                JSON.parse(event.data).rows.forEach(function(change) {
+                    var row = document.getElementById('row-' + change.row);
+                    if (row) {
+                        var badges = row.querySelector('[data-live="badges"]');
+                        if (badges) {
+                            badges.innerHTML = change.badges;
+                        }
+                        var text = row.querySelector('[data-live="transcription"]');
+                        if (text && change.transcription !== null) {
+                            text.textContent = change.transcription;
+                        }
+                    }
+                    var editor = document.querySelector('form[data-live-row="' + change.row + '"]');
+                    if (editor && String(change.version) !== editor.elements.base.value) {
+                        var textarea = editor.elements.text;
+                        if (textarea.value === textarea.defaultValue && change.transcription !== null) {
+                            textarea.value = textarea.defaultValue = change.transcription;
+                            editor.elements.base.value = change.version;
+                        } else {
+                            document.getElementById('live-conflict').classList.remove('hidden');
+                        }
+                    }
+                });
+            };
+            socket.onclose = function() {
+                setTimeout(function() { connectLive(file); }, 5000);
+            };
+        }
+        document.addEventListener('DOMContentLoaded', function() {
+            var live = document.querySelector('[data-live-file]');
+            if (live) {
+                connectLive(live.dataset.liveFile);
+            }
+        });
+    </script>"#;
+
+/// Plays one transcript segment through the trimming parameters of `/audio`, pausing the
+/// players on the page; clicking the playing segment again stops it.
+const SEGMENT_SCRIPT: &str = r#"
+    <script>
+        var segmentAudio = new Audio();
+        function playSegment(button) {
+            if (!concurrentPlayback) {
+                document.querySelectorAll('audio').forEach(function(audio) {
+                    audio.pause();
+                });
+            }
+            if (!segmentAudio.paused && segmentAudio.src.endsWith(button.dataset.src)) {
+                segmentAudio.pause();
+                return;
+            }
+            segmentAudio.src = button.dataset.src;
+            segmentAudio.play();
+        }
+        document.addEventListener('play', function() {
+            if (!concurrentPlayback) {
+                segmentAudio.pause();
+            }
+        }, true);
+    </script>"#;
+
+/// Draws the row page spectrogram from the log-mel features, overlays the F0 track in
+/// red and follows playback with a cursor; clicking seeks.
+const ROW_HEAD_SCRIPTS: &str = r#"
+    <script>
+        function hzToMel(hz) {
+            return 2595 * Math.log10(1 + hz / 700);
+        }
+        function fetchJson(url) {
+            return fetch(url).then(function(response) {
+                if (!response.ok) {
+                    throw new Error(response.status + ' ' + response.statusText);
+                }
+                return response.json();
+            });
+        }
+        // A-B repeat of the detail player: `a` and `b` in seconds, `null` while unset.
+        var loop = {a: null, b: null, enabled: false, redraw: function() {}};
+        function setLoopPoint(which) {
+            var audio = document.querySelector('audio');
+            loop[which] = audio.currentTime;
+            if (loop.a !== null && loop.b !== null && loop.a > loop.b) {
+                var a = loop.a;
+                loop.a = loop.b;
+                loop.b = a;
+            }
+            loop.enabled = loop.a !== null && loop.b !== null;
+            updateLoop();
+        }
+        function clearLoop() {
+            loop.a = loop.b = null;
+            loop.enabled = false;
+            updateLoop();
+        }
+        function updateLoop() {
+            var format = function(t) { return t === null ? '–' : t.toFixed(2) + ' s'; };
+            document.getElementById('loop-a').textContent = format(loop.a);
+            document.getElementById('loop-b').textContent = format(loop.b);
+            document.getElementById('loop-enabled').checked = loop.enabled;
+            loop.redraw();
+        }
+        function watchLoop() {
+            var audio = document.querySelector('audio');
+            if (loop.enabled && loop.a !== null && loop.b !== null && audio.currentTime >= loop.b) {
+                audio.currentTime = loop.a;
+            }
+            if (!audio.paused) {
+                requestAnimationFrame(watchLoop);
+            }
+        }
+        document.addEventListener('DOMContentLoaded', function() {
+            var audio = document.querySelector('audio');
+            audio.addEventListener('play', watchLoop);
+            audio.addEventListener('play', function() {
+                if (loop.enabled && loop.a !== null && (audio.currentTime < loop.a || audio.currentTime >= loop.b)) {
+                    audio.currentTime = loop.a;
+                }
+            });
+        });
+        function drawPitchView(canvas) {
+            var audio = document.querySelector('audio');
+            var caption = canvas.nextElementSibling;
+            Promise.all([fetchJson(canvas.dataset.features), fetchJson(canvas.dataset.pitch)]).then(function(results) {
+                var mel = results[0], pitch = results[1];
+                var frames = mel.shape[0], bands = mel.shape[1];
+                if (frames === 0) {
+                    throw new Error('clip is empty');
+                }
+                var width = canvas.width = canvas.clientWidth * devicePixelRatio;
+                var height = canvas.height = canvas.clientHeight * devicePixelRatio;
+                var seconds = frames * mel.hop_length / mel.sample_rate;
+
+                var max = -Infinity;
+                mel.data.forEach(function(row) {
+                    row.forEach(function(value) { max = Math.max(max, value); });
+                });
+                // About 50 dB of dynamic range, as the values are natural logs of power.
+                var min = max - 12;
+                var spectrogram = document.createElement('canvas');
+                spectrogram.width = frames;
+                spectrogram.height = bands;
+                var image = spectrogram.getContext('2d').createImageData(frames, bands);
+                for (var f = 0; f < frames; f++) {
+                    for (var b = 0; b < bands; b++) {
+                        var v = Math.min(Math.max((mel.data[f][b] - min) / (max - min), 0), 1);
+                        var offset = ((bands - 1 - b) * frames + f) * 4;
+                        image.data[offset] = 255 * Math.sqrt(v);
+                        image.data[offset + 1] = 255 * v * v;
+                        image.data[offset + 2] = 80 + 120 * v;
+                        image.data[offset + 3] = 255;
+                    }
+                }
+                spectrogram.getContext('2d').putImageData(image, 0, 0);
+
+                var base = document.createElement('canvas');
+                base.width = width;
+                base.height = height;
+                var ctx = base.getContext('2d');
+                ctx.imageSmoothingEnabled = false;
+                ctx.drawImage(spectrogram, 0, 0, width, height);
+                var melMax = hzToMel(mel.sample_rate / 2);
+                ctx.strokeStyle = '#ef4444';
+                ctx.lineWidth = 2 * devicePixelRatio;
+                ctx.beginPath();
+                var drawing = false;
+                pitch.f0.forEach(function(hz, i) {
+                    if (hz === null) {
+                        drawing = false;
+                        return;
+                    }
+                    var x = i * pitch.hop_length / pitch.sample_rate / seconds * width;
+                    var y = (1 - hzToMel(hz) / melMax) * height;
+                    if (drawing) {
+                        ctx.lineTo(x, y);
+                    } else {
+                        ctx.moveTo(x, y);
+                    }
+                    drawing = true;
+                });
+                ctx.stroke();
+
+                var view = canvas.getContext('2d');
+                function render() {
+                    view.drawImage(base, 0, 0);
+                    if (loop.a !== null) {
+                        var a = loop.a / seconds * width;
+                        var b = loop.b === null ? a + devicePixelRatio : loop.b / seconds * width;
+                        view.fillStyle = loop.enabled ? 'rgba(250, 204, 21, 0.35)' : 'rgba(250, 204, 21, 0.15)';
+                        view.fillRect(a, 0, Math.max(b - a, devicePixelRatio), height);
+                    }
+                    var x = audio.currentTime / seconds * width;
+                    view.fillStyle = '#ffffff';
+                    view.fillRect(x, 0, devicePixelRatio, height);
+                    if (!audio.paused) {
+                        requestAnimationFrame(render);
+                    }
+                }
+                audio.addEventListener('play', render);
+                audio.addEventListener('seeked', render);
+                loop.redraw = render;
+                // A click seeks; dragging selects the A-B loop.
+                var dragStart = null;
+                var timeAt = function(e) {
+                    return Math.min(Math.max(e.offsetX / canvas.clientWidth, 0), 1) * seconds;
+                };
+                canvas.addEventListener('mousedown', function(e) {
+                    dragStart = e.offsetX;
+                });
+                canvas.addEventListener('mousemove', function(e) {
+                    if (dragStart === null || Math.abs(e.offsetX - dragStart) < 4) {
+                        return;
+                    }
+                    var start = dragStart / canvas.clientWidth * seconds;
+                    loop.a = Math.min(start, timeAt(e));
+                    loop.b = Math.max(start, timeAt(e));
+                    loop.enabled = true;
+                    updateLoop();
+                });
+                canvas.addEventListener('mouseup', function(e) {
+                    if (dragStart !== null && Math.abs(e.offsetX - dragStart) < 4) {
+                        audio.currentTime = timeAt(e);
+                    } else if (loop.enabled) {
+                        audio.currentTime = loop.a;
+                    }
+                    dragStart = null;
+                    render();
+                });
+                render();
+            }).catch(function(err) {
+                caption.textContent = 'Spectrogram unavailable: ' + err.message;
+            });
+        }
+        document.addEventListener('DOMContentLoaded', function() {
+            document.querySelectorAll('canvas[data-pitch]').forEach(drawPitchView);
+        });
+    </script>"#;
+
+/// Serves a paginated view of the Parquet file data.
+async fn view_file(
+    State(state): State<AppState>,
+    AxumPath(filename): AxumPath<String>,
+    Query(mut pagination): Query<Pagination>,
+    Query(filter): Query<RowFilter>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> response::Response {
+    let filter = filter.with_facets(&params);
+    // A zero page or page size would underflow or divide by zero.
+    pagination.page = pagination.page.map(|page| page.max(1));
+    pagination.page_size = pagination
+        .page_size
+        .or(preferences::current().page_size)
+        .map(|size| size.clamp(1, preferences::MAX_PAGE_SIZE));
+    blocking(move || render_file_view(state, filename, pagination, filter)).await
+}
+
+fn render_file_view(
+    state: AppState,
+    filename: String,
+    pagination: Pagination,
+    filter: RowFilter,
+) -> response::Response {
+    if !filename.ends_with(".parquet") && !archive::is_archive(&filename) && !is_lance(&filename) {
+        return Html("Invalid file type".to_string()).into_response();
+    }
+
+    if !is_valid_parquet(&state, &filename) {
+        return Html("File not found".to_string()).into_response();
+    }
+
+    let mut files = extract_parquet_file(&state.tmp_folder, &state.folder, &filename);
+
+    let annotations = state.annotations.file(&filename);
+    let no_annotation = RowAnnotation::default();
+    let annotation = |row: usize| annotations.get(&row).unwrap_or(&no_annotation);
+    apply_corrections(&mut files, &annotations);
+
+    let filter_query = filter.query();
+    let extra_query = pagination.extra_query() + &filter_query;
+    // For the links and the concatenated player; the seek redirect takes it as is.
+    let extra_query_attribute = html::escape(&extra_query);
+
+    let token_counts = cached_token_counts(&state, &filename, &files);
+
+    let filtered = filter_rows(
+        &state,
+        &filename,
+        &files,
+        &annotations,
+        &filter,
+        pagination.sort.as_deref(),
+        token_counts.as_deref().map(Vec::as_slice),
+    );
+    let order = &filtered.order;
+
+    let page = pagination.page.unwrap_or(1);
+    let page_size = pagination.page_size.unwrap_or(10);
+    let mut seek_notice = String::new();
+    if let Some(row) = pagination.row {
+        match order.iter().position(|&r| r == row) {
+            Some(position) => {
+                // Every part is percent-encoded, so the Location is a valid header value.
+                return response::Redirect::to(&format!(
+                    "/view/{}?page={}&page_size={}{}#row-{}",
+                    html::url_encode(&filename),
+                    position / page_size + 1,
+                    page_size,
+                    extra_query,
+                    row
+                ))
+                .into_response();
+            }
+            None => {
+                seek_notice = format!(
+                    r#"<p class="mb-4 text-red-600 dark:text-red-400">Row {} is not among the {} matching rows.</p>"#,
+                    row,
+                    order.len()
+                )
+            }
+        }
+    }
+    let total_items = order.len();
+    let total_pages = (total_items as f64 / page_size as f64).ceil() as usize;
+
+    let start = (page - 1).saturating_mul(page_size);
+    let end = start.saturating_add(page_size).min(total_items);
+
+    let page_rows = if start < order.len() {
+        &order[start..end]
+    } else {
+        &[]
+    };
+    extract_archived_rows(&state, &filename, page_rows.iter().copied());
+    // Level badges and noise floors come from the cache; missing ones are analyzed in
+    // the background and show up on the next load.
+    state
+        .analysis
+        .precompute(page_rows.iter().map(|&row| files[row].path.clone()));
+
+    // Optional columns, listed in the column menu; hidden ones are left out.
+    let preferences = preferences::current();
+    let mut optional_columns: Vec<(String, String)> = Vec::new();
+    let mut column = |key: &str, label: &str| {
+        optional_columns.push((key.to_string(), label.to_string()));
+        preferences.shows(key)
+    };
+    let show_size =
+        files.iter().any(|audio| audio.size.is_some()) && column("size", i18n::t("Size"));
+    let show_tokens = token_counts.is_some() && column("tokens", i18n::t("Tokens"));
+    let show_score = state.scorer.is_some() && column("score", i18n::t("Score"));
+    let show_noise_floor = state.noise_floor && column("noise_floor", i18n::t("Noise floor"));
+    let show_events = state.audio_events && column("event", i18n::t("Event"));
+    let show_wer =
+        files.iter().any(|audio| audio.pseudo_label.is_some()) && column("wer", i18n::t("WER"));
+    let confidence_columns: Vec<&String> = files
+        .first()
+        .map(|audio| audio.confidences.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| column(&format!("confidence-{}", name), name))
+        .collect();
+    let plugin_columns: Vec<&Plugin> = state
+        .plugins
+        .iter()
+        .filter(|plugin| column(&format!("plugin-{}", plugin.name), &plugin.name))
+        .collect();
+    let nested_columns: Vec<&String> = files
+        .first()
+        .map(|audio| audio.nested.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| column(&format!("nested-{}", name), name))
+        .collect();
+    let rendered_columns: Vec<&String> = files
+        .first()
+        .map(|audio| audio.columns.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| column(&format!("column-{}", name), name))
+        .collect();
+
+    let mut extra_headers = String::new();
+    if show_size {
+        extra_headers.push_str(&sort_header(
+            &filename,
+            &pagination,
+            i18n::t("Size"),
+            "size",
+            "right",
+            &filter_query,
+        ));
+    }
+    if show_tokens {
+        extra_headers.push_str(&sort_header(
+            &filename,
+            &pagination,
+            i18n::t("Tokens"),
+            "tokens",
+            "right",
+            &filter_query,
+        ));
+    }
+    if show_score {
+        extra_headers.push_str(&sort_header(
+            &filename,
+            &pagination,
+            i18n::t("Score"),
+            "score",
+            "right",
+            &filter_query,
+        ));
+    }
+    if show_noise_floor {
+        extra_headers.push_str(&sort_header(
+            &filename,
+            &pagination,
+            i18n::t("Noise floor"),
+            "noise_floor",
+            "right",
+            &filter_query,
+        ));
+    }
+    if show_events {
+        extra_headers.push_str(&format!(
+            r#"<th class="px-4 py-2 text-left font-semibold">{}</th>"#,
+            i18n::t("Event")
+        ));
+    }
+    if show_wer {
+        extra_headers.push_str(&sort_header(
+            &filename,
+            &pagination,
+            i18n::t("WER"),
+            "wer",
+            "right",
+            &filter_query,
+        ));
+    }
+    for name in &confidence_columns {
+        extra_headers.push_str(&sort_header(
+            &filename,
+            &pagination,
+            &html::escape(name),
+            &format!("confidence-{}", name),
+            "right",
+            &filter_query,
+        ));
+    }
+    for plugin in &plugin_columns {
+        extra_headers.push_str(&sort_header(
+            &filename,
+            &pagination,
+            &html::escape(&plugin.name),
+            &format!("plugin-{}", plugin.name),
+            "left",
+            &filter_query,
+        ));
+    }
+    for name in &nested_columns {
+        extra_headers.push_str(&format!(
+            r#"<th class="px-4 py-2 text-left font-semibold">{}</th>"#,
+            html::escape(name)
+        ));
+    }
+    for name in &rendered_columns {
+        extra_headers.push_str(&format!(
+            r#"<th class="px-4 py-2 text-left font-semibold">{}</th>"#,
+            html::escape(name)
+        ));
+    }
+
+    let mut rows = String::new();
+    for (position, &row) in page_rows.iter().enumerate() {
+        let audio = &files[row];
+        let mut extra_cells = String::new();
+        if show_size {
+            let size = audio
+                .size
+                .map(format_size)
+                .unwrap_or_else(|| "&mdash;".to_string());
+            extra_cells.push_str(&row_cell(i18n::t("Size"), true, &size));
+        }
+        if let Some(counts) = token_counts.as_ref().filter(|_| show_tokens) {
+            extra_cells.push_str(&row_cell(i18n::t("Tokens"), true, &counts[row].to_string()));
+        }
+        if let Some(scorer) = state.scorer.as_ref().filter(|_| show_score) {
+            let score = scorer
+                .cached(&audio.path)
+                .map(|score| format!("{:.3}", score))
+                .unwrap_or_else(|| "&mdash;".to_string());
+            extra_cells.push_str(&row_cell(i18n::t("Score"), true, &score));
+        }
+        if show_noise_floor {
+            let noise_floor = state
+                .analysis
+                .cached_levels(&audio.path)
+                .map(|levels| format!("{:.1} dBFS", levels.noise_floor_dbfs))
+                .unwrap_or_else(|| "&mdash;".to_string());
+            extra_cells.push_str(&row_cell(i18n::t("Noise floor"), true, &noise_floor));
+        }
+        if show_events {
+            let event = state
+                .analysis
+                .events(&audio.path)
+                .map(|event| event.badge())
+                .unwrap_or_else(|| "&mdash;".to_string());
+            extra_cells.push_str(&row_cell(i18n::t("Event"), false, &event));
+        }
+        if show_wer {
+            let wer = format!(
+                r#"<span title="{}">{}</span>"#,
+                html::escape(audio.pseudo_label.as_deref().unwrap_or_default()),
+                audio
+                    .pseudo_label_wer()
+                    .map(|wer| format!("{:.1}%", wer * 100.0))
+                    .unwrap_or_else(|| "&mdash;".to_string())
+            );
+            extra_cells.push_str(&row_cell(i18n::t("WER"), true, &wer));
+        }
+        for (name, value) in &audio.confidences {
+            if !confidence_columns.contains(&name) {
+                continue;
+            }
+            extra_cells.push_str(&row_cell(
+                &html::escape(name),
+                true,
+                &confidence::badge(*value),
+            ));
+        }
+        for plugin in &plugin_columns {
+            extra_cells.push_str(&row_cell(
+                &html::escape(&plugin.name),
+                false,
+                &plugin_cell(plugin, &filename, row, audio),
+            ));
+        }
+        for (name, value) in &audio.nested {
+            if !nested_columns.contains(&name) {
+                continue;
+            }
+            extra_cells.push_str(&row_cell(
+                &html::escape(name),
+                false,
+                &nested::tree(value, false),
+            ));
+        }
+        for (name, value) in &audio.columns {
+            if !rendered_columns.contains(&name) {
+                continue;
+            }
+            extra_cells.push_str(&row_cell(
+                &html::escape(name),
+                false,
+                &render_column(name, value),
+            ));
+        }
+        let index = audio.path.file_stem().unwrap().to_str().unwrap();
+        let source = audio_sources(&state, &filename, index, pagination.normalize);
+        let level_badge = state
+            .analysis
+            .cached_levels(&audio.path)
+            .map(|levels| levels.badge())
+            .unwrap_or_default();
+        let normalized_text = if pagination.show_normalized {
+            format!(
+                r#"<div class="mt-1 text-sm text-gray-500 dark:text-gray-400" title="Normalized"{}>{}</div>"#,
+                html::text_attributes(&audio.transcription),
+                html::escape(&state.text_normalizer.normalize(&audio.transcription))
+            )
+        } else {
+            String::new()
+        };
+        rows.push_str(&templates::render(
+            "file_row.html",
+            context! {
+                row => row,
+                index => index,
+                filename => &filename,
+                duration => format_duration(audio.duration),
+                duration_seconds => audio.duration,
+                transcription => templates::safe(render_transcription(&state, &audio.transcription, &filter.q)),
+                transcription_attributes => templates::safe(html::text_attributes(&audio.transcription)),
+                audio_label => html::clip_label(&audio.transcription),
+                preload => state.playback.preload.attribute(),
+                lazy_player => state.playback.players_per_page.is_some_and(|players| position >= players),
+                source => templates::safe(source),
+                level_badge => templates::safe(level_badge),
+                segment_timeline => templates::safe(segments::timeline(
+                    &filename,
+                    index,
+                    audio.duration,
+                    &audio.segments,
+                    "h-3 w-full md:max-w-xs my-1",
+                )),
+                images => templates::safe(images::thumbnails(&audio.images, "h-16 max-w-[8rem]")),
+                normalized_text => templates::safe(normalized_text),
+                blocklist_badge => templates::safe(blocklist_badge(&state, &audio.transcription)),
+                annotation_badges => templates::safe(annotation_badges(annotation(row))),
+                annotation => context! {
+                    tags => annotation(row).tags.iter().cloned().collect::<Vec<_>>(),
+                    flagged => annotation(row).flagged,
+                    reviewed => annotation(row).reviewed,
+                    edited => !annotation(row).revisions.is_empty(),
+                },
+                extra_cells => templates::safe(extra_cells),
+            },
+        ));
+    }
+
+    let encoded = html::url_encode(&filename);
+    let pagination_html = if total_pages > 1 {
+        let mut pagination_links = String::new();
+        let window = 2;
+        let mut pages_to_render = vec![];
+
+        // Previous page link
+        if page > 1 {
+            pagination_links.push_str(&format!(
+                r#"<a href="/view/{}?page={}&page_size={}{}" class="px-3 py-1 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 text-blue-600 dark:text-blue-300 hover:bg-gray-100 dark:hover:bg-gray-600 rounded-md">{}</a>"#,
+                encoded,
+                page - 1,
+                page_size,
+                extra_query_attribute,
+                i18n::t("Prev")
+            ));
+        }
+
+        pages_to_render.push(1);
+        if page > window + 2 {
+            pages_to_render.push(0); // Ellipsis
+        }
+
+        for i in (page.saturating_sub(window))..=(page + window) {
+            if i > 1 && i < total_pages {
+                pages_to_render.push(i);
+            }
+        }
+
+        if page < total_pages.saturating_sub(window + 1) {
+            pages_to_render.push(0); // Ellipsis
+        }
+        if total_pages > 1 {
+            pages_to_render.push(total_pages);
+        }
+
+        pages_to_render.dedup();
+
+        for p in pages_to_render {
+            if p == 0 {
+                pagination_links.push_str(r#"<span class="px-3 py-1">...</span>"#);
+            } else {
+                let class = if p == page {
+                    "px-3 py-1 bg-blue-500 text-white rounded-md"
+                } else {
+                    "px-3 py-1 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 text-blue-600 dark:text-blue-300 hover:bg-gray-100 dark:hover:bg-gray-600 rounded-md"
+                };
+                pagination_links.push_str(&format!(
+                    r#"<a href="/view/{}?page={}&page_size={}{}" class="{}">{}</a>"#,
+                    encoded, p, page_size, extra_query_attribute, class, p
+                ));
+            }
+        }
+
+        // Next page link
+        if page < total_pages {
+            pagination_links.push_str(&format!(r#"<a href="/view/{}?page={}&page_size={}{}" class="px-3 py-1 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 text-blue-600 dark:text-blue-300 hover:bg-gray-100 dark:hover:bg-gray-600 rounded-md">{}</a>"#, encoded, page + 1, page_size, extra_query_attribute, i18n::t("Next")));
+        }
+        pagination_links
+    } else {
+        String::new()
+    };
+
+    let page_size_selector = {
+        let mut options = String::new();
+        for &size in &preferences::PAGE_SIZES {
+            let selected = if size == page_size { "selected" } else { "" };
+            options.push_str(&format!(
+                r#"<option value="/view/{}?page=1&page_size={}{}" data-size="{}" {}>{}</option>"#,
+                encoded, size, extra_query_attribute, size, selected, size
+            ));
+        }
+
+        format!(
+            r#"<div class="flex items-center gap-2"><span id="page-size-label">{}</span><select onchange="var url = this.value; savePreference('page_size', this.selectedOptions[0].dataset.size).finally(function() {{ location = url; }});" aria-labelledby="page-size-label" class="bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 text-gray-900 dark:text-gray-100 rounded-md p-1">{}</select></div>"#,
+            i18n::t("Page size:"),
+            options
+        )
+    };
+
+    let view_toggles = [
+        html::flag_toggle(
+            "normalize",
+            i18n::t("Normalize loudness"),
+            pagination.normalize,
+        ),
+        html::flag_toggle(
+            "show_normalized",
+            i18n::t("Show normalized text"),
+            pagination.show_normalized,
+        ),
+        column_menu(&optional_columns, &preferences),
+    ]
+    .concat();
+
+    let charts = file_charts(&state, &files, token_counts.as_deref().map(Vec::as_slice));
+    let plots_html = charts
+        .iter()
+        .map(|chart| {
+            format!(
+                r#"<pre class="mt-2 text-sm text-gray-600 dark:text-gray-300 whitespace-pre-wrap"><code>{}</code></pre>"#,
+                chart.text
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n            <br>\n            ")
+        + &stats::download_links(&format!("/stats/{}", html::url_encode(&filename)));
+
+    let score_form = if state.scorer.is_some() {
+        format!(
+            r#"<form method="post" action="/score/{}" class="mb-4"><button class="px-3 py-1 bg-blue-500 text-white rounded-md text-sm">{}</button></form>"#,
+            encoded,
+            i18n::tf("Score all {count} rows", &[("count", &files.len())])
+        )
+    } else {
+        String::new()
+    };
+
+    let total_label = if filter.is_active() {
+        i18n::tf(
+            "Matching audio files: {count} of {total}",
+            &[("count", &total_items), ("total", &files.len())],
+        )
+    } else {
+        i18n::tf("Total audio files: {count}", &[("count", &total_items)])
+    };
+
+    let content = format!(
+        r#"
+    <div class="max-w-6xl mx-auto bg-white dark:bg-gray-800 shadow-md rounded-lg p-3 md:p-6 relative">
+        {}
+        <h1 class="text-2xl font-bold mb-4" data-live-file="{}">{}</h1>
+        <nav class="mb-4 flex flex-wrap gap-4 text-sm">{}</nav>
+        {}
+        {}
+        <details class="mb-4 bg-gray-50 dark:bg-gray-700 p-4 rounded">
+            <summary class="font-semibold cursor-pointer">{}</summary>
+            {}
+        </details>
+        <div class="flex flex-col md:flex-row gap-4">
+        {}
+        <div class="overflow-x-auto flex-1">
+            <table class="block md:table min-w-full w-full bg-white dark:bg-gray-800 border-collapse">
+            <thead class="hidden md:table-header-group">
+{}
+            </thead>
+            <tbody class="block md:table-row-group">
+                {}
+            </tbody>
+            </table>
+        </div>
+        </div>
+        <div class="mt-4 flex flex-col items-center gap-4">
+            <div class="flex flex-wrap justify-center gap-2">
+                {}
+            </div>
+            <div class="flex flex-wrap justify-center items-center gap-4">
+                {}
+                {}
+            </div>
+            <div class="text-center text-sm text-gray-500 dark:text-gray-400">
+                {}
+            </div>
+        </div>
+    </div>
+"#,
+        html::page_header("/", i18n::t("Back to list")),
+        html::escape(&filename),
+        html::escape(&filename),
+        report_links(Some(&filename))
+            + &annotated_export_links(&filename, annotations.len())
+            + &issue_links(
+                &filename,
+                annotations.values().filter(|a| a.flagged).count()
+            )
+            + &split_link(&filename),
+        sample_rate_banner(&filename, &pagination, &filter, &files)
+            + &file_note(&state, &filename)
+            + &score_form,
+        filter_bar(
+            &state,
+            &filename,
+            &pagination,
+            &filter,
+            filtered.error.as_deref(),
+            total_items,
+            &files
+        ) + &seek_notice
+            + &concat_player(&filename, &extra_query_attribute, total_items),
+        i18n::t("Metadata details"),
+        plots_html,
+        facet_sidebar(&filename, &pagination, &filter, &filtered.facets),
+        templates::render(
+            "file_header.html",
+            context! {
+                duration_header => templates::safe(sort_header(
+                    &filename,
+                    &pagination,
+                    i18n::t("Duration"),
+                    "duration",
+                    "right",
+                    &filter_query
+                )),
+                transcription_header => templates::safe(sort_header(
+                    &filename,
+                    &pagination,
+                    i18n::t("Transcription"),
+                    "transcription",
+                    "left",
+                    &filter_query
+                )),
+                extra_headers => templates::safe(extra_headers),
+            },
+        ),
+        rows,
+        pagination_html,
+        page_size_selector + &seek_form(&filename, &pagination, &filter),
+        view_toggles,
+        total_label
+    );
+
+    Html(html::render_page(
+        &format!("{} - Parquet Viewer", filename),
+        &format!(
+            "{}{}{}{}{}",
+            state.playback.script(),
+            VIEW_HEAD_SCRIPTS,
+            SEGMENT_SCRIPT,
+            HISTORY_SCRIPT,
+            LIVE_SCRIPT
+        ),
+        &content,
+    ))
+    .into_response()
+}
+
+/// "Go to row" box, seeking within the current order and filter.
+fn seek_form(filename: &str, pagination: &Pagination, filter: &RowFilter) -> String {
+    format!(
+        r#"<form method="get" action="/view/{}" class="flex items-center gap-2">{}{}<label for="seek-row">{}</label><input id="seek-row" type="number" name="row" min="0" required class="w-24 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md p-1"><button class="px-3 py-1 bg-blue-500 text-white rounded-md">{}</button></form>"#,
+        html::url_encode(filename),
+        pagination_inputs(pagination),
+        filter.hidden_inputs(),
+        i18n::t("Go to row #"),
+        i18n::t("Go")
+    )
+}
+
+/// Serves the detail page of a single row.
+async fn view_row(
+    State(state): State<AppState>,
+    AxumPath((filename, row)): AxumPath<(String, usize)>,
+    Query(pagination): Query<Pagination>,
+    Query(dump): Query<HexDumpQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    blocking(move || render_row_view(state, filename, row, pagination, dump)).await
+}
+
+/// Bytes shown in the hex dump of the row detail page by default.
+const HEX_DUMP_BYTES: usize = 256;
+/// Most bytes the hex dump shows.
+const MAX_HEX_DUMP_BYTES: usize = 64 * 1024;
+
+/// Length of the hex dump on the row detail page.
+#[derive(Deserialize, Debug)]
+struct HexDumpQuery {
+    bytes: Option<usize>,
+}
+
+/// Collapsed section of the row detail page with the container of the stored audio,
+/// its chunks or metadata blocks, and a hex dump of its first bytes.
+fn raw_bytes_section(filename: &str, row: usize, path: &Path, limit: usize) -> String {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => return format!("<p>{}</p>", html::escape(&err.to_string())),
+    };
+    let (format, items) = container::describe(&bytes);
+    let rows = items
+        .iter()
+        .map(|item| {
+            format!(
+                r#"<tr class="border-b dark:border-gray-700"><td class="px-2 py-1 font-mono">{:#x}</td><td class="px-2 py-1 font-mono">{}</td><td class="px-2 py-1">{}</td></tr>"#,
+                item.offset,
+                html::escape(&item.name),
+                html::escape(&item.detail)
+            )
+        })
+        .collect::<String>();
+    let limit = limit.min(MAX_HEX_DUMP_BYTES);
+    format!(
+        r#"<details class="mt-6"><summary class="text-xl font-semibold mb-2 cursor-pointer">{title}</summary><p class="text-sm mb-2">{summary} <a href="/download/{filename}/{row}?raw=1" class="text-blue-600 dark:text-blue-400 hover:underline">{download}</a></p><table class="text-sm mb-2 border-collapse">{rows}</table><p class="text-xs text-gray-500 dark:text-gray-400 mb-1">{shown}</p><pre class="text-xs overflow-x-auto p-2 rounded bg-gray-50 dark:bg-gray-900"><code>{dump}</code></pre></details>"#,
+        title = i18n::t("Raw bytes"),
+        filename = html::url_encode(filename),
+        summary = i18n::tf(
+            "{format} container, {size} bytes.",
+            &[("format", &format), ("size", &bytes.len())]
+        ),
+        download = i18n::t("Download raw bytes"),
+        shown = i18n::tf(
+            "First {count} bytes (set ?bytes= for more):",
+            &[("count", &limit.min(bytes.len()))]
+        ),
+        dump = html::escape(&container::hex_dump(&bytes[..limit.min(bytes.len())])),
+    )
+}
+
+fn render_row_view(
+    state: AppState,
+    filename: String,
+    row: usize,
+    pagination: Pagination,
+    dump: HexDumpQuery,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    if !is_valid_parquet(&state, &filename) {
+        return Err((http::StatusCode::NOT_FOUND, "File not found".to_string()));
+    }
+
+    let files = extract_parquet_file(&state.tmp_folder, &state.folder, &filename);
+    let audio = files
+        .get(row)
+        .ok_or((http::StatusCode::NOT_FOUND, "Row not found".to_string()))?;
+    extract_archived_rows(&state, &filename, [row]);
+    let index = row.to_string();
+    let encoded = html::url_encode(&filename);
+    let annotation = state.annotations.get(&filename, row);
+    let transcription = annotation.transcription().unwrap_or(&audio.transcription);
+
+    let link_class = "text-blue-600 dark:text-blue-400 hover:underline";
+    let mut nav = Vec::new();
+    if row > 0 {
+        nav.push(format!(
+            r#"<a id="previous-row" href="/row/{}/{}" class="{}">&larr; {}</a>"#,
+            encoded,
+            row - 1,
+            link_class,
+            i18n::t("Previous row")
+        ));
+    }
+    if row + 1 < files.len() {
+        nav.push(format!(
+            r#"<a id="next-row" href="/row/{}/{}" class="{}">{} &rarr;</a>"#,
+            encoded,
+            row + 1,
+            link_class,
+            i18n::t("Next row")
+        ));
+    }
+
+    let help_key = shortcuts::resolve(&state.key_bindings, &preferences::current().key_bindings)
+        .into_iter()
+        .find(|(action, _, _)| *action == "help")
+        .map(|(_, key, _)| key)
+        .unwrap_or_default();
+    nav.push(format!(
+        r#"<button type="button" onclick="toggleShortcuts()" class="{}">{}</button>"#,
+        link_class,
+        i18n::tf(
+            "Keyboard shortcuts ({key})",
+            &[("key", &html::escape(&help_key))]
+        )
+    ));
+
+    let mut properties = vec![
+        (i18n::t("Duration"), format_duration(audio.duration)),
+        (
+            i18n::t("Size"),
+            audio.size.map(format_size).unwrap_or_default(),
+        ),
+        (
+            i18n::t("Characters"),
+            transcription.chars().count().to_string(),
+        ),
+    ];
+    if let Ok(Some(hashes)) = integrity::read_sidecar(&state.folder, &filename) {
+        let recorded = hashes.rows.get(row).cloned().flatten();
+        let current = fs::read(&audio.path).ok().map(|bytes| blobs::hash(&bytes));
+        let changed = if recorded == current {
+            String::new()
+        } else {
+            format!(
+                r#" <span class="px-2 rounded bg-red-100 text-red-800 dark:bg-red-900 dark:text-red-200">{}</span>"#,
+                i18n::t("Changed since the hashes were written")
+            )
+        };
+        properties.push((
+            "SHA-256",
+            format!(
+                r#"<code class="text-sm break-all">{}</code>{}"#,
+                current.as_deref().unwrap_or("&mdash;"),
+                changed
+            ),
+        ));
+    }
+    if let Some(levels) = state.analysis.levels(&audio.path) {
+        properties.push((
+            i18n::t("Level"),
+            format!(
+                "{} peak {:.1} dBFS, loudness {:.1} LUFS, noise floor {:.1} dBFS, SNR {:.1} dB, {:.0}% speech, {} clipped runs",
+                levels.badge(),
+                levels.peak_dbfs,
+                levels.loudness_lufs,
+                levels.noise_floor_dbfs,
+                levels.snr_db,
+                levels.speech_ratio * 100.0,
+                levels.clipped_runs
+            ),
+        ));
+    }
+    if state.audio_events
+        && let Some(event) = state.analysis.events(&audio.path)
+    {
+        properties.push((i18n::t("Event"), event.badge()));
+    }
+    if let Some(tokenizer) = &state.tokenizer {
+        let tokens = tokenizer
+            .encode(transcription, true)
+            .map(|encoding| encoding.len().to_string())
+            .unwrap_or_else(|err| format!("error: {}", err));
+        properties.push((i18n::t("Tokens"), tokens));
+    }
+    if let Some(scorer) = &state.scorer {
+        let score = match scorer.cached(&audio.path) {
+            Some(score) => format!("{:.3}", score),
+            None => format!(
+                r#"<form method="post" action="/score/{}/{}"><button class="px-3 py-1 bg-blue-500 text-white rounded-md text-sm">Score</button></form>"#,
+                encoded, index
+            ),
+        };
+        properties.push((i18n::t("Score"), score));
+    }
+    for (name, value) in &audio.confidences {
+        properties.push((name.as_str(), confidence::badge(*value)));
+    }
+    if let Some(label) = &audio.pseudo_label {
+        properties.push((
+            i18n::t("Pseudo-label"),
+            format!(
+                r#"{}<div class="mt-1 text-xs text-gray-500 dark:text-gray-400">{}</div>"#,
+                render_diff(transcription, label),
+                i18n::tf(
+                    "WER {percent}% against the transcription",
+                    &[(
+                        "percent",
+                        &format!(
+                            "{:.1}",
+                            agreement::word_error_rate(transcription, label) * 100.0
+                        )
+                    )]
+                )
+            ),
+        ));
+    }
+    if let Some(blocklist) = &state.blocklist {
+        let matches = blocklist.matches(transcription);
+        if !matches.is_empty() {
+            properties.push((
+                i18n::t("Blocklisted"),
+                format!(
+                    r#"<span class="px-2 rounded bg-orange-100 text-orange-800 dark:bg-orange-900 dark:text-orange-200">&#9888; {}</span>"#,
+                    html::escape(&matches.join(", "))
+                ),
+            ));
+        }
+    }
+    if let Some(dictionary) = &state.dictionary {
+        let suspects = dictionary.suspect_words(transcription);
+        if !suspects.is_empty() {
+            properties.push((
+                i18n::t("Possible misspellings"),
+                suspects
+                    .iter()
+                    .map(|word| {
+                        format!(
+                            r#"<span class="underline decoration-wavy decoration-red-500">{}</span>"#,
+                            html::escape(word)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+        }
+    }
+    if !annotation.is_empty() {
+        properties.push((i18n::t("Annotations"), annotation_badges(&annotation)));
+    }
+    if !audio.images.is_empty() {
+        properties.push((
+            i18n::t("Images"),
+            images::thumbnails(&audio.images, "h-48 max-w-full"),
+        ));
+    }
+    for (name, value) in &audio.nested {
+        properties.push((name.as_str(), nested::tree(value, true)));
+    }
+    for (name, value) in &audio.columns {
+        properties.push((name.as_str(), render_column(name, value)));
+    }
+    let current = Audio {
+        transcription: transcription.to_string(),
+        ..audio.clone()
+    };
+    for plugin in state.plugins.iter() {
+        properties.push((
+            plugin.name.as_str(),
+            plugin_cell(plugin, &filename, row, &current),
+        ));
+    }
+    if state.markdown {
+        properties.push((i18n::t("Rendered"), html::markdown(transcription, "")));
+    }
+    properties.push((
+        "Normalized text",
+        format!(
+            "<div{}>{}</div>",
+            html::text_attributes(transcription),
+            html::escape(&state.text_normalizer.normalize(transcription))
+        ),
+    ));
+    let properties_html = properties
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                r#"<tr class="border-b dark:border-gray-700"><th class="px-4 py-2 text-left font-semibold align-top w-48">{}</th><td class="px-4 py-2">{}</td></tr>"#,
+                name, value
+            )
+        })
+        .collect::<String>();
+
+    let tools = [
+        (
+            format!("/download/{}/{}", encoded, index),
+            "Download original",
+        ),
+        (
+            format!("/audio/{}/{}?normalize=1", encoded, index),
+            "Normalized WAV",
+        ),
+        (
+            format!("/features/{}/{}?kind=logmel", encoded, index),
+            "Log-mel (JSON)",
+        ),
+        (
+            format!("/features/{}/{}?kind=mfcc", encoded, index),
+            "MFCC (JSON)",
+        ),
+        (
+            format!("/features/{}/{}?kind=logmel&format=npy", encoded, index),
+            "Log-mel (NPY)",
+        ),
+        (format!("/pitch/{}/{}", encoded, index), "F0 (JSON)"),
+        (
+            format!("/energy/{}/{}", encoded, index),
+            "RMS energy (JSON)",
+        ),
+        (
+            format!("/augment/{}/{}?speed=0.9", encoded, index),
+            "Speed 0.9",
+        ),
+        (
+            format!("/augment/{}/{}?speed=1.1", encoded, index),
+            "Speed 1.1",
+        ),
+        (
+            format!("/augment/{}/{}?pitch=2", encoded, index),
+            "Pitch +2",
+        ),
+        (
+            format!("/augment/{}/{}?pitch=-2", encoded, index),
+            "Pitch -2",
+        ),
+        (
+            format!("/augment/{}/{}?noise_snr=10", encoded, index),
+            "Noise 10 dB SNR",
+        ),
+    ]
+    .iter()
+    .map(|(href, label)| {
+        format!(
+            r#"<a href="{}" class="{}">{}</a>"#,
+            href,
+            link_class,
+            i18n::t(label)
+        )
+    })
+    .collect::<Vec<_>>()
+    .join(" ");
+
+    let back_href = format!("/view/{}?row={}", encoded, row);
+    let content = format!(
+        r#"
+    <div class="max-w-6xl mx-auto bg-white dark:bg-gray-800 shadow-md rounded-lg p-6 relative">
+        {header}
+        <h1 class="text-2xl font-bold mb-2">{live_file} #{row}</h1>
+        <nav class="mb-4 flex gap-4 text-sm">{nav}</nav>
+        <audio class="w-full mb-4" controls="" preload="auto" aria-label="{audio_label}" data-clip="{live_file}/{row}">
+            {sources}
+            {no_audio_support}
+        </audio>
+        <figure class="mb-4">
+            <canvas class="w-full h-48 rounded bg-gray-900 cursor-pointer" role="img" aria-label="{spectrogram_caption}" data-features="/features/{encoded}/{index}?kind=logmel" data-pitch="/pitch/{encoded}/{index}"></canvas>
+            <figcaption class="mt-1 text-sm text-gray-500 dark:text-gray-400">{spectrogram_caption}</figcaption>
+        </figure>
+        <div class="mb-4 flex flex-wrap items-center gap-2 text-sm">
+            <span class="font-semibold">{ab_repeat}</span>
+            <button type="button" onclick="setLoopPoint('a')" class="px-2 py-1 bg-gray-200 dark:bg-gray-700 rounded-md">{set_a}</button><span id="loop-a" aria-live="polite">–</span>
+            <button type="button" onclick="setLoopPoint('b')" class="px-2 py-1 bg-gray-200 dark:bg-gray-700 rounded-md">{set_b}</button><span id="loop-b" aria-live="polite">–</span>
+            <label class="flex items-center gap-1"><input id="loop-enabled" type="checkbox" onchange="loop.enabled = this.checked && loop.a !== null && loop.b !== null; updateLoop()">{loop_label}</label>
+            <button type="button" onclick="clearLoop()" class="px-2 py-1 bg-gray-200 dark:bg-gray-700 rounded-md">{clear}</button>
+        </div>
+        <div id="live-conflict" class="hidden mb-4 p-3 rounded bg-yellow-100 text-yellow-900 dark:bg-yellow-900 dark:text-yellow-100 text-sm" role="alert">{conflict}</div>
+        <form method="post" action="/edit/{encoded}/{row}" class="mb-4" data-live-file="{live_file}" data-live-row="{row}">
+            <input type="hidden" name="base" value="{base}">
+            <textarea name="text" rows="{rows}" aria-label="{transcription_label}" class="w-full p-4 rounded bg-gray-50 dark:bg-gray-700 text-lg"{text_attributes}>{transcription}</textarea>
+            <div class="mt-2 flex gap-2 text-sm">
+                <button class="px-3 py-1 bg-blue-500 text-white rounded-md">{save}</button>
+                {undo}
+            </div>
+        </form>
+        <table class="w-full mb-4 border-collapse">{properties}</table>
+        {segments}
+        <div class="flex flex-wrap gap-4 text-sm"><span class="font-semibold">{tools_label}</span>{tools}</div>
+        {augmentation}
+        {commands}
+        {hypotheses}
+        {comments}
+        {history}
+        {raw_bytes}
+        {shortcuts}
+    </div>"#,
+        header = html::page_header(
+            &back_href,
+            &i18n::tf("Back to {name}", &[("name", &html::escape(&filename))])
+        ),
+        nav = nav.join(""),
+        sources = audio_sources(&state, &filename, &index, pagination.normalize),
+        transcription = html::escape(transcription),
+        transcription_label = i18n::t("Transcription"),
+        conflict = i18n::t(
+            "Another reviewer changed this row while you were editing it. Saving now will be rejected; copy your text and reload."
+        ),
+        live_file = html::escape(&filename),
+        base = annotation.version(),
+        audio_label = html::escape(&html::clip_label(transcription)),
+        no_audio_support = i18n::t("Your browser does not support the audio element."),
+        spectrogram_caption = i18n::t(
+            "Log-mel spectrogram with the F0 contour in red; click to seek, drag to select a loop."
+        ),
+        ab_repeat = i18n::t("A-B repeat:"),
+        set_a = i18n::t("Set A"),
+        set_b = i18n::t("Set B"),
+        loop_label = i18n::t("Loop"),
+        clear = i18n::t("Clear"),
+        save = i18n::t("Save transcription"),
+        tools_label = i18n::t("Tools:"),
+        text_attributes = html::text_attributes(transcription),
+        rows = (transcription.chars().count() / 80 + 1).clamp(3, 20),
+        undo = if annotation.revisions.is_empty() {
+            String::new()
+        } else {
+            format!(
+                r#"<button formaction="/undo/{}/{}" class="px-3 py-1 bg-gray-200 dark:bg-gray-700 rounded-md">{}</button>"#,
+                encoded,
+                row,
+                i18n::t("Undo last edit")
+            )
+        },
+        history = revision_history(&filename, row, &audio.transcription, &annotation),
+        comments = row_comments(&filename, row, &annotation),
+        raw_bytes = raw_bytes_section(
+            &filename,
+            row,
+            &audio.path,
+            dump.bytes.unwrap_or(HEX_DUMP_BYTES)
+        ),
+        hypotheses = state
+            .transcriber
+            .as_ref()
+            .map(|transcriber| asr_hypotheses(
+                transcriber,
+                &filename,
+                row,
+                transcription,
+                &annotation
+            ))
+            .unwrap_or_default(),
+        properties = properties_html,
+        segments = if audio.segments.is_empty() {
+            String::new()
+        } else {
+            format!(
+                r#"<h2 class="text-xl font-semibold mb-2">{}</h2>{}{}"#,
+                i18n::t("Segments"),
+                segments::timeline(
+                    &filename,
+                    &index,
+                    audio.duration,
+                    &audio.segments,
+                    "h-4 w-full mb-2"
+                ),
+                segments::table(&filename, &index, &audio.segments)
+            )
+        },
+        commands = state
+            .commands
+            .as_ref()
+            .map(|runner| row_commands(runner, &filename, &index, &audio.path))
+            .unwrap_or_default(),
+        augmentation = augmentation_form(&state, &filename, &index),
+        shortcuts = shortcut_overlay(&state),
+    );
+
+    Ok(Html(html::render_page(
+        &format!("{} #{} - Parquet Viewer", filename, row),
+        &format!(
+            "{}{}{}{}{}{}",
+            state.playback.script(),
+            ROW_HEAD_SCRIPTS,
+            SEGMENT_SCRIPT,
+            HISTORY_SCRIPT,
+            LIVE_SCRIPT,
+            SHORTCUT_SCRIPT
+        ),
+        &content,
+    )))
+}
+
+/// Renders the captured output of a row command.
+fn render_command_output(output: &CommandOutput) -> String {
+    let status = match output.status {
+        Some(0) => r#"<span class="text-green-700 dark:text-green-400">exit 0</span>"#.to_string(),
+        Some(code) => format!(
+            r#"<span class="text-red-700 dark:text-red-400">exit {}</span>"#,
+            code
+        ),
+        None => r#"<span class="text-red-700 dark:text-red-400">killed</span>"#.to_string(),
+    };
+    let mut out = format!(
+        r#"<div class="text-sm mb-2"><code>{}</code> &middot; {}</div><pre class="p-3 rounded bg-gray-50 dark:bg-gray-700 text-sm overflow-x-auto whitespace-pre-wrap">{}</pre>"#,
+        html::escape(&output.command_line),
+        status,
+        html::escape(&output.stdout)
+    );
+    if !output.stderr.trim().is_empty() {
+        out.push_str(&format!(
+            r#"<pre class="mt-2 p-3 rounded bg-red-50 dark:bg-red-900/40 text-sm overflow-x-auto whitespace-pre-wrap">{}</pre>"#,
+            html::escape(&output.stderr)
+        ));
+    }
+    out
+}
+
+/// Command buttons of the row detail page, with the cached output of earlier runs.
+fn row_commands(runner: &CommandRunner, filename: &str, index: &str, wav_path: &Path) -> String {
+    let buttons = runner
+        .commands()
+        .iter()
+        .map(|command| {
+            format!(
+                r#"<form method="post" action="/run/{}/{}/{}"><button title="{}" class="px-3 py-1 bg-gray-200 dark:bg-gray-700 rounded-md text-sm">{}</button></form>"#,
+                html::url_encode(filename),
+                html::url_encode(index),
+                command.name,
+                html::escape(&command.template),
+                command.name
+            )
+        })
+        .collect::<String>();
+    let cached = runner
+        .commands()
+        .iter()
+        .filter_map(|command| {
+            runner.cached(&command.name, wav_path).map(|output| {
+                format!(
+                    r#"<details class="mt-2"><summary class="cursor-pointer font-semibold">{}</summary>{}</details>"#,
+                    command.name,
+                    render_command_output(&output)
+                )
+            })
+        })
+        .collect::<String>();
+    format!(
+        r#"<div class="mt-4"><div class="flex flex-wrap items-center gap-2 text-sm"><span class="font-semibold">{}</span>{}</div>{}</div>"#,
+        i18n::t("Commands:"),
+        buttons,
+        cached
+    )
+}
+
+/// Whether a request was sent by one of the viewer's own pages. Browsers name the site
+/// a form was posted from in `Sec-Fetch-Site` or `Origin`; requests with neither come
+/// from scripts, which cannot be made to post on a visitor's behalf.
+fn same_origin(headers: &http::HeaderMap) -> bool {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    if let Some(site) = header("sec-fetch-site") {
+        return site == "same-origin" || site == "none";
+    }
+    match (header("origin"), header("host")) {
+        (Some(origin), Some(host)) => origin
+            .split_once("://")
+            .is_some_and(|(_, authority)| authority == host),
+        (Some(_), None) => false,
+        (None, _) => true,
+    }
+}
+
+/// Runs a configured command on a row and shows its output.
+///
+/// Commands run on the server, so cross-site posts are refused even without a login.
+async fn run_command(
+    State(state): State<AppState>,
+    AxumPath((filename, index, name)): AxumPath<(String, String, String)>,
+    headers: http::HeaderMap,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    if !same_origin(&headers) {
+        return Err((
+            http::StatusCode::FORBIDDEN,
+            "Cross-site command runs are not allowed".to_string(),
+        ));
+    }
+    let runner = state.commands.as_ref().ok_or((
+        http::StatusCode::NOT_FOUND,
+        "No commands are configured".to_string(),
+    ))?;
+    let audio_path = extracted_audio_path(&state, &filename, &index)
+        .ok_or((http::StatusCode::NOT_FOUND, "Audio not found".to_string()))?;
+
+    let output = runner
+        .run(&name, &audio_path, &filename, &index)
+        .await
+        .map_err(|err| (http::StatusCode::BAD_REQUEST, format!("{:#}", err)))?;
+
+    let content = format!(
+        r#"
+    <div class="max-w-6xl mx-auto bg-white dark:bg-gray-800 shadow-md rounded-lg p-6 relative">
+        {}
+        <h1 class="text-2xl font-bold mb-4">{} &middot; {} #{}</h1>
+        {}
+        <form method="post" class="mt-4"><button class="px-3 py-1 bg-gray-200 dark:bg-gray-700 rounded-md text-sm">Run again</button></form>
+    </div>"#,
+        html::page_header(
+            &format!(
+                "/row/{}/{}",
+                html::url_encode(&filename),
+                html::url_encode(&index)
+            ),
+            &html::escape(&i18n::tf(
+                "Back to {name}",
+                &[("name", &format!("{} #{}", filename, index))]
+            ))
+        ),
+        html::escape(&name),
+        html::escape(&filename),
+        html::escape(&index),
+        render_command_output(&output)
+    );
+    Ok(Html(html::render_page(
+        &format!("{} - {} #{}", name, filename, index),
+        "",
+        &content,
+    )))
+}
+
+/// ASR section of the row detail page: a model picker and every stored hypothesis,
+/// newest first, diffed against the current transcription.
+fn asr_hypotheses(
+    transcriber: &asr::Transcriber,
+    filename: &str,
+    row: usize,
+    transcription: &str,
+    annotation: &RowAnnotation,
+) -> String {
+    let filename = html::url_encode(filename);
+    let options = transcriber
+        .models()
+        .iter()
+        .map(|model| {
+            format!(
+                r#"<option value="{0}" title="{1}">{0}</option>"#,
+                model.name,
+                html::escape(&model.url)
+            )
+        })
+        .collect::<String>();
+    let items = annotation
+        .hypotheses
+        .iter()
+        .rev()
+        .map(|hypothesis| {
+            format!(
+                r#"<li class="py-2 border-b dark:border-gray-700"><div class="flex items-center gap-2 text-xs text-gray-500 dark:text-gray-400"><span class="font-semibold">{}</span><span>{} UTC</span><form method="post" action="/edit/{}/{}"><input type="hidden" name="text" value="{}"><button class="px-2 py-0.5 bg-gray-200 dark:bg-gray-700 rounded-md text-xs">{}</button></form></div><div class="mt-1">{}</div></li>"#,
+                html::escape(&hypothesis.model),
+                annotations::format_timestamp(hypothesis.timestamp),
+                filename,
+                row,
+                html::escape(&hypothesis.text),
+                i18n::t("Use"),
+                render_diff(transcription, &hypothesis.text)
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"<div class="mt-6"><h2 class="text-xl font-semibold mb-2">{}</h2><form method="post" action="/asr/{}/{}" class="flex items-center gap-2 text-sm"><select name="model" aria-label="{}" class="px-2 py-1 rounded-md bg-gray-50 dark:bg-gray-700">{}</select><button class="px-3 py-1 bg-blue-500 text-white rounded-md">{}</button></form><ol class="text-sm">{}</ol></div>"#,
+        i18n::t("ASR hypotheses"),
+        filename,
+        row,
+        i18n::t("Model"),
+        options,
+        i18n::t("Transcribe"),
+        items
+    )
+}
+
+/// Form body of an ASR request.
+#[derive(Deserialize, Debug)]
+struct TranscribeForm {
+    model: String,
+}
+
+/// Re-transcribes a row with an ASR model and stores the hypothesis next to the
+/// earlier ones.
+async fn transcribe_row(
+    State(state): State<AppState>,
+    AxumPath((filename, row)): AxumPath<(String, usize)>,
+    Form(form): Form<TranscribeForm>,
+) -> Result<response::Redirect, (http::StatusCode, String)> {
+    let transcriber = state.transcriber.as_ref().ok_or((
+        http::StatusCode::NOT_FOUND,
+        "ASR is not configured".to_string(),
+    ))?;
+    if !transcriber
+        .models()
+        .iter()
+        .any(|model| model.name == form.model)
+    {
+        return Err((
+            http::StatusCode::BAD_REQUEST,
+            format!("Unknown ASR model `{}`", form.model),
+        ));
+    }
+    let audio_path = extracted_audio_path(&state, &filename, &row.to_string())
+        .ok_or((http::StatusCode::NOT_FOUND, "Audio not found".to_string()))?;
+
+    let text = transcriber
+        .transcribe(&form.model, &audio_path)
+        .await
+        .map_err(|err| (http::StatusCode::BAD_GATEWAY, format!("{:#}", err)))?;
+    let redirect = format!("/row/{}/{}", html::url_encode(&filename), row);
+    blocking(move || {
+        state
+            .annotations
+            .update(&filename, [row], |annotation| {
+                annotation.add_hypothesis(&form.model, text.clone())
+            })
+            .map_err(|err| {
+                (
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("{:#}", err),
+                )
+            })
+    })
+    .await?;
+    Ok(response::Redirect::to(&redirect))
+}
+
+/// Form body of a row comment.
+#[derive(Deserialize, Debug)]
+struct CommentForm {
+    text: String,
+    /// Comment starting the thread to reply to; a new thread when absent.
+    #[serde(default)]
+    reply_to: Option<usize>,
+}
+
+/// Adds a comment to the discussion of a row and returns to it.
+async fn comment_row(
+    State(state): State<AppState>,
+    AxumPath((filename, row)): AxumPath<(String, usize)>,
+    Form(form): Form<CommentForm>,
+) -> Result<response::Redirect, (http::StatusCode, String)> {
+    if !is_valid_parquet(&state, &filename) {
+        return Err((http::StatusCode::NOT_FOUND, "File not found".to_string()));
+    }
+    if form.text.trim().is_empty() {
+        return Err((
+            http::StatusCode::BAD_REQUEST,
+            "Comment is empty".to_string(),
+        ));
+    }
+    let user = acl::current_session().map(|session| session.user);
+    let redirect = format!("/row/{}/{}#comments", html::url_encode(&filename), row);
+    blocking(move || {
+        state
+            .annotations
+            .update(&filename, [row], |annotation| {
+                annotation.add_comment(&form.text, user.clone(), form.reply_to)
+            })
+            .map_err(|err| {
+                (
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("{:#}", err),
+                )
+            })
+    })
+    .await?;
+    Ok(response::Redirect::to(&redirect))
+}
+
+/// Comment threads of a row, each with a reply form, and a form to start a new one.
+fn row_comments(filename: &str, row: usize, annotation: &RowAnnotation) -> String {
+    let filename = html::url_encode(filename);
+    let comment = |comment: &annotations::Comment| {
+        format!(
+            r#"<div class="text-xs text-gray-500 dark:text-gray-400">{}{} UTC</div><div class="whitespace-pre-line">{}</div>"#,
+            comment
+                .user
+                .as_deref()
+                .map(|user| format!(
+                    r#"<span class="font-semibold">{}</span>, "#,
+                    html::escape(user)
+                ))
+                .unwrap_or_default(),
+            annotations::format_timestamp(comment.timestamp),
+            html::escape(&comment.text)
+        )
+    };
+    let threads = annotation
+        .comments
+        .iter()
+        .enumerate()
+        .filter(|(_, thread)| thread.reply_to.is_none())
+        .map(|(index, thread)| {
+            let replies = annotation
+                .comments
+                .iter()
+                .filter(|reply| reply.reply_to == Some(index))
+                .map(|reply| {
+                    format!(
+                        r#"<li class="mt-2 pl-3 border-l-2 border-gray-300 dark:border-gray-600">{}</li>"#,
+                        comment(reply)
+                    )
+                })
+                .collect::<String>();
+            format!(
+                r#"<li class="py-2 border-b dark:border-gray-700">{}<ul class="ml-4">{}</ul><details class="ml-4 mt-1"><summary class="cursor-pointer text-xs text-blue-600 dark:text-blue-400">{}</summary><form method="post" action="/comment/{}/{}" class="mt-1"><input type="hidden" name="reply_to" value="{}"><textarea name="text" rows="2" required aria-label="{}" class="w-full p-2 rounded bg-gray-50 dark:bg-gray-700"></textarea><button class="mt-1 px-3 py-1 bg-blue-500 text-white rounded-md">{}</button></form></details></li>"#,
+                comment(thread),
+                replies,
+                i18n::t("Reply"),
+                filename,
+                row,
+                index,
+                i18n::t("Reply"),
+                i18n::t("Reply")
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"<div id="comments" class="mt-6"><h2 class="text-xl font-semibold mb-2">{}</h2><ol class="text-sm">{}</ol><form method="post" action="/comment/{}/{}" class="mt-2 text-sm"><textarea id="new-comment" name="text" rows="2" required aria-label="{}" class="w-full p-2 rounded bg-gray-50 dark:bg-gray-700"></textarea><button class="mt-1 px-3 py-1 bg-blue-500 text-white rounded-md">{}</button></form></div>"#,
+        i18n::t("Comments"),
+        threads,
+        filename,
+        row,
+        i18n::t("Comment"),
+        i18n::t("Start a thread")
+    )
+}
+
+/// Dialog listing the row page keyboard shortcuts, where each key can be rebound for
+/// the browser by pressing the new one.
+fn shortcut_overlay(state: &AppState) -> String {
+    let bindings = shortcuts::resolve(&state.key_bindings, &BTreeMap::new())
+        .into_iter()
+        .zip(shortcuts::resolve(
+            &state.key_bindings,
+            &preferences::current().key_bindings,
+        ));
+    let rows = bindings
+        .map(|((action, default, label), (_, key, _))| {
+            format!(
+                r#"<tr class="border-b dark:border-gray-700"><td class="py-1 pr-4">{}</td><td class="py-1"><input data-bind="{}" data-default="{}" value="{}" readonly aria-label="{}" class="w-32 font-mono text-center bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md p-1 cursor-pointer"></td><td class="py-1 pl-2"><button type="button" onclick="resetShortcut('{}')" class="text-xs text-blue-600 dark:text-blue-400 hover:underline">{}</button></td></tr>"#,
+                i18n::t(label),
+                action,
+                html::escape(&default),
+                html::escape(&key),
+                i18n::t(label),
+                action,
+                i18n::t("Reset")
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"<div id="shortcuts" class="hidden fixed inset-0 z-50 flex items-center justify-center bg-black/50" onclick="if (event.target === this) toggleShortcuts()"><div role="dialog" aria-modal="true" aria-labelledby="shortcuts-title" class="bg-white dark:bg-gray-800 rounded-lg shadow-lg p-6 max-h-full overflow-y-auto"><h2 id="shortcuts-title" class="text-xl font-semibold mb-2">{}</h2><p class="mb-2 text-sm text-gray-500 dark:text-gray-400">{}</p><table class="text-sm"><thead><tr class="text-left"><th class="pr-4">{}</th><th>{}</th><th></th></tr></thead><tbody>{}</tbody></table><p id="shortcut-error" class="mt-2 text-sm text-red-700 dark:text-red-400" role="alert"></p><button type="button" onclick="toggleShortcuts()" class="mt-2 px-3 py-1 bg-gray-200 dark:bg-gray-700 rounded-md text-sm">{}</button></div></div>"#,
+        i18n::t("Keyboard shortcuts"),
+        i18n::t(
+            "Click a key and press the new one to rebind it in this browser; Escape cancels. Keys without Ctrl, Alt or Meta are ignored while typing."
+        ),
+        i18n::t("Action"),
+        i18n::t("Key"),
+        rows,
+        i18n::t("Close")
+    )
+}
+
+/// Replaces transcriptions by their latest edited revision.
+fn apply_corrections(files: &mut [Audio], annotations: &BTreeMap<usize, RowAnnotation>) {
+    for (&row, annotation) in annotations {
+        if let (Some(audio), Some(text)) = (files.get_mut(row), annotation.transcription()) {
+            audio.transcription = text.to_string();
+        }
+    }
+}
+
+/// Renders a word-level diff with removed words struck through and added words highlighted.
+fn render_diff(old: &str, new: &str) -> String {
+    text::diff_words(old, new)
+        .iter()
+        .map(|segment| match segment {
+            text::Diff::Same(word) => html::escape(word),
+            text::Diff::Removed(word) => format!(
+                r#"<del class="bg-red-100 dark:bg-red-900/60">{}</del>"#,
+                html::escape(word)
+            ),
+            text::Diff::Added(word) => format!(
+                r#"<ins class="no-underline bg-green-100 dark:bg-green-900/60">{}</ins>"#,
+                html::escape(word)
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Edit history of a row, newest first, each revision diffed against the one before it.
+fn revision_history(
+    filename: &str,
+    row: usize,
+    original: &str,
+    annotation: &RowAnnotation,
+) -> String {
+    if annotation.revisions.is_empty() {
+        return String::new();
+    }
+    let filename = html::url_encode(filename);
+    let restore_button = |query: String| {
+        format!(
+            r#"<form method="post" action="/restore/{}/{}{}"><button class="px-2 py-0.5 bg-gray-200 dark:bg-gray-700 rounded-md text-xs">{}</button></form>"#,
+            filename,
+            row,
+            query,
+            i18n::t("Restore")
+        )
+    };
+
+    let latest = annotation.revisions.len() - 1;
+    let mut items = Vec::new();
+    for (i, revision) in annotation.revisions.iter().enumerate().rev() {
+        let previous = match i {
+            0 => original,
+            _ => &annotation.revisions[i - 1].text,
+        };
+        items.push(format!(
+            r#"<li class="py-2 border-b dark:border-gray-700"><div class="flex items-center gap-2 text-xs text-gray-500 dark:text-gray-400"><span>{} &middot; {} UTC</span>{}</div><div class="mt-1">{}</div></li>"#,
+            i18n::tf("Revision {number}", &[("number", &(i + 1))]),
+            annotations::format_timestamp(revision.timestamp),
+            if i == latest {
+                format!(
+                    r#"<span class="font-semibold">{}</span>"#,
+                    i18n::t("current")
+                )
+            } else {
+                restore_button(format!("?revision={}", i))
+            },
+            render_diff(previous, &revision.text)
+        ));
+    }
+    items.push(format!(
+        r#"<li class="py-2"><div class="flex items-center gap-2 text-xs text-gray-500 dark:text-gray-400"><span>{}</span>{}</div><div class="mt-1">{}</div></li>"#,
+        i18n::t("Original"),
+        restore_button(String::new()),
+        html::escape(original)
+    ));
+
+    format!(
+        r#"<div class="mt-6"><h2 class="text-xl font-semibold mb-2">{}</h2><ol class="text-sm">{}</ol></div>"#,
+        i18n::t("History"),
+        items.concat()
+    )
+}
+
+/// Form body of a transcription edit.
+#[derive(Deserialize, Debug)]
+struct EditForm {
+    text: String,
+    /// Version of the transcription the edit started from (see
+    /// [`RowAnnotation::version`]); a newer version means someone else saved meanwhile.
+    base: Option<usize>,
+}
+
+/// Revision to restore; the original transcription when absent.
+#[derive(Deserialize, Debug)]
+struct RestoreQuery {
+    revision: Option<usize>,
+}
+
+/// Original transcription of a row, from the Parquet file.
+fn original_transcription(
+    state: &AppState,
+    filename: &str,
+    row: usize,
+) -> Result<String, (http::StatusCode, String)> {
+    if !is_valid_parquet(state, filename) {
+        return Err((http::StatusCode::NOT_FOUND, "File not found".to_string()));
+    }
+    extract_parquet_file(&state.tmp_folder, &state.folder, filename)
+        .into_iter()
+        .nth(row)
+        .map(|audio| audio.transcription)
+        .ok_or((http::StatusCode::NOT_FOUND, "Row not found".to_string()))
+}
+
+/// Records a new revision of a row's transcription, with the text `choose` picks from the
+/// original and the row's annotation; its rejection is the response otherwise.
+fn revise_row<E: IntoResponse>(
+    state: &AppState,
+    filename: &str,
+    row: usize,
+    choose: impl FnOnce(&str, &RowAnnotation) -> Result<String, E>,
+) -> response::Response {
+    let original = match original_transcription(state, filename, row) {
+        Ok(original) => original,
+        Err(err) => return err.into_response(),
+    };
+    // The text is chosen under the store lock, from the row as the revision is saved on.
+    let revised = state.annotations.try_update(filename, row, |annotation| {
+        let text = choose(&original, annotation)?;
+        let changed = annotation.transcription().unwrap_or(&original) != text;
+        annotation.revise(&original, text.clone());
+        Ok::<_, E>((text, changed))
+    });
+    let (text, changed) = match revised {
+        Ok(Ok(revised)) => revised,
+        Ok(Err(rejection)) => return rejection.into_response(),
+        Err(err) => {
+            return (
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("{:#}", err),
+            )
+                .into_response();
+        }
+    };
+    if let Some(webhooks) = state.webhooks.as_ref().filter(|_| changed) {
+        let user = acl::current_session().map(|session| session.user);
+        let mut notification =
+            webhooks::Notification::new(webhooks::Event::Edited, filename, vec![row], user);
+        notification.transcription = Some(text);
+        webhooks.notify(notification);
+    }
+    response::Redirect::to(&format!("/row/{}/{}", html::url_encode(filename), row)).into_response()
+}
+
+/// Saves an edited transcription as a new revision.
+///
+/// Edits carrying the version they started from are only saved while the row is still
+/// at that version; otherwise the reviewer gets a merge page with both texts.
+async fn edit_row(
+    State(state): State<AppState>,
+    AxumPath((filename, row)): AxumPath<(String, usize)>,
+    Form(form): Form<EditForm>,
+) -> response::Response {
+    let text = form.text.trim().replace("\r\n", "\n");
+    blocking(move || {
+        revise_row(&state, &filename, row, |original, annotation| {
+            if form.base.is_some_and(|base| base != annotation.version()) {
+                return Err((
+                    http::StatusCode::CONFLICT,
+                    render_conflict(&filename, row, original, annotation, &text),
+                ));
+            }
+            Ok(text)
+        })
+    })
+    .await
+}
+
+/// Merge page for an edit that conflicts with one saved meanwhile: the saved text, the
+/// reviewer's text, the words that differ, and an editor holding the reviewer's text to
+/// merge and save on top of the saved version.
+fn render_conflict(
+    filename: &str,
+    row: usize,
+    original: &str,
+    annotation: &RowAnnotation,
+    yours: &str,
+) -> Html<String> {
+    let theirs = annotation.transcription().unwrap_or(original);
+    let saved_at = annotation
+        .revisions
+        .last()
+        .map(|revision| {
+            format!(
+                " &middot; {} UTC",
+                annotations::format_timestamp(revision.timestamp)
+            )
+        })
+        .unwrap_or_default();
+    let content = format!(
+        r#"
+    <div class="max-w-6xl mx-auto bg-white dark:bg-gray-800 shadow-md rounded-lg p-6 relative">
+        {header}
+        <h1 class="text-2xl font-bold mb-2">{title}</h1>
+        <p class="mb-4 text-sm">{explanation}</p>
+        <div class="grid md:grid-cols-2 gap-4 mb-4">
+            <div><h2 class="font-semibold mb-1">{theirs_label}<span class="font-normal text-xs text-gray-500 dark:text-gray-400">{saved_at}</span></h2><div class="p-3 rounded bg-gray-50 dark:bg-gray-700 whitespace-pre-wrap"{theirs_attributes}>{theirs}</div></div>
+            <div><h2 class="font-semibold mb-1">{yours_label}</h2><div class="p-3 rounded bg-gray-50 dark:bg-gray-700 whitespace-pre-wrap"{yours_attributes}>{yours}</div></div>
+        </div>
+        <h2 class="font-semibold mb-1">{diff_label}</h2>
+        <div class="mb-4 p-3 rounded bg-gray-50 dark:bg-gray-700">{diff}</div>
+        <form method="post" action="/edit/{filename}/{row}">
+            <input type="hidden" name="base" value="{version}">
+            <textarea name="text" rows="{rows}" aria-label="{merged_label}" class="w-full p-4 rounded bg-gray-50 dark:bg-gray-700 text-lg"{yours_attributes}>{yours}</textarea>
+            <div class="mt-2 flex items-center gap-4 text-sm">
+                <button class="px-3 py-1 bg-blue-500 text-white rounded-md">{save}</button>
+                <a href="/row/{filename}/{row}" class="text-blue-600 dark:text-blue-400 hover:underline">{discard}</a>
+            </div>
+        </form>
+    </div>"#,
+        header = html::page_header(
+            &format!("/row/{}/{}", html::url_encode(filename), row),
+            &i18n::tf(
+                "Back to {name}",
+                &[("name", &format!("{} #{}", html::escape(filename), row))]
+            )
+        ),
+        filename = html::url_encode(filename),
+        title = i18n::t("Edit conflict"),
+        explanation = i18n::t(
+            "Another reviewer saved this row after you opened it. Compare the texts, merge them in the editor below and save again."
+        ),
+        theirs_label = i18n::t("Saved by another reviewer"),
+        yours_label = i18n::t("Your edit"),
+        diff_label = i18n::t("Changes from the saved text to yours"),
+        merged_label = i18n::t("Merged transcription"),
+        save = i18n::t("Save merged transcription"),
+        discard = i18n::t("Discard my edit"),
+        theirs = html::escape(theirs),
+        theirs_attributes = html::text_attributes(theirs),
+        yours = html::escape(yours),
+        yours_attributes = html::text_attributes(yours),
+        diff = render_diff(theirs, yours),
+        version = annotation.version(),
+        rows = (yours.chars().count() / 80 + 1).clamp(3, 20),
+    );
+    Html(html::render_page(
+        &format!("{} - {} #{}", i18n::t("Edit conflict"), filename, row),
+        "",
+        &content,
+    ))
+}
+
+/// Rejection of an undo or restore naming a revision the row does not have.
+fn no_such_revision() -> (http::StatusCode, String) {
+    (
+        http::StatusCode::BAD_REQUEST,
+        "No such revision".to_string(),
+    )
+}
+
+/// Reverts the latest edit by recording the text it replaced as a new revision.
+async fn undo_row(
+    State(state): State<AppState>,
+    AxumPath((filename, row)): AxumPath<(String, usize)>,
+) -> response::Response {
+    blocking(move || {
+        revise_row(
+            &state,
+            &filename,
+            row,
+            |original, annotation| match annotation.revisions.len() {
+                0 => Err(no_such_revision()),
+                1 => Ok(original.to_string()),
+                n => Ok(annotation.revisions[n - 2].text.clone()),
+            },
+        )
+    })
+    .await
+}
+
+/// Restores an earlier revision, or the original transcription.
+async fn restore_row(
+    State(state): State<AppState>,
+    AxumPath((filename, row)): AxumPath<(String, usize)>,
+    Query(query): Query<RestoreQuery>,
+) -> response::Response {
+    blocking(move || {
+        revise_row(&state, &filename, row, |original, annotation| {
+            match query.revision {
+                Some(i) => annotation
+                    .revisions
+                    .get(i)
+                    .map(|r| r.text.clone())
+                    .ok_or_else(no_such_revision),
+                None => Ok(original.to_string()),
+            }
+        })
+    })
+    .await
+}
+
+/// Rendered pages served with an ETag, by path prefix (`/` only matches itself).
+const CONDITIONAL_PAGES: [&str; 20] = [
+    "/",
+    "/view/",
+    "/row/",
+    "/charset",
+    "/ngrams",
+    "/duplicates",
+    "/phonemes",
+    "/speakers",
+    "/map",
+    "/completeness",
+    "/schema",
+    "/wpm",
+    "/agreement",
+    "/encodings",
+    "/spelling",
+    "/expansions",
+    "/style",
+    "/splits",
+    "/search",
+    "/api/rows/",
+];
+
+/// Tags rendered pages with a weak ETag and answers a matching `If-None-Match` with
+/// `304 Not Modified` without rendering the page again.
+///
+/// Non-GET requests may change what pages show, so each one bumps a counter that is
+/// part of the ETag.
+async fn conditional_get(
+    State(state): State<AppState>,
+    request: Request,
+    next: middleware::Next,
+) -> response::Response {
+    if request.method() != http::Method::GET {
+        let response = next.run(request).await;
+        state.mutations.fetch_add(1, Ordering::Relaxed);
+        return response;
+    }
+    let path = request.uri().path();
+    let conditional = CONDITIONAL_PAGES
+        .iter()
+        .any(|page| path == *page || (page.len() > 1 && path.starts_with(page)));
+    if !conditional {
+        return next.run(request).await;
+    }
+
+    let etag = page_etag(&state, request.uri());
+    let not_modified = request
+        .headers()
+        .get(http::header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|tags| {
+            tags.split(',')
+                .any(|tag| tag.trim() == etag || tag.trim() == "*")
+        });
+    if not_modified {
+        return response::Response::builder()
+            .status(http::StatusCode::NOT_MODIFIED)
+            .header(http::header::ETAG, &etag)
+            .body(body::Body::empty())
+            .unwrap();
+    }
+
+    let mut response = next.run(request).await;
+    if response.status() == http::StatusCode::OK
+        && let Ok(value) = http::HeaderValue::from_str(&etag)
+    {
+        let headers = response.headers_mut();
+        headers.insert(http::header::ETAG, value);
+        // Behind a login pages differ per user, so shared caches must not keep them.
+        headers.insert(
+            http::header::CACHE_CONTROL,
+            http::HeaderValue::from_static(if state.oidc.is_some() {
+                "private, no-cache"
+            } else {
+                "no-cache"
+            }),
+        );
+    }
+    response
+}
+
+/// Weak ETag of a page: its URL, the logged-in user and groups, the modification times
+/// of the data files, and the annotation and mutation counters.
+fn page_etag(state: &AppState, uri: &http::Uri) -> String {
+    let mut hasher = DefaultHasher::new();
+    uri.hash(&mut hasher);
+    // The access control lists decide which files a page lists.
+    acl::current_session()
+        .map(|session| (session.user, session.groups))
+        .hash(&mut hasher);
+    state.annotations.version().hash(&mut hasher);
+    state.mutations.load(Ordering::Relaxed).hash(&mut hasher);
+    preferences::current().hash(&mut hasher);
+    // The file list shows sizes once the background scan has reached the files.
+    state.folder_index.scanned().hash(&mut hasher);
+    for file in list_parquet_files(&state.folder) {
+        let modified = fs::metadata(state.folder.join(&file)).and_then(|m| m.modified());
+        (file, modified.ok()).hash(&mut hasher);
+    }
+    format!("W/\"{:016x}\"", hasher.finish())
+}
+
+#[derive(Deserialize)]
+struct LoginParams {
+    #[serde(default)]
+    next: String,
+}
+
+/// Sends the browser to the OIDC provider.
+async fn login(
+    State(state): State<AppState>,
+    Query(params): Query<LoginParams>,
+) -> Result<response::Redirect, (http::StatusCode, String)> {
+    let oidc = state.oidc.as_ref().ok_or((
+        http::StatusCode::NOT_FOUND,
+        "Login is not enabled".to_string(),
+    ))?;
+    let url = oidc
+        .login_url(&params.next)
+        .map_err(|err| (http::StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    Ok(response::Redirect::to(&url))
+}
+
+#[derive(Deserialize)]
+struct CallbackParams {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// Completes a login and sets the session cookie.
+async fn auth_callback(
+    State(state): State<AppState>,
+    Query(params): Query<CallbackParams>,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    let oidc = state.oidc.as_ref().ok_or((
+        http::StatusCode::NOT_FOUND,
+        "Login is not enabled".to_string(),
+    ))?;
+    let (Some(code), Some(login_state)) = (params.code, params.state) else {
+        let reason = params
+            .error_description
+            .or(params.error)
+            .unwrap_or_else(|| "missing authorization code".to_string());
+        return Err((
+            http::StatusCode::UNAUTHORIZED,
+            format!("Login failed: {}", reason),
+        ));
+    };
+    let (session, next) = oidc
+        .complete(&code, &login_state)
+        .await
+        .map_err(|err| (http::StatusCode::FORBIDDEN, format!("{:#}", err)))?;
+    Ok((
+        [(http::header::SET_COOKIE, oidc.cookie(&session))],
+        response::Redirect::to(&next),
+    )
+        .into_response())
+}
+
+/// Ends the session and returns to the file list, which starts a new login.
+async fn logout(State(state): State<AppState>, headers: http::HeaderMap) -> response::Response {
+    let Some(oidc) = &state.oidc else {
+        return response::Redirect::to("/").into_response();
+    };
+    oidc.logout(
+        headers
+            .get(http::header::COOKIE)
+            .and_then(|value| value.to_str().ok()),
+    );
+    (
+        [(http::header::SET_COOKIE, oidc.cookie(""))],
+        response::Redirect::to("/"),
+    )
+        .into_response()
+}
+
+/// WebSocket pushing the annotation changes of a file to the pages showing it, so
+/// reviewers working on the same file see each other's edits.
+async fn live_updates(
+    State(state): State<AppState>,
+    AxumPath(filename): AxumPath<String>,
+    upgrade: WebSocketUpgrade,
+) -> response::Response {
+    upgrade.on_upgrade(move |socket| forward_changes(state, filename, socket))
+}
+
+/// Sends a JSON message per change of the file, with the badges, current transcription
+/// (null when unedited) and transcription version of each changed row.
+async fn forward_changes(state: AppState, filename: String, mut socket: WebSocket) {
+    let mut changes = state.annotations.subscribe();
+    loop {
+        tokio::select! {
+            change = changes.recv() => {
+                let change = match change {
+                    Ok(change) if change.file == filename => change,
+                    Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                };
+                let rows: Vec<_> = change
+                    .rows
+                    .iter()
+                    .map(|&row| {
+                        let annotation = state.annotations.get(&filename, row);
+                        serde_json::json!({
+                            "row": row,
+                            "badges": annotation_badges(&annotation),
+                            "transcription": annotation.transcription(),
+                            "version": annotation.version(),
+                        })
+                    })
+                    .collect();
+                let message = serde_json::json!({ "rows": rows }).to_string();
+                if socket.send(Message::Text(message.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => {
+                if !matches!(message, Some(Ok(_))) {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Makes the preferences from the signed cookie available while the request is handled.
+async fn with_preferences(
+    State(state): State<AppState>,
+    request: Request,
+    next: middleware::Next,
+) -> response::Response {
+    let preferences = state.preferences.read(
+        request
+            .headers()
+            .get(http::header::COOKIE)
+            .and_then(|value| value.to_str().ok()),
+    );
+    preferences::scope(preferences, next.run(request)).await
+}
+
+/// Changes to the preferences; fields left out keep their value.
+#[derive(Deserialize, Debug)]
+struct PreferencesForm {
+    /// `light`, `dark`, or empty to follow the system setting.
+    theme: Option<String>,
+    page_size: Option<usize>,
+    /// Column to hide from the file view.
+    hide: Option<String>,
+    /// Column to show again.
+    show: Option<String>,
+    /// Shortcut action to rebind to `key`.
+    bind: Option<String>,
+    /// New key of the `bind` action; empty restores the configured one.
+    key: Option<String>,
+}
+
+/// Stores preferences in the signed cookie; called by the theme toggle, the page size
+/// selector, the column menu and the keyboard shortcut overlay.
+async fn save_preferences(
+    State(state): State<AppState>,
+    Form(form): Form<PreferencesForm>,
+) -> response::Response {
+    let mut preferences = preferences::current();
+    if let Some(theme) = form.theme {
+        preferences.theme = match theme.as_str() {
+            "light" | "dark" => Some(theme),
+            "" => None,
+            _ => return (http::StatusCode::BAD_REQUEST, "Unknown theme").into_response(),
+        };
+    }
+    if let Some(size) = form.page_size {
+        if !preferences::PAGE_SIZES.contains(&size) {
+            return (http::StatusCode::BAD_REQUEST, "Unsupported page size").into_response();
+        }
+        preferences.page_size = Some(size);
+    }
+    if let Some(column) = form.hide {
+        preferences.hidden_columns.insert(column);
+    }
+    if let Some(column) = form.show {
+        preferences.hidden_columns.remove(&column);
+    }
+    if let Some(action) = form.bind {
+        if !shortcuts::ACTIONS
+            .iter()
+            .any(|(name, _, _)| *name == action)
+        {
+            return (http::StatusCode::BAD_REQUEST, "Unknown shortcut action").into_response();
+        }
+        match form.key.unwrap_or_default().as_str() {
+            "" => {
+                preferences.key_bindings.remove(&action);
+            }
+            key if !shortcuts::is_valid_key(key) => {
+                return (http::StatusCode::BAD_REQUEST, "Invalid key").into_response();
+            }
+            key => {
+                let taken = shortcuts::resolve(&state.key_bindings, &preferences.key_bindings)
+                    .into_iter()
+                    .find(|(other, bound, _)| *other != action && bound == key);
+                if let Some((_, _, label)) = taken {
+                    return (
+                        http::StatusCode::CONFLICT,
+                        i18n::tf(
+                            "{key} is already bound to {action}",
+                            &[
+                                ("key", &key.to_string()),
+                                ("action", &i18n::t(label).to_string()),
+                            ],
+                        ),
+                    )
+                        .into_response();
+                }
+                preferences.key_bindings.insert(action, key.to_string());
+            }
+        }
+    }
+    (
+        http::StatusCode::NO_CONTENT,
+        [(
+            http::header::SET_COOKIE,
+            state.preferences.cookie(&preferences),
+        )],
+    )
+        .into_response()
+}
+
+/// Routes that hand out the rows or original clips of a file rather than showing them.
+/// The concatenated player stays viewable: it plays transcoded clips like `/audio`.
+const EXPORT_ROUTES: [&str; 3] = ["/export/", "/download/", "/api/rows/"];
+
+/// Enforces the access control lists on the routes of a dataset file, found by their
+/// `filename` path parameter: reading needs the view permission, changes the annotate
+/// permission, and downloads, exports, the JSON API and stored clips the export
+/// permission. Corpus-wide pages only include the files the user may view.
+///
+/// Runs as a route layer, after routing, so every route with a `{filename}` is covered.
+async fn enforce_acl(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: middleware::Next,
+) -> response::Response {
+    let session = request.extensions().get::<auth::Session>().cloned();
+    if state.acl.is_some() {
+        let params = match request.extract_parts::<RawPathParams>().await {
+            Ok(params) => params,
+            Err(rejection) => return rejection.into_response(),
+        };
+        if let Some((_, file)) = params.iter().find(|(name, _)| *name == "filename") {
+            let route = request
+                .extensions()
+                .get::<MatchedPath>()
+                .map_or_else(String::new, |route| route.as_str().to_string());
+            // `raw=1` on /audio serves the stored bytes, like a download.
+            let raw = request
+                .extract_parts::<Query<DownloadQuery>>()
+                .await
+                .is_ok_and(|Query(query)| query.raw);
+            let permission = if EXPORT_ROUTES.iter().any(|prefix| route.starts_with(prefix)) || raw
+            {
+                acl::Permission::Export
+            } else if request.method() != http::Method::GET {
+                acl::Permission::Annotate
+            } else {
+                acl::Permission::View
+            };
+            let allowed = acl::sync_scope(session.clone(), || allows(&state, file, permission));
+            if !allowed {
+                return (
+                    http::StatusCode::FORBIDDEN,
+                    format!(
+                        "You may not {} {}",
+                        format!("{:?}", permission).to_lowercase(),
+                        file
+                    ),
+                )
+                    .into_response();
+            }
+        }
+    }
+    acl::scope(session, next.run(request)).await
+}
+
+/// Requires an OIDC session for everything but the login routes, when login is enabled.
+///
+/// Browsers are redirected to the provider; other clients get 401. The session travels
+/// on the response so the access log can name the user.
+async fn require_login(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: middleware::Next,
+) -> response::Response {
+    let Some(oidc) = &state.oidc else {
+        return next.run(request).await;
+    };
+    if request.uri().path().starts_with("/auth/") {
+        return next.run(request).await;
+    }
+    let session = oidc.session(
+        request
+            .headers()
+            .get(http::header::COOKIE)
+            .and_then(|value| value.to_str().ok()),
+    );
+    if let Some(session) = session {
+        request.extensions_mut().insert(session.clone());
+        let mut response = next.run(request).await;
+        response.extensions_mut().insert(session);
+        return response;
+    }
+
+    let wants_html = request
+        .headers()
+        .get(http::header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+    if request.method() == http::Method::GET && wants_html {
+        let target = request
+            .uri()
+            .path_and_query()
+            .map_or("/", |target| target.as_str());
+        response::Redirect::to(&format!("/auth/login?next={}", html::url_encode(target)))
+            .into_response()
+    } else {
+        (http::StatusCode::UNAUTHORIZED, "Login required").into_response()
+    }
+}
+
+/// Error bodies longer than this are not shown on error pages.
+const MAX_ERROR_MESSAGE: usize = 4096;
+
+/// Replaces plain error responses with a themed page for browsers.
+///
+/// Only requests accepting HTML are affected, so API clients and in-page `fetch` calls
+/// still receive the bare message.
+async fn error_page(request: Request, next: middleware::Next) -> response::Response {
+    let path = request.uri().path().to_string();
+    let wants_html = request
+        .headers()
+        .get(http::header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+    let response = next.run(request).await;
+    let status = response.status();
+    let is_html = response
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("text/html"));
+    if !wants_html || is_html || !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let message = body::to_bytes(body, MAX_ERROR_MESSAGE)
+        .await
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+        .unwrap_or_default();
+    let content = templates::render(
+        "error.html",
+        context! {
+            header => templates::safe(html::page_header("/", i18n::t("Back to list"))),
+            status => status.as_u16(),
+            reason => status.canonical_reason(),
+            message => message.trim(),
+            path => path,
+        },
+    );
+    let title = match status.canonical_reason() {
+        Some(reason) => format!("{} {}", status.as_u16(), reason),
+        None => status.as_u16().to_string(),
+    };
+    parts.headers.remove(http::header::CONTENT_LENGTH);
+    parts.headers.insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("text/html; charset=utf-8"),
+    );
+    response::Response::from_parts(
+        parts,
+        body::Body::from(html::render_page(&title, "", &content)),
+    )
+}
+
+/// Streams a file from disk with the given headers.
+async fn stream_file(
+    path: &Path,
+    headers: &[(http::HeaderName, String)],
+) -> Result<response::Response, http::StatusCode> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|_| http::StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let stream = io::ReaderStream::new(file);
+    let body = body::Body::from_stream(stream);
+
+    let mut builder = response::Response::builder();
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+    Ok(builder.body(body).unwrap())
+}
+
+/// Resolves the extracted WAV file for a row, if it exists.
+///
+/// Rows of zip datasets are extracted on first access.
+fn extracted_audio_path(state: &AppState, filename: &str, index: &str) -> Option<PathBuf> {
+    let audio_path = state
+        .tmp_folder
+        .join(filename)
+        .join(format!("{}.wav", index));
+    if !audio_path.exists()
+        && is_valid_parquet(state, filename)
+        && let Ok(row) = index.parse()
+    {
+        extract_archived_rows(state, filename, [row]);
+    }
+
+    (audio_path.exists() && audio_path.is_file()).then_some(audio_path)
+}
+
+/// Decodes a WAV file, keeps the `start..end` span (in seconds) and, if asked, rescales
+/// it to the normalization target level.
+fn processed_wav(
+    path: &Path,
+    normalize: bool,
+    start: Option<f64>,
+    end: Option<f64>,
+) -> anyhow::Result<Vec<u8>> {
+    let mut wav = wav::Wav::decode(&fs::read(path)?)?;
+    if start.is_some() || end.is_some() {
+        let channels = wav.channels as usize;
+        let frames = wav.samples.len() / channels;
+        let frame =
+            |seconds: f64| ((seconds.max(0.0) * wav.sample_rate as f64) as usize).min(frames);
+        let first = start.map_or(0, frame);
+        let last = end.map_or(frames, frame).max(first);
+        wav.samples = wav.samples[first * channels..last * channels].to_vec();
+    }
+    if normalize {
+        let gain = dsp::normalization_gain(&wav.samples);
+        dsp::apply_gain(&mut wav.samples, gain);
+    }
+    wav.encode_pcm16()
+}
+
+fn default_concat_limit() -> usize {
+    20
+}
+
+fn default_concat_gap() -> f64 {
+    0.5
+}
+
+/// Query parameters of the concatenated preview, next to the filter and view options.
+#[derive(Deserialize, Debug)]
+struct ConcatQuery {
+    /// Number of clips, at most [`MAX_CONCAT_CLIPS`].
+    #[serde(default = "default_concat_limit")]
+    limit: usize,
+    /// Seconds of silence between clips.
+    #[serde(default = "default_concat_gap")]
+    gap: f64,
+}
+
+const MAX_CONCAT_CLIPS: usize = 200;
+/// Clips are appended until the preview reaches this length.
+const MAX_CONCAT_SECONDS: f64 = 1800.0;
+
+/// Concatenates the first clips matching the filter, in the view order, into one WAV
+/// file with short silences between them, for listening to a sample in one go.
+async fn serve_concat(
+    State(state): State<AppState>,
+    AxumPath(filename): AxumPath<String>,
+    Query(pagination): Query<Pagination>,
+    Query(filter): Query<RowFilter>,
+    Query(query): Query<ConcatQuery>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    if !is_valid_parquet(&state, &filename) {
+        return Err((http::StatusCode::NOT_FOUND, "File not found".to_string()));
+    }
+    let filter = filter.with_facets(&params);
+    let bytes = blocking(move || {
+        let annotations = state.annotations.file(&filename);
+        let mut files = extract_parquet_file(&state.tmp_folder, &state.folder, &filename);
+        apply_corrections(&mut files, &annotations);
+        let token_counts = cached_token_counts(&state, &filename, &files);
+        let filtered = filter_rows(
+            &state,
+            &filename,
+            &files,
+            &annotations,
+            &filter,
+            pagination.sort.as_deref(),
+            token_counts.as_deref().map(Vec::as_slice),
+        );
+        if let Some(err) = &filtered.error {
+            return Err((
+                http::StatusCode::BAD_REQUEST,
+                format!("Invalid filter: {}", err),
+            ));
+        }
+        let rows: Vec<usize> = filtered
+            .order
+            .iter()
+            .copied()
+            .take(query.limit.min(MAX_CONCAT_CLIPS))
+            .collect();
+        extract_archived_rows(&state, &filename, rows.iter().copied());
+
+        let mut out = wav::Wav {
+            sample_rate: 0,
+            channels: 1,
+            samples: Vec::new(),
+        };
+        let mut played = Vec::new();
+        for row in rows {
+            let Ok(source) = fs::read(&files[row].path)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| wav::Wav::decode(&bytes))
+            else {
+                continue;
+            };
+            if out.sample_rate == 0 {
+                out.sample_rate = source.sample_rate;
+            }
+            let mut samples = source.mono();
+            if source.sample_rate != out.sample_rate {
+                samples = augment::resample_linear(
+                    &samples,
+                    source.sample_rate as f32 / out.sample_rate as f32,
+                );
+            }
+            if pagination.normalize {
+                let gain = dsp::normalization_gain(&samples);
+                dsp::apply_gain(&mut samples, gain);
+            }
+            if !out.samples.is_empty() {
+                let gap = (query.gap.clamp(0.0, 10.0) * out.sample_rate as f64) as usize;
+                out.samples.extend(std::iter::repeat_n(0.0, gap));
+            }
+            out.samples.extend(samples);
+            played.push(row);
+            if out.samples.len() as f64 >= MAX_CONCAT_SECONDS * out.sample_rate as f64 {
+                break;
+            }
+        }
+        if out.samples.is_empty() {
+            return Err((
+                http::StatusCode::NOT_FOUND,
+                "No decodable clips match the filter".to_string(),
+            ));
+        }
+        audit_downloads(&state, &filename, &played, audit::Kind::Concat)
+            .map_err(|status| (status, "Failed to record the download".to_string()))?;
+        out.encode_pcm16().map_err(|err| {
+            (
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("{:#}", err),
+            )
+        })
+    })
+    .await?;
+
+    Ok(response::Response::builder()
+        .header(http::header::CONTENT_TYPE, "audio/wav")
+        .body(body::Body::from(bytes))
+        .unwrap())
+}
+
+/// Player for the concatenated preview of the clips matching the current filter;
+/// `extra_query` is HTML-escaped.
+fn concat_player(filename: &str, extra_query: &str, matching: usize) -> String {
+    if matching == 0 {
+        return String::new();
+    }
+    let limit = default_concat_limit().min(matching);
+    format!(
+        r#"<div class="mb-4 flex flex-wrap items-center gap-2 text-sm"><span id="concat-label">{}</span><audio controls="" preload="none" src="/concat/{}?limit={}{}" aria-labelledby="concat-label" class="h-8"></audio></div>"#,
+        i18n::tf(
+            "Listen to the first {count} matching clips in a row:",
+            &[("count", &limit)]
+        ),
+        html::url_encode(filename),
+        limit,
+        extra_query
+    )
+}
+
+/// Serves audio files from the temporary folder.
+///
+/// With `?normalize=1` the clip is decoded and re-encoded with a gain that
+/// brings it to a common loudness level; `?start=&end=` (seconds) trim it, e.g. to one
+/// transcript segment of a long recording. With `--preview`, clients whose `Accept`
+/// header prefers WebM or Ogg audio get the untouched clip as Opus instead of WAV.
+async fn serve_audio(
+    State(state): State<AppState>,
+    AxumPath((filename, index)): AxumPath<(String, String)>,
+    Query(query): Query<AudioQuery>,
+    headers: http::HeaderMap,
+) -> Result<response::Response, http::StatusCode> {
+    if query.raw {
+        return raw_audio(state, filename, index, audit::Kind::Stream, None).await;
+    }
+    let audio_path =
+        extracted_audio_path(&state, &filename, &index).ok_or(http::StatusCode::NOT_FOUND)?;
+    audit_clip(&state, &filename, &index, audit::Kind::Stream)?;
+
+    if query.normalize || query.start.is_some() || query.end.is_some() {
+        let bytes = tokio::task::spawn_blocking(move || {
+            processed_wav(&audio_path, query.normalize, query.start, query.end)
+        })
+        .await
+        .map_err(|_| http::StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|err| {
+            eprintln!("Decoding failed: {:#}", err);
+            http::StatusCode::UNPROCESSABLE_ENTITY
+        })?;
+        return Ok(response::Response::builder()
+            .header(http::header::CONTENT_TYPE, "audio/wav")
+            .body(body::Body::from(bytes))
+            .unwrap());
+    }
+
+    let vary = (http::header::VARY, "Accept".to_string());
+    let container = headers
+        .get(http::header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .and_then(preview::negotiate);
+    if let (Some(config), Some(container)) = (&state.preview, container) {
+        let blobs = blobs::BlobStore::new(&state.tmp_folder);
+        match preview::ensure_preview(config, &blobs, &audio_path, container).await {
+            Ok(opus_path) => {
+                return stream_file(
+                    &opus_path,
+                    &[
+                        (
+                            http::header::CONTENT_TYPE,
+                            container.content_type().to_string(),
+                        ),
+                        vary,
+                    ],
+                )
+                .await;
+            }
+            // The WAV file plays everywhere.
+            Err(err) => eprintln!("Transcoding failed for {}: {:#}", audio_path.display(), err),
+        }
+    }
+
+    stream_file(
+        &audio_path,
+        &[(http::header::CONTENT_TYPE, "audio/wav".to_string()), vary],
+    )
+    .await
+}
+
+/// Records clips of a file served to the current user in the audit log, when one is
+/// kept. Clips are only served once they are recorded.
+fn audit_downloads(
+    state: &AppState,
+    file: &str,
+    rows: &[usize],
+    kind: audit::Kind,
+) -> Result<(), http::StatusCode> {
+    let Some(log) = &state.audit else {
+        return Ok(());
+    };
+    let user = acl::current_session().map(|session| session.user);
+    log.record(user.as_deref(), kind, file, rows)
+        .map_err(|err| {
+            eprintln!("Failed to record download of {}: {:#}", file, err);
+            http::StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// Records one extracted clip, named by its row index, in the audit log.
+fn audit_clip(
+    state: &AppState,
+    file: &str,
+    index: &str,
+    kind: audit::Kind,
+) -> Result<(), http::StatusCode> {
+    match index.parse() {
+        Ok(row) => audit_downloads(state, file, &[row], kind),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Exports the download audit log as CSV (or JSON with `?format=json`), filtered by
+/// `user`, `file` and a `from`/`to` date range.
+///
+/// With login enabled, only the `--audit-admin` principals may export it.
+async fn export_audit(
+    State(state): State<AppState>,
+    Query(filter): Query<audit::Filter>,
+    Query(format): Query<AuditFormatQuery>,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    let Some(log) = state.audit.clone() else {
+        return Err((
+            http::StatusCode::NOT_FOUND,
+            "No audit log is kept; start the server with --audit-log".to_string(),
+        ));
+    };
+    let admin = state.oidc.is_none()
+        || acl::current_session().is_some_and(|session| {
+            state
+                .audit_admins
+                .iter()
+                .any(|principal| acl::is_principal(&session, principal))
+        });
+    if !admin {
+        return Err((
+            http::StatusCode::FORBIDDEN,
+            "Only audit admins may export the audit log".to_string(),
+        ));
+    }
+    let downloads = blocking(move || log.query(&filter))
+        .await
+        .map_err(|err| (http::StatusCode::BAD_REQUEST, format!("{:#}", err)))?;
+    let (content_type, extension, body) =
+        audit::encode(&downloads, format.format.unwrap_or(stats::Format::Csv));
+    Ok(response::Response::builder()
+        .header(http::header::CONTENT_TYPE, content_type)
+        .header(
+            http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"downloads.{}\"", extension),
+        )
+        .body(body::Body::from(body))
+        .unwrap())
+}
+
+/// Serves an image stored in a binary or `Image` struct column of a row.
+async fn serve_image(
+    State(state): State<AppState>,
+    AxumPath((filename, index, column)): AxumPath<(String, usize, String)>,
+) -> Result<response::Response, http::StatusCode> {
+    if !is_valid_parquet(&state, &filename) {
+        return Err(http::StatusCode::NOT_FOUND);
+    }
+    let bytes = tokio::task::spawn_blocking(move || {
+        let path = dataset_path(&state.tmp_folder, &state.folder, &filename);
+        let df = parquet_reader(&path)
+            .and_then(|reader| reader.with_columns(Some(vec![column.clone()])).finish())
+            .ok()?;
+        images::image_bytes(df.column(&column).ok()?, index)
+    })
+    .await
+    .map_err(|_| http::StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(http::StatusCode::NOT_FOUND)?;
+    let content_type = images::sniff(&bytes).ok_or(http::StatusCode::UNSUPPORTED_MEDIA_TYPE)?;
+    Ok(response::Response::builder()
+        .header(http::header::CONTENT_TYPE, content_type)
+        .body(body::Body::from(bytes))
+        .unwrap())
+}
+
+/// Serves the original audio bytes as an attachment.
+async fn download_audio(
+    State(state): State<AppState>,
+    AxumPath((filename, index)): AxumPath<(String, String)>,
+    Query(query): Query<DownloadQuery>,
+) -> Result<response::Response, http::StatusCode> {
+    let stem = filename.trim_end_matches(".parquet").to_string();
+    if query.raw {
+        let download_name = format!("{}-{}.bin", stem, index);
+        return raw_audio(
+            state,
+            filename,
+            index,
+            audit::Kind::Download,
+            Some(download_name),
+        )
+        .await;
+    }
+    let audio_path =
+        extracted_audio_path(&state, &filename, &index).ok_or(http::StatusCode::NOT_FOUND)?;
+    audit_clip(&state, &filename, &index, audit::Kind::Download)?;
+
+    let download_name = format!("{}-{}.wav", stem, index);
+    stream_file(
+        &audio_path,
+        &[
+            (http::header::CONTENT_TYPE, "audio/wav".to_string()),
+            (
+                http::header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", download_name),
+            ),
+        ],
+    )
+    .await
+}
+
+/// Reads the audio of a row exactly as the dataset stores it: the `audio.bytes` cell of
+/// a Parquet file or Lance dataset or the entry of a zip dataset, without going through
+/// the extracted copy.
+fn stored_audio(state: &AppState, filename: &str, row: usize) -> anyhow::Result<Vec<u8>> {
+    let path = dataset_path(&state.tmp_folder, &state.folder, filename);
+    if archive::is_archive(filename) {
+        let entry = load_string_column(&path, archive::FILE_NAME_COLUMN)?
+            .into_iter()
+            .nth(row)
+            .flatten()
+            .ok_or_else(|| anyhow::anyhow!("{} has no row {}", filename, row))?;
+        return archive::read(&state.folder.join(filename), &entry);
+    }
+    let df = parquet_reader(&path)?
+        .with_columns(Some(vec!["audio".to_string()]))
+        .with_slice(Some((row, 1)))
+        .finish()?
+        .unnest(["audio"], None)?;
+    let bytes = df.column("bytes")?.binary()?.get(0);
+    bytes
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| anyhow::anyhow!("{} has no audio bytes in row {}", filename, row))
+}
+
+/// Serves the stored bytes of a row untouched, as an attachment when named.
+async fn raw_audio(
+    state: AppState,
+    filename: String,
+    index: String,
+    kind: audit::Kind,
+    download_name: Option<String>,
+) -> Result<response::Response, http::StatusCode> {
+    let row = index.parse().map_err(|_| http::StatusCode::NOT_FOUND)?;
+    if !is_valid_parquet(&state, &filename) {
+        return Err(http::StatusCode::NOT_FOUND);
+    }
+    let bytes = {
+        let (state, filename) = (state.clone(), filename.clone());
+        blocking(move || stored_audio(&state, &filename, row))
+            .await
+            .map_err(|err| {
+                eprintln!("Failed to read the stored audio: {:#}", err);
+                http::StatusCode::NOT_FOUND
+            })?
+    };
+    audit_downloads(&state, &filename, &[row], kind)?;
+    let mut builder = response::Response::builder()
+        .header(http::header::CONTENT_TYPE, "application/octet-stream");
+    if let Some(name) = download_name {
+        builder = builder.header(
+            http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", name),
+        );
+    }
+    Ok(builder.body(body::Body::from(bytes)).unwrap())
+}
+
+/// Form auditioning the clip mixed with one of the `--noise-dir` recordings at a chosen
+/// SNR and/or convolved with one of the `--rir-dir` impulse responses, with a player for
+/// the result.
+fn augmentation_form(state: &AppState, filename: &str, index: &str) -> String {
+    let input_class =
+        "bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md p-1";
+    let select = |bank: &Option<Arc<augment::SoundBank>>, name: &str, label: &str, none: &str| {
+        let Some(bank) = bank.as_ref().filter(|bank| !bank.names().is_empty()) else {
+            return String::new();
+        };
+        let options: String = bank
+            .names()
+            .iter()
+            .map(|file| {
+                format!(
+                    r#"<option value="{}">{}</option>"#,
+                    html::escape(file),
+                    html::escape(file)
+                )
+            })
+            .collect();
+        format!(
+            r#"<select name="{}" class="{}" aria-label="{}"><option value="">{}</option>{}</select>"#,
+            name,
+            input_class,
+            i18n::t(label),
+            i18n::t(none),
+            options
+        )
+    };
+    let noise = select(&state.noise_bank, "noise", "Noise file", "No noise");
+    let rir = select(&state.rir_bank, "rir", "Room impulse response", "No reverb");
+    if noise.is_empty() && rir.is_empty() {
+        return String::new();
+    }
+    let snr = if noise.is_empty() {
+        String::new()
+    } else {
+        format!(
+            r#"<label>{} <input type="number" name="noise_snr" value="10" min="-20" max="80" step="any" class="{} w-16"></label>"#,
+            i18n::t("SNR (dB)"),
+            input_class
+        )
+    };
+    format!(
+        r#"<form class="mt-2 flex flex-wrap items-center gap-2 text-sm" onsubmit="event.preventDefault(); var data = new FormData(this); if (!data.get('noise')) data.delete('noise_snr'); var player = this.querySelector('audio'); player.src = '/augment/{}/{}?' + new URLSearchParams([...data].filter(function (entry) {{ return entry[1]; }})); player.play()">
+            <span class="font-semibold">{}</span>
+            {}{}{}
+            <button class="px-3 py-1 bg-gray-200 dark:bg-gray-700 rounded-md">{}</button>
+            <audio controls="" preload="none" class="h-8" aria-label="{}"></audio>
+        </form>"#,
+        html::url_encode(filename),
+        html::url_encode(index),
+        i18n::t("Simulate environment:"),
+        noise,
+        snr,
+        rir,
+        i18n::t("Play"),
+        i18n::t("Augmented clip"),
+    )
+}
+
+/// The bank a recording named by an augmentation parameter comes from.
+fn sound_bank(
+    name: &Option<String>,
+    bank: &Option<Arc<augment::SoundBank>>,
+    flag: &str,
+) -> Result<Option<Arc<augment::SoundBank>>, (http::StatusCode, String)> {
+    match (name, bank) {
+        (None, _) => Ok(None),
+        (Some(_), Some(bank)) => Ok(Some(bank.clone())),
+        (Some(_), None) => Err((
+            http::StatusCode::BAD_REQUEST,
+            format!("No {} is configured", flag),
+        )),
+    }
+}
+
+/// Serves a clip with speed perturbation, pitch shift, reverb and/or additive noise
+/// applied.
+async fn serve_augmented(
+    State(state): State<AppState>,
+    AxumPath((filename, index)): AxumPath<(String, String)>,
+    Query(params): Query<augment::AugmentParams>,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    let audio_path = extracted_audio_path(&state, &filename, &index)
+        .ok_or((http::StatusCode::NOT_FOUND, "Audio not found".to_string()))?;
+
+    let noise_bank = sound_bank(&params.noise, &state.noise_bank, "--noise-dir")?;
+    let rir_bank = sound_bank(&params.rir, &state.rir_bank, "--rir-dir")?;
+
+    let bytes = tokio::task::spawn_blocking(move || {
+        let source = wav::Wav::decode(&fs::read(&audio_path)?)?;
+        let read = |name: &Option<String>, bank: Option<Arc<augment::SoundBank>>| match (name, bank)
+        {
+            (Some(name), Some(bank)) => bank.read(name, source.sample_rate).map(Some),
+            _ => Ok(None),
+        };
+        let recordings = augment::Recordings {
+            noise: read(&params.noise, noise_bank)?,
+            rir: read(&params.rir, rir_bank)?,
+        };
+        let samples = augment::apply(&source.mono(), &params, &recordings)?;
+        wav::Wav {
+            sample_rate: source.sample_rate,
+            channels: 1,
+            samples,
+        }
+        .encode_pcm16()
+    })
+    .await
+    .map_err(|err| (http::StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+    .map_err(|err| (http::StatusCode::BAD_REQUEST, format!("{:#}", err)))?;
+    audit_clip(&state, &filename, &index, audit::Kind::Augment)
+        .map_err(|status| (status, "Failed to record the download".to_string()))?;
+
+    Ok(response::Response::builder()
+        .header(http::header::CONTENT_TYPE, "audio/wav")
+        .body(body::Body::from(bytes))
+        .unwrap())
+}
+
+/// Computes log-mel or MFCC features for a clip, returned as JSON or `.npy`.
+async fn serve_features(
+    State(state): State<AppState>,
+    AxumPath((filename, index)): AxumPath<(String, String)>,
+    Query(params): Query<features::FeatureParams>,
+    Query(output): Query<FeatureFormatQuery>,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    let audio_path = extracted_audio_path(&state, &filename, &index)
+        .ok_or((http::StatusCode::NOT_FOUND, "Audio not found".to_string()))?;
+
+    let features = tokio::task::spawn_blocking(move || {
+        let wav = wav::Wav::decode(&fs::read(&audio_path)?)?;
+        features::compute(&wav.mono(), wav.sample_rate, &params)
+    })
+    .await
+    .map_err(|err| (http::StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+    .map_err(|err| (http::StatusCode::BAD_REQUEST, format!("{:#}", err)))?;
+
+    match output.format {
+        FeatureFormat::Json => Ok(response::Json(features).into_response()),
+        FeatureFormat::Npy => Ok(response::Response::builder()
+            .header(http::header::CONTENT_TYPE, "application/octet-stream")
+            .header(
+                http::header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}-{}-{}.npy\"",
+                    filename.trim_end_matches(".parquet"),
+                    index,
+                    features.kind.as_str()
+                ),
+            )
+            .body(body::Body::from(features.to_npy()))
+            .unwrap()),
+    }
+}
+
+/// Serves the F0 track of a clip as JSON.
+async fn serve_pitch(
+    State(state): State<AppState>,
+    AxumPath((filename, index)): AxumPath<(String, String)>,
+    Query(params): Query<pitch::PitchParams>,
+) -> Result<response::Json<pitch::PitchTrack>, (http::StatusCode, String)> {
+    let audio_path = extracted_audio_path(&state, &filename, &index)
+        .ok_or((http::StatusCode::NOT_FOUND, "Audio not found".to_string()))?;
+
+    let track = tokio::task::spawn_blocking(move || {
+        let wav = wav::Wav::decode(&fs::read(&audio_path)?)?;
+        pitch::track(&wav.mono(), wav.sample_rate, &params)
+    })
+    .await
+    .map_err(|err| (http::StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+    .map_err(|err| (http::StatusCode::BAD_REQUEST, format!("{:#}", err)))?;
+    Ok(response::Json(track))
+}
+
+/// Output encoding for the energy endpoint.
+#[derive(Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum EnergyFormat {
+    #[default]
+    Json,
+    Svg,
+}
+
+fn default_energy_points() -> usize {
+    64
+}
+
+/// Query parameters of the energy endpoint.
+#[derive(Deserialize, Debug)]
+struct EnergyQuery {
+    /// Number of envelope points, at most [`MAX_ENERGY_POINTS`].
+    #[serde(default = "default_energy_points")]
+    points: usize,
+    #[serde(default)]
+    format: EnergyFormat,
+}
+
+const MAX_ENERGY_POINTS: usize = 4096;
+
+/// Downsampled RMS envelope of a clip.
+#[derive(serde::Serialize)]
+struct Energy {
+    sample_rate: u32,
+    duration: f64,
+    /// Linear RMS of each of the equally long slices of the clip.
+    rms: Vec<f32>,
+}
+
+/// Serves the RMS envelope of a clip as JSON or as an SVG sparkline.
+async fn serve_energy(
+    State(state): State<AppState>,
+    AxumPath((filename, index)): AxumPath<(String, String)>,
+    Query(query): Query<EnergyQuery>,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    let audio_path = extracted_audio_path(&state, &filename, &index)
+        .ok_or((http::StatusCode::NOT_FOUND, "Audio not found".to_string()))?;
+    let points = query.points.clamp(1, MAX_ENERGY_POINTS);
+
+    let energy = tokio::task::spawn_blocking(move || {
+        let wav = wav::Wav::decode(&fs::read(&audio_path)?)?;
+        let samples = wav.mono();
+        Ok::<_, anyhow::Error>(Energy {
+            sample_rate: wav.sample_rate,
+            duration: samples.len() as f64 / wav.sample_rate as f64,
+            rms: dsp::rms_envelope(&samples, points),
+        })
+    })
+    .await
+    .map_err(|err| (http::StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+    .map_err(|err| (http::StatusCode::BAD_REQUEST, format!("{:#}", err)))?;
+
+    match query.format {
+        EnergyFormat::Json => Ok(response::Json(energy).into_response()),
+        EnergyFormat::Svg => {
+            let levels: Vec<f32> = energy.rms.iter().map(|&rms| dsp::to_dbfs(rms)).collect();
+            Ok((
+                [(http::header::CONTENT_TYPE, "image/svg+xml")],
+                html::sparkline(&levels),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Identifies a filtered view: the file version, the annotation version and the query.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct FilterKey {
+    filename: String,
+    modified: Option<std::time::SystemTime>,
+    annotations: u64,
+    /// Filter and sort parameters, plus the number of scored clips when sorting by score.
+    query: String,
+}
+
+/// Rows of a file matching a filter, in display order, with the facet counts.
+struct FilteredRows {
+    order: Vec<usize>,
+    facets: Vec<Facet>,
+    /// Why the filter expression could not be evaluated; no row matches then.
+    error: Option<String>,
+}
+
+/// Filters and sorts the (corrected) rows of a file, reusing the result while the
+/// file, its annotations and the query are unchanged.
+fn filter_rows(
+    state: &AppState,
+    filename: &str,
+    files: &[Audio],
+    annotations: &BTreeMap<usize, RowAnnotation>,
+    filter: &RowFilter,
+    sort: Option<&str>,
+    token_counts: Option<&[usize]>,
+) -> Arc<FilteredRows> {
+    let path = dataset_path(&state.tmp_folder, &state.folder, filename);
+    let mut query = format!("{}&sort={}", filter.query(), sort.unwrap_or_default());
+    if let (Some("score"), Some(scorer)) = (sort.map(|s| s.trim_start_matches('-')), &state.scorer)
+    {
+        query.push_str(&format!("&scored={}", scorer.scored()));
+    }
+    let key = FilterKey {
+        filename: filename.to_string(),
+        modified: fs::metadata(&path).and_then(|m| m.modified()).ok(),
+        annotations: state.annotations.version(),
+        query,
+    };
+    state.filtered.get_or_insert_with(key, || {
+        if state.audio_events || state.noise_floor {
+            // These columns analyze every row, not just the displayed ones.
+            extract_archived_rows(state, filename, 0..files.len());
+        }
+        let no_annotation = RowAnnotation::default();
+        let annotation = |row: usize| annotations.get(&row).unwrap_or(&no_annotation);
+        let mut filter = filter.clone();
+        let error = filter
+            .evaluate(&path, files)
+            .err()
+            .map(|err| format!("{:#}", err));
+
+        // Plugin scores are only computed for every row when sorting by them.
+        let sort_plugin = sort
+            .map(|sort| sort.trim_start_matches('-'))
+            .and_then(|column| column.strip_prefix("plugin-"))
+            .and_then(|name| state.plugins.iter().find(|plugin| plugin.name == name));
+        let plugin_scores: Option<Vec<Option<f64>>> = sort_plugin.map(|plugin| {
+            (0..files.len())
+                .map(|row| {
+                    run_plugin(plugin, filename, row, &files[row])
+                        .ok()
+                        .and_then(|output| output.score)
+                })
+                .collect()
+        });
+
+        let mut order = sort_rows(
+            files,
+            &state.analysis,
+            token_counts,
+            state.scorer.as_ref(),
+            plugin_scores.as_deref(),
+            sort,
+        );
+        order.retain(|&row| filter.matches(row, &files[row], annotation(row)));
+        if !filter.event.is_empty() {
+            order.retain(|&row| {
+                state
+                    .analysis
+                    .events(&files[row].path)
+                    .map(|event| event.as_str())
+                    == Some(filter.event.as_str())
+            });
+        }
+        if let Some(blocklist) = &state.blocklist {
+            match filter.blocklist.as_str() {
+                "only" => {
+                    order.retain(|&row| !blocklist.matches(&files[row].transcription).is_empty())
+                }
+                "exclude" => {
+                    order.retain(|&row| blocklist.matches(&files[row].transcription).is_empty())
+                }
+                _ => {}
+            }
+        }
+        FilteredRows {
+            order,
+            facets: facet_counts(files, &filter, |row| annotation(row).clone()),
+            error,
+        }
+    })
+}
+
+/// Rows of `filename` matching `filter`, in file order.
+fn matching_rows(
+    state: &AppState,
+    filename: &str,
+    filter: &RowFilter,
+) -> Result<Vec<usize>, (http::StatusCode, String)> {
+    let annotations = state.annotations.file(filename);
+    let mut files = extract_parquet_file(&state.tmp_folder, &state.folder, filename);
+    apply_corrections(&mut files, &annotations);
+    let filtered = filter_rows(state, filename, &files, &annotations, filter, None, None);
+    match &filtered.error {
+        Some(err) => Err((
+            http::StatusCode::BAD_REQUEST,
+            format!("Invalid filter: {}", err),
+        )),
+        None => Ok(filtered.order.clone()),
+    }
+}
+
+/// A row of the JSON API.
+#[derive(serde::Serialize)]
+struct ApiRow<'a> {
+    row: usize,
+    duration: f64,
+    transcription: &'a str,
+    #[serde(flatten)]
+    facets: &'a BTreeMap<String, String>,
+}
+
+/// Serves the rows matching a filter as JSON, in file order and paginated like the view.
+async fn api_rows(
+    State(state): State<AppState>,
+    AxumPath(filename): AxumPath<String>,
+    Query(pagination): Query<Pagination>,
+    Query(filter): Query<RowFilter>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    let filter = filter.with_facets(&params);
+    blocking(move || rows_json(state, filename, pagination, filter)).await
+}
+
+fn rows_json(
+    state: AppState,
+    filename: String,
+    pagination: Pagination,
+    filter: RowFilter,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    if !is_valid_parquet(&state, &filename) {
+        return Err((http::StatusCode::NOT_FOUND, "File not found".to_string()));
+    }
+    let rows = matching_rows(&state, &filename, &filter)?;
+    let mut files = extract_parquet_file(&state.tmp_folder, &state.folder, &filename);
+    apply_corrections(&mut files, &state.annotations.file(&filename));
+
+    let page = pagination.page.unwrap_or(1).max(1);
+    let page_size = pagination.page_size.unwrap_or(10);
+    let page_rows: Vec<ApiRow> = rows
+        .iter()
+        .skip((page - 1) * page_size)
+        .take(page_size)
+        .map(|&row| ApiRow {
+            row,
+            duration: files[row].duration,
+            transcription: &files[row].transcription,
+            facets: &files[row].facets,
+        })
+        .collect();
+    Ok(response::Json(serde_json::json!({
+        "total": rows.len(),
+        "page": page,
+        "page_size": page_size,
+        "rows": page_rows,
+    }))
+    .into_response())
+}
+
+/// Checks a batch request before it is confirmed or started.
+fn validate_batch(
+    state: &AppState,
+    filename: &str,
+    batch: &BatchQuery,
+) -> Result<(), (http::StatusCode, String)> {
+    if !is_valid_parquet(state, filename) {
+        return Err((http::StatusCode::NOT_FOUND, "File not found".to_string()));
+    }
+    if matches!(batch.action, BatchAction::Tag | BatchAction::Untag)
+        && batch.value.trim().is_empty()
+    {
+        return Err((
+            http::StatusCode::BAD_REQUEST,
+            "A tag name is required".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Confirmation page of a batch operation.
+async fn confirm_batch(
+    State(state): State<AppState>,
+    AxumPath(filename): AxumPath<String>,
+    Query(batch): Query<BatchQuery>,
+    Query(filter): Query<RowFilter>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    validate_batch(&state, &filename, &batch)?;
+    let filter = filter.with_facets(&params);
+    let rows = {
+        let (state, filename, filter) = (state.clone(), filename.clone(), filter.clone());
+        blocking(move || matching_rows(&state, &filename, &filter)).await?
+    };
+    let query = format!(
+        "action={}&value={}{}",
+        batch.action.as_str(),
+        html::url_encode(&batch.value),
+        filter.query()
+    );
+
+    let content = format!(
+        r#"
+    <div class="max-w-2xl mx-auto bg-white dark:bg-gray-800 shadow-md rounded-lg p-6 relative">
+        {}
+        <h1 class="text-2xl font-bold mb-4">Confirm batch operation</h1>
+        <p class="mb-4">{} on <b>{}</b> matching rows of {}?</p>
+        <form method="post" action="/batch/{}?{}" class="flex gap-4 items-center">
+            <button class="px-3 py-1 bg-blue-500 text-white rounded-md">Apply to {} rows</button>
+            <a href="/view/{}?page=1{}" class="text-blue-600 dark:text-blue-400 hover:underline">Cancel</a>
+        </form>
+    </div>"#,
+        html::page_header(
+            &html::escape(&format!(
+                "/view/{}?page=1{}",
+                html::url_encode(&filename),
+                filter.query()
+            )),
+            &i18n::tf("Back to {name}", &[("name", &html::escape(&filename))])
+        ),
+        html::escape(&batch.describe()),
+        rows.len(),
+        html::escape(&filename),
+        html::url_encode(&filename),
+        html::escape(&query),
+        rows.len(),
+        html::url_encode(&filename),
+        html::escape(&filter.query()),
+    );
+    Ok(Html(html::render_page(
+        "Confirm batch operation",
+        "",
+        &content,
+    )))
+}
+
+/// Number of rows annotated between two saves of the annotation store.
+const BATCH_CHUNK: usize = 100;
+
+/// Starts a batch operation in the background and redirects to its progress page.
+async fn start_batch(
+    State(state): State<AppState>,
+    AxumPath(filename): AxumPath<String>,
+    Query(batch): Query<BatchQuery>,
+    Query(filter): Query<RowFilter>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<response::Redirect, (http::StatusCode, String)> {
+    validate_batch(&state, &filename, &batch)?;
+    if matches!(batch.action, BatchAction::Export)
+        && !allows(&state, &filename, acl::Permission::Export)
+    {
+        return Err((
+            http::StatusCode::FORBIDDEN,
+            format!("You may not export {}", filename),
+        ));
+    }
+    let filter = filter.with_facets(&params);
+    let rows = {
+        let (state, filename, filter) = (state.clone(), filename.clone(), filter.clone());
+        blocking(move || matching_rows(&state, &filename, &filter)).await?
+    };
+    let job = state.jobs.start(
+        format!(
+            "{} on {} rows of {}",
+            batch.describe(),
+            rows.len(),
+            filename
+        ),
+        rows.len(),
+        filename.clone(),
+        if matches!(batch.action, BatchAction::Export) {
+            acl::Permission::Export
+        } else {
+            acl::Permission::Annotate
+        },
+    );
+
+    let job_id = job.id;
+    let user = acl::current_session().map(|session| session.user);
+    tokio::task::spawn_blocking(move || {
+        let result = match batch.action {
+            BatchAction::Export => {
+                let out_path = state
+                    .tmp_folder
+                    .join("exports")
+                    .join(format!("{}-{}", job.id, filename));
+                export_rows(
+                    &dataset_path(&state.tmp_folder, &state.folder, &filename),
+                    &rows,
+                    &out_path,
+                )
+                .map(|()| {
+                    job.advance(rows.len());
+                    Some(out_path)
+                })
+            }
+            action => {
+                rows.chunks(BATCH_CHUNK)
+                    .try_for_each(|chunk| {
+                        state.annotations.update(
+                            &filename,
+                            chunk.iter().copied(),
+                            |annotation| match action {
+                                BatchAction::Tag => {
+                                    annotation.tags.insert(batch.value.trim().to_string());
+                                }
+                                BatchAction::Untag => {
+                                    annotation.tags.remove(batch.value.trim());
+                                }
+                                BatchAction::Flag => annotation.flagged = true,
+                                BatchAction::Unflag => annotation.flagged = false,
+                                BatchAction::Review => annotation.reviewed = true,
+                                BatchAction::Unreview => annotation.reviewed = false,
+                                BatchAction::Export => unreachable!(),
+                            },
+                        )?;
+                        job.advance(chunk.len());
+                        Ok(())
+                    })
+                    .map(|()| {
+                        notify_batch(&state, &filename, &batch, rows, user);
+                        None
+                    })
+            }
+        };
+        job.finish(result);
+    });
+
+    Ok(response::Redirect::to(&format!("/jobs/{}", job_id)))
+}
+
+/// Tells the webhooks about a completed annotation batch, and about the rows it flagged.
+fn notify_batch(
+    state: &AppState,
+    filename: &str,
+    batch: &BatchQuery,
+    rows: Vec<usize>,
+    user: Option<String>,
+) {
+    let Some(webhooks) = &state.webhooks else {
+        return;
+    };
+    if batch.action == BatchAction::Flag {
+        webhooks.notify(webhooks::Notification::new(
+            webhooks::Event::Flagged,
+            filename,
+            rows.clone(),
+            user.clone(),
+        ));
+    }
+    let mut notification =
+        webhooks::Notification::new(webhooks::Event::BatchCompleted, filename, rows, user);
+    notification.batch = Some(batch.describe());
+    webhooks.notify(notification);
+}
+
+/// Writes the given rows of a Parquet file to a new Parquet file.
+fn export_rows(source: &Path, rows: &[usize], out_path: &Path) -> anyhow::Result<()> {
+    let mut selected = select_rows(source, rows)?;
+    if let Some(parent) = out_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    ParquetWriter::new(File::create(out_path)?).finish(&mut selected)?;
+    Ok(())
+}
+
+/// Reads the given rows of a Parquet file, in the given order.
+fn select_rows(source: &Path, rows: &[usize]) -> anyhow::Result<DataFrame> {
+    let df = parquet_reader(source)?.finish()?;
+    let indices = IdxCa::from_vec(
+        "idx".into(),
+        rows.iter().map(|&row| row as IdxSize).collect(),
+    );
+    Ok(df.take(&indices)?)
+}
+
+/// Output encoding of the annotated rows export.
+#[derive(Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum ExportFormat {
+    /// The source columns plus the annotation columns.
+    #[default]
+    Parquet,
+    /// One JSON object per row with the annotation only, without audio.
+    Jsonl,
+}
+
+/// Query parameters of the annotated rows export.
+#[derive(Deserialize, Debug)]
+struct ExportQuery {
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+/// One line of the JSONL export of annotated rows.
+#[derive(serde::Serialize)]
+struct AnnotatedRow<'a> {
+    row: usize,
+    original_transcription: &'a str,
+    corrected_transcription: Option<&'a str>,
+    tags: &'a BTreeSet<String>,
+    flagged: bool,
+    reviewed: bool,
+}
+
+/// Builds the export of the annotated rows of a file.
+///
+/// Parquet keeps every source column (with the original `transcription`) and adds
+/// `row_index`, `corrected_transcription`, `tags`, `flagged` and `reviewed`.
+fn export_annotated(
+    source: &Path,
+    original: &[Audio],
+    annotations: &BTreeMap<usize, RowAnnotation>,
+    format: &ExportFormat,
+) -> anyhow::Result<Vec<u8>> {
+    let annotated: Vec<(usize, &RowAnnotation)> = annotations
+        .iter()
+        .filter(|(row, _)| **row < original.len())
+        .map(|(row, annotation)| (*row, annotation))
+        .collect();
+
+    if *format == ExportFormat::Jsonl {
+        let mut out = Vec::new();
+        for (row, annotation) in annotated {
+            serde_json::to_writer(
+                &mut out,
+                &AnnotatedRow {
+                    row,
+                    original_transcription: &original[row].transcription,
+                    corrected_transcription: annotation.transcription(),
+                    tags: &annotation.tags,
+                    flagged: annotation.flagged,
+                    reviewed: annotation.reviewed,
+                },
+            )?;
+            out.push(b'\n');
+        }
+        return Ok(out);
+    }
+
+    let rows: Vec<usize> = annotated.iter().map(|(row, _)| *row).collect();
+    let mut df = select_rows(source, &rows)?;
+    let tags: Vec<Series> = annotated
+        .iter()
+        .map(|(_, a)| {
+            Series::new(
+                "".into(),
+                a.tags.iter().map(String::as_str).collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+    df.with_column(Column::new(
+        "row_index".into(),
+        rows.iter().map(|&row| row as u64).collect::<Vec<_>>(),
+    ))?;
+    df.with_column(Column::new(
+        "corrected_transcription".into(),
+        annotated
+            .iter()
+            .map(|(_, a)| a.transcription())
+            .collect::<Vec<_>>(),
+    ))?;
+    df.with_column(Column::new("tags".into(), tags))?;
+    df.with_column(Column::new(
+        "flagged".into(),
+        annotated.iter().map(|(_, a)| a.flagged).collect::<Vec<_>>(),
+    ))?;
+    df.with_column(Column::new(
+        "reviewed".into(),
+        annotated
+            .iter()
+            .map(|(_, a)| a.reviewed)
+            .collect::<Vec<_>>(),
+    ))?;
+
+    let mut out = Vec::new();
+    ParquetWriter::new(&mut out).finish(&mut df)?;
+    Ok(out)
+}
+
+/// Downloads the rows of a file that were edited, flagged, reviewed or tagged.
+async fn export_annotated_rows(
+    State(state): State<AppState>,
+    AxumPath(filename): AxumPath<String>,
+    Query(query): Query<ExportQuery>,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    if !is_valid_parquet(&state, &filename) {
+        return Err((http::StatusCode::NOT_FOUND, "File not found".to_string()));
+    }
+
+    let annotations = state.annotations.file(&filename);
+    let extension = match query.format {
+        ExportFormat::Parquet => "parquet",
+        ExportFormat::Jsonl => "jsonl",
+    };
+    let bytes = {
+        let state = state.clone();
+        let filename = filename.clone();
+        tokio::task::spawn_blocking(move || {
+            let original = extract_parquet_file(&state.tmp_folder, &state.folder, &filename);
+            export_annotated(
+                &dataset_path(&state.tmp_folder, &state.folder, &filename),
+                &original,
+                &annotations,
+                &query.format,
+            )
+        })
+        .await
+        .map_err(|err| (http::StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+        .map_err(|err| {
+            (
+                http::StatusCode::INTERNAL_SERVER_ERROR,
+                format!("{:#}", err),
+            )
+        })?
+    };
+
+    Ok(response::Response::builder()
+        .header(http::header::CONTENT_TYPE, "application/octet-stream")
+        .header(
+            http::header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"{}-annotated.{}\"",
+                filename.trim_end_matches(".parquet"),
+                extension
+            ),
+        )
+        .body(body::Body::from(bytes))
+        .unwrap())
+}
+
+fn default_split_max_duration() -> f64 {
+    20.0
+}
+
+/// Query parameters of the long row splitter.
+#[derive(Deserialize, Debug, Clone)]
+struct SplitQuery {
+    /// Rows longer than this are cut, in seconds.
+    #[serde(default = "default_split_max_duration")]
+    max_duration: f64,
+    #[serde(default)]
+    method: split::Method,
+}
+
+/// Long rows shown on the split preview at most.
+const MAX_SPLIT_PREVIEW: usize = 50;
+
+/// Plans the cuts of the rows of a file longer than the maximum duration: each becomes
+/// segments carrying the text of the transcript segments they contain. Rows whose audio
+/// cannot be decoded carry the error instead and are kept whole.
+fn plan_split(
+    state: &AppState,
+    filename: &str,
+    files: &[Audio],
+    query: &SplitQuery,
+) -> Vec<(usize, Result<Vec<segments::Segment>, String>)> {
+    let long: Vec<usize> = (0..files.len())
+        .filter(|&row| files[row].duration > query.max_duration)
+        .collect();
+    extract_archived_rows(state, filename, long.iter().copied());
+    long.into_iter()
+        .map(|row| {
+            let audio = &files[row];
+            let cuts = fs::read(&audio.path)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| wav::Wav::decode(&bytes))
+                .map(|wav| {
+                    split::plan(&wav, query.max_duration, query.method)
+                        .into_iter()
+                        .map(|span| segments::Segment {
+                            start: span.0,
+                            end: span.1,
+                            text: split::text(span, &audio.segments),
+                        })
+                        .collect()
+                })
+                .map_err(|err| format!("{:#}", err));
+            (row, cuts)
+        })
+        .collect()
+}
+
+fn validate_split(
+    state: &AppState,
+    filename: &str,
+    query: &SplitQuery,
+) -> Result<(), (http::StatusCode, String)> {
+    if !is_valid_parquet(state, filename) {
+        return Err((http::StatusCode::NOT_FOUND, "File not found".to_string()));
+    }
+    if !query.max_duration.is_finite() || query.max_duration <= 0.0 {
+        return Err((
+            http::StatusCode::BAD_REQUEST,
+            "The maximum duration must be positive".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Previews how the rows of a file longer than `?max_duration=` would be cut, with a
+/// player per segment, and offers to write the segmented dataset.
+async fn preview_split(
+    State(state): State<AppState>,
+    AxumPath(filename): AxumPath<String>,
+    Query(query): Query<SplitQuery>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    validate_split(&state, &filename, &query)?;
+    let (rows, plan) = {
+        let (state, filename, query) = (state.clone(), filename.clone(), query.clone());
+        blocking(move || {
+            let files = extract_parquet_file(&state.tmp_folder, &state.folder, &filename);
+            let plan = plan_split(&state, &filename, &files, &query);
+            (files.len(), plan)
+        })
+        .await
+    };
+    let segments: usize = plan
+        .iter()
+        .map(|(_, cuts)| cuts.as_ref().map_or(1, Vec::len))
+        .sum();
+    let failed = plan.iter().filter(|(_, cuts)| cuts.is_err()).count();
 
-    let list_items: String = files
+    let input_class =
+        "bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-md p-1";
+    let method_options: String = [
+        (split::Method::Vad, "At pauses (VAD)"),
+        (split::Method::Fixed, "Fixed windows"),
+    ]
+    .iter()
+    .map(|(method, label)| {
+        format!(
+            r#"<option value="{}"{}>{}</option>"#,
+            method.as_str(),
+            if *method == query.method {
+                " selected"
+            } else {
+                ""
+            },
+            i18n::t(label)
+        )
+    })
+    .collect();
+    let query_string = format!(
+        "max_duration={}&method={}",
+        query.max_duration,
+        query.method.as_str()
+    );
+    let listed: String = plan
         .iter()
-        .map(|f| {
+        .take(MAX_SPLIT_PREVIEW)
+        .map(|(row, cuts)| {
+            let body = match cuts {
+                Ok(cuts) => segments::table(&filename, &row.to_string(), cuts),
+                Err(err) => format!(
+                    r#"<p class="mb-4 text-red-600 dark:text-red-400">{}</p>"#,
+                    html::escape(err)
+                ),
+            };
             format!(
-                r#"<li><a href="/view/{}" class="text-blue-600 hover:underline">{}</a></li>"#,
-                f, f
+                r#"<h2 class="text-lg font-semibold mb-2"><a href="/row/{}/{}" class="text-blue-600 dark:text-blue-400 hover:underline">#{}</a></h2>{}"#,
+                html::url_encode(&filename),
+                row,
+                row,
+                body
             )
         })
         .collect();
 
-    let html = format!(
+    let content = format!(
         r#"
-<!DOCTYPE html>
-<html lang="en" class="">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>Parquet Files</title>
-    <script src="https://cdn.tailwindcss.com"></script>
-    <script>
-        tailwind.config = {{
-            darkMode: 'class',
-    }}
-    </script>
-    <script>
-        if (localStorage.theme === 'dark' || (!('theme' in localStorage) && window.matchMedia('(prefers-color-scheme: dark)').matches)) {{
-            document.documentElement.classList.add('dark')
-        }} else {{
-            document.documentElement.classList.remove('dark')
-        }}
-        function toggleTheme() {{
-            if (localStorage.theme === 'dark') {{
-                localStorage.theme = 'light';
-                document.documentElement.classList.remove('dark');
-            }} else {{
-                localStorage.theme = 'dark';
-                document.documentElement.classList.add('dark');
-            }}
-        }}
-    </script>
-</head>
-<body class="bg-gray-100 dark:bg-gray-900 p-8 text-gray-900 dark:text-gray-100">
-    <div class="max-w-4xl mx-auto bg-white dark:bg-gray-800 shadow-md rounded-lg p-6 relative">
-        <button onclick="toggleTheme()" class="absolute top-4 right-4 px-3 py-1 bg-gray-200 dark:bg-gray-700 rounded-md text-sm">
-            Toggle Theme
-        </button>
-        <h1 class="text-2xl font-bold mb-4">Parquet Files</h1>
-        <ul class="list-disc pl-5 space-y-2">
-            {}
-        </ul>
-    </div>
-    <footer class="text-center mt-4">
-        <a href="https://github.com/RustedBytes/data-viewer-audio" class="text-sm text-gray-500 dark:text-gray-400 hover:underline"><b>data-viewer-audio</b> on GitHub</a>
-    </footer>
-</body>
-</html>
-"#,
-        list_items
+    <div class="max-w-5xl mx-auto bg-white dark:bg-gray-800 shadow-md rounded-lg p-6 relative">
+        {}
+        <h1 class="text-2xl font-bold mb-4">{}</h1>
+        <form method="get" action="/split/{}" class="flex flex-wrap gap-2 items-center mb-4 text-sm">
+            <label>{} <input type="number" name="max_duration" value="{}" min="1" step="any" class="{} w-20"></label>
+            <select name="method" class="{}" aria-label="{}">{}</select>
+            <button class="px-3 py-1 bg-gray-200 dark:bg-gray-700 rounded-md">{}</button>
+        </form>
+        <p class="mb-4">{}</p>
+        <form method="post" action="/split/{}?{}" class="mb-6">
+            <button class="px-3 py-1 bg-blue-500 text-white rounded-md">{}</button>
+        </form>
+        {}
+    </div>"#,
+        html::page_header(
+            &format!("/view/{}", html::url_encode(&filename)),
+            &i18n::tf("Back to {name}", &[("name", &html::escape(&filename))])
+        ),
+        i18n::t("Split long rows"),
+        html::url_encode(&filename),
+        i18n::t("Maximum duration (s)"),
+        query.max_duration,
+        input_class,
+        input_class,
+        i18n::t("Method"),
+        method_options,
+        i18n::t("Preview"),
+        i18n::tf(
+            "{long} of {rows} rows are longer than {max} s and become {segments} segments; {failed} could not be decoded and stay whole.",
+            &[
+                ("long", &plan.len().to_string()),
+                ("rows", &rows.to_string()),
+                ("max", &query.max_duration.to_string()),
+                ("segments", &segments.to_string()),
+                ("failed", &failed.to_string()),
+            ]
+        ),
+        html::url_encode(&filename),
+        html::escape(&query_string),
+        i18n::t("Write segmented Parquet"),
+        listed,
     );
-
-    Html(html)
+    Ok(Html(html::render_page(
+        i18n::t("Split long rows"),
+        "",
+        &content,
+    )))
 }
 
-/// Serves a paginated view of the Parquet file data.
-async fn view_file(
+/// Writes the segmented dataset of a file in the background and redirects to the job,
+/// whose download is a Parquet file with one row per segment and the short rows as is.
+async fn start_split(
     State(state): State<AppState>,
     AxumPath(filename): AxumPath<String>,
-    Query(pagination): Query<Pagination>,
-) -> Html<String> {
-    if !filename.ends_with(".parquet") {
-        return Html("Invalid file type".to_string());
-    }
-
-    let path = state.folder.join(&filename);
-    if !path.exists() || !path.is_file() {
-        return Html("File not found".to_string());
-    }
-
-    let files = extract_parquet_file(&state.tmp_folder, &state.folder, &filename);
-
-    let page = pagination.page.unwrap_or(1);
-    let page_size = pagination.page_size.unwrap_or(10);
-    let total_items = files.len();
-    let total_pages = (total_items as f64 / page_size as f64).ceil() as usize;
-
-    let start = (page - 1) * page_size;
-    let end = (start + page_size).min(total_items);
-
-    let paginated_files = if start < files.len() {
-        &files[start..end]
-    } else {
-        &[]
-    };
-    let mut rows = String::new();
-    for audio in paginated_files {
-        let audio_src = format!(
-            "/audio/{}/{}",
-            filename,
-            audio.path.file_stem().unwrap().to_str().unwrap()
-        );
-        rows.push_str(&format!(
-            r#"
-            <tr class="block md:table-row border-b dark:border-gray-700 hover:bg-gray-50 dark:hover:bg-gray-700 cursor-pointer" onclick="var audio = this.querySelector('audio'); if (audio.paused) {{ audio.play(); }} else {{ audio.pause(); }}">
-                <td class="block md:table-cell px-4 py-2 md:py-4"><span class="md:hidden font-bold">Audio: </span><audio class="h-dvh max-h-[2.25rem] w-full min-w-[300px] max-w-xs inline-block" controls="" preload="none">
-                    <source src="{}" type="audio/wav">
-                        Your browser does not support the audio element.
-                    </audio>
-                </td>
-                <td class="block md:table-cell px-4 py-2 md:py-4 md:text-right"><span class="md:hidden font-bold">Duration: </span>{}</td>
-                <td class="block md:table-cell px-4 py-2 md:py-4"><span class="md:hidden font-bold">Transcription: </span>{}</td>
-            </tr>
-            "#,
-            audio_src,
-            format_duration(audio.duration),
-            &audio.transcription,
+    Query(query): Query<SplitQuery>,
+) -> Result<response::Redirect, (http::StatusCode, String)> {
+    validate_split(&state, &filename, &query)?;
+    if !allows(&state, &filename, acl::Permission::Export) {
+        return Err((
+            http::StatusCode::FORBIDDEN,
+            format!("You may not export {}", filename),
         ));
     }
+    let files = {
+        let (state, filename) = (state.clone(), filename.clone());
+        blocking(move || extract_parquet_file(&state.tmp_folder, &state.folder, &filename)).await
+    };
+    let job = state.jobs.start(
+        format!(
+            "Split of the rows of {} longer than {} s",
+            filename, query.max_duration
+        ),
+        files.len(),
+        filename.clone(),
+        acl::Permission::Export,
+    );
 
-    let pagination_html = if total_pages > 1 {
-        let mut pagination_links = String::new();
-        let window = 2;
-        let mut pages_to_render = vec![];
-
-        // Previous page link
-        if page > 1 {
-            pagination_links.push_str(&format!(
-                r#"<a href="/view/{}?page={}&page_size={}" class="px-3 py-1 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 text-blue-600 dark:text-blue-300 hover:bg-gray-100 dark:hover:bg-gray-600 rounded-md">Prev</a>"#,
-                filename, page - 1, page_size
+    let job_id = job.id;
+    tokio::task::spawn_blocking(move || {
+        let result = (|| {
+            let plan: BTreeMap<usize, Vec<segments::Segment>> =
+                plan_split(&state, &filename, &files, &query)
+                    .into_iter()
+                    .filter_map(|(row, cuts)| Some((row, cuts.ok()?)))
+                    .collect();
+            extract_archived_rows(&state, &filename, 0..files.len());
+            let mut pieces = Vec::new();
+            for (row, audio) in files.iter().enumerate() {
+                match plan.get(&row) {
+                    Some(cuts) => {
+                        let wav = wav::Wav::decode(&fs::read(&audio.path)?)?;
+                        for cut in cuts {
+                            pieces.push(split::Piece {
+                                row,
+                                start: cut.start,
+                                end: cut.end,
+                                audio: split::cut(&wav, (cut.start, cut.end))?,
+                                sampling_rate: Some(wav.sample_rate),
+                                transcription: cut.text.clone(),
+                            });
+                        }
+                    }
+                    None => {
+                        let bytes = fs::read(&audio.path)?;
+                        pieces.push(split::Piece {
+                            row,
+                            start: 0.0,
+                            end: audio.duration,
+                            sampling_rate: wav::sample_rate(&bytes),
+                            audio: bytes,
+                            transcription: audio.transcription.clone(),
+                        });
+                    }
+                }
+                job.advance(1);
+            }
+            let source =
+                parquet_reader(&dataset_path(&state.tmp_folder, &state.folder, &filename))?
+                    .finish()?;
+            let mut df = split::dataframe(source, &pieces)?;
+            let out_path = state.tmp_folder.join("exports").join(format!(
+                "{}-{}-segmented.parquet",
+                job.id,
+                filename
+                    .trim_end_matches(".parquet")
+                    .trim_end_matches(".zip")
             ));
-        }
+            fs::create_dir_all(out_path.parent().unwrap())?;
+            ParquetWriter::new(File::create(&out_path)?).finish(&mut df)?;
+            Ok(Some(out_path))
+        })();
+        job.finish(result);
+    });
 
-        pages_to_render.push(1);
-        if page > window + 2 {
-            pages_to_render.push(0); // Ellipsis
-        }
+    Ok(response::Redirect::to(&format!("/jobs/{}", job_id)))
+}
 
-        for i in (page.saturating_sub(window))..=(page + window) {
-            if i > 1 && i < total_pages {
-                pages_to_render.push(i);
-            }
-        }
+/// Output encoding of the issue list.
+#[derive(Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum IssueFormat {
+    #[default]
+    Markdown,
+    Csv,
+}
 
-        if page < total_pages.saturating_sub(window + 1) {
-            pages_to_render.push(0); // Ellipsis
-        }
-        if total_pages > 1 {
-            pages_to_render.push(total_pages);
-        }
+#[derive(Deserialize, Debug)]
+struct IssueQuery {
+    #[serde(default)]
+    format: IssueFormat,
+}
 
-        pages_to_render.dedup();
+/// Scheme and host the client reached the server at, for absolute links; honors the
+/// `X-Forwarded-Proto` and `X-Forwarded-Host` headers of a reverse proxy.
+fn request_origin(headers: &http::HeaderMap) -> String {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| {
+                value
+                    .split(',')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string()
+            })
+    };
+    format!(
+        "{}://{}",
+        header("x-forwarded-proto").unwrap_or_else(|| "http".to_string()),
+        header("x-forwarded-host")
+            .or_else(|| header("host"))
+            .unwrap_or_else(|| "localhost".to_string())
+    )
+}
 
-        for p in pages_to_render {
-            if p == 0 {
-                pagination_links.push_str(r#"<span class="px-3 py-1">...</span>"#);
-            } else {
-                let class = if p == page {
-                    "px-3 py-1 bg-blue-500 text-white rounded-md"
-                } else {
-                    "px-3 py-1 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 text-blue-600 dark:text-blue-300 hover:bg-gray-100 dark:hover:bg-gray-600 rounded-md"
+/// Downloads the flagged rows of one or all files as an issue list (file, row link,
+/// tags as the reason, start of the transcription) in Markdown or CSV, for handing them
+/// over to data engineers in GitHub or Jira.
+async fn export_issues(
+    State(state): State<AppState>,
+    headers: http::HeaderMap,
+    filename: Option<AxumPath<String>>,
+    Query(query): Query<IssueQuery>,
+) -> Result<response::Response, (http::StatusCode, String)> {
+    let filename = filename.map(|AxumPath(f)| f);
+    let origin = request_origin(&headers);
+    let (body, name) = blocking(move || {
+        let mut issues = Vec::new();
+        for file in report_files(&state, filename.as_deref())? {
+            let annotations = state.annotations.file(&file);
+            if !annotations.values().any(|annotation| annotation.flagged) {
+                continue;
+            }
+            let rows = load_durations_and_transcriptions(&dataset_path(
+                &state.tmp_folder,
+                &state.folder,
+                &file,
+            ))
+            .map_err(|err| {
+                (
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to read {}: {}", file, err),
+                )
+            })?;
+            for (row, annotation) in annotations.iter().filter(|(_, a)| a.flagged) {
+                let Some((_, original)) = rows.get(*row) else {
+                    continue;
                 };
-                pagination_links.push_str(&format!(
-                    r#"<a href="/view/{}?page={}&page_size={}" class="{}">{}</a>"#,
-                    filename, p, page_size, class, p
-                ));
+                issues.push(issues::Issue {
+                    file: file.clone(),
+                    row: *row,
+                    url: format!("{}/row/{}/{}", origin, html::url_encode(&file), row),
+                    reason: annotation
+                        .tags
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    transcription: annotation.transcription().unwrap_or(original).to_string(),
+                });
             }
         }
+        let stem = filename
+            .as_deref()
+            .map_or("flagged", |file| {
+                file.trim_end_matches(".parquet").trim_end_matches(".zip")
+            })
+            .to_string();
+        Ok::<_, (http::StatusCode, String)>(match query.format {
+            IssueFormat::Markdown => (issues::markdown(&issues), format!("{}-issues.md", stem)),
+            IssueFormat::Csv => (issues::csv(&issues), format!("{}-issues.csv", stem)),
+        })
+    })
+    .await?;
 
-        // Next page link
-        if page < total_pages {
-            pagination_links.push_str(&format!(r#"<a href="/view/{}?page={}&page_size={}" class="px-3 py-1 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 text-blue-600 dark:text-blue-300 hover:bg-gray-100 dark:hover:bg-gray-600 rounded-md">Next</a>"#, filename, page + 1, page_size));
-        }
-        pagination_links
+    let content_type = if name.ends_with(".csv") {
+        "text/csv; charset=utf-8"
     } else {
-        String::new()
+        "text/markdown; charset=utf-8"
     };
+    Ok(response::Response::builder()
+        .header(http::header::CONTENT_TYPE, content_type)
+        .header(
+            http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", name),
+        )
+        .body(body::Body::from(body))
+        .unwrap())
+}
 
-    let page_size_selector = {
-        let sizes = [10, 25, 50, 100];
-        let mut options = String::new();
-        for &size in &sizes {
-            let selected = if size == page_size { "selected" } else { "" };
-            options.push_str(&format!(
-                r#"<option value="/view/{}?page=1&page_size={}" {}>{}</option>"#,
-                filename, size, selected, size
-            ));
-        }
+/// Form fields of a file note.
+#[derive(Deserialize, Debug)]
+struct NoteForm {
+    #[serde(default)]
+    text: String,
+}
 
-        format!(
-            r#"<div class="flex items-center gap-2"><span>Page size:</span><select onchange="location = this.value;" class="bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 text-gray-900 dark:text-gray-100 rounded-md p-1">{}</select></div>"#,
-            options
-        )
+/// Pins a note to a file, or removes it when blank, and returns to the file view.
+async fn save_note(
+    State(state): State<AppState>,
+    AxumPath(filename): AxumPath<String>,
+    Form(form): Form<NoteForm>,
+) -> Result<response::Redirect, (http::StatusCode, String)> {
+    if !is_valid_parquet(&state, &filename) {
+        return Err((http::StatusCode::NOT_FOUND, "File not found".to_string()));
+    }
+    let user = acl::current_session().map(|session| session.user);
+    {
+        let filename = filename.clone();
+        blocking(move || state.notes.set(&filename, &form.text, user))
+            .await
+            .map_err(|err| {
+                (
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("{:#}", err),
+                )
+            })?;
+    }
+    Ok(response::Redirect::to(&format!(
+        "/view/{}",
+        html::url_encode(&filename)
+    )))
+}
+
+/// The note pinned to a file with its author and date, and a form to change it.
+fn file_note(state: &AppState, filename: &str) -> String {
+    let note = state.notes.get(filename);
+    let form = format!(
+        r#"<details class="mb-4 text-sm">
+            <summary class="cursor-pointer text-blue-600 dark:text-blue-400">{}</summary>
+            <form method="post" action="/note/{}" class="mt-2">
+                <textarea name="text" rows="3" aria-label="{}" class="w-full p-2 rounded bg-gray-50 dark:bg-gray-700">{}</textarea>
+                <button class="mt-1 px-3 py-1 bg-blue-500 text-white rounded-md">{}</button>
+            </form>
+        </details>"#,
+        i18n::t(if note.is_some() {
+            "Edit note"
+        } else {
+            "Add note"
+        }),
+        html::url_encode(filename),
+        i18n::t("Note"),
+        note.as_ref()
+            .map(|note| html::escape(&note.text))
+            .unwrap_or_default(),
+        i18n::t("Save note"),
+    );
+    let Some(note) = note else {
+        return form;
     };
+    let author = note
+        .user
+        .as_deref()
+        .map(|user| format!("{}, ", html::escape(user)))
+        .unwrap_or_default();
+    format!(
+        r#"<div class="mb-2 p-3 rounded bg-yellow-100 text-yellow-900 dark:bg-yellow-900 dark:text-yellow-100" role="note"><div class="whitespace-pre-line">{}</div><div class="mt-1 text-xs opacity-75">{}{} UTC</div></div>{}"#,
+        html::escape(&note.text),
+        author,
+        annotations::format_timestamp(note.timestamp),
+        form
+    )
+}
 
-    let durations: Vec<f64> = files.iter().map(|a| a.duration).collect();
-    let durations_plot = plot_durations(&durations);
+/// Link to the long row splitter of a file.
+fn split_link(filename: &str) -> String {
+    format!(
+        r#" <a href="/split/{}" class="text-blue-600 dark:text-blue-400 hover:underline">{}</a>"#,
+        html::url_encode(filename),
+        i18n::t("Split long rows")
+    )
+}
 
-    let transcriptions: Vec<usize> = files.iter().map(|a| a.transcription.len()).collect();
-    let transcriptions_plot = plot_transcription_lengths(&transcriptions);
+/// Progress page of a background job; refreshes itself until the job is done.
+async fn view_job(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<usize>,
+) -> Result<Html<String>, (http::StatusCode, String)> {
+    let job = permitted_job(&state, id)?;
+
+    let percent = job.done() * 100 / job.total.max(1);
+    let (status, refresh) = match job.status() {
+        JobStatus::Running => (
+            format!("Running: {} of {} rows", job.done(), job.total),
+            true,
+        ),
+        JobStatus::Finished(Some(_)) => (
+            format!(
+                r#"Done. <a href="/jobs/{}/download" class="text-blue-600 dark:text-blue-400 hover:underline">Download export</a>"#,
+                id
+            ),
+            false,
+        ),
+        JobStatus::Finished(None) => (format!("Done: {} rows updated", job.done()), false),
+        JobStatus::Failed(err) => (
+            format!(
+                r#"<span class="text-red-700 dark:text-red-400">Failed: {}</span>"#,
+                html::escape(&err)
+            ),
+            false,
+        ),
+    };
 
-    let html = format!(
+    let content = format!(
         r#"
-<!DOCTYPE html>
-<html lang="en" class="">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>{} - Parquet Viewer</title>
-    <script src="https://cdn.tailwindcss.com"></script>
-    <script>
-        tailwind.config = {{
-            darkMode: 'class',
-    }}
-    </script>
-    <script>
-        if (localStorage.theme === 'dark' || (!('theme' in localStorage) && window.matchMedia('(prefers-color-scheme: dark)').matches)) {{
-            document.documentElement.classList.add('dark')
-        }} else {{
-            document.documentElement.classList.remove('dark')
-        }}
-        function toggleTheme() {{
-            if (localStorage.theme === 'dark') {{
-                localStorage.theme = 'light';
-                document.documentElement.classList.remove('dark');
-            }} else {{
-                localStorage.theme = 'dark';
-                document.documentElement.classList.add('dark');
-            }}
-        }}
-    </script>
-    <script>
-        document.addEventListener('play', function(e) {{
-            var audios = document.getElementsByTagName('audio');
-            for (var i = 0, len = audios.length; i < len; i++) {{
-                if (audios[i] != e.target) {{
-                    audios[i].pause();
-                }}
-            }}
-        }}, true);
-    </script>
-</head>
-<body class="bg-gray-100 dark:bg-gray-900 p-8 text-gray-900 dark:text-gray-100">
-    <div class="max-w-6xl mx-auto bg-white dark:bg-gray-800 shadow-md rounded-lg p-6 relative">
-        <div class="flex justify-between items-center mb-4">
-            <a href="/" class="text-blue-600 dark:text-blue-400 hover:underline">Back to list</a>
-            <button onclick="toggleTheme()" class="px-3 py-1 bg-gray-200 dark:bg-gray-700 rounded-md text-sm">
-                Toggle Theme
-            </button>
-        </div>
-        <h1 class="text-2xl font-bold mb-4">{}</h1>
-        <details class="mb-4 bg-gray-50 dark:bg-gray-700 p-4 rounded">
-            <summary class="font-semibold cursor-pointer">Metadata details</summary>
-            <pre class="mt-2 text-sm text-gray-600 dark:text-gray-300 whitespace-pre-wrap"><code>{}</code></pre>
-            <br>
-            <pre class="mt-2 text-sm text-gray-600 dark:text-gray-300 whitespace-pre-wrap"><code>{}</code></pre>
-        </details>
-        <div class="overflow-x-auto">
-            <table class="min-w-full w-full bg-white dark:bg-gray-800 border-collapse">
-            <thead class="hidden md:table-header-group">
-                <tr class="border-b-2 dark:border-gray-700">
-                    <th class="px-4 py-2 text-left font-semibold">Audio</th>
-                    <th class="px-4 py-2 text-right font-semibold">Duration</th>
-                    <th class="px-4 py-2 text-left font-semibold">Transcription</th>
-                </tr>
-            </thead>
-            <tbody>
-                {}
-            </tbody>
-            </table>
-        </div>
-        <div class="mt-4 flex flex-col items-center gap-4">
-            <div class="flex flex-wrap justify-center gap-2">
-                {}
-            </div>
-            <div class="flex flex-wrap justify-center gap-2">
-                {}
-            </div>
-            <div class="text-center text-sm text-gray-500 dark:text-gray-400">
-                Total audio files: {}
-            </div>
-        </div>
-    </div>
-    <footer class="text-center mt-4">
-        <a href="https://github.com/RustedBytes/data-viewer-audio" class="text-sm text-gray-500 dark:text-gray-400 hover:underline"><b>data-viewer-audio</b> on GitHub</a>
-    </footer>
-</body>
-</html>
-"#,
-        filename,
-        filename,
-        durations_plot,
-        transcriptions_plot,
-        rows,
-        pagination_html,
-        page_size_selector,
-        total_items
+    <div class="max-w-2xl mx-auto bg-white dark:bg-gray-800 shadow-md rounded-lg p-6 relative">
+        {}
+        <h1 class="text-2xl font-bold mb-4">Job #{}</h1>
+        <p class="mb-4">{}</p>
+        <div class="w-full h-4 mb-4 rounded bg-gray-200 dark:bg-gray-700"><div class="h-4 rounded bg-blue-500" style="width: {}%"></div></div>
+        <p>{}</p>
+    </div>"#,
+        html::page_header("/", i18n::t("Back to list")),
+        id,
+        html::escape(&job.description),
+        percent,
+        status
     );
+    let head = if refresh {
+        r#"<meta http-equiv="refresh" content="1">"#
+    } else {
+        ""
+    };
+    Ok(Html(html::render_page(
+        &format!("Job #{}", id),
+        head,
+        &content,
+    )))
+}
+
+/// A job, if the user may do what it does with its file; job ids are sequential, so
+/// they are no secret.
+fn permitted_job(state: &AppState, id: usize) -> Result<Arc<Job>, (http::StatusCode, String)> {
+    let job = state
+        .jobs
+        .get(id)
+        .ok_or((http::StatusCode::NOT_FOUND, "Job not found".to_string()))?;
+    if !allows(state, &job.file, job.permission) {
+        return Err((
+            http::StatusCode::FORBIDDEN,
+            format!(
+                "You may not {} {}",
+                format!("{:?}", job.permission).to_lowercase(),
+                job.file
+            ),
+        ));
+    }
+    Ok(job)
+}
 
-    Html(html)
+/// Downloads the file produced by an export job.
+async fn download_job(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<usize>,
+) -> Result<response::Response, http::StatusCode> {
+    let job = permitted_job(&state, id).map_err(|(status, _)| status)?;
+    let JobStatus::Finished(Some(path)) = job.status() else {
+        return Err(http::StatusCode::NOT_FOUND);
+    };
+    let download_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    stream_file(
+        &path,
+        &[
+            (
+                http::header::CONTENT_TYPE,
+                "application/octet-stream".to_string(),
+            ),
+            (
+                http::header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", download_name),
+            ),
+        ],
+    )
+    .await
 }
 
-/// Serves audio files from the temporary folder.
-async fn serve_audio(
+/// Scores a single clip with the external quality model, then returns to its detail page.
+async fn score_row(
     State(state): State<AppState>,
     AxumPath((filename, index)): AxumPath<(String, String)>,
-) -> Result<response::Response, http::StatusCode> {
-    let audio_path = state
-        .tmp_folder
-        .join(&filename)
-        .join(format!("{}.wav", index));
+) -> Result<response::Redirect, (http::StatusCode, String)> {
+    let scorer = state.scorer.as_ref().ok_or((
+        http::StatusCode::NOT_FOUND,
+        "Scoring is not configured".to_string(),
+    ))?;
+    let audio_path = extracted_audio_path(&state, &filename, &index)
+        .ok_or((http::StatusCode::NOT_FOUND, "Audio not found".to_string()))?;
 
-    if !audio_path.exists() || !audio_path.is_file() {
-        return Err(http::StatusCode::NOT_FOUND);
+    scorer
+        .score(&audio_path)
+        .await
+        .map_err(|err| (http::StatusCode::BAD_GATEWAY, format!("{:#}", err)))?;
+    Ok(response::Redirect::to(&format!(
+        "/row/{}/{}",
+        html::url_encode(&filename),
+        html::url_encode(&index)
+    )))
+}
+
+/// Scores every clip of a file, then shows the file sorted by descending score.
+async fn score_file(
+    State(state): State<AppState>,
+    AxumPath(filename): AxumPath<String>,
+) -> Result<response::Redirect, (http::StatusCode, String)> {
+    let scorer = state.scorer.as_ref().ok_or((
+        http::StatusCode::NOT_FOUND,
+        "Scoring is not configured".to_string(),
+    ))?;
+    if !is_valid_parquet(&state, &filename) {
+        return Err((http::StatusCode::NOT_FOUND, "File not found".to_string()));
     }
 
-    let file = tokio::fs::File::open(&audio_path)
+    let paths = {
+        let (state, filename) = (state.clone(), filename.clone());
+        blocking(move || {
+            let audios = extract_parquet_file(&state.tmp_folder, &state.folder, &filename);
+            extract_archived_rows(&state, &filename, 0..audios.len());
+            audios.into_iter().map(|audio| audio.path).collect()
+        })
         .await
-        .map_err(|_| http::StatusCode::INTERNAL_SERVER_ERROR)?;
+    };
+    let (scored, errors) = scorer.score_all(paths).await;
+    if let Some(first) = errors.first() {
+        return Err((
+            http::StatusCode::BAD_GATEWAY,
+            format!(
+                "Scored {} of {} rows. First error: {:#}",
+                scored,
+                scored + errors.len(),
+                first
+            ),
+        ));
+    }
+    Ok(response::Redirect::to(&format!(
+        "/view/{}?sort=-score",
+        html::url_encode(&filename)
+    )))
+}
 
-    let stream = io::ReaderStream::new(file);
-    let body = body::Body::from_stream(stream);
+/// Serves a low-bitrate Opus preview, transcoding and caching it on first request.
+async fn serve_preview(
+    State(state): State<AppState>,
+    AxumPath((filename, index)): AxumPath<(String, String)>,
+) -> Result<response::Response, http::StatusCode> {
+    let config = state.preview.as_ref().ok_or(http::StatusCode::NOT_FOUND)?;
+    let audio_path =
+        extracted_audio_path(&state, &filename, &index).ok_or(http::StatusCode::NOT_FOUND)?;
 
-    Ok(response::Response::builder()
-        .header("Content-Type", "audio/wav")
-        .body(body)
-        .unwrap())
+    let container = preview::Container::Ogg;
+    let preview_path = preview::ensure_preview(
+        config,
+        &blobs::BlobStore::new(&state.tmp_folder),
+        &audio_path,
+        container,
+    )
+    .await
+    .map_err(|err| {
+        eprintln!(
+            "Preview transcoding failed for {}: {:#}",
+            audio_path.display(),
+            err
+        );
+        http::StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    stream_file(
+        &preview_path,
+        &[(
+            http::header::CONTENT_TYPE,
+            container.content_type().to_string(),
+        )],
+    )
+    .await
 }
 
 #[tokio::main]
@@ -664,25 +8929,448 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let tmp_folder = PathBuf::from(args.tmp_folder.clone());
     if tmp_folder.exists() && tmp_folder.is_dir() {
-        fs::remove_dir_all(&tmp_folder)?;
+        // Extracted audio is recreated on demand; the folder index and the blobs the
+        // audio is linked to are kept.
+        for entry in fs::read_dir(&tmp_folder)? {
+            let entry = entry?;
+            if entry.file_name() == folder_index::FILE_NAME || entry.file_name() == blobs::DIR_NAME
+            {
+                continue;
+            }
+            if entry.file_type()?.is_dir() {
+                fs::remove_dir_all(entry.path())?;
+            } else {
+                fs::remove_file(entry.path())?;
+            }
+        }
     }
     fs::create_dir_all(&tmp_folder)?;
+    let folder_index = Arc::new(FolderIndex::load(tmp_folder.join(folder_index::FILE_NAME)));
     if !tmp_folder.exists() || !tmp_folder.is_dir() {
         return Err("Provided tmp_folder does not exist or is not a directory".into());
     }
 
-    let state = AppState { folder, tmp_folder };
+    let tokenizer = match &args.tokenizer {
+        Some(path) => Some(Arc::new(Tokenizer::from_file(path).map_err(|err| {
+            format!("Failed to load tokenizer {}: {}", path.display(), err)
+        })?)),
+        None => None,
+    };
+
+    let blocklist = match &args.blocklist {
+        Some(path) => Some(Arc::new(Blocklist::load(path)?)),
+        None => None,
+    };
+
+    let preview = args.preview.then(|| PreviewConfig {
+        ffmpeg: args.ffmpeg.clone(),
+        bitrate: args.preview_bitrate.clone(),
+    });
+
+    let _ = PARQUET_READ.set(args.parquet_read);
+    if args.check_schema {
+        let files = list_parquet_files(&folder);
+        let columns =
+            schema_check::compare(&file_schemas(&tmp_folder, &folder, &folder_index, &files)?);
+        print!("{}", schema_check::render_text(&files, &columns));
+        if columns.iter().all(|column| column.consistent()) {
+            return Ok(());
+        }
+        return Err("Schemas differ between files".into());
+    }
+    if args.engine == Engine::Duckdb && !cfg!(feature = "duckdb") {
+        return Err("This build has no DuckDB support; rebuild with `--features duckdb`".into());
+    }
+    let _ = ENGINE.set(args.engine);
+    let _ = PSEUDO_LABEL_COLUMN.set(args.pseudo_label_column.clone());
+    if let Some(path) = &args.column_renderers {
+        let _ = COLUMN_RENDERERS.set(columns::Renderers::load(path)?);
+    }
+    i18n::init(&args.lang, args.locale_dir.as_deref())?;
+    templates::init(
+        args.templates.as_deref(),
+        &templates::Branding {
+            name: args.instance_name.clone(),
+            logo_url: args.logo_url.clone(),
+            footer: args.footer_text.clone(),
+        },
+    )?;
+
+    let plugins = args
+        .plugins
+        .iter()
+        .map(Plugin::load)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let annotations_path = args
+        .annotations
+        .clone()
+        .unwrap_or_else(|| folder.join("annotations.json"));
+    let annotations = AnnotationStore::open(annotations_path)?;
+    let notes = notes::NoteStore::open(
+        args.notes
+            .clone()
+            .unwrap_or_else(|| folder.join("notes.json")),
+    )?;
+    let oidc = match (
+        &args.oidc_issuer,
+        &args.oidc_client_id,
+        &args.oidc_redirect_url,
+    ) {
+        (Some(issuer), Some(client_id), Some(redirect_url)) => {
+            let config = auth::OidcConfig {
+                issuer: issuer.clone(),
+                client_id: client_id.clone(),
+                client_secret: args.oidc_client_secret.clone(),
+                redirect_url: redirect_url.clone(),
+                groups_claim: args.oidc_groups_claim.clone(),
+                allowed_groups: args.oidc_groups.iter().cloned().collect(),
+            };
+            Some(Arc::new(auth::Oidc::discover(config).await?))
+        }
+        _ => None,
+    };
+
+    let search_index = args
+        .search_index
+        .as_deref()
+        .map(SearchIndex::open)
+        .transpose()?
+        .map(Arc::new);
+
+    let state = AppState {
+        folder,
+        tmp_folder,
+        preview,
+        analysis: AnalysisCache::default(),
+        text_normalizer: TextNormalizer::new(args.text_normalization.clone()),
+        tokenizer,
+        alphabet: args
+            .alphabet
+            .as_ref()
+            .map(|chars| Arc::new(chars.chars().collect())),
+        blocklist,
+        dictionary: if args.dictionaries.is_empty() {
+            None
+        } else {
+            Some(Arc::new(spellcheck::Dictionary::load(&args.dictionaries)?))
+        },
+        scorer: args
+            .score_url
+            .clone()
+            .map(|url| Scorer::new(url, args.score_field.clone(), args.score_concurrency)),
+        embedder: args.embedding_url.clone().map(|url| {
+            Embedder::new(
+                url,
+                args.embedding_field.clone(),
+                args.embedding_concurrency,
+            )
+        }),
+        speaker_column: args.speaker_column.clone(),
+        commands: (!args.commands.is_empty()).then(|| {
+            CommandRunner::new(
+                args.commands.clone(),
+                Duration::from_secs(args.command_timeout),
+                args.cache_command_output,
+            )
+        }),
+        transcriber: (!args.asr_models.is_empty())
+            .then(|| asr::Transcriber::new(args.asr_models.clone(), args.asr_field.clone())),
+        annotations,
+        notes,
+        webhooks: (!args.webhooks.is_empty())
+            .then(|| webhooks::Webhooks::new(args.webhooks.clone())),
+        jobs: JobRegistry::default(),
+        plugins: Arc::new(plugins),
+        markdown: args.markdown,
+        audio_events: args.audio_events,
+        noise_floor: args.noise_floor,
+        noise_bank: args
+            .noise_dir
+            .as_deref()
+            .map(augment::SoundBank::load)
+            .transpose()?
+            .map(Arc::new),
+        rir_bank: args
+            .rir_dir
+            .as_deref()
+            .map(augment::SoundBank::load)
+            .transpose()?
+            .map(Arc::new),
+        key_bindings: Arc::new(
+            args.key_bindings
+                .as_deref()
+                .map(shortcuts::load)
+                .transpose()?
+                .unwrap_or_default(),
+        ),
+        wpm_range: (args.min_wpm, args.max_wpm),
+        search_index,
+        folder_index,
+        filtered: Arc::default(),
+        token_counts: Arc::default(),
+        mutations: Arc::default(),
+        oidc,
+        acl: args
+            .acl
+            .as_deref()
+            .map(acl::Acl::load)
+            .transpose()?
+            .map(Arc::new),
+        audit: args
+            .audit_log
+            .as_deref()
+            .map(audit::AuditLog::open)
+            .transpose()?
+            .map(Arc::new),
+        audit_admins: Arc::new(args.audit_admins.clone()),
+        preferences: Arc::new(preferences::Signer::new(
+            args.preferences_secret.as_deref(),
+        )?),
+        g2p: Arc::new(g2p::G2p {
+            espeak: args.espeak.clone(),
+            language: args.g2p_language.clone(),
+            inventory: args
+                .phoneme_inventory
+                .as_deref()
+                .map(g2p::G2p::read_inventory)
+                .transpose()?,
+        }),
+        playback: Playback {
+            preload: args.audio_preload,
+            players_per_page: args.players_per_page,
+            concurrent: args.concurrent_playback,
+        },
+    };
+
+    if args.write_stats {
+        for file in list_parquet_files(&state.folder) {
+            let summary = compute_summary(&state, &file);
+            let sidecar = stats::write_sidecar(&state.folder, &file, &summary)?;
+            println!("{}: {} -> {}", file, summary.label(), sidecar.display());
+        }
+        return Ok(());
+    }
+
+    if let Some(out) = &args.analysis_out {
+        write_analysis_columns(&state, out)?;
+        return Ok(());
+    }
+
+    if args.write_hashes {
+        for file in list_parquet_files(&state.folder) {
+            let hashes = audio_hashes(&state, &file);
+            let sidecar = integrity::write_sidecar(&state.folder, &file, &hashes)?;
+            println!(
+                "{}: {} rows -> {}",
+                file,
+                hashes.rows.len(),
+                sidecar.display()
+            );
+        }
+        return Ok(());
+    }
+
+    if args.verify_hashes {
+        let mut differing = 0;
+        for file in list_parquet_files(&state.folder) {
+            let Some(recorded) = integrity::read_sidecar(&state.folder, &file)? else {
+                println!("{}: no hashes recorded", file);
+                differing += 1;
+                continue;
+            };
+            let current = audio_hashes(&state, &file);
+            let changes = integrity::compare(&recorded, &current);
+            for (row, change) in &changes {
+                println!("{}: row {}: {}", file, row, change.describe());
+            }
+            if changes.is_empty() {
+                println!("{}: {} rows unchanged", file, current.rows.len());
+            } else {
+                differing += 1;
+            }
+        }
+        if differing == 0 {
+            return Ok(());
+        }
+        return Err(format!("{} files differ from their recorded hashes", differing).into());
+    }
+
+    if let Some(path) = &args.dataset_card {
+        let splits = list_parquet_files(&state.folder)
+            .iter()
+            .map(|file| card_split(&state, file, &args.language_column))
+            .collect::<Vec<_>>();
+        let name = state
+            .folder
+            .canonicalize()?
+            .file_name()
+            .map_or("Dataset".to_string(), |name| {
+                name.to_string_lossy().into_owned()
+            });
+        fs::write(path, card::render(&name, &splits))?;
+        println!("Dataset card -> {}", path.display());
+        return Ok(());
+    }
+
+    if args.recompute_durations {
+        let off = recompute_durations(
+            &state,
+            &args.ffmpeg,
+            args.duration_tolerance,
+            args.durations_out.as_deref(),
+        )?;
+        if off == 0 || args.durations_out.is_some() {
+            return Ok(());
+        }
+        return Err(format!("{} durations are off", off).into());
+    }
+
+    // Scan the files changed since the last run in the background.
+    {
+        let state = state.clone();
+        tokio::task::spawn_blocking(move || {
+            match refresh_folder_index(&state.tmp_folder, &state.folder, &state.folder_index) {
+                Ok(rescanned) => println!("Folder index up to date ({} files scanned)", rescanned),
+                Err(err) => eprintln!("Failed to scan the data folder: {:#}", err),
+            }
+        });
+    }
+
+    // Build the search index in the background; searches wait for it to finish.
+    if let Some(index) = state.search_index.clone() {
+        let state = state.clone();
+        tokio::task::spawn_blocking(move || match refresh_search_index(&state, &index) {
+            Ok(reindexed) => println!("Search index up to date ({} files reindexed)", reindexed),
+            Err(err) => eprintln!("Failed to build the search index: {:#}", err),
+        });
+    }
+
+    if let Some(schedule::Every(period)) = args.reindex_every {
+        let state = state.clone();
+        tokio::spawn(schedule::run_every(period, move || {
+            let state = state.clone();
+            async move {
+                if let Err(err) = tokio::task::spawn_blocking(move || reindex(&state)).await {
+                    eprintln!("Scheduled reindex failed: {}", err);
+                }
+            }
+        }));
+    }
 
     let app = Router::new()
         .route("/", get(list_files))
+        .route("/search", get(search))
         .route("/view/{filename}", get(view_file))
+        .route("/row/{filename}/{row}", get(view_row))
+        .route("/edit/{filename}/{row}", post(edit_row))
+        .route("/undo/{filename}/{row}", post(undo_row))
+        .route("/restore/{filename}/{row}", post(restore_row))
         .route("/audio/{filename}/{index}", get(serve_audio))
+        .route("/download/{filename}/{index}", get(download_audio))
+        .route("/concat/{filename}", get(serve_concat))
+        .route("/image/{filename}/{index}/{column}", get(serve_image))
+        .route("/preview/{filename}/{index}", get(serve_preview))
+        .route("/features/{filename}/{index}", get(serve_features))
+        .route("/pitch/{filename}/{index}", get(serve_pitch))
+        .route("/energy/{filename}/{index}", get(serve_energy))
+        .route("/augment/{filename}/{index}", get(serve_augmented))
+        .route("/score/{filename}", post(score_file))
+        .route("/score/{filename}/{index}", post(score_row))
+        .route("/run/{filename}/{index}/{name}", post(run_command))
+        .route("/asr/{filename}/{row}", post(transcribe_row))
+        .route("/comment/{filename}/{row}", post(comment_row))
+        .route("/batch/{filename}", get(confirm_batch).post(start_batch))
+        .route("/split/{filename}", get(preview_split).post(start_split))
+        .route("/note/{filename}", post(save_note))
+        .route("/issues", get(export_issues))
+        .route("/issues/{filename}", get(export_issues))
+        .route("/jobs/{id}", get(view_job))
+        .route("/export/{filename}", get(export_annotated_rows))
+        .route("/api/rows/{filename}", get(api_rows))
+        .route("/jobs/{id}/download", get(download_job))
+        .route("/audit", get(export_audit))
+        .route("/charset", get(charset_report))
+        .route("/charset/{filename}", get(charset_report))
+        .route("/spelling", get(spelling_report))
+        .route("/spelling/{filename}", get(spelling_report))
+        .route("/expansions", get(expansion_report))
+        .route("/expansions/{filename}", get(expansion_report))
+        .route("/splits", get(splits_report))
+        .route("/splits/speakers", get(speaker_leakage_report))
+        .route("/splits/text", get(text_leakage_report))
+        .route("/style", get(style_report))
+        .route("/style/{filename}", get(style_report))
+        .route("/ngrams", get(ngram_report))
+        .route("/ngrams/{filename}", get(ngram_report))
+        .route("/duplicates", get(duplicates_report))
+        .route("/duplicates/{filename}", get(duplicates_report))
+        .route("/phonemes", get(phoneme_report))
+        .route("/phonemes/{filename}", get(phoneme_report))
+        .route("/speakers", get(speakers_report).post(embed_speakers))
+        .route(
+            "/speakers/{filename}",
+            get(speakers_report).post(embed_speakers),
+        )
+        .route("/map", get(embedding_map))
+        .route("/map/{filename}", get(embedding_map))
+        .route("/completeness", get(completeness_report))
+        .route("/completeness/{filename}", get(completeness_report))
+        .route("/schema", get(schema_report))
+        .route("/schema/{filename}", get(schema_report))
+        .route("/wpm", get(wpm_report))
+        .route("/wpm/{filename}", get(wpm_report))
+        .route("/agreement", get(agreement_report))
+        .route("/agreement/{filename}", get(agreement_report))
+        .route("/encodings", get(encoding_report))
+        .route("/encodings/{filename}", get(encoding_report))
+        .route("/stats/{filename}", get(file_stats))
+        .route("/auth/login", get(login))
+        .route("/auth/callback", get(auth_callback))
+        .route("/auth/logout", get(logout))
+        .route("/preferences", post(save_preferences))
+        .route("/live/{filename}", get(live_updates))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            conditional_get,
+        ))
+        .route_layer(middleware::from_fn_with_state(state.clone(), enforce_acl))
+        .layer(middleware::from_fn_with_state(state.clone(), require_login))
+        .layer(middleware::from_fn(error_page))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            with_preferences,
+        ))
         .with_state(state);
+    let app = match args.access_log {
+        Some(format) => {
+            let log = access_log::AccessLog::open(format, args.access_log_file.as_deref())?;
+            app.layer(middleware::from_fn_with_state(
+                Arc::new(log),
+                access_log::record,
+            ))
+        }
+        None => app,
+    };
 
-    println!("Listening on http://{}", args.bind);
+    let listener = server::bind(&args.bind, args.port_fallback).await?;
+    let urls = server::urls(listener.local_addr()?);
+    println!("Listening on {}", listener.local_addr()?);
+    for url in &urls {
+        println!("  {}", url);
+    }
+    if args.open
+        && let Err(err) = server::open_browser(&urls[0])
+    {
+        eprintln!("Failed to open a browser: {}", err);
+    }
 
-    let listener = TcpListener::bind(&args.bind).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 
     Ok(())
 }
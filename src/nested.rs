@@ -0,0 +1,113 @@
+use crate::html;
+use polars::prelude::*;
+use serde_json::Value;
+
+/// Arrays longer than this (alignments, token lists, ...) show their first items only.
+const MAX_ITEMS: usize = 100;
+
+/// Names of the struct and list columns, which have no scalar rendering.
+///
+/// `skip` names columns already rendered in another way, such as segments or images.
+pub fn nested_columns(df: &DataFrame, skip: &[&str]) -> Vec<String> {
+    df.columns()
+        .iter()
+        .filter(|column| {
+            let name = column.name().as_str();
+            matches!(column.dtype(), DataType::Struct(_) | DataType::List(_))
+                && !name.starts_with("audio_")
+                && !skip.contains(&name)
+        })
+        .map(|column| column.name().to_string())
+        .collect()
+}
+
+/// Converts a cell to JSON; binary data becomes a byte count.
+pub fn to_json(value: AnyValue) -> Value {
+    match value.into_static() {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(b) => Value::Bool(b),
+        AnyValue::String(s) => Value::String(s.to_string()),
+        AnyValue::StringOwned(s) => Value::String(s.to_string()),
+        AnyValue::Binary(bytes) => Value::String(format!("<{} bytes>", bytes.len())),
+        AnyValue::BinaryOwned(bytes) => Value::String(format!("<{} bytes>", bytes.len())),
+        AnyValue::List(series) => Value::Array(series.iter().map(to_json).collect()),
+        AnyValue::StructOwned(payload) => {
+            let (values, fields) = *payload;
+            Value::Object(
+                fields
+                    .iter()
+                    .zip(values)
+                    .map(|(field, value)| (field.name().to_string(), to_json(value)))
+                    .collect(),
+            )
+        }
+        value => match value.extract::<f64>() {
+            Some(number) if value.is_integer() => Value::from(number as i64),
+            Some(number) => serde_json::Number::from_f64(number)
+                .map(Value::Number)
+                .unwrap_or(Value::Null),
+            None => Value::String(value.to_string()),
+        },
+    }
+}
+
+/// Renders a JSON value as a tree of collapsible `<details>` elements; only the root is
+/// expanded when `open` is set.
+pub fn tree(value: &Value, open: bool) -> String {
+    match value {
+        Value::Object(map) => collapsible(
+            &format!("{{{} keys}}", map.len()),
+            map.iter().map(|(key, value)| (html::escape(key), value)),
+            map.len(),
+            open,
+        ),
+        Value::Array(items) => collapsible(
+            &format!("[{} items]", items.len()),
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, value)| (i.to_string(), value)),
+            items.len(),
+            open,
+        ),
+        Value::String(s) => format!(
+            r#"<span class="text-green-700 dark:text-green-400">"{}"</span>"#,
+            html::escape(s)
+        ),
+        Value::Null => r#"<span class="text-gray-500">null</span>"#.to_string(),
+        scalar => format!(
+            r#"<span class="text-blue-700 dark:text-blue-400">{}</span>"#,
+            scalar
+        ),
+    }
+}
+
+fn collapsible<'a>(
+    summary: &str,
+    children: impl Iterator<Item = (String, &'a Value)>,
+    len: usize,
+    open: bool,
+) -> String {
+    let mut items: String = children
+        .take(MAX_ITEMS)
+        .map(|(key, value)| {
+            format!(
+                r#"<li><span class="font-semibold">{}</span>: {}</li>"#,
+                key,
+                tree(value, false)
+            )
+        })
+        .collect();
+    if len > MAX_ITEMS {
+        items.push_str(&format!(
+            r#"<li class="text-gray-500">&hellip; {} more</li>"#,
+            len - MAX_ITEMS
+        ));
+    }
+    format!(
+        r#"<details class="inline-block align-top font-mono text-xs"{} onclick="event.stopPropagation()"><summary class="cursor-pointer text-gray-600 dark:text-gray-400">{}</summary><ul class="ml-4">{}</ul></details>"#,
+        if open { " open" } else { "" },
+        summary,
+        items
+    )
+}
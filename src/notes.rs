@@ -0,0 +1,83 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Free-text note pinned to a file, e.g. "v3 after profanity filtering, do not use for
+/// eval".
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Note {
+    pub text: String,
+    /// Unix time in seconds of the last change.
+    pub timestamp: u64,
+    /// Logged-in user who wrote it, when OIDC login is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// Notes per file, persisted as a JSON file rewritten on every change, next to the
+/// annotation store.
+#[derive(Clone)]
+pub struct NoteStore {
+    path: PathBuf,
+    notes: Arc<Mutex<BTreeMap<String, Note>>>,
+}
+
+impl NoteStore {
+    /// Opens the store at `path`, starting empty if the file does not exist yet.
+    pub fn open(path: PathBuf) -> anyhow::Result<Self> {
+        let notes = if path.is_file() {
+            let data =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            serde_json::from_slice(&data)
+                .with_context(|| format!("failed to parse {}", path.display()))?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self {
+            path,
+            notes: Arc::new(Mutex::new(notes)),
+        })
+    }
+
+    pub fn get(&self, filename: &str) -> Option<Note> {
+        self.notes.lock().unwrap().get(filename).cloned()
+    }
+
+    /// Notes of every file.
+    pub fn all(&self) -> BTreeMap<String, Note> {
+        self.notes.lock().unwrap().clone()
+    }
+
+    /// Replaces the note of a file, or removes it when `text` is blank, and saves the
+    /// store.
+    pub fn set(&self, filename: &str, text: &str, user: Option<String>) -> anyhow::Result<()> {
+        let mut notes = self.notes.lock().unwrap();
+        let text = text.trim();
+        if text.is_empty() {
+            notes.remove(filename);
+        } else {
+            notes.insert(
+                filename.to_string(),
+                Note {
+                    text: text.replace("\r\n", "\n"),
+                    timestamp: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |elapsed| elapsed.as_secs()),
+                    user,
+                },
+            );
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(&*notes)?)
+            .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("failed to replace {}", self.path.display()))?;
+        Ok(())
+    }
+}
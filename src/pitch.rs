@@ -0,0 +1,118 @@
+use crate::dsp;
+use anyhow::ensure;
+use serde::{Deserialize, Serialize};
+
+/// Cumulative mean normalized difference below which a lag counts as a period (YIN).
+const YIN_THRESHOLD: f32 = 0.15;
+/// Frames quieter than this are unvoiced without searching for a period.
+const SILENCE_DBFS: f32 = -50.0;
+
+/// Parameters of the F0 tracker; the defaults cover adult speech at 10 ms frames.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PitchParams {
+    #[serde(default = "default_f_min")]
+    pub f_min: f32,
+    #[serde(default = "default_f_max")]
+    pub f_max: f32,
+    #[serde(default = "default_hop_length")]
+    pub hop_length: usize,
+}
+
+fn default_f_min() -> f32 {
+    60.0
+}
+
+fn default_f_max() -> f32 {
+    500.0
+}
+
+fn default_hop_length() -> usize {
+    160
+}
+
+/// F0 per frame; frame `i` is centered on sample `i * hop_length`, like the STFT frames
+/// of the feature endpoint.
+#[derive(Serialize)]
+pub struct PitchTrack {
+    pub sample_rate: u32,
+    pub hop_length: usize,
+    /// Fundamental frequency in Hz, `null` where the frame is unvoiced.
+    pub f0: Vec<Option<f32>>,
+}
+
+/// Tracks F0 of a mono signal with the YIN algorithm.
+pub fn track(
+    samples: &[f32],
+    sample_rate: u32,
+    params: &PitchParams,
+) -> anyhow::Result<PitchTrack> {
+    ensure!(params.hop_length > 0, "hop_length must be positive");
+    ensure!(
+        params.f_min > 0.0 && params.f_min < params.f_max,
+        "f_min must be positive and below f_max"
+    );
+    let rate = sample_rate as f32;
+    ensure!(
+        params.f_max < rate / 2.0,
+        "f_max must be below the Nyquist frequency"
+    );
+
+    let max_lag = (rate / params.f_min).ceil() as usize;
+    let min_lag = ((rate / params.f_max).floor() as usize).max(2);
+    // One period of the lowest pitch is integrated per lag.
+    let window = max_lag;
+    let frames = if samples.is_empty() {
+        0
+    } else {
+        1 + samples.len() / params.hop_length
+    };
+
+    let f0 = (0..frames)
+        .map(|frame| {
+            let start = (frame * params.hop_length).checked_sub(window / 2)?;
+            let frame = samples.get(start..start + window + max_lag)?;
+            yin(frame, window, min_lag, max_lag).map(|lag| rate / lag)
+        })
+        .collect();
+
+    Ok(PitchTrack {
+        sample_rate,
+        hop_length: params.hop_length,
+        f0,
+    })
+}
+
+/// Period of `frame` in samples (with sub-sample precision), if it is voiced.
+fn yin(frame: &[f32], window: usize, min_lag: usize, max_lag: usize) -> Option<f32> {
+    if dsp::to_dbfs(dsp::rms(&frame[..window])) < SILENCE_DBFS {
+        return None;
+    }
+    let mut normalized = vec![1.0f32; max_lag + 1];
+    let mut running = 0.0f32;
+    for lag in 1..=max_lag {
+        let difference: f32 = (0..window)
+            .map(|i| {
+                let delta = frame[i] - frame[i + lag];
+                delta * delta
+            })
+            .sum();
+        running += difference;
+        if running > 0.0 {
+            normalized[lag] = difference * lag as f32 / running;
+        }
+    }
+
+    // First dip below the threshold, followed down to its minimum.
+    let mut lag = (min_lag..max_lag).find(|&lag| normalized[lag] < YIN_THRESHOLD)?;
+    while lag + 1 < max_lag && normalized[lag + 1] < normalized[lag] {
+        lag += 1;
+    }
+    let (before, at, after) = (normalized[lag - 1], normalized[lag], normalized[lag + 1]);
+    let curvature = before - 2.0 * at + after;
+    let shift = if curvature.abs() > f32::EPSILON {
+        0.5 * (before - after) / curvature
+    } else {
+        0.0
+    };
+    Some(lag as f32 + shift.clamp(-0.5, 0.5))
+}
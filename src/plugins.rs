@@ -0,0 +1,168 @@
+use anyhow::{Context, anyhow, ensure};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Instructions a plugin may execute per row before it is aborted.
+const FUEL_PER_CALL: u64 = 100_000_000;
+
+/// Maximum linear memory of a plugin instance.
+const MAX_MEMORY: usize = 64 << 20;
+
+/// A `--plugin NAME=PATH` argument.
+#[derive(Clone, Debug)]
+pub struct PluginSpec {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl FromStr for PluginSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, path) = s
+            .split_once('=')
+            .ok_or_else(|| "expected NAME=PATH".to_string())?;
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err("plugin names may only contain letters, digits, '-' and '_'".to_string());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+/// Row data passed to plugins, serialized as JSON.
+#[derive(Serialize)]
+pub struct RowData<'a> {
+    pub file: &'a str,
+    pub row: usize,
+    pub duration: f64,
+    pub transcription: &'a str,
+}
+
+/// Result of running a plugin on one row.
+#[derive(Clone, Debug, Default)]
+pub struct PluginOutput {
+    pub score: Option<f64>,
+    /// HTML for the plugin's table cell.
+    pub html: Option<String>,
+}
+
+/// A WebAssembly plugin adding a column to the file view.
+///
+/// The module must export `memory` and `alloc(len: i32) -> i32`, plus at least one of
+/// `score(ptr: i32, len: i32) -> f64` and `render(ptr: i32, len: i32) -> i64`. Both
+/// receive the row as UTF-8 JSON (see [`RowData`]) written into the buffer returned by
+/// `alloc`; `render` returns the HTML cell content as `(ptr << 32) | len`.
+///
+/// Each row runs in a fresh instance without imports, with bounded fuel and memory.
+#[derive(Clone)]
+pub struct Plugin {
+    pub name: String,
+    engine: Engine,
+    module: Module,
+    has_score: bool,
+    has_render: bool,
+    cache: Arc<Mutex<HashMap<String, PluginOutput>>>,
+}
+
+impl Plugin {
+    /// Compiles a `.wasm` (or `.wat`) plugin and checks its exports.
+    pub fn load(spec: &PluginSpec) -> anyhow::Result<Self> {
+        let bytes = std::fs::read(&spec.path)
+            .with_context(|| format!("failed to read {}", spec.path.display()))?;
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, &bytes)
+            .map_err(|err| anyhow!("invalid plugin {}: {}", spec.path.display(), err))?;
+
+        let has_export = |name: &str| module.exports().any(|export| export.name() == name);
+        ensure!(
+            has_export("memory") && has_export("alloc"),
+            "plugin {} must export `memory` and `alloc`",
+            spec.name
+        );
+        let (has_score, has_render) = (has_export("score"), has_export("render"));
+        ensure!(
+            has_score || has_render,
+            "plugin {} must export `score` or `render`",
+            spec.name
+        );
+
+        Ok(Self {
+            name: spec.name.clone(),
+            engine,
+            module,
+            has_score,
+            has_render,
+            cache: Arc::default(),
+        })
+    }
+
+    /// Runs the plugin on a row, reusing the result for identical row data.
+    pub fn run(&self, row: &RowData) -> anyhow::Result<PluginOutput> {
+        let input = serde_json::to_string(row)?;
+        if let Some(output) = self.cache.lock().unwrap().get(&input) {
+            return Ok(output.clone());
+        }
+        let output = self
+            .call(input.as_bytes())
+            .map_err(|err| anyhow!("plugin {}: {}", self.name, err))?;
+        self.cache.lock().unwrap().insert(input, output.clone());
+        Ok(output)
+    }
+
+    fn call(&self, input: &[u8]) -> Result<PluginOutput, wasmi::Error> {
+        let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+        let mut store: Store<StoreLimits> = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL_PER_CALL)?;
+
+        let instance = Linker::<StoreLimits>::new(&self.engine)
+            .instantiate_and_start(&mut store, &self.module)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| wasmi::Error::new("missing `memory` export"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc")?;
+
+        let len = input.len() as i32;
+        let write_input = |store: &mut Store<StoreLimits>| -> Result<i32, wasmi::Error> {
+            let ptr = alloc.call(&mut *store, len)?;
+            memory.write(&mut *store, ptr as u32 as usize, input)?;
+            Ok(ptr)
+        };
+
+        let mut output = PluginOutput::default();
+        if self.has_score {
+            let score = instance.get_typed_func::<(i32, i32), f64>(&store, "score")?;
+            let ptr = write_input(&mut store)?;
+            output.score = Some(score.call(&mut store, (ptr, len))?).filter(|s| s.is_finite());
+        }
+        if self.has_render {
+            let render = instance.get_typed_func::<(i32, i32), i64>(&store, "render")?;
+            let ptr = write_input(&mut store)?;
+            let packed = render.call(&mut store, (ptr, len))? as u64;
+            let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xFFFF_FFFF) as usize);
+            if out_len > MAX_MEMORY {
+                return Err(wasmi::Error::new("`render` output is too large"));
+            }
+            let mut html = vec![0u8; out_len];
+            memory.read(&store, out_ptr, &mut html)?;
+            output.html = Some(String::from_utf8_lossy(&html).into_owned());
+        }
+        Ok(output)
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::anyhow;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Cookie holding the signed preferences.
+pub const PREFERENCES_COOKIE: &str = "dva_prefs";
+/// Preferences are kept for a year after the last change.
+const MAX_AGE_SECS: u64 = 365 * 24 * 3600;
+/// Page sizes offered by the file view.
+pub const PAGE_SIZES: [usize; 4] = [10, 25, 50, 100];
+/// Largest page size a URL can ask for.
+pub const MAX_PAGE_SIZE: usize = PAGE_SIZES[PAGE_SIZES.len() - 1];
+
+/// View settings remembered for a browser.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Hash)]
+pub struct Preferences {
+    /// `light` or `dark`; unset follows the system setting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Page size of the file view when the URL names none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<usize>,
+    /// Optional file view columns left out, by the key of the column (its sort key).
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub hidden_columns: BTreeSet<String>,
+    /// Row page keyboard shortcuts rebound in this browser, by action name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub key_bindings: BTreeMap<String, String>,
+}
+
+impl Preferences {
+    pub fn shows(&self, column: &str) -> bool {
+        !self.hidden_columns.contains(column)
+    }
+}
+
+tokio::task_local! {
+    static CURRENT: Preferences;
+}
+
+/// Preferences of the request being handled; defaults outside of one.
+pub fn current() -> Preferences {
+    CURRENT.try_with(Clone::clone).unwrap_or_default()
+}
+
+/// Runs a future with the preferences of its request.
+pub async fn scope<F: Future>(preferences: Preferences, future: F) -> F::Output {
+    CURRENT.scope(preferences, future).await
+}
+
+/// Runs blocking work of a request with the preferences of the request.
+pub fn sync_scope<T>(preferences: Preferences, work: impl FnOnce() -> T) -> T {
+    CURRENT.sync_scope(preferences, work)
+}
+
+/// Signs and verifies the preferences cookie with HMAC-SHA256, so the server only
+/// trusts values it wrote itself.
+pub struct Signer {
+    key: [u8; 32],
+}
+
+impl Signer {
+    /// Derives the key from a secret; without one a random key is used, and the
+    /// preferences are forgotten when the server restarts.
+    pub fn new(secret: Option<&str>) -> anyhow::Result<Self> {
+        let key = match secret {
+            Some(secret) => Sha256::digest(secret.as_bytes()).into(),
+            None => {
+                let mut key = [0u8; 32];
+                getrandom::fill(&mut key)
+                    .map_err(|err| anyhow!("No randomness available: {}", err))?;
+                key
+            }
+        };
+        Ok(Self { key })
+    }
+
+    /// Preferences from the `Cookie` header; missing, tampered or malformed cookies
+    /// give the defaults.
+    pub fn read(&self, cookies: Option<&str>) -> Preferences {
+        cookies
+            .and_then(|cookies| {
+                cookies.split(';').find_map(|cookie| {
+                    let (key, value) = cookie.trim().split_once('=')?;
+                    (key == PREFERENCES_COOKIE).then_some(value)
+                })
+            })
+            .and_then(|value| self.verify(value))
+            .unwrap_or_default()
+    }
+
+    /// `Set-Cookie` value storing the preferences.
+    pub fn cookie(&self, preferences: &Preferences) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(preferences).unwrap());
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(payload.as_bytes()));
+        format!(
+            "{}={}.{}; Path=/; HttpOnly; SameSite=Lax; Max-Age={}",
+            PREFERENCES_COOKIE, payload, signature, MAX_AGE_SECS
+        )
+    }
+
+    fn verify(&self, value: &str) -> Option<Preferences> {
+        let (payload, signature) = value.split_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        let expected = self.mac(payload.as_bytes());
+        // Compare in constant time.
+        let matches = signature.len() == expected.len()
+            && signature
+                .iter()
+                .zip(expected)
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0;
+        if !matches {
+            return None;
+        }
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()
+    }
+
+    /// HMAC-SHA256 (RFC 2104) of a message.
+    fn mac(&self, message: &[u8]) -> [u8; 32] {
+        let mut inner_pad = [0x36u8; 64];
+        let mut outer_pad = [0x5cu8; 64];
+        for (i, byte) in self.key.iter().enumerate() {
+            inner_pad[i] ^= byte;
+            outer_pad[i] ^= byte;
+        }
+        let inner = Sha256::new()
+            .chain_update(inner_pad)
+            .chain_update(message)
+            .finalize();
+        Sha256::new()
+            .chain_update(outer_pad)
+            .chain_update(inner)
+            .finalize()
+            .into()
+    }
+}
@@ -0,0 +1,181 @@
+use crate::blobs::{self, BlobStore};
+use anyhow::{Context, bail};
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tokio::process::Command;
+
+/// Settings for the low-bitrate preview transcoder.
+#[derive(Clone, Debug)]
+pub struct PreviewConfig {
+    /// Path to the `ffmpeg` executable.
+    pub ffmpeg: PathBuf,
+    /// Target Opus bitrate, passed verbatim to ffmpeg (e.g. `32k`).
+    pub bitrate: String,
+}
+
+impl PreviewConfig {
+    /// Encoder settings a transcode depends on, so that changing them does not serve
+    /// transcodes made with the old ones.
+    fn settings(&self) -> String {
+        format!("libopus -b:a {}", self.bitrate)
+    }
+}
+
+/// Container the Opus stream of a preview is muxed into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Container {
+    Ogg,
+    WebM,
+}
+
+impl Container {
+    /// Extension of the cached transcode.
+    fn extension(self) -> &'static str {
+        match self {
+            Container::Ogg => "opus",
+            Container::WebM => "webm",
+        }
+    }
+
+    /// ffmpeg output format.
+    fn format(self) -> &'static str {
+        match self {
+            Container::Ogg => "ogg",
+            Container::WebM => "webm",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Container::Ogg => "audio/ogg; codecs=opus",
+            Container::WebM => "audio/webm; codecs=opus",
+        }
+    }
+}
+
+/// Picks the encoding of a clip from an `Accept` header: Opus when the client prefers
+/// WebM or Ogg audio over WAV, `None` for WAV.
+///
+/// The highest quality wins; on a tie an explicitly listed type beats a wildcard, and
+/// earlier types beat later ones, so `*/*` alone keeps WAV.
+pub fn negotiate(accept: &str) -> Option<Container> {
+    // (quality, specificity, negative position) of each candidate's best matching range.
+    let rank = |candidates: &[&str]| {
+        accept
+            .split(',')
+            .enumerate()
+            .filter_map(|(position, range)| {
+                let mut params = range.split(';');
+                let media = params.next()?.trim().to_ascii_lowercase();
+                let quality = params
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                let specificity = if candidates.contains(&media.as_str()) {
+                    2
+                } else if media == "audio/*" {
+                    1
+                } else if media == "*/*" {
+                    0
+                } else {
+                    return None;
+                };
+                Some((quality, specificity, -(position as i64)))
+            })
+            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .filter(|&(quality, ..)| quality > 0.0)
+    };
+    let wav = rank(&["audio/wav", "audio/wave", "audio/x-wav"]);
+    [
+        (Container::WebM, rank(&["audio/webm"])),
+        (Container::Ogg, rank(&["audio/ogg", "application/ogg"])),
+    ]
+    .into_iter()
+    .filter_map(|(container, rank)| Some((container, rank?)))
+    .filter(|(_, rank)| wav.is_none_or(|wav| *rank > wav))
+    .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+    .map(|(container, _)| container)
+}
+
+/// Counter used to give concurrent transcodes of the same clip distinct temp files.
+static TRANSCODE_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the cached preview path for an extracted WAV file, named after a short hash
+/// of the encoder settings.
+fn preview_path(config: &PreviewConfig, wav_path: &Path, container: Container) -> PathBuf {
+    let settings = blobs::hash(config.settings().as_bytes());
+    wav_path.with_extension(format!("{}.{}", &settings[..8], container.extension()))
+}
+
+/// Transcodes `wav_path` to Opus in `container` next to it, unless a cached preview
+/// already exists.
+///
+/// Transcodes are kept in the blob store under the hash of the clip and the encoder
+/// settings, so a clip duplicated across shards is transcoded once. The output is written to a temp file
+/// first and renamed into place, so a half-written preview is never served.
+pub async fn ensure_preview(
+    config: &PreviewConfig,
+    blobs: &BlobStore,
+    wav_path: &Path,
+    container: Container,
+) -> anyhow::Result<PathBuf> {
+    let out_path = preview_path(config, wav_path, container);
+    if out_path.is_file() {
+        return Ok(out_path);
+    }
+    // Hashing reads the whole clip.
+    let (blobs, wav, out, settings) = (
+        blobs.clone(),
+        wav_path.to_path_buf(),
+        out_path.clone(),
+        config.settings(),
+    );
+    let (blob, cached) = tokio::task::spawn_blocking(move || {
+        let blob = blobs.derived(&wav, &settings, container.extension())?;
+        let cached = blob.is_file();
+        if cached {
+            blobs::link(&blob, &out)?;
+        }
+        anyhow::Ok((blob, cached))
+    })
+    .await??;
+    if cached {
+        return Ok(out_path);
+    }
+
+    let seq = TRANSCODE_SEQ.fetch_add(1, Ordering::Relaxed);
+    let tmp_path = blob.with_extension(format!("{}.{}.tmp", container.extension(), seq));
+
+    let output = Command::new(&config.ffmpeg)
+        .arg("-nostdin")
+        .args(["-loglevel", "error", "-y", "-i"])
+        .arg(wav_path)
+        .args([
+            "-vn",
+            "-c:a",
+            "libopus",
+            "-b:a",
+            &config.bitrate,
+            "-f",
+            container.format(),
+        ])
+        .arg(&tmp_path)
+        .output()
+        .await
+        .with_context(|| format!("failed to run {}", config.ffmpeg.display()))?;
+
+    if !output.status.success() {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        bail!(
+            "ffmpeg exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    tokio::fs::rename(&tmp_path, &blob).await?;
+    blobs::link(&blob, &out_path)?;
+    Ok(out_path)
+}
@@ -0,0 +1,213 @@
+use serde::Deserialize;
+
+/// Above this many points t-SNE (quadratic in the number of points) falls back to PCA.
+pub const MAX_TSNE_POINTS: usize = 2000;
+const TSNE_ITERATIONS: usize = 500;
+const EARLY_EXAGGERATION: f32 = 12.0;
+const EXAGGERATION_ITERATIONS: usize = 100;
+const LEARNING_RATE: f32 = 200.0;
+
+/// How embeddings are projected to the plane.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Method {
+    /// t-SNE, for up to [`MAX_TSNE_POINTS`] points.
+    #[default]
+    Tsne,
+    Pca,
+}
+
+/// Projects the vectors to 2D; all vectors must have the same length.
+///
+/// Vectors are unit-normalized first, so distances follow cosine similarity.
+pub fn project(vectors: &[&[f32]], method: Method, perplexity: f32) -> Vec<[f32; 2]> {
+    let normalized: Vec<Vec<f32>> = vectors
+        .iter()
+        .map(|vector| {
+            let norm = vector
+                .iter()
+                .map(|x| x * x)
+                .sum::<f32>()
+                .sqrt()
+                .max(f32::EPSILON);
+            vector.iter().map(|x| x / norm).collect()
+        })
+        .collect();
+    let initial = pca(&normalized);
+    if method == Method::Pca || normalized.len() > MAX_TSNE_POINTS || normalized.len() < 4 {
+        return initial;
+    }
+    tsne(&normalized, initial, perplexity)
+}
+
+/// First two principal components, by power iteration on the centered data.
+fn pca(vectors: &[Vec<f32>]) -> Vec<[f32; 2]> {
+    let dims = vectors.first().map_or(0, Vec::len);
+    let n = vectors.len().max(1) as f32;
+    let mean: Vec<f32> = (0..dims)
+        .map(|d| vectors.iter().map(|v| v[d]).sum::<f32>() / n)
+        .collect();
+    let centered: Vec<Vec<f32>> = vectors
+        .iter()
+        .map(|v| v.iter().zip(&mean).map(|(x, m)| x - m).collect())
+        .collect();
+
+    let mut components: Vec<Vec<f32>> = Vec::new();
+    for k in 0..2 {
+        // Deterministic start, different per component.
+        let mut axis: Vec<f32> = (0..dims)
+            .map(|d| if (d + k) % 2 == 0 { 1.0 } else { 0.5 })
+            .collect();
+        for _ in 0..100 {
+            let mut next = vec![0.0f32; dims];
+            for row in &centered {
+                let dot: f32 = row.iter().zip(&axis).map(|(x, a)| x * a).sum();
+                for (n, x) in next.iter_mut().zip(row) {
+                    *n += dot * x;
+                }
+            }
+            for component in &components {
+                let dot: f32 = next.iter().zip(component).map(|(x, c)| x * c).sum();
+                for (n, c) in next.iter_mut().zip(component) {
+                    *n -= dot * c;
+                }
+            }
+            let norm = next.iter().map(|x| x * x).sum::<f32>().sqrt();
+            if norm <= f32::EPSILON {
+                break;
+            }
+            axis = next.iter().map(|x| x / norm).collect();
+        }
+        components.push(axis);
+    }
+
+    centered
+        .iter()
+        .map(|row| {
+            let coordinate =
+                |axis: &Vec<f32>| row.iter().zip(axis).map(|(x, a)| x * a).sum::<f32>();
+            [coordinate(&components[0]), coordinate(&components[1])]
+        })
+        .collect()
+}
+
+/// Exact t-SNE (van der Maaten & Hinton, 2008) started from `initial`.
+fn tsne(vectors: &[Vec<f32>], initial: Vec<[f32; 2]>, perplexity: f32) -> Vec<[f32; 2]> {
+    let n = vectors.len();
+    // The usual rule of thumb: at least three neighbours per unit of perplexity.
+    let perplexity = perplexity.min((n - 1) as f32 / 3.0).max(1.0);
+    let distances: Vec<f32> = (0..n * n)
+        .map(|k| {
+            let (i, j) = (k / n, k % n);
+            vectors[i]
+                .iter()
+                .zip(&vectors[j])
+                .map(|(a, b)| (a - b) * (a - b))
+                .sum()
+        })
+        .collect();
+
+    // Conditional affinities with a per-point bandwidth matching the perplexity.
+    let target_entropy = perplexity.ln();
+    let mut p = vec![0.0f32; n * n];
+    for i in 0..n {
+        let row = &distances[i * n..(i + 1) * n];
+        let (mut beta, mut low, mut high) = (1.0f32, 0.0f32, f32::INFINITY);
+        for _ in 0..50 {
+            let weights: Vec<f32> = (0..n)
+                .map(|j| if j == i { 0.0 } else { (-row[j] * beta).exp() })
+                .collect();
+            let sum = weights.iter().sum::<f32>().max(f32::MIN_POSITIVE);
+            let entropy = sum.ln() + beta * (0..n).map(|j| row[j] * weights[j]).sum::<f32>() / sum;
+            for j in 0..n {
+                p[i * n + j] = weights[j] / sum;
+            }
+            if (entropy - target_entropy).abs() < 1e-4 {
+                break;
+            }
+            if entropy > target_entropy {
+                low = beta;
+                beta = if high.is_finite() {
+                    (beta + high) / 2.0
+                } else {
+                    beta * 2.0
+                };
+            } else {
+                high = beta;
+                beta = (beta + low) / 2.0;
+            }
+        }
+    }
+    let mut joint = vec![0.0f32; n * n];
+    for i in 0..n {
+        for j in 0..n {
+            joint[i * n + j] = ((p[i * n + j] + p[j * n + i]) / (2.0 * n as f32)).max(1e-12);
+        }
+    }
+
+    // Small initial layout keeping the PCA arrangement.
+    let spread = initial
+        .iter()
+        .map(|[x, y]| x * x + y * y)
+        .sum::<f32>()
+        .sqrt()
+        .max(f32::EPSILON)
+        / (n as f32).sqrt();
+    let mut points: Vec<[f32; 2]> = initial
+        .iter()
+        .map(|[x, y]| [x / spread * 1e-2, y / spread * 1e-2])
+        .collect();
+    let mut velocity = vec![[0.0f32; 2]; n];
+    let mut gains = vec![[1.0f32; 2]; n];
+    let mut numerators = vec![0.0f32; n * n];
+
+    for iteration in 0..TSNE_ITERATIONS {
+        let exaggeration = if iteration < EXAGGERATION_ITERATIONS {
+            EARLY_EXAGGERATION
+        } else {
+            1.0
+        };
+        let momentum = if iteration < 250 { 0.5 } else { 0.8 };
+
+        let mut total = 0.0f32;
+        for i in 0..n {
+            for j in 0..n {
+                let value = if i == j {
+                    0.0
+                } else {
+                    let dx = points[i][0] - points[j][0];
+                    let dy = points[i][1] - points[j][1];
+                    1.0 / (1.0 + dx * dx + dy * dy)
+                };
+                numerators[i * n + j] = value;
+                total += value;
+            }
+        }
+        let total = total.max(f32::MIN_POSITIVE);
+
+        for i in 0..n {
+            let mut gradient = [0.0f32; 2];
+            for j in 0..n {
+                let numerator = numerators[i * n + j];
+                let q = (numerator / total).max(1e-12);
+                let force = 4.0 * (exaggeration * joint[i * n + j] - q) * numerator;
+                gradient[0] += force * (points[i][0] - points[j][0]);
+                gradient[1] += force * (points[i][1] - points[j][1]);
+            }
+            for d in 0..2 {
+                gains[i][d] = if (gradient[d] > 0.0) != (velocity[i][d] > 0.0) {
+                    gains[i][d] + 0.2
+                } else {
+                    (gains[i][d] * 0.8).max(0.01)
+                };
+                velocity[i][d] =
+                    momentum * velocity[i][d] - LEARNING_RATE * gains[i][d] * gradient[d];
+            }
+        }
+        for (point, step) in points.iter_mut().zip(&velocity) {
+            point[0] += step[0];
+            point[1] += step[1];
+        }
+    }
+    points
+}
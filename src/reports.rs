@@ -0,0 +1,1655 @@
+use crate::{
+    agreement::BUCKETS,
+    card, embeddings, expansions, html,
+    schema_check::ColumnTypes,
+    spellcheck::Dictionary,
+    splits::{self, Split},
+    style,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
+
+/// Counts occurrences and returns them sorted by descending frequency, then key.
+fn sorted_counts<K: Ord + std::hash::Hash + Clone>(
+    items: impl IntoIterator<Item = K>,
+) -> Vec<(K, usize)> {
+    let mut counts: HashMap<K, usize> = HashMap::new();
+    for item in items {
+        *counts.entry(item).or_default() += 1;
+    }
+    let mut sorted: Vec<(K, usize)> = counts.into_iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sorted
+}
+
+/// Human-readable label for a character, making whitespace and control characters visible.
+fn char_label(c: char) -> String {
+    match c {
+        ' ' => "␠ (space)".to_string(),
+        '\t' => "\\t".to_string(),
+        '\n' => "\\n".to_string(),
+        '\r' => "\\r".to_string(),
+        c if c.is_control() || c.is_whitespace() => format!("{:?}", c),
+        c => html::escape(&c.to_string()),
+    }
+}
+
+const TABLE_CLASS: &str = "min-w-full text-sm border-collapse";
+const TH_CLASS: &str = "px-3 py-2 text-left font-semibold border-b-2 dark:border-gray-700";
+const TD_CLASS: &str = "px-3 py-1 border-b dark:border-gray-700";
+
+/// Renders the character (and optionally word) frequency report.
+///
+/// Characters outside `alphabet` are highlighted and summarized at the top.
+pub fn render_charset(
+    texts: &[String],
+    alphabet: Option<&HashSet<char>>,
+    words: bool,
+    limit: usize,
+) -> String {
+    let chars = sorted_counts(texts.iter().flat_map(|t| t.chars()));
+    let total: usize = chars.iter().map(|(_, n)| n).sum();
+    let outside: Vec<&(char, usize)> = match alphabet {
+        Some(alphabet) => chars
+            .iter()
+            .filter(|(c, _)| !alphabet.contains(c))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut out = String::new();
+    out.push_str(&format!(
+        r#"<p class="mb-4">{} transcriptions, {} characters, {} distinct.</p>"#,
+        texts.len(),
+        total,
+        chars.len()
+    ));
+
+    if alphabet.is_some() {
+        if outside.is_empty() {
+            out.push_str(r#"<p class="mb-4 text-green-700 dark:text-green-400">All characters are inside the configured alphabet.</p>"#);
+        } else {
+            let rows_affected = texts
+                .iter()
+                .filter(|t| t.chars().any(|c| outside.iter().any(|(o, _)| *o == c)))
+                .count();
+            out.push_str(&format!(
+                r#"<p class="mb-4 text-red-700 dark:text-red-400">{} characters outside the alphabet ({} occurrences in {} transcriptions): {}</p>"#,
+                outside.len(),
+                outside.iter().map(|(_, n)| n).sum::<usize>(),
+                rows_affected,
+                outside
+                    .iter()
+                    .map(|(c, _)| format!("<code>{}</code>", char_label(*c)))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ));
+        }
+    }
+
+    out.push_str(&format!(
+        r#"<h2 class="text-xl font-semibold mb-2">Characters</h2><div class="overflow-x-auto mb-6"><table class="{}"><thead><tr><th class="{}">Character</th><th class="{}">Code point</th><th class="{}">Count</th><th class="{}">Share</th></tr></thead><tbody>"#,
+        TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS
+    ));
+    for (c, count) in &chars {
+        let flagged = alphabet.is_some_and(|a| !a.contains(c));
+        out.push_str(&format!(
+            r#"<tr class="{}"><td class="{}"><code>{}</code></td><td class="{}">U+{:04X}</td><td class="{}">{}</td><td class="{}">{:.3}%</td></tr>"#,
+            if flagged { "bg-red-50 dark:bg-red-900/40" } else { "" },
+            TD_CLASS,
+            char_label(*c),
+            TD_CLASS,
+            *c as u32,
+            TD_CLASS,
+            count,
+            TD_CLASS,
+            *count as f64 * 100.0 / total.max(1) as f64
+        ));
+    }
+    out.push_str("</tbody></table></div>");
+
+    if words {
+        let words = sorted_counts(texts.iter().flat_map(|t| t.split_whitespace()));
+        out.push_str(&format!(
+            r#"<h2 class="text-xl font-semibold mb-2">Words</h2><p class="mb-2 text-sm text-gray-500 dark:text-gray-400">{} distinct words, showing the top {}.</p><div class="overflow-x-auto"><table class="{}"><thead><tr><th class="{}">Word</th><th class="{}">Count</th></tr></thead><tbody>"#,
+            words.len(),
+            limit.min(words.len()),
+            TABLE_CLASS,
+            TH_CLASS,
+            TH_CLASS
+        ));
+        for (word, count) in words.iter().take(limit) {
+            let flagged = alphabet.is_some_and(|a| word.chars().any(|c| !a.contains(&c)));
+            out.push_str(&format!(
+                r#"<tr class="{}"><td class="{}">{}</td><td class="{}">{}</td></tr>"#,
+                if flagged {
+                    "bg-red-50 dark:bg-red-900/40"
+                } else {
+                    ""
+                },
+                TD_CLASS,
+                html::escape(word),
+                TD_CLASS,
+                count
+            ));
+        }
+        out.push_str("</tbody></table></div>");
+    }
+
+    out
+}
+
+/// Renders the top-`k` word unigrams, bigrams and trigrams side by side.
+///
+/// N-grams never span transcriptions.
+pub fn render_ngrams(texts: &[String], k: usize) -> String {
+    let tokenized: Vec<Vec<&str>> = texts
+        .iter()
+        .map(|t| t.split_whitespace().collect())
+        .collect();
+
+    let mut out = String::from(r#"<div class="grid grid-cols-1 md:grid-cols-3 gap-6">"#);
+    for (n, label) in [(1, "Unigrams"), (2, "Bigrams"), (3, "Trigrams")] {
+        let grams = sorted_counts(
+            tokenized
+                .iter()
+                .flat_map(|words| words.windows(n).map(|w| w.join(" "))),
+        );
+        let total: usize = grams.iter().map(|(_, c)| c).sum();
+        out.push_str(&format!(
+            r#"<div><h2 class="text-xl font-semibold mb-2">{}</h2><p class="mb-2 text-sm text-gray-500 dark:text-gray-400">{} distinct, {} total</p><table class="{}"><thead><tr><th class="{}">N-gram</th><th class="{}">Count</th><th class="{}">Share</th></tr></thead><tbody>"#,
+            label,
+            grams.len(),
+            total,
+            TABLE_CLASS,
+            TH_CLASS,
+            TH_CLASS,
+            TH_CLASS
+        ));
+        for (gram, count) in grams.iter().take(k) {
+            out.push_str(&format!(
+                r#"<tr><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{:.2}%</td></tr>"#,
+                TD_CLASS,
+                html::escape(gram),
+                TD_CLASS,
+                count,
+                TD_CLASS,
+                *count as f64 * 100.0 / total.max(1) as f64
+            ));
+        }
+        out.push_str("</tbody></table></div>");
+    }
+    out.push_str("</div>");
+    out
+}
+
+/// Transcriptions per parquet file, in file order.
+pub type Corpus = Vec<(String, Vec<String>)>;
+
+/// Rows of a duplicate group as `(file, row, transcription)`.
+type GroupRows<'a> = Vec<(&'a str, usize, &'a str)>;
+
+/// Maximum number of row links listed per duplicate group.
+const MAX_GROUP_LINKS: usize = 100;
+
+/// Renders transcriptions occurring at least `min_count` times, grouped by `key`,
+/// with links to every row in each group.
+pub fn render_duplicates(
+    corpus: &Corpus,
+    min_count: usize,
+    key: impl Fn(&str) -> String,
+) -> String {
+    let mut groups: HashMap<String, GroupRows> = HashMap::new();
+    for (file, texts) in corpus {
+        for (row, text) in texts.iter().enumerate() {
+            let key = key(text);
+            if key.trim().is_empty() {
+                continue;
+            }
+            groups
+                .entry(key)
+                .or_default()
+                .push((file.as_str(), row, text.as_str()));
+        }
+    }
+
+    let mut groups: Vec<(String, GroupRows)> = groups
+        .into_iter()
+        .filter(|(_, rows)| rows.len() >= min_count)
+        .collect();
+    groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+
+    let total_rows: usize = corpus.iter().map(|(_, t)| t.len()).sum();
+    let duplicated_rows: usize = groups.iter().map(|(_, rows)| rows.len()).sum();
+    let mut out = format!(
+        r#"<p class="mb-4">{} groups with at least {} occurrences, covering {} of {} rows ({} removable duplicates).</p>"#,
+        groups.len(),
+        min_count,
+        duplicated_rows,
+        total_rows,
+        duplicated_rows - groups.len()
+    );
+
+    for (key, rows) in &groups {
+        let links = rows
+            .iter()
+            .take(MAX_GROUP_LINKS)
+            .map(|(file, row, text)| {
+                format!(
+                    r#"<a href="/row/{}/{}" title="{}" class="text-blue-600 dark:text-blue-400 hover:underline">{}#{}</a>"#,
+                    html::url_encode(file),
+                    row,
+                    html::escape(text),
+                    html::escape(file),
+                    row
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let more = rows.len().saturating_sub(MAX_GROUP_LINKS);
+        out.push_str(&format!(
+            r#"<div class="mb-4 p-3 rounded bg-gray-50 dark:bg-gray-700"><div class="font-semibold">{} <span class="ml-2 text-sm text-gray-500 dark:text-gray-400">&times;{}</span></div><div class="mt-1 text-sm">{}{}</div></div>"#,
+            html::escape(key),
+            rows.len(),
+            links,
+            if more > 0 {
+                format!(" and {} more", more)
+            } else {
+                String::new()
+            }
+        ));
+    }
+
+    out
+}
+
+/// Renders phoneme frequencies from a G2P pass over the transcriptions.
+///
+/// Phonemes seen fewer than `min_count` times are highlighted as rare. With an
+/// `inventory`, its phonemes that never occur are listed as missing and phonemes
+/// outside it are flagged.
+pub fn render_phonemes(
+    texts: usize,
+    phonemes: &[String],
+    language: &str,
+    inventory: Option<&BTreeSet<String>>,
+    min_count: usize,
+) -> String {
+    let counts = sorted_counts(phonemes.iter().cloned());
+    let total = phonemes.len();
+    let rare: Vec<&str> = counts
+        .iter()
+        .filter(|(_, count)| *count < min_count)
+        .map(|(phoneme, _)| phoneme.as_str())
+        .collect();
+
+    let mut out = format!(
+        r#"<p class="mb-4">{} transcriptions phonemized as <code>{}</code>: {} phonemes, {} distinct.</p>"#,
+        texts,
+        html::escape(language),
+        total,
+        counts.len()
+    );
+
+    if let Some(inventory) = inventory {
+        let missing: Vec<&String> = inventory
+            .iter()
+            .filter(|phoneme| !counts.iter().any(|(seen, _)| seen == *phoneme))
+            .collect();
+        let covered = inventory.len() - missing.len();
+        if missing.is_empty() {
+            out.push_str(&format!(
+                r#"<p class="mb-4 text-green-700 dark:text-green-400">All {} phonemes of the inventory occur.</p>"#,
+                inventory.len()
+            ));
+        } else {
+            out.push_str(&format!(
+                r#"<p class="mb-4 text-red-700 dark:text-red-400">{} of {} inventory phonemes covered ({:.1}%); missing: {}</p>"#,
+                covered,
+                inventory.len(),
+                covered as f64 * 100.0 / inventory.len() as f64,
+                missing
+                    .iter()
+                    .map(|phoneme| format!("<code>{}</code>", html::escape(phoneme)))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ));
+        }
+    }
+    if !rare.is_empty() {
+        out.push_str(&format!(
+            r#"<p class="mb-4 text-amber-700 dark:text-amber-400">{} phonemes occur fewer than {} times: {}</p>"#,
+            rare.len(),
+            min_count,
+            rare.iter()
+                .map(|phoneme| format!("<code>{}</code>", html::escape(phoneme)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ));
+    }
+
+    out.push_str(&format!(
+        r#"<div class="overflow-x-auto"><table class="{}"><thead><tr><th class="{}">Phoneme</th><th class="{}">Count</th><th class="{}">Share</th></tr></thead><tbody>"#,
+        TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS
+    ));
+    for (phoneme, count) in &counts {
+        let class = if inventory.is_some_and(|inventory| !inventory.contains(phoneme)) {
+            "bg-red-50 dark:bg-red-900/40"
+        } else if *count < min_count {
+            "bg-amber-50 dark:bg-amber-900/40"
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            r#"<tr class="{}"><td class="{}"><code>{}</code></td><td class="{}">{}</td><td class="{}">{:.3}%</td></tr>"#,
+            class,
+            TD_CLASS,
+            html::escape(phoneme),
+            TD_CLASS,
+            count,
+            TD_CLASS,
+            *count as f64 * 100.0 / total.max(1) as f64
+        ));
+    }
+    out.push_str("</tbody></table></div>");
+    out
+}
+
+/// A clip with its speaker label and embedding, for the speaker report and map.
+pub struct SpeakerClip {
+    pub file: String,
+    pub row: usize,
+    pub speaker: String,
+    pub transcription: String,
+    pub embedding: Arc<Vec<f32>>,
+}
+
+/// Maximum number of rows listed per speaker report section.
+const MAX_SPEAKER_ROWS: usize = 200;
+
+fn row_link(file: &str, row: usize) -> String {
+    format!(
+        r#"<a href="/row/{}/{}" class="text-blue-600 dark:text-blue-400 hover:underline">{}#{}</a>"#,
+        html::url_encode(file),
+        row,
+        html::escape(file),
+        row
+    )
+}
+
+/// Renders the speaker consistency report from per-clip embeddings.
+///
+/// Files are treated as splits. Lists clips closer to another speaker than to their own
+/// label, speakers present in several splits (by id, or by voice when their centroids
+/// are at least `threshold` similar), and embedding clusters mixing speaker ids.
+pub fn render_speakers(clips: &[SpeakerClip], total: usize, threshold: f32) -> String {
+    let mut by_speaker: BTreeMap<&str, Vec<&SpeakerClip>> = BTreeMap::new();
+    for clip in clips {
+        by_speaker.entry(&clip.speaker).or_default().push(clip);
+    }
+    let files: BTreeSet<&str> = clips.iter().map(|clip| clip.file.as_str()).collect();
+    let mut out = format!(
+        r#"<p class="mb-4">{} of {} clips embedded, {} speakers in {} files. Similarity threshold {:.2}.</p>"#,
+        clips.len(),
+        total,
+        by_speaker.len(),
+        files.len(),
+        threshold
+    );
+
+    // Clips whose embedding is closer to another speaker's centroid than to their own,
+    // which is computed without the clip itself.
+    let sums: BTreeMap<&str, Vec<f32>> = by_speaker
+        .iter()
+        .map(|(speaker, clips)| {
+            let sum = embeddings::centroid(clips.iter().map(|clip| clip.embedding.as_slice()));
+            (*speaker, sum)
+        })
+        .collect();
+    let mut suspects = Vec::new();
+    for clip in clips {
+        if by_speaker[clip.speaker.as_str()].len() < 2 {
+            continue;
+        }
+        let own_centroid: Vec<f32> = {
+            let unit = embeddings::centroid([clip.embedding.as_slice()]);
+            sums[clip.speaker.as_str()]
+                .iter()
+                .zip(&unit)
+                .map(|(sum, x)| sum - x)
+                .collect()
+        };
+        let own = embeddings::cosine(&clip.embedding, &own_centroid);
+        let nearest = sums
+            .iter()
+            .filter(|(speaker, _)| **speaker != clip.speaker)
+            .map(|(speaker, sum)| (*speaker, embeddings::cosine(&clip.embedding, sum)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((other, similarity)) = nearest
+            && similarity > own
+        {
+            suspects.push((clip, own, other, similarity));
+        }
+    }
+    suspects.sort_by(|a, b| (b.3 - b.1).total_cmp(&(a.3 - a.1)));
+    out.push_str(&format!(
+        r#"<h2 class="text-xl font-semibold mb-2">Possible mislabels</h2><p class="mb-2 text-sm text-gray-500 dark:text-gray-400">{} clips sound closer to another speaker than to their own label.</p>"#,
+        suspects.len()
+    ));
+    if !suspects.is_empty() {
+        out.push_str(&format!(
+            r#"<div class="overflow-x-auto mb-6"><table class="{}"><thead><tr><th class="{}">Row</th><th class="{}">Label</th><th class="{}">Similarity</th><th class="{}">Nearest speaker</th><th class="{}">Similarity</th></tr></thead><tbody>"#,
+            TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS
+        ));
+        for (clip, own, other, similarity) in suspects.iter().take(MAX_SPEAKER_ROWS) {
+            out.push_str(&format!(
+                r#"<tr><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{:.3}</td><td class="{}">{}</td><td class="{}">{:.3}</td></tr>"#,
+                TD_CLASS,
+                row_link(&clip.file, clip.row),
+                TD_CLASS,
+                html::escape(&clip.speaker),
+                TD_CLASS,
+                own,
+                TD_CLASS,
+                html::escape(other),
+                TD_CLASS,
+                similarity
+            ));
+        }
+        out.push_str("</tbody></table></div>");
+    }
+
+    // Speakers per split, compared across splits.
+    let mut by_split: BTreeMap<(&str, &str), Vec<&SpeakerClip>> = BTreeMap::new();
+    for clip in clips {
+        by_split
+            .entry((&clip.file, &clip.speaker))
+            .or_default()
+            .push(clip);
+    }
+    let split_centroids: Vec<((&str, &str), usize, Vec<f32>)> = by_split
+        .iter()
+        .map(|(key, clips)| {
+            let centroid = embeddings::centroid(clips.iter().map(|clip| clip.embedding.as_slice()));
+            (*key, clips.len(), centroid)
+        })
+        .collect();
+    let mut overlaps = Vec::new();
+    for (i, ((file_a, speaker_a), count_a, centroid_a)) in split_centroids.iter().enumerate() {
+        for ((file_b, speaker_b), count_b, centroid_b) in &split_centroids[i + 1..] {
+            if file_a == file_b {
+                continue;
+            }
+            let similarity = embeddings::cosine(centroid_a, centroid_b);
+            if speaker_a == speaker_b || similarity >= threshold {
+                overlaps.push((
+                    (*file_a, *speaker_a, *count_a),
+                    (*file_b, *speaker_b, *count_b),
+                    similarity,
+                ));
+            }
+        }
+    }
+    overlaps.sort_by(|a, b| b.2.total_cmp(&a.2));
+    out.push_str(&format!(
+        r#"<h2 class="text-xl font-semibold mb-2">Speakers shared between files</h2><p class="mb-2 text-sm text-gray-500 dark:text-gray-400">{} pairs with the same id or a similar voice in different files.</p>"#,
+        overlaps.len()
+    ));
+    if !overlaps.is_empty() {
+        out.push_str(&format!(
+            r#"<div class="overflow-x-auto mb-6"><table class="{}"><thead><tr><th class="{}">Speaker</th><th class="{}">Speaker</th><th class="{}">Similarity</th></tr></thead><tbody>"#,
+            TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS
+        ));
+        for (a, b, similarity) in overlaps.iter().take(MAX_SPEAKER_ROWS) {
+            let same_id = a.1 == b.1;
+            out.push_str(&format!(
+                r#"<tr class="{}"><td class="{}">{} <span class="text-gray-500 dark:text-gray-400">in {} ({} clips)</span></td><td class="{}">{} <span class="text-gray-500 dark:text-gray-400">in {} ({} clips)</span></td><td class="{}">{:.3}</td></tr>"#,
+                if same_id {
+                    "bg-red-50 dark:bg-red-900/40"
+                } else {
+                    ""
+                },
+                TD_CLASS,
+                html::escape(a.1),
+                html::escape(a.0),
+                a.2,
+                TD_CLASS,
+                html::escape(b.1),
+                html::escape(b.0),
+                b.2,
+                TD_CLASS,
+                similarity
+            ));
+        }
+        out.push_str("</tbody></table></div>");
+    }
+
+    // Clusters of similar clips carrying different labels.
+    let vectors: Vec<&[f32]> = clips.iter().map(|clip| clip.embedding.as_slice()).collect();
+    let mut clusters: BTreeMap<usize, Vec<&SpeakerClip>> = BTreeMap::new();
+    for (clip, cluster) in clips.iter().zip(embeddings::cluster(&vectors, threshold)) {
+        clusters.entry(cluster).or_default().push(clip);
+    }
+    let mut mixed: Vec<Vec<&SpeakerClip>> = clusters
+        .into_values()
+        .filter(|members| {
+            members
+                .iter()
+                .any(|clip| clip.speaker != members[0].speaker)
+        })
+        .collect();
+    mixed.sort_by_key(|members| std::cmp::Reverse(members.len()));
+    out.push_str(&format!(
+        r#"<h2 class="text-xl font-semibold mb-2">Clusters with several labels</h2><p class="mb-2 text-sm text-gray-500 dark:text-gray-400">{} clusters of similar clips carry more than one speaker id.</p>"#,
+        mixed.len()
+    ));
+    for members in &mixed {
+        let labels = sorted_counts(members.iter().map(|clip| clip.speaker.as_str()))
+            .iter()
+            .map(|(speaker, count)| format!("{} &times;{}", html::escape(speaker), count))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let links = members
+            .iter()
+            .take(MAX_GROUP_LINKS)
+            .map(|clip| row_link(&clip.file, clip.row))
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!(
+            r#"<div class="mb-4 p-3 rounded bg-gray-50 dark:bg-gray-700"><div class="font-semibold">{} clips: {}</div><div class="mt-1 text-sm">{}</div></div>"#,
+            members.len(),
+            labels,
+            links
+        ));
+    }
+
+    out
+}
+
+/// Missing values of one column, summed over the files that have it.
+#[derive(Clone, Debug, Default)]
+pub struct ColumnCompleteness {
+    /// Column name; struct fields are listed as `column.field`.
+    pub name: String,
+    pub dtype: String,
+    pub files: usize,
+    pub rows: usize,
+    pub nulls: usize,
+    /// NaN values of float columns.
+    pub nans: usize,
+    /// Empty or blank strings, empty binary values and empty lists.
+    pub empty: usize,
+}
+
+impl ColumnCompleteness {
+    pub fn missing(&self) -> usize {
+        self.nulls + self.nans + self.empty
+    }
+
+    /// Share of missing values, in percent.
+    pub fn missing_percent(&self) -> f64 {
+        self.missing() as f64 * 100.0 / self.rows.max(1) as f64
+    }
+}
+
+/// Renders null, NaN and empty counts per column, highlighting columns with more than
+/// `max_missing` percent of missing values.
+pub fn render_completeness(
+    columns: &[ColumnCompleteness],
+    files: usize,
+    max_missing: f64,
+) -> String {
+    let sparse = columns
+        .iter()
+        .filter(|column| column.missing_percent() > max_missing)
+        .count();
+    let mut out = format!(
+        r#"<p class="mb-4">{} columns in {} files; {} columns have more than {}% missing values (null, NaN or empty; change with <code>?max_missing=</code>).</p>"#,
+        columns.len(),
+        files,
+        sparse,
+        max_missing
+    );
+    out.push_str(&format!(
+        r#"<div class="overflow-x-auto"><table class="{}"><thead><tr><th class="{}">Column</th><th class="{}">Type</th><th class="{}">Files</th><th class="{}">Rows</th><th class="{}">Null</th><th class="{}">NaN</th><th class="{}">Empty</th><th class="{}">Missing</th></tr></thead><tbody>"#,
+        TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS
+    ));
+    for column in columns {
+        let percent = column.missing_percent();
+        let highlight = if column.rows > 0 && column.missing() == column.rows {
+            "bg-red-50 dark:bg-red-900/40"
+        } else if percent > max_missing {
+            "bg-yellow-50 dark:bg-yellow-900/40"
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            r#"<tr class="{}"><td class="{}"><code>{}</code></td><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{:.2}%</td></tr>"#,
+            highlight,
+            TD_CLASS,
+            html::escape(&column.name),
+            TD_CLASS,
+            html::escape(&column.dtype),
+            TD_CLASS,
+            column.files,
+            TD_CLASS,
+            column.rows,
+            TD_CLASS,
+            column.nulls,
+            TD_CLASS,
+            column.nans,
+            TD_CLASS,
+            column.empty,
+            TD_CLASS,
+            percent
+        ));
+    }
+    out.push_str("</tbody></table></div>");
+    out
+}
+
+/// Renders the column types of every file side by side, inconsistent columns first.
+pub fn render_schemas(files: &[String], columns: &[ColumnTypes]) -> String {
+    let mut sorted: Vec<&ColumnTypes> = columns.iter().collect();
+    sorted.sort_by_key(|column| column.consistent());
+    let inconsistent = columns.iter().filter(|c| !c.consistent()).count();
+    let mut out = format!(
+        r#"<p class="mb-4">{} files, {} columns (struct fields as <code>column.field</code>).</p>"#,
+        files.len(),
+        columns.len()
+    );
+    if inconsistent == 0 {
+        out.push_str(r#"<p class="mb-4 text-green-700 dark:text-green-400">All files have the same columns and types.</p>"#);
+    } else {
+        out.push_str(&format!(
+            r#"<p class="mb-4 text-red-700 dark:text-red-400">{} columns are missing from some files or have different types.</p>"#,
+            inconsistent
+        ));
+    }
+    out.push_str(&format!(
+        r#"<div class="overflow-x-auto"><table class="{}"><thead><tr><th class="{}">Column</th><th class="{}">Type</th><th class="{}">Files</th><th class="{}">Issue</th></tr></thead><tbody>"#,
+        TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS
+    ));
+    for column in sorted {
+        let groups = column.type_groups(files);
+        let present: usize = groups.iter().map(|(_, files)| files.len()).sum();
+        out.push_str(&format!(
+            r#"<tr class="{}"><td class="{}"><code>{}</code></td><td class="{}">{}</td><td class="{}">{} / {}</td><td class="{}">{}</td></tr>"#,
+            if column.consistent() {
+                ""
+            } else {
+                "bg-red-50 dark:bg-red-900/40"
+            },
+            TD_CLASS,
+            html::escape(&column.name),
+            TD_CLASS,
+            groups
+                .iter()
+                .map(|(dtype, _)| format!("<code>{}</code>", html::escape(dtype)))
+                .collect::<Vec<_>>()
+                .join(" "),
+            TD_CLASS,
+            present,
+            files.len(),
+            TD_CLASS,
+            html::escape(&column.describe(files))
+        ));
+    }
+    out.push_str("</tbody></table></div>");
+    out
+}
+
+/// Renders the speaking rate histogram and, per file, the median rate and the number of
+/// clips outside `(min, max)` words per minute, linking to the file sorted by rate.
+pub fn render_wpm(plot: &str, files: &[(String, Vec<f64>)], (min, max): (f64, f64)) -> String {
+    let clips: usize = files.iter().map(|(_, rates)| rates.len()).sum();
+    if clips == 0 {
+        return r#"<p>No clips with a duration and a transcription.</p>"#.to_string();
+    }
+    let mut out = format!(
+        r#"<p class="mb-4">{} clips; rates outside {} – {} words per minute are marked with <code>!</code> (change with <code>?min_wpm=</code> and <code>?max_wpm=</code>).</p><pre class="mb-4 text-sm whitespace-pre-wrap"><code>{}</code></pre>"#,
+        clips,
+        min,
+        max,
+        html::escape(plot)
+    );
+    out.push_str(&format!(
+        r#"<div class="overflow-x-auto"><table class="{}"><thead><tr><th class="{}">File</th><th class="{}">Clips</th><th class="{}">Median WPM</th><th class="{}">Slower</th><th class="{}">Faster</th></tr></thead><tbody>"#,
+        TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS
+    ));
+    for (file, rates) in files {
+        let mut sorted = rates.clone();
+        sorted.sort_by(f64::total_cmp);
+        let median = sorted
+            .get(sorted.len() / 2)
+            .map(|rate| format!("{:.1}", rate))
+            .unwrap_or_default();
+        let slower = rates.iter().filter(|&&rate| rate < min).count();
+        let faster = rates.iter().filter(|&&rate| rate > max).count();
+        let highlight = if slower + faster > 0 {
+            "bg-yellow-50 dark:bg-yellow-900/40"
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            r#"<tr class="{}"><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{}</td><td class="{}"><a href="/view/{}?sort=wpm" class="text-blue-600 dark:text-blue-400 hover:underline">{}</a></td><td class="{}"><a href="/view/{}?sort=-wpm" class="text-blue-600 dark:text-blue-400 hover:underline">{}</a></td></tr>"#,
+            highlight,
+            TD_CLASS,
+            html::escape(file),
+            TD_CLASS,
+            rates.len(),
+            TD_CLASS,
+            median,
+            TD_CLASS,
+            html::url_encode(file),
+            slower,
+            TD_CLASS,
+            html::url_encode(file),
+            faster
+        ));
+    }
+    out.push_str("</tbody></table></div>");
+    out
+}
+
+/// Rows listed as deviating from the dominant encoding at most.
+const MAX_DEVIATING_ROWS: usize = 500;
+
+/// Renders the encoding breakdown of `(file, row, encoding)` triples, highlighting the
+/// rows whose encoding differs from the most common one.
+pub fn render_encodings(rows: &[(String, usize, String)]) -> String {
+    let counts = sorted_counts(rows.iter().map(|(_, _, encoding)| encoding.as_str()));
+    let Some(&(dominant, _)) = counts.first() else {
+        return "<p>No clips.</p>".to_string();
+    };
+    let deviating: Vec<&(String, usize, String)> = rows
+        .iter()
+        .filter(|(_, _, encoding)| encoding != dominant)
+        .collect();
+    let mut out = format!(
+        r#"<p class="mb-4">{} clips; the dominant encoding is <strong>{}</strong> and {} clips deviate from it.</p>"#,
+        rows.len(),
+        html::escape(dominant),
+        deviating.len()
+    );
+    out.push_str(&format!(
+        r#"<div class="overflow-x-auto mb-6"><table class="{}"><thead><tr><th class="{}">Encoding</th><th class="{}">Clips</th><th class="{}">Share</th></tr></thead><tbody>"#,
+        TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS
+    ));
+    for (encoding, count) in &counts {
+        let highlight = if *encoding == dominant {
+            ""
+        } else {
+            "bg-yellow-50 dark:bg-yellow-900/40"
+        };
+        out.push_str(&format!(
+            r#"<tr class="{}"><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{:.2}%</td></tr>"#,
+            highlight,
+            TD_CLASS,
+            html::escape(encoding),
+            TD_CLASS,
+            count,
+            TD_CLASS,
+            *count as f64 / rows.len() as f64 * 100.0
+        ));
+    }
+    out.push_str("</tbody></table></div>");
+    if deviating.is_empty() {
+        return out;
+    }
+    out.push_str(&format!(
+        r#"<h2 class="text-xl font-semibold mb-2">Deviating clips{}</h2><div class="overflow-x-auto"><table class="{}"><thead><tr><th class="{}">File</th><th class="{}">Row</th><th class="{}">Encoding</th></tr></thead><tbody>"#,
+        if deviating.len() > MAX_DEVIATING_ROWS {
+            format!(" (first {})", MAX_DEVIATING_ROWS)
+        } else {
+            String::new()
+        },
+        TABLE_CLASS,
+        TH_CLASS,
+        TH_CLASS,
+        TH_CLASS
+    ));
+    for (file, row, encoding) in deviating.iter().take(MAX_DEVIATING_ROWS) {
+        out.push_str(&format!(
+            r#"<tr><td class="{}">{}</td><td class="{}"><a href="/row/{}/{}" class="text-blue-600 dark:text-blue-400 hover:underline">{}</a></td><td class="{}">{}</td></tr>"#,
+            TD_CLASS,
+            html::escape(file),
+            TD_CLASS,
+            html::url_encode(file),
+            row,
+            row,
+            TD_CLASS,
+            html::escape(encoding)
+        ));
+    }
+    out.push_str("</tbody></table></div>");
+    out
+}
+
+/// Agreement of the human transcriptions of a file with its pseudo-labels.
+#[derive(Clone, Debug)]
+pub struct FileAgreement {
+    pub file: String,
+    /// Rows with a pseudo-label.
+    pub rows: usize,
+    /// Word errors of the pseudo-labels, summed over the rows.
+    pub errors: usize,
+    /// Words of the human transcriptions, summed over the rows.
+    pub words: usize,
+    /// Rows per bucket of [`BUCKETS`].
+    pub buckets: [usize; BUCKETS.len()],
+}
+
+impl FileAgreement {
+    /// Corpus-level word error rate, in percent.
+    pub fn wer_percent(&self) -> f64 {
+        self.errors as f64 * 100.0 / self.words.max(1) as f64
+    }
+}
+
+/// Renders the rows per word error rate bucket, overall and per file, each count linking
+/// to the file view filtered to the bucket with the worst rows first.
+pub fn render_agreement(column: &str, files: &[FileAgreement], unlabeled: &[String]) -> String {
+    let mut out = String::new();
+    if !unlabeled.is_empty() {
+        out.push_str(&format!(
+            r#"<p class="mb-4 text-gray-500 dark:text-gray-400">No <code>{}</code> column (set with <code>--pseudo-label-column</code>) in {}.</p>"#,
+            html::escape(column),
+            html::escape(&unlabeled.join(", "))
+        ));
+    }
+    let rows: usize = files.iter().map(|file| file.rows).sum();
+    if rows == 0 {
+        out.push_str(r#"<p>No rows with a pseudo-label.</p>"#);
+        return out;
+    }
+    let total = FileAgreement {
+        file: String::new(),
+        rows,
+        errors: files.iter().map(|file| file.errors).sum(),
+        words: files.iter().map(|file| file.words).sum(),
+        buckets: std::array::from_fn(|i| files.iter().map(|file| file.buckets[i]).sum()),
+    };
+    out.push_str(&format!(
+        r#"<p class="mb-4">{} rows compare the <code>transcription</code> (with edits) to the <code>{}</code> column: {:.1}% word error rate overall. Review the rows in the high buckets first.</p>"#,
+        rows,
+        html::escape(column),
+        total.wer_percent()
+    ));
+
+    let max_count = total.buckets.iter().copied().max().unwrap_or(0).max(1);
+    let mut plot = String::new();
+    for (bucket, &count) in BUCKETS.iter().zip(&total.buckets) {
+        plot.push_str(&format!(
+            "{:<10}{:>8}  {:>5.1}%  {}\n",
+            bucket.label,
+            count,
+            count as f64 * 100.0 / rows as f64,
+            "*".repeat((count * 40).div_ceil(max_count))
+        ));
+    }
+    out.push_str(&format!(
+        r#"<pre class="mb-4 text-sm whitespace-pre-wrap"><code>{}</code></pre>"#,
+        html::escape(&plot)
+    ));
+
+    let bucket_headers = BUCKETS
+        .iter()
+        .map(|bucket| format!(r#"<th class="{}">{}</th>"#, TH_CLASS, bucket.label))
+        .collect::<String>();
+    out.push_str(&format!(
+        r#"<div class="overflow-x-auto"><table class="{}"><thead><tr><th class="{}">File</th><th class="{}">Rows</th><th class="{}">WER</th>{}</tr></thead><tbody>"#,
+        TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, bucket_headers
+    ));
+    for file in files {
+        let cells = BUCKETS
+            .iter()
+            .zip(&file.buckets)
+            .map(|(bucket, &count)| {
+                if count == 0 {
+                    format!(r#"<td class="{}">0</td>"#, TD_CLASS)
+                } else {
+                    format!(
+                        r#"<td class="{}"><a href="/view/{}?wer={}&sort=-wer" class="text-blue-600 dark:text-blue-400 hover:underline">{}</a></td>"#,
+                        TD_CLASS,
+                        html::url_encode(&file.file),
+                        bucket.name,
+                        count
+                    )
+                }
+            })
+            .collect::<String>();
+        out.push_str(&format!(
+            r#"<tr><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{:.1}%</td>{}</tr>"#,
+            TD_CLASS,
+            html::escape(&file.file),
+            TD_CLASS,
+            file.rows,
+            TD_CLASS,
+            file.wer_percent(),
+            cells
+        ));
+    }
+    out.push_str("</tbody></table></div>");
+    out
+}
+
+/// Rows linked per suspect word in the spelling report.
+const MAX_SPELLING_ROWS: usize = 5;
+
+/// Renders the `k` most frequent likely misspellings of a corpus with the number of rows
+/// they occur in and links to the first ones; a word frequent across many rows is more
+/// likely a systematic labeling error, or a term missing from the dictionary.
+pub fn render_spelling(corpus: &Corpus, dictionary: &Dictionary, k: usize) -> String {
+    let mut rows: HashMap<String, Vec<(&str, usize)>> = HashMap::new();
+    let mut words = Vec::new();
+    for (file, texts) in corpus {
+        for (row, text) in texts.iter().enumerate() {
+            let suspects = dictionary.suspect_words(text);
+            for word in suspects.iter().collect::<BTreeSet<_>>() {
+                rows.entry(word.clone()).or_default().push((file, row));
+            }
+            words.extend(suspects);
+        }
+    }
+    if words.is_empty() {
+        return "<p>No likely misspellings.</p>".to_string();
+    }
+    let total = words.len();
+    let counts = sorted_counts(words);
+    let mut out = format!(
+        r#"<p class="mb-4">{} occurrences of {} distinct words not found in the dictionary. The most frequent ones are listed first.</p><div class="overflow-x-auto"><table class="{}"><thead><tr><th class="{}">Word</th><th class="{}">Count</th><th class="{}">Rows</th><th class="{}">Examples</th></tr></thead><tbody>"#,
+        total,
+        counts.len(),
+        TABLE_CLASS,
+        TH_CLASS,
+        TH_CLASS,
+        TH_CLASS,
+        TH_CLASS
+    );
+    for (word, count) in counts.iter().take(k) {
+        let word_rows = &rows[word];
+        let links = word_rows
+            .iter()
+            .take(MAX_SPELLING_ROWS)
+            .map(|(file, row)| row_link(file, *row))
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!(
+            r#"<tr><td class="{}"><a href="/search?q={}" class="text-blue-600 dark:text-blue-400 hover:underline">{}</a></td><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{}{}</td></tr>"#,
+            TD_CLASS,
+            html::url_encode(word),
+            html::escape(word),
+            TD_CLASS,
+            count,
+            TD_CLASS,
+            word_rows.len(),
+            TD_CLASS,
+            links,
+            if word_rows.len() > MAX_SPELLING_ROWS {
+                ", &hellip;"
+            } else {
+                ""
+            }
+        ));
+    }
+    out.push_str("</tbody></table></div>");
+    out
+}
+
+/// Rows listed per inconsistent form in the expansion report.
+const MAX_EXPANSION_ROWS: usize = 100;
+
+/// Renders how consistently a corpus writes numbers, ordinals, currencies, percentages
+/// and abbreviations: per kind, the rows using the compact form (`20`, `$`, `Dr.`) and
+/// the spelled-out one (`twenty`, `dollars`, `doctor`). Where both occur, the rows using
+/// the less common form are listed, as TTS models learn to read mixed forms poorly.
+pub fn render_expansions(corpus: &Corpus) -> String {
+    type FormRows<'a> = [BTreeMap<(&'a str, usize), Vec<String>>; 2];
+    let mut groups: BTreeMap<(usize, &str), FormRows> = BTreeMap::new();
+    for (file, texts) in corpus {
+        for (row, text) in texts.iter().enumerate() {
+            for found in expansions::uses(text) {
+                let order = expansions::CATEGORIES
+                    .iter()
+                    .position(|category| *category == found.category)
+                    .unwrap_or_default();
+                groups.entry((order, found.key)).or_default()[found.spelled_out as usize]
+                    .entry((file.as_str(), row))
+                    .or_default()
+                    .push(found.token);
+            }
+        }
+    }
+    if groups.is_empty() {
+        return "<p>No numbers, currencies, percentages or known abbreviations.</p>".to_string();
+    }
+
+    let mut out = format!(
+        r#"<div class="overflow-x-auto mb-6"><table class="{}"><thead><tr><th class="{}">Kind</th><th class="{}">Compact rows</th><th class="{}">Spelled-out rows</th><th class="{}">Less common form</th></tr></thead><tbody>"#,
+        TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS
+    );
+    let mut details = String::new();
+    for ((_, key), [compact, spelled]) in &groups {
+        let mixed = !compact.is_empty() && !spelled.is_empty();
+        let (minority, form) = if spelled.len() < compact.len() {
+            (spelled, "spelled-out")
+        } else {
+            (compact, "compact")
+        };
+        out.push_str(&format!(
+            r#"<tr class="{}"><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{}</td></tr>"#,
+            if mixed {
+                "bg-yellow-50 dark:bg-yellow-900/40"
+            } else {
+                ""
+            },
+            TD_CLASS,
+            html::escape(&expansions::label(key)),
+            TD_CLASS,
+            compact.len(),
+            TD_CLASS,
+            spelled.len(),
+            TD_CLASS,
+            if mixed {
+                format!(
+                    "{} ({:.1}%)",
+                    form,
+                    minority.len() as f64 * 100.0 / (compact.len() + spelled.len()) as f64
+                )
+            } else {
+                "consistent".to_string()
+            }
+        ));
+        if !mixed {
+            continue;
+        }
+        details.push_str(&format!(
+            r#"<h2 class="text-xl font-semibold mb-2">{}: {} rows use the {} form{}</h2><div class="overflow-x-auto mb-6"><table class="{}"><thead><tr><th class="{}">Row</th><th class="{}">Tokens</th></tr></thead><tbody>"#,
+            html::escape(&expansions::label(key)),
+            minority.len(),
+            form,
+            if minority.len() > MAX_EXPANSION_ROWS {
+                format!(" (first {})", MAX_EXPANSION_ROWS)
+            } else {
+                String::new()
+            },
+            TABLE_CLASS,
+            TH_CLASS,
+            TH_CLASS
+        ));
+        for ((file, row), tokens) in minority.iter().take(MAX_EXPANSION_ROWS) {
+            details.push_str(&format!(
+                r#"<tr><td class="{}">{}</td><td class="{}">{}</td></tr>"#,
+                TD_CLASS,
+                row_link(file, *row),
+                TD_CLASS,
+                html::escape(&tokens.join(" "))
+            ));
+        }
+        details.push_str("</tbody></table></div>");
+    }
+    out.push_str("</tbody></table></div>");
+    out.push_str(&details);
+    out
+}
+
+/// Unusual characters listed in the style report.
+const MAX_UNUSUAL_CHARS: usize = 50;
+/// Rows linked per unusual character in the style report.
+const MAX_UNUSUAL_CHAR_ROWS: usize = 5;
+
+/// Renders the share of transcriptions per casing and punctuation class, with links to
+/// the matching rows of each file, and the unusual characters found with their rows.
+pub fn render_style(corpus: &Corpus) -> String {
+    let total: usize = corpus.iter().map(|(_, texts)| texts.len()).sum();
+    if total == 0 {
+        return "<p>No transcriptions.</p>".to_string();
+    }
+    let mut out = format!(
+        r#"<p class="mb-4">{} transcriptions.</p><div class="overflow-x-auto mb-6"><table class="{}"><thead><tr><th class="{}">Class</th><th class="{}">Rows</th><th class="{}">Share</th><th class="{}">View</th></tr></thead><tbody>"#,
+        total, TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS
+    );
+    for class in style::Class::ALL {
+        let per_file: Vec<(&str, usize)> = corpus
+            .iter()
+            .map(|(file, texts)| {
+                (
+                    file.as_str(),
+                    texts.iter().filter(|text| class.matches(text)).count(),
+                )
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        let count: usize = per_file.iter().map(|(_, count)| count).sum();
+        let links = per_file
+            .iter()
+            .map(|(file, count)| {
+                format!(
+                    r#"<a href="/view/{}?style={}" class="text-blue-600 dark:text-blue-400 hover:underline">{} ({})</a>"#,
+                    html::url_encode(file),
+                    class.as_str(),
+                    html::escape(file),
+                    count
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!(
+            r#"<tr><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{:.2}%</td><td class="{}">{}</td></tr>"#,
+            TD_CLASS,
+            class.label(),
+            TD_CLASS,
+            count,
+            TD_CLASS,
+            count as f64 * 100.0 / total as f64,
+            TD_CLASS,
+            links
+        ));
+    }
+    out.push_str("</tbody></table></div>");
+
+    let mut rows: HashMap<char, Vec<(&str, usize)>> = HashMap::new();
+    for (file, texts) in corpus {
+        for (row, text) in texts.iter().enumerate() {
+            for c in style::unusual_chars(text).collect::<BTreeSet<_>>() {
+                rows.entry(c).or_default().push((file, row));
+            }
+        }
+    }
+    if rows.is_empty() {
+        return out;
+    }
+    let counts = sorted_counts(
+        corpus
+            .iter()
+            .flat_map(|(_, texts)| texts)
+            .flat_map(|text| style::unusual_chars(text)),
+    );
+    out.push_str(&format!(
+        r#"<h2 class="text-xl font-semibold mb-2">Unusual characters</h2><div class="overflow-x-auto"><table class="{}"><thead><tr><th class="{}">Character</th><th class="{}">Code point</th><th class="{}">Count</th><th class="{}">Rows</th><th class="{}">Examples</th></tr></thead><tbody>"#,
+        TABLE_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS, TH_CLASS
+    ));
+    for (c, count) in counts.iter().take(MAX_UNUSUAL_CHARS) {
+        let char_rows = &rows[c];
+        out.push_str(&format!(
+            r#"<tr><td class="{}">{}</td><td class="{}">U+{:04X}</td><td class="{}">{}</td><td class="{}">{}</td><td class="{}">{}</td></tr>"#,
+            TD_CLASS,
+            char_label(*c),
+            TD_CLASS,
+            *c as u32,
+            TD_CLASS,
+            count,
+            TD_CLASS,
+            char_rows.len(),
+            TD_CLASS,
+            char_rows
+                .iter()
+                .take(MAX_UNUSUAL_CHAR_ROWS)
+                .map(|(file, row)| row_link(file, *row))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    out.push_str("</tbody></table></div>");
+    out
+}
+
+/// Shared speakers listed by name per pair of splits.
+const MAX_SHARED_SPEAKERS: usize = 20;
+
+/// Table with a header row; cells are HTML.
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let head: String = headers
+        .iter()
+        .map(|header| format!(r#"<th class="{}">{}</th>"#, TH_CLASS, header))
+        .collect();
+    let body: String = rows
+        .iter()
+        .map(|row| {
+            let cells: String = row
+                .iter()
+                .map(|cell| format!(r#"<td class="{}">{}</td>"#, TD_CLASS, cell))
+                .collect();
+            format!("<tr>{}</tr>", cells)
+        })
+        .collect();
+    format!(
+        r#"<div class="overflow-x-auto mb-6"><table class="{}"><thead><tr>{}</tr></thead><tbody>{}</tbody></table></div>"#,
+        TABLE_CLASS, head, body
+    )
+}
+
+/// Renders the comparison of the train, validation and test splits: sizes, duration and
+/// transcription length distributions, speaker overlap (flagged as leakage) and how
+/// much of each split's vocabulary the first split covers.
+pub fn render_splits(splits: &[Split], unassigned: &[String], speaker_column: &str) -> String {
+    let pairs: Vec<(&Split, &Split)> = splits
+        .iter()
+        .enumerate()
+        .flat_map(|(i, a)| splits[i + 1..].iter().map(move |b| (a, b)))
+        .collect();
+    let shared: Vec<Vec<(&str, usize, usize)>> = pairs
+        .iter()
+        .map(|(a, b)| splits::shared_speakers(a, b))
+        .collect();
+    let leaked: BTreeSet<&str> = shared
+        .iter()
+        .flatten()
+        .map(|(speaker, _, _)| *speaker)
+        .collect();
+    let labeled = splits.iter().all(|split| split.speakers.is_some());
+
+    let mut out = String::new();
+    if !labeled {
+        out.push_str(&format!(
+            r#"<p class="mb-4 p-3 rounded bg-gray-100 dark:bg-gray-700">Speaker overlap is not checked: not every file has a <code>{}</code> column.</p>"#,
+            html::escape(speaker_column)
+        ));
+    } else if leaked.is_empty() {
+        out.push_str(r#"<p class="mb-4 p-3 rounded bg-green-100 text-green-900 dark:bg-green-900 dark:text-green-100">No speaker appears in more than one split.</p>"#);
+    } else {
+        out.push_str(&format!(
+            r#"<p class="mb-4 p-3 rounded border-l-4 border-red-500 bg-red-100 text-red-900 dark:bg-red-900 dark:text-red-100" role="alert"><strong>Speaker leakage:</strong> {} speakers appear in more than one split, so evaluation results overstate how well a model generalizes to new speakers.</p>"#,
+            leaked.len()
+        ));
+    }
+    if !unassigned.is_empty() {
+        out.push_str(&format!(
+            r#"<p class="mb-4 text-sm text-gray-500 dark:text-gray-400">Not in any split: {}.</p>"#,
+            unassigned
+                .iter()
+                .map(|file| html::escape(file))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    let overview: Vec<Vec<String>> = splits
+        .iter()
+        .map(|split| {
+            let mut durations = split.durations.clone();
+            durations.sort_by(f64::total_cmp);
+            let mut words: Vec<f64> = split
+                .transcriptions
+                .iter()
+                .map(|text| splits::words(text).count() as f64)
+                .collect();
+            words.sort_by(f64::total_cmp);
+            let chars: usize = split
+                .transcriptions
+                .iter()
+                .map(|text| text.chars().count())
+                .sum();
+            let clips = split.durations.len();
+            let mean = |sum: f64| if clips == 0 { 0.0 } else { sum / clips as f64 };
+            let quantile = |sorted: &[f64], q: f64| {
+                if sorted.is_empty() {
+                    0.0
+                } else {
+                    crate::quantile(sorted, q)
+                }
+            };
+            vec![
+                split.name.to_string(),
+                split
+                    .files
+                    .iter()
+                    .map(|file| {
+                        format!(
+                            r#"<a href="/view/{}" class="text-blue-600 dark:text-blue-400 hover:underline">{}</a>"#,
+                            html::url_encode(file),
+                            html::escape(file)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                clips.to_string(),
+                format!("{:.2}", split.durations.iter().sum::<f64>() / 3600.0),
+                format!("{:.2}", mean(split.durations.iter().sum())),
+                format!("{:.2}", quantile(&durations, 0.5)),
+                format!("{:.1}", mean(chars as f64)),
+                format!("{:.1}", mean(words.iter().sum())),
+                format!("{:.0}", quantile(&words, 0.5)),
+                format!("{:.0}", quantile(&words, 0.9)),
+                split.vocabulary().len().to_string(),
+                match &split.speakers {
+                    Some(_) => split.speaker_counts().len().to_string(),
+                    None => "&mdash;".to_string(),
+                },
+            ]
+        })
+        .collect();
+    out.push_str(r#"<h2 class="text-xl font-semibold mb-2">Overview</h2>"#);
+    out.push_str(&table(
+        &[
+            "Split",
+            "Files",
+            "Clips",
+            "Hours",
+            "Mean s",
+            "Median s",
+            "Mean characters",
+            "Mean words",
+            "Median words",
+            "P90 words",
+            "Vocabulary",
+            "Speakers",
+        ],
+        &overview,
+    ));
+
+    let mut headers = vec!["Split".to_string()];
+    let mut lower = 0.0;
+    for upper in card::DURATION_BUCKETS {
+        headers.push(format!("{}–{} s", lower, upper));
+        lower = upper;
+    }
+    headers.push(format!("≥ {} s", lower));
+    let buckets: Vec<Vec<String>> = splits
+        .iter()
+        .map(|split| {
+            let mut counts = [0usize; card::DURATION_BUCKETS.len() + 1];
+            for duration in &split.durations {
+                counts[card::DURATION_BUCKETS
+                    .iter()
+                    .position(|upper| duration < upper)
+                    .unwrap_or(card::DURATION_BUCKETS.len())] += 1;
+            }
+            std::iter::once(split.name.to_string())
+                .chain(counts.iter().map(|&count| {
+                    format!(
+                        "{:.1}%",
+                        count as f64 * 100.0 / split.durations.len().max(1) as f64
+                    )
+                }))
+                .collect()
+        })
+        .collect();
+    out.push_str(r#"<h2 class="text-xl font-semibold mb-2">Clip durations</h2><p class="mb-2 text-sm text-gray-500 dark:text-gray-400">Share of the clips of each split per duration range; very different shapes mean the evaluation does not reflect the training data.</p>"#);
+    out.push_str(&table(
+        &headers.iter().map(String::as_str).collect::<Vec<_>>(),
+        &buckets,
+    ));
+
+    if labeled {
+        let rows: Vec<Vec<String>> = pairs
+            .iter()
+            .zip(&shared)
+            .map(|((a, b), shared)| {
+                let count = if shared.is_empty() {
+                    "0".to_string()
+                } else {
+                    format!(
+                        r#"<span class="px-2 rounded bg-red-100 text-red-800 dark:bg-red-900 dark:text-red-200">{}</span>"#,
+                        shared.len()
+                    )
+                };
+                let mut sorted = shared.clone();
+                sorted.sort_by_key(|(_, a, b)| std::cmp::Reverse(a + b));
+                vec![
+                    format!("{} / {}", a.name, b.name),
+                    count,
+                    shared.iter().map(|(_, a, _)| a).sum::<usize>().to_string(),
+                    shared.iter().map(|(_, _, b)| b).sum::<usize>().to_string(),
+                    sorted
+                        .iter()
+                        .take(MAX_SHARED_SPEAKERS)
+                        .map(|(speaker, a, b)| format!("{} ({} / {})", html::escape(speaker), a, b))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ]
+            })
+            .collect();
+        out.push_str(r#"<h2 class="text-xl font-semibold mb-2">Speaker overlap</h2><p class="mb-2 text-sm"><a href="/splits/speakers" class="text-blue-600 dark:text-blue-400 hover:underline">Speaker leakage check</a>: the rows of every shared speaker, and similar voices across splits.</p>"#);
+        out.push_str(&table(
+            &[
+                "Splits",
+                "Shared speakers",
+                "Clips in first",
+                "Clips in second",
+                "Speakers (clips)",
+            ],
+            &rows,
+        ));
+    }
+
+    if let Some((reference, others)) = splits.split_first() {
+        let rows: Vec<Vec<String>> = others
+            .iter()
+            .map(|split| {
+                let (types, tokens) = splits::vocabulary_coverage(split, reference);
+                vec![
+                    split.name.to_string(),
+                    format!("{:.1}%", types * 100.0),
+                    format!("{:.2}%", (1.0 - tokens) * 100.0),
+                ]
+            })
+            .collect();
+        if !rows.is_empty() {
+            out.push_str(&format!(
+                r#"<h2 class="text-xl font-semibold mb-2">Vocabulary overlap</h2><p class="mb-2 text-sm text-gray-500 dark:text-gray-400">Words of each split compared with the {} vocabulary; the out-of-vocabulary rate is the share of running words never seen in {}. The <a href="/splits/text" class="text-blue-600 dark:text-blue-400 hover:underline">text leakage check</a> lists transcriptions repeated across splits.</p>"#,
+                reference.name, reference.name
+            ));
+            out.push_str(&table(
+                &["Split", "Distinct words covered", "Out-of-vocabulary rate"],
+                &rows,
+            ));
+        }
+    }
+    out
+}
+
+/// Rows linked per split of a leaking speaker or voice cluster.
+const MAX_LEAK_ROWS: usize = 10;
+
+/// Row links of a leaking group, one line per split.
+fn leak_rows(clips: &[&splits::Clip]) -> String {
+    splits::by_split(clips)
+        .iter()
+        .map(|(split, members)| {
+            let more = members.len().saturating_sub(MAX_LEAK_ROWS);
+            format!(
+                r#"<div><span class="font-semibold">{}</span> ({}): {}{}</div>"#,
+                split,
+                members.len(),
+                members
+                    .iter()
+                    .take(MAX_LEAK_ROWS)
+                    .map(|clip| row_link(&clip.file, clip.row))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                if more > 0 {
+                    format!(", &hellip; {} more", more)
+                } else {
+                    String::new()
+                }
+            )
+        })
+        .collect()
+}
+
+/// Renders the speaker leakage check: speaker labels used in more than one split and,
+/// with `threshold`, clusters of similar voices spanning splits, which also catch a
+/// speaker labeled differently in each split.
+pub fn render_speaker_leakage(
+    clips: &[splits::Clip],
+    labeled: bool,
+    speaker_column: &str,
+    threshold: Option<f32>,
+) -> String {
+    let labels = splits::label_leaks(clips);
+    let clusters = threshold
+        .map(|threshold| splits::cluster_leaks(clips, threshold))
+        .unwrap_or_default();
+    let embedded = clips.iter().filter(|clip| clip.embedding.is_some()).count();
+
+    let mut out = if labels.is_empty() && clusters.is_empty() {
+        r#"<p class="mb-4 p-3 rounded bg-green-100 text-green-900 dark:bg-green-900 dark:text-green-100">No speaker leakage found.</p>"#.to_string()
+    } else {
+        format!(
+            r#"<p class="mb-4 p-3 rounded border-l-4 border-red-500 bg-red-100 text-red-900 dark:bg-red-900 dark:text-red-100" role="alert"><strong>Speaker leakage:</strong> {} speaker label{} and {} voice cluster{} with clips in more than one split. Move each speaker's clips into a single split.</p>"#,
+            labels.len(),
+            if labels.len() == 1 { "" } else { "s" },
+            clusters.len(),
+            if clusters.len() == 1 { "" } else { "s" }
+        )
+    };
+
+    out.push_str(r#"<h2 class="text-xl font-semibold mb-2">Speaker labels</h2>"#);
+    if !labeled {
+        out.push_str(&format!(
+            r#"<p class="mb-6 text-sm text-gray-500 dark:text-gray-400">Not every file has a <code>{}</code> column; rows without a label are not compared.</p>"#,
+            html::escape(speaker_column)
+        ));
+    }
+    if labels.is_empty() {
+        out.push_str(r#"<p class="mb-6">No speaker label is used in more than one split.</p>"#);
+    } else {
+        let rows: Vec<Vec<String>> = labels
+            .iter()
+            .map(|(speaker, members)| {
+                vec![
+                    html::escape(speaker),
+                    members.len().to_string(),
+                    leak_rows(members),
+                ]
+            })
+            .collect();
+        out.push_str(&table(&["Speaker", "Clips", "Rows"], &rows));
+    }
+
+    out.push_str(r#"<h2 class="text-xl font-semibold mb-2">Voice clusters</h2>"#);
+    match threshold {
+        None => out.push_str(r#"<p class="mb-6 text-sm text-gray-500 dark:text-gray-400">Set <code>--embedding-url</code> to also compare voices, which catches speakers labeled differently in each split.</p>"#),
+        Some(threshold) => {
+            out.push_str(&format!(
+                r#"<p class="mb-2 text-sm text-gray-500 dark:text-gray-400">{} of {} clips embedded (compute them on the <a href="/speakers" class="text-blue-600 dark:text-blue-400 hover:underline">Speakers</a> page). Similarity threshold {:.2}.</p>"#,
+                embedded,
+                clips.len(),
+                threshold
+            ));
+            if clusters.is_empty() {
+                out.push_str(r#"<p class="mb-6">No cluster of similar voices spans splits.</p>"#);
+            }
+            for members in &clusters {
+                let labels = sorted_counts(
+                    members
+                        .iter()
+                        .filter(|clip| !clip.speaker.is_empty())
+                        .map(|clip| clip.speaker.as_str()),
+                )
+                .iter()
+                .map(|(speaker, count)| format!("{} &times;{}", html::escape(speaker), count))
+                .collect::<Vec<_>>()
+                .join(", ");
+                out.push_str(&format!(
+                    r#"<div class="mb-4 p-3 rounded bg-gray-50 dark:bg-gray-700"><div class="font-semibold">{} clips{}</div><div class="mt-1 text-sm">{}</div></div>"#,
+                    members.len(),
+                    if labels.is_empty() {
+                        String::new()
+                    } else {
+                        format!(": {}", labels)
+                    },
+                    leak_rows(members)
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// Leaked transcriptions listed on the text leakage check; the rest are only counted.
+const MAX_TEXT_LEAKS: usize = 500;
+
+/// Renders the text leakage check: transcriptions of the validation and test splits
+/// identical or nearly identical to one of an earlier split, closest matches first.
+pub fn render_text_leakage(lines: &[splits::Line], max_distance: f64, min_words: usize) -> String {
+    let mut leaks = splits::text_leaks(lines, max_distance, min_words);
+    leaks.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+    let mut out = if leaks.is_empty() {
+        r#"<p class="mb-4 p-3 rounded bg-green-100 text-green-900 dark:bg-green-900 dark:text-green-100">No transcription of a later split matches one of an earlier split.</p>"#.to_string()
+    } else {
+        let identical = leaks.iter().filter(|leak| leak.distance == 0.0).count();
+        format!(
+            r#"<p class="mb-4 p-3 rounded border-l-4 border-red-500 bg-red-100 text-red-900 dark:bg-red-900 dark:text-red-100" role="alert"><strong>Text leakage:</strong> {} transcription{} of a later split ({} identical) found in an earlier split. Remove them from the evaluation split or its scores will be inflated.</p>"#,
+            leaks.len(),
+            if leaks.len() == 1 { "" } else { "s" },
+            identical
+        )
+    };
+    out.push_str(&format!(
+        r#"<p class="mb-4 text-sm text-gray-500 dark:text-gray-400">Transcriptions are compared lowercased and without punctuation; a match differs by at most {:.0}% of its characters (<code>max_distance={}</code>). Transcriptions of fewer than {} words are skipped (<code>min_words</code>).</p>"#,
+        max_distance * 100.0,
+        max_distance,
+        min_words
+    ));
+
+    let rows: Vec<Vec<String>> = splits::SPLITS
+        .iter()
+        .skip(1)
+        .filter_map(|(split, _)| {
+            let total = lines.iter().filter(|line| line.split == *split).count();
+            if total == 0 {
+                return None;
+            }
+            let leaked: Vec<&splits::TextLeak> = leaks
+                .iter()
+                .filter(|leak| leak.line.split == *split)
+                .collect();
+            Some(vec![
+                split.to_string(),
+                total.to_string(),
+                leaked
+                    .iter()
+                    .filter(|leak| leak.distance == 0.0)
+                    .count()
+                    .to_string(),
+                leaked.len().to_string(),
+                format!("{:.1}%", leaked.len() as f64 * 100.0 / total as f64),
+            ])
+        })
+        .collect();
+    out.push_str(&table(
+        &["Split", "Rows", "Identical", "Identical or near", "Leaked"],
+        &rows,
+    ));
+
+    if leaks.is_empty() {
+        return out;
+    }
+    if leaks.len() > MAX_TEXT_LEAKS {
+        out.push_str(&format!(
+            r#"<p class="mb-2 text-sm text-gray-500 dark:text-gray-400">The {} closest of {} matches:</p>"#,
+            MAX_TEXT_LEAKS,
+            leaks.len()
+        ));
+    }
+    let rows: Vec<Vec<String>> = leaks
+        .iter()
+        .take(MAX_TEXT_LEAKS)
+        .map(|leak| {
+            vec![
+                row_link(&leak.line.file, leak.line.row),
+                html::escape(&leak.line.text),
+                row_link(&leak.closest.file, leak.closest.row),
+                html::escape(&leak.closest.text),
+                format!("{:.2}", leak.distance),
+            ]
+        })
+        .collect();
+    out.push_str(&table(
+        &[
+            "Row",
+            "Transcription",
+            "Matches",
+            "Transcription",
+            "Distance",
+        ],
+        &rows,
+    ));
+    out
+}
@@ -0,0 +1,71 @@
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::{Arc, Mutex},
+};
+
+/// Number of entries a cache keeps in memory unless created with another capacity.
+const CAPACITY: usize = 64;
+
+/// Cache of computed row lists (filtered and sorted row indices, token counts) and
+/// clip analysis, keyed by everything they depend on; the oldest entries are evicted first.
+///
+/// Keys include the file modification time and annotation version, so stale
+/// entries are never hit and simply age out.
+pub struct RowCache<K, V> {
+    entries: Mutex<Entries<K, V>>,
+    capacity: usize,
+}
+
+struct Entries<K, V> {
+    values: HashMap<K, Arc<V>>,
+    /// Keys in insertion order, oldest first.
+    order: VecDeque<K>,
+}
+
+impl<K: Clone + Eq + Hash, V> Default for RowCache<K, V> {
+    fn default() -> Self {
+        Self::with_capacity(CAPACITY)
+    }
+}
+
+impl<K: Clone + Eq + Hash, V> RowCache<K, V> {
+    /// A cache keeping at most `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(Entries {
+                values: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+            capacity,
+        }
+    }
+
+    /// Returns the cached value for `key` without computing it on a miss.
+    pub fn get<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> Option<Arc<V>>
+    where
+        K: Borrow<Q>,
+    {
+        self.entries.lock().unwrap().values.get(key).cloned()
+    }
+
+    /// Returns the cached value for `key`, computing and storing it on a miss.
+    pub fn get_or_insert_with(&self, key: K, compute: impl FnOnce() -> V) -> Arc<V> {
+        if let Some(value) = self.entries.lock().unwrap().values.get(&key) {
+            return value.clone();
+        }
+        // Computed without the lock held; concurrent misses may compute twice.
+        let value = Arc::new(compute());
+        let mut entries = self.entries.lock().unwrap();
+        if entries.values.insert(key.clone(), value.clone()).is_none() {
+            entries.order.push_back(key);
+            if entries.order.len() > self.capacity
+                && let Some(oldest) = entries.order.pop_front()
+            {
+                entries.values.remove(&oldest);
+            }
+        }
+        value
+    }
+}
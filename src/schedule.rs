@@ -0,0 +1,40 @@
+use std::{str::FromStr, time::Duration};
+
+/// A period given as a number and a unit: `90s`, `30m`, `1h` or `1d`.
+#[derive(Clone, Copy, Debug)]
+pub struct Every(pub Duration);
+
+impl FromStr for Every {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (amount, unit) = s.split_at(split);
+        let amount: u64 = amount
+            .parse()
+            .map_err(|_| format!("invalid period `{}`, expected e.g. 30m or 1h", s))?;
+        let seconds = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86_400,
+            _ => return Err(format!("invalid unit in `{}`, expected s, m, h or d", s)),
+        };
+        if amount == 0 {
+            return Err("the period must be positive".to_string());
+        }
+        Ok(Self(Duration::from_secs(amount * seconds)))
+    }
+}
+
+/// Runs `work` every `period`, starting one period from now. A run that takes longer
+/// than the period delays the next one instead of overlapping it.
+pub async fn run_every<F: Future<Output = ()>>(period: Duration, mut work: impl FnMut() -> F) {
+    let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticks.tick().await;
+        work().await;
+    }
+}
@@ -0,0 +1,146 @@
+use polars::prelude::*;
+
+/// Files named per type or per missing column before the rest is summarized.
+const MAX_FILE_NAMES: usize = 10;
+
+/// Types of one column across the compared files.
+#[derive(Clone, Debug)]
+pub struct ColumnTypes {
+    pub name: String,
+    /// Type in each file, in file order; `None` where the column is missing.
+    pub types: Vec<Option<String>>,
+}
+
+impl ColumnTypes {
+    /// Present in every file with the same type.
+    pub fn consistent(&self) -> bool {
+        self.types
+            .iter()
+            .all(|t| t.is_some() && *t == self.types[0])
+    }
+
+    /// Distinct types with the files having them, most common first.
+    pub fn type_groups<'a>(&self, files: &'a [String]) -> Vec<(&str, Vec<&'a str>)> {
+        let mut groups: Vec<(&str, Vec<&str>)> = Vec::new();
+        for (file, dtype) in files.iter().zip(&self.types) {
+            let Some(dtype) = dtype else { continue };
+            match groups.iter_mut().find(|(t, _)| t == dtype) {
+                Some((_, files)) => files.push(file),
+                None => groups.push((dtype, vec![file])),
+            }
+        }
+        groups.sort_by_key(|(_, files)| std::cmp::Reverse(files.len()));
+        groups
+    }
+
+    /// Files missing the column.
+    pub fn missing_in<'a>(&self, files: &'a [String]) -> Vec<&'a str> {
+        files
+            .iter()
+            .zip(&self.types)
+            .filter(|(_, dtype)| dtype.is_none())
+            .map(|(file, _)| file.as_str())
+            .collect()
+    }
+
+    /// One-line description of the inconsistency, empty when consistent.
+    pub fn describe(&self, files: &[String]) -> String {
+        let mut parts = Vec::new();
+        let missing = self.missing_in(files);
+        if !missing.is_empty() {
+            parts.push(format!("missing in {}", file_list(&missing)));
+        }
+        let groups = self.type_groups(files);
+        if groups.len() > 1 {
+            let types = groups
+                .iter()
+                .enumerate()
+                .map(|(i, (dtype, files))| {
+                    if i == 0 {
+                        format!("{} in {} files", dtype, files.len())
+                    } else {
+                        format!("{} in {}", dtype, file_list(files))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            parts.push(types);
+        }
+        parts.join("; ")
+    }
+}
+
+fn file_list(files: &[&str]) -> String {
+    let mut list = files
+        .iter()
+        .take(MAX_FILE_NAMES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if files.len() > MAX_FILE_NAMES {
+        list.push_str(&format!(" and {} more", files.len() - MAX_FILE_NAMES));
+    }
+    list
+}
+
+/// Column types of a file, with struct fields flattened to `column.field` and the
+/// fields of list elements to `column[].field`, so a field missing deep inside a column
+/// is reported by name.
+pub fn flatten(columns: impl IntoIterator<Item = (String, DataType)>) -> Vec<(String, String)> {
+    let mut flat = Vec::new();
+    for (name, dtype) in columns {
+        flatten_into(&name, &dtype, &mut flat);
+    }
+    flat
+}
+
+fn flatten_into(name: &str, dtype: &DataType, flat: &mut Vec<(String, String)>) {
+    match dtype {
+        DataType::Struct(fields) => {
+            for field in fields {
+                flatten_into(&format!("{}.{}", name, field.name()), field.dtype(), flat);
+            }
+        }
+        DataType::List(inner) if matches!(**inner, DataType::Struct(_)) => {
+            flatten_into(&format!("{}[]", name), inner, flat);
+        }
+        dtype => flat.push((name.to_string(), dtype.to_string())),
+    }
+}
+
+/// Aligns the flattened schemas of several files by column name, in order of first
+/// appearance.
+pub fn compare(schemas: &[Vec<(String, String)>]) -> Vec<ColumnTypes> {
+    let mut columns: Vec<ColumnTypes> = Vec::new();
+    for (i, schema) in schemas.iter().enumerate() {
+        for (name, dtype) in schema {
+            let index = match columns.iter().position(|column| &column.name == name) {
+                Some(index) => index,
+                None => {
+                    columns.push(ColumnTypes {
+                        name: name.clone(),
+                        types: vec![None; schemas.len()],
+                    });
+                    columns.len() - 1
+                }
+            };
+            columns[index].types[i] = Some(dtype.clone());
+        }
+    }
+    columns
+}
+
+/// Plain-text report for the command line.
+pub fn render_text(files: &[String], columns: &[ColumnTypes]) -> String {
+    let inconsistent: Vec<&ColumnTypes> = columns.iter().filter(|c| !c.consistent()).collect();
+    let mut out = format!(
+        "Compared {} files, {} columns: {} inconsistent.\n",
+        files.len(),
+        columns.len(),
+        inconsistent.len()
+    );
+    for column in inconsistent {
+        out.push_str(&format!("{}: {}\n", column.name, column.describe(files)));
+    }
+    out
+}
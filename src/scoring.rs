@@ -0,0 +1,112 @@
+use anyhow::{Context, anyhow};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tokio::{sync::Semaphore, task::JoinSet};
+
+/// Client for an external per-clip quality model (MOS predictor, DNSMOS service, ...).
+///
+/// Each clip is POSTed as `audio/wav`; the response is either a bare number or a
+/// JSON object holding the score under `field` (dots descend into nested objects).
+#[derive(Clone)]
+pub struct Scorer {
+    client: reqwest::Client,
+    url: String,
+    field: String,
+    concurrency: usize,
+    scores: Arc<Mutex<HashMap<PathBuf, f64>>>,
+}
+
+impl Scorer {
+    pub fn new(url: String, field: String, concurrency: usize) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            field,
+            concurrency: concurrency.max(1),
+            scores: Arc::default(),
+        }
+    }
+
+    /// Returns the score of a clip if it has already been computed.
+    pub fn cached(&self, wav_path: &Path) -> Option<f64> {
+        self.scores.lock().unwrap().get(wav_path).copied()
+    }
+
+    /// Number of clips scored so far.
+    pub fn scored(&self) -> usize {
+        self.scores.lock().unwrap().len()
+    }
+
+    /// Scores a clip, reusing the cached value when present.
+    pub async fn score(&self, wav_path: &Path) -> anyhow::Result<f64> {
+        if let Some(score) = self.cached(wav_path) {
+            return Ok(score);
+        }
+
+        let bytes = tokio::fs::read(wav_path)
+            .await
+            .with_context(|| format!("failed to read {}", wav_path.display()))?;
+        let response = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "audio/wav")
+            .body(bytes)
+            .send()
+            .await?
+            .error_for_status()?;
+        let score = parse_score(&response.text().await?, &self.field)?;
+
+        self.scores
+            .lock()
+            .unwrap()
+            .insert(wav_path.to_path_buf(), score);
+        Ok(score)
+    }
+
+    /// Scores every clip with at most `concurrency` requests in flight.
+    ///
+    /// Returns the number of scored clips and the errors of the failed ones.
+    pub async fn score_all(&self, wav_paths: Vec<PathBuf>) -> (usize, Vec<anyhow::Error>) {
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let mut tasks = JoinSet::new();
+        for path in wav_paths {
+            let scorer = self.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await?;
+                scorer
+                    .score(&path)
+                    .await
+                    .with_context(|| format!("failed to score {}", path.display()))
+            });
+        }
+
+        let mut scored = 0;
+        let mut errors = Vec::new();
+        while let Some(result) = tasks.join_next().await {
+            match result.map_err(anyhow::Error::from).and_then(|r| r) {
+                Ok(_) => scored += 1,
+                Err(err) => errors.push(err),
+            }
+        }
+        (scored, errors)
+    }
+}
+
+/// Extracts the score from a response body.
+fn parse_score(body: &str, field: &str) -> anyhow::Result<f64> {
+    if let Ok(score) = body.trim().parse::<f64>() {
+        return Ok(score);
+    }
+
+    let json: serde_json::Value =
+        serde_json::from_str(body).context("response is neither a number nor JSON")?;
+    field
+        .split('.')
+        .try_fold(&json, |value, key| value.get(key))
+        .and_then(|value| value.as_f64())
+        .ok_or_else(|| anyhow!("response has no numeric `{}` field", field))
+}
@@ -0,0 +1,212 @@
+use anyhow::Context;
+use rusqlite::{Connection, OptionalExtension, params};
+use std::{collections::HashMap, path::Path, sync::Mutex, time::UNIX_EPOCH};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS files (
+        name TEXT PRIMARY KEY,
+        modified INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS rows (
+        id INTEGER PRIMARY KEY,
+        file TEXT NOT NULL,
+        row INTEGER NOT NULL,
+        duration REAL NOT NULL,
+        transcription TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS rows_file_row ON rows (file, row);
+    CREATE VIRTUAL TABLE IF NOT EXISTS transcripts USING fts5(
+        transcription, content='rows', content_rowid='id', tokenize='trigram'
+    );
+    CREATE TRIGGER IF NOT EXISTS rows_insert AFTER INSERT ON rows BEGIN
+        INSERT INTO transcripts (rowid, transcription) VALUES (new.id, new.transcription);
+    END;
+    CREATE TRIGGER IF NOT EXISTS rows_delete AFTER DELETE ON rows BEGIN
+        INSERT INTO transcripts (transcripts, rowid, transcription)
+            VALUES ('delete', old.id, old.transcription);
+    END;
+";
+
+/// A transcription matching a search.
+pub struct SearchHit {
+    pub file: String,
+    pub row: usize,
+    pub duration: f64,
+    pub transcription: String,
+}
+
+/// Full-text index of the original transcriptions of every Parquet file, persisted in
+/// SQLite (FTS5 with the trigram tokenizer, so any substring of three or more characters
+/// is looked up in the index).
+pub struct SearchIndex {
+    connection: Mutex<Connection>,
+    /// Held while files are reindexed, so concurrent refreshes do not index a file twice.
+    refreshing: Mutex<()>,
+    /// Files and modification times as of the last refresh, so refreshing is a no-op
+    /// while they are unchanged.
+    versions: Mutex<Vec<(String, i64)>>,
+}
+
+impl SearchIndex {
+    /// Opens the index at `path`, creating it if needed.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let connection =
+            Connection::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        connection
+            .execute_batch(SCHEMA)
+            .with_context(|| format!("failed to create the search index in {}", path.display()))?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+            refreshing: Mutex::new(()),
+            versions: Mutex::default(),
+        })
+    }
+
+    /// Reindexes the files whose modification time changed since they were indexed and
+    /// drops the files that are gone. Returns the number of reindexed files; while no file
+    /// changed since the last refresh, that costs a `stat` per file.
+    ///
+    /// `load` reads the duration and transcription of every row of a file.
+    pub fn refresh(
+        &self,
+        folder: &Path,
+        files: &[String],
+        load: impl Fn(&str) -> anyhow::Result<Vec<(f64, String)>>,
+    ) -> anyhow::Result<usize> {
+        let versions = files
+            .iter()
+            .map(|file| Ok((file.clone(), modified_nanos(&folder.join(file))?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let _refreshing = self.refreshing.lock().unwrap();
+        if *self.versions.lock().unwrap() == versions {
+            return Ok(0);
+        }
+        let indexed: HashMap<String, i64> = {
+            let connection = self.connection.lock().unwrap();
+            let mut statement = connection.prepare("SELECT name, modified FROM files")?;
+            statement
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<_, _>>()?
+        };
+
+        let mut reindexed = 0;
+        for (file, modified) in &versions {
+            let modified = *modified;
+            if indexed.get(file) == Some(&modified) {
+                continue;
+            }
+            let rows = load(file).with_context(|| format!("failed to read {}", file))?;
+            let mut connection = self.connection.lock().unwrap();
+            let transaction = connection.transaction()?;
+            transaction.execute("DELETE FROM rows WHERE file = ?1", [file])?;
+            {
+                let mut insert = transaction.prepare(
+                    "INSERT INTO rows (file, row, duration, transcription) VALUES (?1, ?2, ?3, ?4)",
+                )?;
+                for (row, (duration, transcription)) in rows.iter().enumerate() {
+                    insert.execute(params![file, row as i64, duration, transcription])?;
+                }
+            }
+            transaction.execute(
+                "INSERT OR REPLACE INTO files (name, modified) VALUES (?1, ?2)",
+                params![file, modified],
+            )?;
+            transaction.commit()?;
+            reindexed += 1;
+        }
+
+        let connection = self.connection.lock().unwrap();
+        for file in indexed.keys().filter(|file| !files.contains(file)) {
+            connection.execute("DELETE FROM rows WHERE file = ?1", [file])?;
+            connection.execute("DELETE FROM files WHERE name = ?1", [file])?;
+        }
+        *self.versions.lock().unwrap() = versions;
+        Ok(reindexed)
+    }
+
+    /// Returns the first `limit` rows (by file and row) of `files` whose transcription
+    /// contains `query`, case-insensitively, and the total number of matching rows.
+    pub fn search(
+        &self,
+        query: &str,
+        files: &[String],
+        limit: usize,
+    ) -> anyhow::Result<(Vec<SearchHit>, usize)> {
+        let (condition, pattern) = match_condition(query);
+        let files = serde_json::to_string(files)?;
+        let connection = self.connection.lock().unwrap();
+        let total: i64 = connection.query_row(
+            &format!(
+                "SELECT count(*) FROM rows WHERE {} AND file IN (SELECT value FROM json_each(?2))",
+                condition
+            ),
+            params![pattern, files],
+            |row| row.get(0),
+        )?;
+        let mut statement = connection.prepare(&format!(
+            "SELECT file, row, duration, transcription FROM rows
+             WHERE {} AND file IN (SELECT value FROM json_each(?2))
+             ORDER BY file, row LIMIT ?3",
+            condition
+        ))?;
+        let hits = statement
+            .query_map(params![pattern, files, limit as i64], |row| {
+                Ok(SearchHit {
+                    file: row.get(0)?,
+                    row: row.get::<_, i64>(1)? as usize,
+                    duration: row.get(2)?,
+                    transcription: row.get(3)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok((hits, total as usize))
+    }
+
+    /// Duration of an indexed row and whether its original transcription matches `query`.
+    pub fn row(&self, query: &str, file: &str, row: usize) -> anyhow::Result<Option<(f64, bool)>> {
+        let (condition, pattern) = match_condition(query);
+        let connection = self.connection.lock().unwrap();
+        Ok(connection
+            .query_row(
+                &format!(
+                    "SELECT duration, {} FROM rows WHERE file = ?2 AND row = ?3",
+                    condition
+                ),
+                params![pattern, file, row as i64],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?)
+    }
+}
+
+/// SQL condition on `rows` matching `?1`, and the value to bind.
+///
+/// The trigram index needs at least three characters; shorter queries scan with `LIKE`.
+fn match_condition(query: &str) -> (&'static str, String) {
+    if query.chars().count() >= 3 {
+        (
+            "id IN (SELECT rowid FROM transcripts WHERE transcripts MATCH ?1)",
+            format!("\"{}\"", query.replace('"', "\"\"")),
+        )
+    } else {
+        let escaped = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        (
+            "transcription LIKE ?1 ESCAPE '\\'",
+            format!("%{}%", escaped),
+        )
+    }
+}
+
+fn modified_nanos(path: &Path) -> anyhow::Result<i64> {
+    let modified = path
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(modified
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as i64)
+}
@@ -0,0 +1,137 @@
+use crate::html;
+use polars::prelude::*;
+
+/// List-of-struct column holding the transcript segments of a long recording.
+pub const COLUMN: &str = "segments";
+/// Accepted struct field names, in order of preference.
+const START_FIELDS: [&str; 2] = ["start", "begin"];
+const END_FIELDS: [&str; 1] = ["end"];
+const TEXT_FIELDS: [&str; 2] = ["text", "transcription"];
+
+/// A transcribed span of a recording, in seconds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// Reads the segments of one row from a `segments` cell; anything that is not a list of
+/// structs with start and end fields yields no segments.
+pub fn parse(value: AnyValue) -> Vec<Segment> {
+    let AnyValue::List(series) = value else {
+        return Vec::new();
+    };
+    parse_series(&series).unwrap_or_default()
+}
+
+fn parse_series(series: &Series) -> PolarsResult<Vec<Segment>> {
+    let fields = series.struct_()?.fields_as_series();
+    let field = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| fields.iter().find(|field| field.name() == name))
+    };
+    let (Some(start), Some(end)) = (field(&START_FIELDS), field(&END_FIELDS)) else {
+        return Ok(Vec::new());
+    };
+    let start = start.cast(&DataType::Float64)?;
+    let end = end.cast(&DataType::Float64)?;
+    let text = field(&TEXT_FIELDS)
+        .map(|text| text.cast(&DataType::String))
+        .transpose()?;
+
+    let mut segments: Vec<Segment> = start
+        .f64()?
+        .into_iter()
+        .zip(end.f64()?)
+        .enumerate()
+        .filter_map(|(i, (start, end))| {
+            let text = text
+                .as_ref()
+                .and_then(|text| text.str().ok()?.get(i).map(str::to_string));
+            Some(Segment {
+                start: start?,
+                end: end?,
+                text: text.unwrap_or_default(),
+            })
+        })
+        .collect();
+    segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Ok(segments)
+}
+
+/// URL playing one segment of a row through the trimming parameters of `/audio`.
+pub fn audio_url(filename: &str, index: &str, segment: &Segment) -> String {
+    format!(
+        "/audio/{}/{}?start={:.3}&end={:.3}",
+        html::url_encode(filename),
+        html::url_encode(index),
+        segment.start,
+        segment.end
+    )
+}
+
+/// Draws the segments as blocks on a bar spanning the recording; clicking a block plays
+/// that segment. `class` sizes the bar.
+pub fn timeline(
+    filename: &str,
+    index: &str,
+    duration: f64,
+    segments: &[Segment],
+    class: &str,
+) -> String {
+    let total = segments
+        .iter()
+        .map(|segment| segment.end)
+        .fold(duration, f64::max);
+    if segments.is_empty() || total <= 0.0 {
+        return String::new();
+    }
+    let blocks: String = segments
+        .iter()
+        .map(|segment| {
+            let left = (segment.start / total * 100.0).clamp(0.0, 100.0);
+            let width = ((segment.end - segment.start) / total * 100.0).clamp(0.0, 100.0 - left);
+            format!(
+                r#"<button type="button" class="absolute top-0 h-full min-w-[2px] bg-blue-500 hover:bg-blue-700 border-x border-white dark:border-gray-800" style="left: {:.3}%; width: {:.3}%" title="{label}" aria-label="{label}" data-src="{}" onclick="event.stopPropagation(); playSegment(this)"></button>"#,
+                left,
+                width,
+                audio_url(filename, index, segment),
+                label = html::escape(&format!("{} {}", span_label(segment), segment.text)),
+            )
+        })
+        .collect();
+    format!(
+        r#"<div class="relative rounded bg-gray-200 dark:bg-gray-700 {}" title="{} segments">{}</div>"#,
+        class,
+        segments.len(),
+        blocks
+    )
+}
+
+/// Table of the segments of a row with their own players, for the row page.
+pub fn table(filename: &str, index: &str, segments: &[Segment]) -> String {
+    let rows: String = segments
+        .iter()
+        .enumerate()
+        .map(|(i, segment)| {
+            format!(
+                r#"<tr class="border-b dark:border-gray-700"><td class="px-2 py-1 text-right">{}</td><td class="px-2 py-1 whitespace-nowrap">{}</td><td class="px-2 py-1"><audio controls="" preload="none" src="{}" aria-label="{}" class="h-8"></audio></td><td class="px-2 py-1">{}</td></tr>"#,
+                i + 1,
+                span_label(segment),
+                audio_url(filename, index, segment),
+                html::escape(&html::clip_label(&segment.text)),
+                html::escape(&segment.text),
+            )
+        })
+        .collect();
+    format!(
+        r#"<table class="w-full mb-4 border-collapse text-sm"><thead><tr class="text-left"><th class="px-2 py-1 text-right">#</th><th class="px-2 py-1">Span</th><th class="px-2 py-1">Audio</th><th class="px-2 py-1">Text</th></tr></thead><tbody>{}</tbody></table>"#,
+        rows
+    )
+}
+
+fn span_label(segment: &Segment) -> String {
+    format!("{:.2}–{:.2} s", segment.start, segment.end)
+}
@@ -0,0 +1,104 @@
+use anyhow::Context;
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    process::{Command, Stdio},
+};
+use tokio::net::TcpListener;
+
+/// URLs the server can be reached at: the bound address itself or, for a wildcard
+/// address, localhost followed by the address of every network interface.
+pub fn urls(addr: SocketAddr) -> Vec<String> {
+    let ips: Vec<IpAddr> = if addr.ip().is_unspecified() {
+        let loopback = if addr.is_ipv4() {
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        } else {
+            IpAddr::V6(Ipv6Addr::LOCALHOST)
+        };
+        let interfaces = if_addrs::get_if_addrs().unwrap_or_default();
+        std::iter::once(loopback)
+            .chain(
+                interfaces
+                    .iter()
+                    .filter(|interface| !interface.is_loopback() && !interface.is_link_local())
+                    // An IPv6 wildcard usually accepts IPv4 connections as well.
+                    .filter(|interface| addr.is_ipv6() || interface.ip().is_ipv4())
+                    .map(|interface| interface.ip()),
+            )
+            .collect()
+    } else {
+        vec![addr.ip()]
+    };
+    ips.into_iter()
+        .map(|ip| format!("http://{}", SocketAddr::new(ip, addr.port())))
+        .collect()
+}
+
+/// Opens `url` in the default browser without waiting for it.
+pub fn open_browser(url: &str) -> io::Result<()> {
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let mut command = Command::new("xdg-open");
+
+    command
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
+
+/// How many ports after the requested one are tried before asking the OS for any free port.
+const FALLBACK_PORTS: u16 = 10;
+
+/// Binds `addr`. With `fallback`, a busy port moves on to the next few ports and then
+/// to an OS-assigned one; the chosen address is available from `local_addr`.
+pub async fn bind(addr: &str, fallback: bool) -> anyhow::Result<TcpListener> {
+    let err = match TcpListener::bind(addr).await {
+        Ok(listener) => return Ok(listener),
+        Err(err) => err,
+    };
+    if err.kind() != io::ErrorKind::AddrInUse {
+        return Err(err).with_context(|| format!("Failed to bind {}", addr));
+    }
+    if !fallback {
+        return Err(err).with_context(|| {
+            format!(
+                "Failed to bind {} (pass --port-fallback to use another port)",
+                addr
+            )
+        });
+    }
+    let requested: SocketAddr = tokio::net::lookup_host(addr)
+        .await?
+        .next()
+        .with_context(|| format!("Failed to resolve {}", addr))?;
+    let ports = (1..=FALLBACK_PORTS)
+        .filter_map(|offset| requested.port().checked_add(offset))
+        .chain([0]);
+    for port in ports {
+        match TcpListener::bind(SocketAddr::new(requested.ip(), port)).await {
+            Ok(listener) => {
+                eprintln!(
+                    "Port {} is in use, using {} instead",
+                    requested.port(),
+                    listener.local_addr()?.port()
+                );
+                return Ok(listener);
+            }
+            Err(err) if err.kind() == io::ErrorKind::AddrInUse => continue,
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("Failed to bind {}:{}", requested.ip(), port));
+            }
+        }
+    }
+    Err(err).with_context(|| format!("Failed to bind {}", addr))
+}
@@ -0,0 +1,91 @@
+use anyhow::{Context, bail};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// Row page actions that can be bound to a key: name, default key and label.
+pub const ACTIONS: [(&str, &str, &str); 12] = [
+    ("play", "k", "Play or pause"),
+    ("back", "j", "Seek back 5 seconds"),
+    ("forward", "l", "Seek forward 5 seconds"),
+    ("previous", "p", "Previous row"),
+    ("next", "n", "Next row"),
+    ("edit", "e", "Edit transcription"),
+    ("save", "ctrl+enter", "Save transcription"),
+    ("loop_a", "a", "Set A"),
+    ("loop_b", "b", "Set B"),
+    ("loop", "o", "Toggle loop"),
+    ("comment", "c", "Comment"),
+    ("help", "?", "Keyboard shortcuts"),
+];
+
+/// Modifiers a key may be prefixed with, in this order, as in `ctrl+alt+enter`.
+const MODIFIERS: [&str; 4] = ["ctrl", "alt", "meta", "shift"];
+
+/// Whether `key` is a key name as the row page script writes them: optional modifiers
+/// followed by the lowercased `KeyboardEvent.key`, with `space` for the space bar.
+pub fn is_valid_key(key: &str) -> bool {
+    let (modifiers, name) = match key.len() {
+        // A lone `+` is the plus key, not a separator.
+        1 => ("", key),
+        _ => key.rsplit_once('+').unwrap_or(("", key)),
+    };
+    let mut allowed = MODIFIERS.iter();
+    !name.is_empty()
+        && !name.contains(char::is_whitespace)
+        && name == name.to_lowercase()
+        && modifiers
+            .split('+')
+            .filter(|modifier| !modifier.is_empty())
+            .all(|modifier| allowed.any(|allowed| *allowed == modifier))
+}
+
+/// Reads default bindings from a JSON object of action names to keys, e.g.
+/// `{"play": "space", "next": "ctrl+arrowdown"}`; unlisted actions keep their default.
+pub fn load(path: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let data = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let bindings: BTreeMap<String, String> = serde_json::from_slice(&data)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    for (action, key) in &bindings {
+        if !ACTIONS.iter().any(|(name, _, _)| name == action) {
+            bail!("unknown action `{}` in {}", action, path.display());
+        }
+        if !is_valid_key(key) {
+            bail!(
+                "invalid key `{}` for `{}` in {}",
+                key,
+                action,
+                path.display()
+            );
+        }
+    }
+    let resolved = resolve(&bindings, &BTreeMap::new());
+    for (i, (action, key, _)) in resolved.iter().enumerate() {
+        if let Some((other, _, _)) = resolved[..i].iter().find(|(_, other, _)| other == key) {
+            bail!(
+                "`{}` and `{}` are both bound to `{}` in {}",
+                other,
+                action,
+                key,
+                path.display()
+            );
+        }
+    }
+    Ok(bindings)
+}
+
+/// Key of every action: the browser's own bindings override the configured defaults,
+/// which override the built-in ones.
+pub fn resolve(
+    defaults: &BTreeMap<String, String>,
+    overrides: &BTreeMap<String, String>,
+) -> Vec<(&'static str, String, &'static str)> {
+    ACTIONS
+        .iter()
+        .map(|&(action, key, label)| {
+            let key = overrides
+                .get(action)
+                .or_else(|| defaults.get(action))
+                .map_or(key, String::as_str);
+            (action, key.to_string(), label)
+        })
+        .collect()
+}
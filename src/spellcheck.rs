@@ -0,0 +1,91 @@
+use anyhow::Context;
+use std::{collections::HashSet, fs, ops::Range, path::PathBuf};
+
+/// Known words transcriptions are spell-checked against.
+#[derive(Clone, Debug)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Merges word lists with one word per line, e.g. a language dictionary and a list of
+    /// domain terms. Hunspell `.dic` files work too: their leading word count and the
+    /// `/FLAGS` after each word are skipped, but affixes are not expanded, so inflected
+    /// forms missing from the file are reported.
+    pub fn load(paths: &[PathBuf]) -> anyhow::Result<Self> {
+        let mut words = HashSet::new();
+        for path in paths {
+            let text = fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            words.extend(
+                text.lines()
+                    .map(|line| line.split('/').next().unwrap_or_default().trim())
+                    .filter(|word| {
+                        !word.is_empty()
+                            && !word.starts_with('#')
+                            && !word.chars().all(|c| c.is_ascii_digit())
+                    })
+                    .map(str::to_lowercase),
+            );
+        }
+        Ok(Self { words })
+    }
+
+    fn knows(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+
+    /// Whether a word is a likely misspelling: not in the dictionary, case-insensitively,
+    /// and not a hyphenated compound of known words. Words with digits are never reported.
+    fn is_suspect(&self, word: &str) -> bool {
+        let word = word.trim_end_matches(['\'', '’']);
+        !word.chars().any(|c| c.is_numeric())
+            && !self.knows(word)
+            && !word
+                .split('-')
+                .all(|part| !part.is_empty() && self.knows(part))
+    }
+
+    /// Byte ranges of the likely misspellings of a text.
+    pub fn suspects(&self, text: &str) -> Vec<Range<usize>> {
+        words(text)
+            .into_iter()
+            .filter(|range| self.is_suspect(&text[range.clone()]))
+            .collect()
+    }
+
+    /// The likely misspellings of a text, lowercased, in text order.
+    pub fn suspect_words(&self, text: &str) -> Vec<String> {
+        self.suspects(text)
+            .into_iter()
+            .map(|range| text[range].to_lowercase())
+            .collect()
+    }
+}
+
+/// Byte ranges of the words of a text: runs of letters and digits, with apostrophes and
+/// hyphens kept inside a word (`don't`, `well-known`).
+fn words(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if !c.is_alphanumeric() {
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        while let Some(&(i, c)) = chars.peek() {
+            let joins = matches!(c, '\'' | '’' | '-')
+                && text[i + c.len_utf8()..]
+                    .chars()
+                    .next()
+                    .is_some_and(char::is_alphanumeric);
+            if !c.is_alphanumeric() && !joins {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        ranges.push(start..end);
+    }
+    ranges
+}
@@ -0,0 +1,202 @@
+use crate::{analysis, archive, dsp, segments, wav::Wav};
+use polars::prelude::*;
+use serde::Deserialize;
+
+/// Length of the frames the pause detector measures, in seconds.
+const FRAME_SECONDS: f64 = 0.03;
+/// Frames at most this far above the noise floor count as a pause, in dB.
+const PAUSE_MARGIN_DB: f32 = 6.0;
+/// Shortest pause a cut is placed in, in seconds.
+const MIN_PAUSE_SECONDS: f64 = 0.2;
+/// Pieces are not cut shorter than this fraction of the maximum duration, so a pause
+/// right after a cut does not leave a sliver.
+const MIN_FILL: f64 = 0.5;
+
+/// How rows longer than the maximum duration are cut.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Method {
+    /// In the middle of the last pause that keeps the piece under the maximum, found by
+    /// an energy-based voice activity detector; hard cuts where speech does not pause.
+    #[default]
+    Vad,
+    /// Into consecutive windows of the maximum duration.
+    Fixed,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Vad => "vad",
+            Method::Fixed => "fixed",
+        }
+    }
+}
+
+/// Spans covering the whole clip, none longer than `max_duration` seconds.
+pub fn plan(wav: &Wav, max_duration: f64, method: Method) -> Vec<(f64, f64)> {
+    let total = wav.duration();
+    let pauses = match method {
+        Method::Vad => pauses(wav),
+        Method::Fixed => Vec::new(),
+    };
+    let mut spans = Vec::new();
+    let mut start = 0.0;
+    while total - start > max_duration {
+        let earliest = start + max_duration * MIN_FILL;
+        let cut = pauses
+            .iter()
+            .copied()
+            .rfind(|&pause| pause > earliest && pause <= start + max_duration)
+            .unwrap_or(start + max_duration);
+        spans.push((start, cut));
+        start = cut;
+    }
+    spans.push((start, total));
+    spans
+}
+
+/// Middles of the pauses of a clip, in seconds.
+fn pauses(wav: &Wav) -> Vec<f64> {
+    let channels = wav.channels.max(1) as usize;
+    let frame = ((FRAME_SECONDS * wav.sample_rate as f64) as usize).max(1) * channels;
+    let threshold = analysis::noise_floor(wav) + PAUSE_MARGIN_DB;
+    let min_frames = (MIN_PAUSE_SECONDS / FRAME_SECONDS).ceil() as usize;
+    let frame_seconds = frame as f64 / channels as f64 / wav.sample_rate as f64;
+
+    let mut pauses = Vec::new();
+    let mut run = 0;
+    let quiet: Vec<bool> = wav
+        .samples
+        .chunks(frame)
+        .map(|chunk| dsp::to_dbfs(dsp::rms(chunk)) <= threshold)
+        .chain([false])
+        .collect();
+    for (i, quiet) in quiet.into_iter().enumerate() {
+        if quiet {
+            run += 1;
+            continue;
+        }
+        if run >= min_frames {
+            pauses.push((i as f64 - run as f64 / 2.0) * frame_seconds);
+        }
+        run = 0;
+    }
+    pauses
+}
+
+/// Transcription of a span: the text of the transcript segments whose middle lies in it.
+pub fn text(span: (f64, f64), transcript: &[segments::Segment]) -> String {
+    transcript
+        .iter()
+        .filter(|segment| {
+            let middle = (segment.start + segment.end) / 2.0;
+            middle >= span.0 && middle < span.1
+        })
+        .map(|segment| segment.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A row of the segmented dataset.
+pub struct Piece {
+    /// Row of the source file the piece was cut from.
+    pub row: usize,
+    pub start: f64,
+    pub end: f64,
+    /// WAV bytes of the piece.
+    pub audio: Vec<u8>,
+    /// Sampling rate of the audio, unknown for rows kept whole in a format other than WAV.
+    pub sampling_rate: Option<u32>,
+    pub transcription: String,
+}
+
+/// Builds the segmented dataset from the source table (without its audio for zip
+/// datasets): each piece keeps the columns of its source row, with its own `audio`,
+/// `duration` and `transcription`, plus `source_row`, `start` and `end`. The `segments`
+/// column, whose times refer to the source row, is dropped.
+pub fn dataframe(source: DataFrame, pieces: &[Piece]) -> PolarsResult<DataFrame> {
+    let indices = IdxCa::from_vec(
+        "idx".into(),
+        pieces.iter().map(|piece| piece.row as IdxSize).collect(),
+    );
+    let mut df = source.take(&indices)?.drop_many([
+        "audio",
+        segments::COLUMN,
+        archive::FILE_NAME_COLUMN,
+        archive::SIZE_COLUMN,
+    ]);
+
+    let bytes = Series::new(
+        "bytes".into(),
+        pieces
+            .iter()
+            .map(|piece| piece.audio.as_slice())
+            .collect::<Vec<_>>(),
+    );
+    let paths = Series::new(
+        "path".into(),
+        pieces
+            .iter()
+            .map(|piece| format!("{}_{:.2}.wav", piece.row, piece.start))
+            .collect::<Vec<_>>(),
+    );
+    let sampling_rates = Series::new(
+        "sampling_rate".into(),
+        pieces
+            .iter()
+            .map(|piece| piece.sampling_rate)
+            .collect::<Vec<_>>(),
+    );
+    let audio = StructChunked::from_series(
+        "audio".into(),
+        pieces.len(),
+        [bytes, sampling_rates, paths].iter(),
+    )?;
+    df.with_column(audio.into_series().into_column())?;
+    df.with_column(Column::new(
+        "duration".into(),
+        pieces
+            .iter()
+            .map(|piece| piece.end - piece.start)
+            .collect::<Vec<_>>(),
+    ))?;
+    df.with_column(Column::new(
+        "transcription".into(),
+        pieces
+            .iter()
+            .map(|piece| piece.transcription.as_str())
+            .collect::<Vec<_>>(),
+    ))?;
+    df.with_column(Column::new(
+        "source_row".into(),
+        pieces
+            .iter()
+            .map(|piece| piece.row as u64)
+            .collect::<Vec<_>>(),
+    ))?;
+    df.with_column(Column::new(
+        "start".into(),
+        pieces.iter().map(|piece| piece.start).collect::<Vec<_>>(),
+    ))?;
+    df.with_column(Column::new(
+        "end".into(),
+        pieces.iter().map(|piece| piece.end).collect::<Vec<_>>(),
+    ))?;
+    Ok(df)
+}
+
+/// WAV bytes of a span of a clip, as 16-bit PCM.
+pub fn cut(wav: &Wav, span: (f64, f64)) -> anyhow::Result<Vec<u8>> {
+    let channels = wav.channels.max(1) as usize;
+    let frame = |seconds: f64| (seconds * wav.sample_rate as f64).round() as usize * channels;
+    let end = frame(span.1).min(wav.samples.len());
+    let start = frame(span.0).min(end);
+    Wav {
+        sample_rate: wav.sample_rate,
+        channels: wav.channels,
+        samples: wav.samples[start..end].to_vec(),
+    }
+    .encode_pcm16()
+}
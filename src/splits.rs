@@ -0,0 +1,292 @@
+use crate::embeddings;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Arc,
+};
+
+/// Dataset splits recognized in file names, in display order, with the words naming them.
+pub const SPLITS: [(&str, &[&str]); 3] = [
+    ("train", &["train", "training"]),
+    ("validation", &["validation", "valid", "val", "dev"]),
+    ("test", &["test", "eval", "evaluation"]),
+];
+
+/// Split of a data folder entry, from a word of its name, e.g. `train` for
+/// `train-00000-of-00002.parquet` and `validation` for `dev_clean.zip`.
+pub fn recognize(filename: &str) -> Option<&'static str> {
+    let stem = filename
+        .rsplit_once('.')
+        .map_or(filename, |(stem, _)| stem)
+        .to_lowercase();
+    stem.split(|c: char| !c.is_alphanumeric()).find_map(|word| {
+        SPLITS
+            .iter()
+            .find(|(_, words)| words.contains(&word))
+            .map(|(split, _)| *split)
+    })
+}
+
+/// Files grouped by split, in [`SPLITS`] order; unrecognized files are left out.
+pub fn group(files: &[String]) -> Vec<(&'static str, Vec<String>)> {
+    SPLITS
+        .iter()
+        .map(|(split, _)| {
+            let files: Vec<String> = files
+                .iter()
+                .filter(|file| recognize(file) == Some(*split))
+                .cloned()
+                .collect();
+            (*split, files)
+        })
+        .filter(|(_, files)| !files.is_empty())
+        .collect()
+}
+
+/// The rows of one split, across its files.
+#[derive(Clone, Debug, Default)]
+pub struct Split {
+    pub name: &'static str,
+    pub files: Vec<String>,
+    pub durations: Vec<f64>,
+    pub transcriptions: Vec<String>,
+    /// Speaker label of every row, when all files have the speaker column.
+    pub speakers: Option<Vec<String>>,
+}
+
+impl Split {
+    /// Distinct speakers with their number of clips; unlabeled rows are left out.
+    pub fn speaker_counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for speaker in self
+            .speakers
+            .iter()
+            .flatten()
+            .filter(|speaker| !speaker.is_empty())
+        {
+            *counts.entry(speaker.as_str()).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Distinct words of the transcriptions with their number of occurrences.
+    pub fn vocabulary(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for word in self.transcriptions.iter().flat_map(|text| words(text)) {
+            *counts.entry(word).or_default() += 1;
+        }
+        counts
+    }
+}
+
+/// Lowercased words of a text, stripped of surrounding punctuation.
+pub fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+}
+
+/// Speakers labeled in both splits, with their clips in each.
+pub fn shared_speakers<'a>(a: &'a Split, b: &Split) -> Vec<(&'a str, usize, usize)> {
+    let theirs = b.speaker_counts();
+    a.speaker_counts()
+        .into_iter()
+        .filter_map(|(speaker, count)| Some((speaker, count, *theirs.get(speaker)?)))
+        .collect()
+}
+
+/// How much of the vocabulary of `split` is covered by `reference`: the share of its
+/// distinct words and of its running words that occur in `reference`.
+pub fn vocabulary_coverage(split: &Split, reference: &Split) -> (f64, f64) {
+    let known: BTreeSet<String> = reference.vocabulary().into_keys().collect();
+    let vocabulary = split.vocabulary();
+    let (mut types, mut tokens, mut total) = (0, 0, 0);
+    for (word, count) in &vocabulary {
+        total += count;
+        if known.contains(word) {
+            types += 1;
+            tokens += count;
+        }
+    }
+    let share = |part: usize, whole: usize| {
+        if whole == 0 {
+            0.0
+        } else {
+            part as f64 / whole as f64
+        }
+    };
+    (share(types, vocabulary.len()), share(tokens, total))
+}
+
+/// A clip checked for speaker leakage.
+#[derive(Clone, Debug)]
+pub struct Clip {
+    pub split: &'static str,
+    pub file: String,
+    pub row: usize,
+    /// Speaker label, empty when the row has none.
+    pub speaker: String,
+    /// Cached speaker embedding, if computed.
+    pub embedding: Option<Arc<Vec<f32>>>,
+}
+
+/// Clips of a group (a speaker or a cluster of voices) by split, in [`SPLITS`] order.
+pub fn by_split<'a>(clips: &[&'a Clip]) -> Vec<(&'static str, Vec<&'a Clip>)> {
+    SPLITS
+        .iter()
+        .map(|(split, _)| {
+            let members: Vec<&Clip> = clips
+                .iter()
+                .copied()
+                .filter(|clip| clip.split == *split)
+                .collect();
+            (*split, members)
+        })
+        .filter(|(_, members)| !members.is_empty())
+        .collect()
+}
+
+/// Speaker labels used in more than one split with their clips, most clips first.
+pub fn label_leaks(clips: &[Clip]) -> Vec<(&str, Vec<&Clip>)> {
+    let mut by_speaker: BTreeMap<&str, Vec<&Clip>> = BTreeMap::new();
+    for clip in clips.iter().filter(|clip| !clip.speaker.is_empty()) {
+        by_speaker.entry(&clip.speaker).or_default().push(clip);
+    }
+    let mut leaks: Vec<(&str, Vec<&Clip>)> = by_speaker
+        .into_iter()
+        .filter(|(_, clips)| clips.iter().any(|clip| clip.split != clips[0].split))
+        .collect();
+    leaks.sort_by_key(|(_, clips)| std::cmp::Reverse(clips.len()));
+    leaks
+}
+
+/// Clusters of similar voices (see [`embeddings::cluster`]) with clips in more than one
+/// split, whatever their labels, largest first. Only clips with an embedding are
+/// clustered.
+pub fn cluster_leaks(clips: &[Clip], threshold: f32) -> Vec<Vec<&Clip>> {
+    let embedded: Vec<&Clip> = clips
+        .iter()
+        .filter(|clip| clip.embedding.is_some())
+        .collect();
+    let vectors: Vec<&[f32]> = embedded
+        .iter()
+        .filter_map(|clip| clip.embedding.as_deref().map(Vec::as_slice))
+        .collect();
+    let mut clusters: BTreeMap<usize, Vec<&Clip>> = BTreeMap::new();
+    for (clip, cluster) in embedded
+        .into_iter()
+        .zip(embeddings::cluster(&vectors, threshold))
+    {
+        clusters.entry(cluster).or_default().push(clip);
+    }
+    let mut leaks: Vec<Vec<&Clip>> = clusters
+        .into_values()
+        .filter(|members| members.iter().any(|clip| clip.split != members[0].split))
+        .collect();
+    leaks.sort_by_key(|members| std::cmp::Reverse(members.len()));
+    leaks
+}
+
+/// A transcription checked for text leakage.
+#[derive(Clone, Debug)]
+pub struct Line {
+    pub split: &'static str,
+    pub file: String,
+    pub row: usize,
+    pub text: String,
+}
+
+impl Line {
+    /// The words of the transcription (see [`words`]) joined by single spaces.
+    pub fn normalized(&self) -> String {
+        words(&self.text).collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// A transcription of a later split matching one of an earlier split.
+#[derive(Clone, Debug)]
+pub struct TextLeak<'a> {
+    pub line: &'a Line,
+    /// The closest transcription of the earlier splits.
+    pub closest: &'a Line,
+    /// Character edit distance between the normalized transcriptions, divided by the
+    /// length of the longer one; 0 for identical transcriptions.
+    pub distance: f64,
+}
+
+/// Character edit distance, or `None` once it exceeds `bound`.
+fn edit_distance(a: &[char], b: &[char], bound: usize) -> Option<usize> {
+    // previous[j] = distance between a[..i] and b[..j].
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(x != y);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        if current.iter().min().is_some_and(|&min| min > bound) {
+            return None;
+        }
+        previous = current;
+    }
+    Some(previous[b.len()]).filter(|&distance| distance <= bound)
+}
+
+/// Transcriptions of every split but the first that are identical or nearly identical
+/// to one of an earlier split (in [`SPLITS`] order, so validation and test are checked
+/// against train, and test against validation): each with its closest match, at a
+/// normalized edit distance of at most `max_distance`. Transcriptions of fewer than
+/// `min_words` words are left out, since short phrases recur in any corpus.
+pub fn text_leaks(lines: &[Line], max_distance: f64, min_words: usize) -> Vec<TextLeak<'_>> {
+    let mut earlier: Vec<(Vec<char>, &Line)> = Vec::new();
+    let mut identical: HashMap<String, &Line> = HashMap::new();
+    let mut leaks = Vec::new();
+    for (split, _) in SPLITS {
+        let current: Vec<(String, &Line)> = lines
+            .iter()
+            .filter(|line| line.split == split)
+            .map(|line| (line.normalized(), line))
+            .filter(|(text, _)| !text.is_empty() && text.split(' ').count() >= min_words)
+            .collect();
+        for (normalized, line) in &current {
+            if let Some(closest) = identical.get(normalized) {
+                leaks.push(TextLeak {
+                    line,
+                    closest,
+                    distance: 0.0,
+                });
+                continue;
+            }
+            let text: Vec<char> = normalized.chars().collect();
+            // The distance is at least the difference in length, so only transcriptions
+            // of a close length can match.
+            let closest = earlier
+                .iter()
+                .filter(|(other, _)| {
+                    text.len().abs_diff(other.len())
+                        <= (max_distance * text.len().max(other.len()) as f64) as usize
+                })
+                .filter_map(|(other, closest)| {
+                    let longest = text.len().max(other.len());
+                    let bound = (max_distance * longest as f64) as usize;
+                    let distance = edit_distance(&text, other, bound)?;
+                    Some((distance as f64 / longest as f64, *closest))
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            if let Some((distance, closest)) = closest {
+                leaks.push(TextLeak {
+                    line,
+                    closest,
+                    distance,
+                });
+            }
+        }
+        for (normalized, line) in current {
+            earlier.push((normalized.chars().collect(), line));
+            identical.entry(normalized).or_insert(line);
+        }
+    }
+    leaks
+}
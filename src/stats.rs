@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Encoding of downloaded chart data.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+/// One value behind a chart: a histogram bin (`count` of the values in `[start, end)`),
+/// a category count or a summary statistic such as `median`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Record {
+    pub chart: String,
+    pub statistic: String,
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+    pub value: f64,
+}
+
+impl Record {
+    pub fn bin(chart: &str, start: f64, end: f64, count: usize) -> Self {
+        Self {
+            chart: chart.to_string(),
+            statistic: "count".to_string(),
+            start: Some(start),
+            end: Some(end),
+            value: count as f64,
+        }
+    }
+
+    pub fn value(chart: &str, statistic: &str, value: f64) -> Self {
+        Self {
+            chart: chart.to_string(),
+            statistic: statistic.to_string(),
+            start: None,
+            end: None,
+            value,
+        }
+    }
+}
+
+/// A text plot and the records it was drawn from, offered for download so the data can
+/// be plotted again elsewhere.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Chart {
+    pub text: String,
+    pub records: Vec<Record>,
+}
+
+/// Content type, file extension and body of the records in `format`.
+pub fn encode(records: &[Record], format: Format) -> (&'static str, &'static str, String) {
+    match format {
+        Format::Json => (
+            "application/json",
+            "json",
+            serde_json::to_string_pretty(records).unwrap_or_default(),
+        ),
+        Format::Csv => {
+            let mut out = String::from("chart,statistic,start,end,value\n");
+            let number = |n: Option<f64>| n.map(|n| n.to_string()).unwrap_or_default();
+            for record in records {
+                out.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    csv_field(&record.chart),
+                    csv_field(&record.statistic),
+                    number(record.start),
+                    number(record.end),
+                    record.value
+                ));
+            }
+            ("text/csv", "csv", out)
+        }
+    }
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Links downloading the data of the charts at `url` as CSV and JSON.
+pub fn download_links(url: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!(
+        r#"<p class="mt-2 text-sm">Download data: <a href="{url}{separator}format=csv" class="text-blue-600 dark:text-blue-400 hover:underline">CSV</a> <a href="{url}{separator}format=json" class="text-blue-600 dark:text-blue-400 hover:underline">JSON</a></p>"#,
+    )
+}
+
+/// Statistics of a whole file: its size and the charts of its metadata details.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Summary {
+    pub rows: usize,
+    /// Total duration in seconds.
+    pub duration: f64,
+    pub charts: Vec<Chart>,
+}
+
+impl Summary {
+    pub fn label(&self) -> String {
+        size_label(self.rows, self.duration)
+    }
+}
+
+/// Short description of the size of a file, e.g. "1200 clips, 1.52 h".
+pub fn size_label(rows: usize, duration: f64) -> String {
+    format!("{} clips, {:.2} h", rows, duration / 3600.0)
+}
+
+/// Sidecar caching the [`Summary`] of a data folder entry, next to it.
+pub fn sidecar_path(folder: &Path, filename: &str) -> PathBuf {
+    folder.join(format!("{}.stats.json", filename))
+}
+
+/// Reads the sidecar of a file, unless it is missing, unreadable or older than the file.
+pub fn read_sidecar(folder: &Path, filename: &str) -> Option<Summary> {
+    let sidecar = sidecar_path(folder, filename);
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    if modified(&sidecar)? < modified(&folder.join(filename))? {
+        return None;
+    }
+    serde_json::from_slice(&fs::read(&sidecar).ok()?).ok()
+}
+
+/// Writes the sidecar of a file.
+pub fn write_sidecar(folder: &Path, filename: &str, summary: &Summary) -> anyhow::Result<PathBuf> {
+    let sidecar = sidecar_path(folder, filename);
+    let partial = sidecar.with_extension("json.partial");
+    fs::write(&partial, serde_json::to_vec_pretty(summary)?)?;
+    fs::rename(&partial, &sidecar)?;
+    Ok(sidecar)
+}
@@ -0,0 +1,79 @@
+/// Punctuation common in transcriptions; any other character that is neither a letter,
+/// a digit nor whitespace counts as unusual.
+const COMMON_PUNCTUATION: &str = ".,!?;:'\"-()…’‘“”«»–—¿¡。、，！？；：؟،؛";
+/// Characters that end a sentence.
+const TERMINAL_PUNCTUATION: &str = ".!?…。！？؟";
+/// Closing quotes and brackets allowed after the terminal punctuation.
+const CLOSING: &str = "\"'’”»)]";
+
+/// Casing and punctuation classes of a transcription, which suggest whether it was
+/// normalized (lowercased, stripped of punctuation) and how.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    /// Has letters with case, all lowercase.
+    Lowercase,
+    /// Has letters with case, all uppercase.
+    Uppercase,
+    /// Does not end with sentence-final punctuation.
+    Unterminated,
+    /// Contains punctuation or symbols outside [`COMMON_PUNCTUATION`].
+    UnusualPunctuation,
+}
+
+impl Class {
+    pub const ALL: [Class; 4] = [
+        Class::Lowercase,
+        Class::Uppercase,
+        Class::Unterminated,
+        Class::UnusualPunctuation,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Class::Lowercase => "lowercase",
+            Class::Uppercase => "uppercase",
+            Class::Unterminated => "unterminated",
+            Class::UnusualPunctuation => "unusual",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Class::Lowercase => "All lowercase",
+            Class::Uppercase => "All uppercase",
+            Class::Unterminated => "No terminal punctuation",
+            Class::UnusualPunctuation => "Unusual punctuation",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Class> {
+        Class::ALL.into_iter().find(|class| class.as_str() == name)
+    }
+
+    /// Whether a transcription belongs to the class; empty transcriptions belong to none.
+    pub fn matches(&self, text: &str) -> bool {
+        let text = text.trim();
+        let cased = || {
+            text.chars()
+                .filter(|c| c.is_lowercase() || c.is_uppercase())
+        };
+        match self {
+            Class::Lowercase => cased().next().is_some() && cased().all(char::is_lowercase),
+            // One capital (`I`, an initial) does not make a text uppercase.
+            Class::Uppercase => cased().nth(1).is_some() && cased().all(char::is_uppercase),
+            Class::Unterminated => text
+                .trim_end_matches(|c| CLOSING.contains(c))
+                .chars()
+                .next_back()
+                .is_some_and(|c| !TERMINAL_PUNCTUATION.contains(c)),
+            Class::UnusualPunctuation => unusual_chars(text).next().is_some(),
+        }
+    }
+}
+
+/// Characters of a text that are neither letters, digits, whitespace nor common
+/// punctuation, e.g. `#`, `*`, `/`, `[`, emoji or control characters.
+pub fn unusual_chars(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.chars()
+        .filter(|&c| !c.is_alphanumeric() && !c.is_whitespace() && !COMMON_PUNCTUATION.contains(c))
+}
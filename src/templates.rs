@@ -0,0 +1,91 @@
+use anyhow::Context;
+use minijinja::{Environment, Value, context};
+use std::{fs, path::Path, sync::OnceLock};
+
+/// Built-in templates, overridable by files of the same name in `--templates`.
+const BUILTIN: [(&str, &str); 5] = [
+    ("layout.html", include_str!("../templates/layout.html")),
+    ("index.html", include_str!("../templates/index.html")),
+    (
+        "file_header.html",
+        include_str!("../templates/file_header.html"),
+    ),
+    ("file_row.html", include_str!("../templates/file_row.html")),
+    ("error.html", include_str!("../templates/error.html")),
+];
+
+static ENVIRONMENT: OnceLock<Environment<'static>> = OnceLock::new();
+
+/// Instance branding, available to every template as `brand`.
+#[derive(Clone, Debug, Default)]
+pub struct Branding {
+    /// Instance name, shown in the page header and appended to page titles.
+    pub name: Option<String>,
+    /// Logo shown next to the instance name.
+    pub logo_url: Option<String>,
+    /// Footer text replacing the default project link.
+    pub footer: Option<String>,
+}
+
+/// Loads the built-in templates, replacing those present in `override_dir`.
+///
+/// Must be called before the first page is rendered; later calls are ignored.
+pub fn init(override_dir: Option<&Path>, branding: &Branding) -> anyhow::Result<()> {
+    let _ = ENVIRONMENT.set(environment(override_dir, branding)?);
+    Ok(())
+}
+
+fn environment(
+    override_dir: Option<&Path>,
+    branding: &Branding,
+) -> anyhow::Result<Environment<'static>> {
+    let mut env = Environment::new();
+    env.add_function("t", |text: String| crate::i18n::t(&text).to_string());
+    env.add_filter("urlencode", |text: String| crate::html::url_encode(&text));
+    env.add_global("lang", crate::i18n::lang());
+    env.add_global(
+        "brand",
+        context! {
+            name => branding.name.clone(),
+            logo_url => branding.logo_url.clone(),
+            footer => branding.footer.clone(),
+        },
+    );
+    for (name, source) in BUILTIN {
+        let custom = override_dir
+            .map(|dir| dir.join(name))
+            .filter(|path| path.is_file());
+        match custom {
+            Some(path) => {
+                let source = fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                env.add_template_owned(name, source)
+                    .with_context(|| format!("invalid template {}", path.display()))?;
+            }
+            None => env.add_template(name, source)?,
+        }
+    }
+    Ok(env)
+}
+
+/// Renders a template; errors are rendered in place so a broken override is visible.
+pub fn render(name: &str, ctx: Value) -> String {
+    let env = ENVIRONMENT.get_or_init(|| {
+        environment(None, &Branding::default()).expect("built-in templates are valid")
+    });
+    env.get_template(name)
+        .and_then(|template| template.render(ctx))
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to render {}: {:#}", name, err);
+            format!(
+                "<pre>Failed to render {}: {}</pre>",
+                name,
+                crate::html::escape(&err.to_string())
+            )
+        })
+}
+
+/// Marks pre-rendered HTML so templates do not escape it again.
+pub fn safe(html: impl Into<String>) -> Value {
+    Value::from_safe_string(html.into())
+}
@@ -0,0 +1,354 @@
+use clap::ValueEnum;
+use std::ops::Range;
+
+/// A single step of the text normalization chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TextStep {
+    /// Lowercase all characters.
+    Lowercase,
+    /// Remove punctuation, keeping apostrophes inside words.
+    StripPunctuation,
+    /// Spell out integers and decimals as English words.
+    ExpandNumbers,
+}
+
+/// Applies the configured normalization steps in order, then collapses whitespace.
+#[derive(Clone, Debug)]
+pub struct TextNormalizer {
+    steps: Vec<TextStep>,
+}
+
+impl TextNormalizer {
+    pub fn new(steps: Vec<TextStep>) -> Self {
+        Self { steps }
+    }
+
+    pub fn normalize(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for step in &self.steps {
+            out = match step {
+                TextStep::Lowercase => out.to_lowercase(),
+                TextStep::StripPunctuation => strip_punctuation(&out),
+                TextStep::ExpandNumbers => expand_numbers(&out),
+            };
+        }
+        out.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+fn strip_punctuation(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            let inner_apostrophe = (c == '\'' || c == '’')
+                && i > 0
+                && chars[i - 1].is_alphanumeric()
+                && chars.get(i + 1).is_some_and(|n| n.is_alphanumeric());
+            if c.is_alphanumeric() || c.is_whitespace() || inner_apostrophe {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
+
+/// Replaces digit runs (optionally with `,` thousands separators and a `.` fraction) with words.
+fn expand_numbers(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        let mut integer = String::new();
+        while i < chars.len() {
+            if chars[i].is_ascii_digit() {
+                integer.push(chars[i]);
+            } else if chars[i] == ','
+                && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())
+                && !integer.is_empty()
+            {
+                // Thousands separator, skip it.
+            } else {
+                break;
+            }
+            i += 1;
+        }
+
+        let mut fraction = String::new();
+        if chars.get(i) == Some(&'.') && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()) {
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                fraction.push(chars[i]);
+                i += 1;
+            }
+        }
+
+        let mut words = match integer.parse::<u64>() {
+            Ok(n) => number_to_words(n),
+            // Too large to read as a number: spell digit by digit.
+            Err(_) => digits_to_words(&integer),
+        };
+        if !fraction.is_empty() {
+            words.push_str(" point ");
+            words.push_str(&digits_to_words(&fraction));
+        }
+
+        if out.chars().last().is_some_and(|c| c.is_alphanumeric()) {
+            out.push(' ');
+        }
+        out.push_str(&words);
+        if chars.get(i).is_some_and(|c| c.is_alphanumeric()) {
+            out.push(' ');
+        }
+    }
+    out
+}
+
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES: [&str; 7] = [
+    "",
+    "thousand",
+    "million",
+    "billion",
+    "trillion",
+    "quadrillion",
+    "quintillion",
+];
+
+fn digits_to_words(digits: &str) -> String {
+    digits
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| ONES[d as usize])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn below_thousand(n: u64) -> String {
+    let mut parts = Vec::new();
+    let hundreds = n / 100;
+    let rest = n % 100;
+    if hundreds > 0 {
+        parts.push(format!("{} hundred", ONES[hundreds as usize]));
+    }
+    if rest >= 20 {
+        let tens = TENS[(rest / 10) as usize];
+        if rest.is_multiple_of(10) {
+            parts.push(tens.to_string());
+        } else {
+            parts.push(format!("{} {}", tens, ONES[(rest % 10) as usize]));
+        }
+    } else if rest > 0 {
+        parts.push(ONES[rest as usize].to_string());
+    }
+    parts.join(" ")
+}
+
+/// Whether a lowercase word is an English cardinal number word (`seven`, `forty`,
+/// `hundred`, `million`, ...).
+pub fn is_number_word(word: &str) -> bool {
+    word == "hundred"
+        || ONES.contains(&word)
+        || (!word.is_empty() && (TENS.contains(&word) || SCALES.contains(&word)))
+}
+
+/// Spells out a non-negative integer in English (short scale).
+pub fn number_to_words(mut n: u64) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+    let mut groups = Vec::new();
+    let mut scale = 0;
+    while n > 0 {
+        let group = n % 1000;
+        if group > 0 {
+            let words = below_thousand(group);
+            groups.push(if SCALES[scale].is_empty() {
+                words
+            } else {
+                format!("{} {}", words, SCALES[scale])
+            });
+        }
+        n /= 1000;
+        scale += 1;
+    }
+    groups.reverse();
+    groups.join(" ")
+}
+
+/// One segment of a word-level diff.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Diff<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Word-level diff of two texts (longest common subsequence of whitespace-separated words).
+pub fn diff_words<'a>(old: &'a str, new: &'a str) -> Vec<Diff<'a>> {
+    let old: Vec<&str> = old.split_whitespace().collect();
+    let new: Vec<&str> = new.split_whitespace().collect();
+
+    // lcs[i][j] = LCS length of old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            out.push(Diff::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push(Diff::Removed(old[i]));
+            i += 1;
+        } else {
+            out.push(Diff::Added(new[j]));
+            j += 1;
+        }
+    }
+    out
+}
+
+/// Byte ranges of the non-overlapping, case-insensitive occurrences of `query` in `text`.
+pub fn find_matches(text: &str, query: &str) -> Vec<Range<usize>> {
+    let needle: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let mut matches = Vec::new();
+    if needle.is_empty() {
+        return matches;
+    }
+    let mut start = 0;
+    while let Some(c) = text[start..].chars().next() {
+        match match_len(&text[start..], &needle) {
+            Some(len) => {
+                matches.push(start..start + len);
+                start += len;
+            }
+            None => start += c.len_utf8(),
+        }
+    }
+    matches
+}
+
+/// Length in bytes of the prefix of `text` that lowercases to `needle`.
+fn match_len(text: &str, needle: &[char]) -> Option<usize> {
+    let mut matched = 0;
+    for (i, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            if needle.get(matched) != Some(&lower) {
+                return None;
+            }
+            matched += 1;
+        }
+        if matched == needle.len() {
+            return Some(i + c.len_utf8());
+        }
+    }
+    None
+}
+
+/// Writing systems rendered with their own direction, language tag or fonts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Script {
+    Arabic,
+    Hebrew,
+    Chinese,
+    Japanese,
+    Korean,
+    Other,
+}
+
+/// Most frequent of the recognized scripts among the letters of a text; Han characters
+/// count as Japanese when the text also has kana.
+pub fn script(text: &str) -> Script {
+    let mut counts = [0usize; 5];
+    let mut kana = false;
+    for c in text.chars() {
+        let index = match c as u32 {
+            0x0600..=0x06FF
+            | 0x0750..=0x077F
+            | 0x08A0..=0x08FF
+            | 0xFB50..=0xFDFF
+            | 0xFE70..=0xFEFF => 0,
+            0x0590..=0x05FF | 0xFB1D..=0xFB4F => 1,
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F => 2,
+            0x3040..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => {
+                kana = true;
+                3
+            }
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => 4,
+            _ => continue,
+        };
+        counts[index] += 1;
+    }
+    if kana {
+        counts[3] += counts[2];
+        counts[2] = 0;
+    }
+    let Some((index, _)) = counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .max_by_key(|(index, count)| (**count, std::cmp::Reverse(*index)))
+    else {
+        return Script::Other;
+    };
+    [
+        Script::Arabic,
+        Script::Hebrew,
+        Script::Chinese,
+        Script::Japanese,
+        Script::Korean,
+    ][index]
+}
+
+/// Whether a text reads right to left: its first strongly directional letter is from a
+/// right-to-left script (Hebrew, Arabic, Syriac, Thaana, N'Ko).
+pub fn is_rtl(text: &str) -> bool {
+    text.chars()
+        .find(|c| c.is_alphabetic())
+        .is_some_and(|c| matches!(c as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF))
+}
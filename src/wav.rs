@@ -0,0 +1,155 @@
+use anyhow::{Context, bail, ensure};
+
+/// `WAVE_FORMAT_PCM`
+const FORMAT_PCM: u16 = 1;
+/// `WAVE_FORMAT_IEEE_FLOAT`
+const FORMAT_FLOAT: u16 = 3;
+/// `WAVE_FORMAT_EXTENSIBLE`, the real format tag lives in the sub-format GUID.
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+fn is_wav(bytes: &[u8]) -> bool {
+    bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE"
+}
+
+/// Body of the first chunk with the given id.
+fn chunk<'a>(bytes: &'a [u8], wanted: &[u8; 4]) -> Option<&'a [u8]> {
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let start = pos + 8;
+        if id == wanted {
+            // Truncated files are common in scraped datasets; clamp the last chunk.
+            return Some(&bytes[start..start.saturating_add(size).min(bytes.len())]);
+        }
+        // Chunks are padded to an even size.
+        pos = start.saturating_add(size).saturating_add(size & 1);
+    }
+    None
+}
+
+/// Sampling rate from the header of a RIFF/WAVE buffer, without decoding the samples.
+pub fn sample_rate(bytes: &[u8]) -> Option<u32> {
+    if !is_wav(bytes) {
+        return None;
+    }
+    let fmt = chunk(bytes, b"fmt ")?;
+    Some(u32::from_le_bytes(fmt.get(4..8)?.try_into().unwrap())).filter(|&rate| rate > 0)
+}
+
+/// Decoded PCM audio with samples normalized to `[-1.0, 1.0]`.
+#[derive(Clone, Debug)]
+pub struct Wav {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Interleaved samples.
+    pub samples: Vec<f32>,
+}
+
+impl Wav {
+    /// Parses a RIFF/WAVE byte buffer.
+    pub fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        ensure!(is_wav(bytes), "not a RIFF/WAVE file");
+
+        let fmt = chunk(bytes, b"fmt ").context("missing fmt chunk")?;
+        let data = chunk(bytes, b"data").context("missing data chunk")?;
+        ensure!(fmt.len() >= 16, "fmt chunk too short");
+
+        let mut format_tag = u16::from_le_bytes([fmt[0], fmt[1]]);
+        let channels = u16::from_le_bytes([fmt[2], fmt[3]]);
+        let sample_rate = u32::from_le_bytes(fmt[4..8].try_into().unwrap());
+        let bits_per_sample = u16::from_le_bytes([fmt[14], fmt[15]]);
+        if format_tag == FORMAT_EXTENSIBLE {
+            ensure!(fmt.len() >= 26, "extensible fmt chunk too short");
+            format_tag = u16::from_le_bytes([fmt[24], fmt[25]]);
+        }
+        ensure!(channels > 0, "zero channels");
+        ensure!(sample_rate > 0, "zero sample rate");
+
+        let width = (bits_per_sample as usize).div_ceil(8);
+        let samples: Vec<f32> = match (format_tag, bits_per_sample) {
+            (FORMAT_PCM, 8) => data.iter().map(|&b| (b as f32 - 128.0) / 128.0).collect(),
+            (FORMAT_PCM, 16) => data
+                .chunks_exact(width)
+                .map(|c| i16::from_le_bytes([c[0], c[1]]) as f32 / 32768.0)
+                .collect(),
+            (FORMAT_PCM, 24) => data
+                .chunks_exact(width)
+                .map(|c| (i32::from_le_bytes([0, c[0], c[1], c[2]]) >> 8) as f32 / 8_388_608.0)
+                .collect(),
+            (FORMAT_PCM, 32) => data
+                .chunks_exact(width)
+                .map(|c| i32::from_le_bytes(c.try_into().unwrap()) as f32 / 2_147_483_648.0)
+                .collect(),
+            (FORMAT_FLOAT, 32) => data
+                .chunks_exact(width)
+                .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+                .collect(),
+            (FORMAT_FLOAT, 64) => data
+                .chunks_exact(width)
+                .map(|c| f64::from_le_bytes(c.try_into().unwrap()) as f32)
+                .collect(),
+            (tag, bits) => bail!("unsupported WAV encoding (format {}, {} bits)", tag, bits),
+        };
+
+        Ok(Self {
+            sample_rate,
+            channels,
+            samples,
+        })
+    }
+
+    /// Length in seconds, from the number of decoded frames.
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / self.channels as f64 / self.sample_rate as f64
+    }
+
+    /// Returns the samples downmixed to a single channel.
+    pub fn mono(&self) -> Vec<f32> {
+        if self.channels == 1 {
+            return self.samples.clone();
+        }
+        let channels = self.channels as usize;
+        self.samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect()
+    }
+
+    /// Encodes the samples as 16-bit PCM WAV, clamping anything out of range.
+    ///
+    /// Fails when the header fields do not fit their 16 and 32-bit slots.
+    pub fn encode_pcm16(&self) -> anyhow::Result<Vec<u8>> {
+        let data_len = self
+            .samples
+            .len()
+            .checked_mul(2)
+            .and_then(|len| u32::try_from(len).ok())
+            .filter(|len| len.checked_add(36).is_some())
+            .context("too many samples for a WAV file")?;
+        let block_align = self.channels.checked_mul(2).context("too many channels")?;
+        let byte_rate = self
+            .sample_rate
+            .checked_mul(block_align as u32)
+            .context("sample rate too high")?;
+
+        let mut out = Vec::with_capacity(44 + data_len as usize);
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data_len).to_le_bytes());
+        out.extend_from_slice(b"WAVEfmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&FORMAT_PCM.to_le_bytes());
+        out.extend_from_slice(&self.channels.to_le_bytes());
+        out.extend_from_slice(&self.sample_rate.to_le_bytes());
+        out.extend_from_slice(&byte_rate.to_le_bytes());
+        out.extend_from_slice(&block_align.to_le_bytes());
+        out.extend_from_slice(&16u16.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&data_len.to_le_bytes());
+        for &sample in &self.samples {
+            let value = (sample.clamp(-1.0, 1.0) * 32767.0).round() as i16;
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,162 @@
+use serde::Serialize;
+use std::{
+    str::FromStr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Annotation event a webhook can subscribe to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    /// Rows were flagged by a batch operation.
+    Flagged,
+    /// A transcription was edited, undone or restored.
+    Edited,
+    /// A batch operation annotating rows finished.
+    BatchCompleted,
+}
+
+impl FromStr for Event {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "flagged" => Ok(Event::Flagged),
+            "edited" => Ok(Event::Edited),
+            "batch_completed" => Ok(Event::BatchCompleted),
+            other => Err(format!(
+                "unknown event `{}`, expected flagged, edited or batch_completed",
+                other
+            )),
+        }
+    }
+}
+
+/// A URL notified of annotation events, given as `URL` for every event or as
+/// `EVENTS=URL` with a comma-separated list of events.
+#[derive(Clone, Debug)]
+pub struct Webhook {
+    pub url: String,
+    /// Events the hook is called for; every event when empty.
+    pub events: Vec<Event>,
+}
+
+impl FromStr for Webhook {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // URLs may hold `=` in their query, but never before their scheme.
+        let (events, url) = match s.split_once('=') {
+            Some((events, url)) if !events.contains(':') => (
+                events
+                    .split(',')
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()?,
+                url,
+            ),
+            _ => (Vec::new(), s),
+        };
+        let url = url.trim();
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(format!("webhook URL `{}` is not an http(s) URL", url));
+        }
+        Ok(Self {
+            url: url.to_string(),
+            events,
+        })
+    }
+}
+
+/// Body POSTed to a webhook.
+#[derive(Serialize, Debug)]
+pub struct Notification {
+    pub event: Event,
+    pub file: String,
+    pub rows: Vec<usize>,
+    /// Logged-in user who made the change, when OIDC login is enabled.
+    pub user: Option<String>,
+    /// Unix time in seconds.
+    pub timestamp: u64,
+    /// New transcription of an edit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcription: Option<String>,
+    /// Description of a completed batch operation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch: Option<String>,
+    /// One-line summary, shown by Slack-compatible incoming webhooks.
+    pub text: String,
+}
+
+impl Notification {
+    pub fn new(event: Event, file: &str, rows: Vec<usize>, user: Option<String>) -> Self {
+        let subject = match rows[..] {
+            [row] => format!("{} #{}", file, row),
+            _ => format!("{} rows of {}", rows.len(), file),
+        };
+        let by = user
+            .as_deref()
+            .map(|user| format!(" by {}", user))
+            .unwrap_or_default();
+        let text = match event {
+            Event::Flagged => format!("{} flagged{}", subject, by),
+            Event::Edited => format!("{} edited{}", subject, by),
+            Event::BatchCompleted => format!("Batch on {} completed{}", subject, by),
+        };
+        Self {
+            event,
+            file: file.to_string(),
+            rows,
+            user,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            transcription: None,
+            batch: None,
+            text,
+        }
+    }
+}
+
+/// Client POSTing annotation events as JSON to the configured webhooks, so chat
+/// channels and downstream pipelines can react to them.
+///
+/// Deliveries run in the background and are not retried; failures are only logged.
+#[derive(Clone)]
+pub struct Webhooks {
+    client: reqwest::Client,
+    hooks: Arc<Vec<Webhook>>,
+}
+
+impl Webhooks {
+    pub fn new(hooks: Vec<Webhook>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            hooks: Arc::new(hooks),
+        }
+    }
+
+    /// Sends a notification to every hook subscribed to its event. Must be called
+    /// within the Tokio runtime, blocking tasks included.
+    pub fn notify(&self, notification: Notification) {
+        let body = serde_json::to_vec(&notification).unwrap_or_default();
+        for hook in self.hooks.iter() {
+            if !hook.events.is_empty() && !hook.events.contains(&notification.event) {
+                continue;
+            }
+            let (client, url, body) = (self.client.clone(), hook.url.clone(), body.clone());
+            tokio::spawn(async move {
+                let result = client
+                    .post(&url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body)
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status);
+                if let Err(err) = result {
+                    eprintln!("Webhook {} failed: {}", url, err);
+                }
+            });
+        }
+    }
+}